@@ -1,6 +1,6 @@
 //! To allow for batch testing, we define a list of test cases that can be run in sequence.
 
-use alloc::{boxed::Box, string::String, string::ToString, vec::Vec};
+use alloc::{boxed::Box, format, string::String, string::ToString, vec::Vec};
 
 #[allow(dead_code)]
 pub const SDCARD_TESTCASES: &[&str] = &[
@@ -108,8 +108,16 @@ pub fn run_batch_testcases(envs: &Vec<String>) {
         let now_process_id = user_process.get_process_id() as isize;
         let mut exit_code = 0;
         loop {
-            if unsafe { axstarry::wait_pid(now_process_id, &mut exit_code as *mut i32) }.is_ok() {
-                break;
+            match unsafe { axstarry::wait_pid(now_process_id, &mut exit_code as *mut i32) } {
+                Ok(_) => break,
+                // It is no longer a child of the kernel, so waiting would never end
+                Err(axstarry::WaitStatus::NotExist) => {
+                    axstarry::println(
+                        format!("process {} was reaped elsewhere", now_process_id).as_str(),
+                    );
+                    break;
+                }
+                Err(_) => {}
             }
             axstarry::yield_now_task();
         }
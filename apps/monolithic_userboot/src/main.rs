@@ -3,7 +3,7 @@
 #![no_main]
 
 #[allow(unused)]
-use axstarry::{println, recycle_user_process, wait_pid, yield_now_task, Process};
+use axstarry::{println, recycle_user_process, wait_pid, yield_now_task, Process, WaitStatus};
 
 mod batch;
 mod fs;
@@ -57,8 +57,14 @@ fn main() {
         let now_process_id = user_process.get_process_id() as isize;
         let mut exit_code = 0;
         loop {
-            if unsafe { wait_pid(now_process_id, &mut exit_code as *mut i32) }.is_ok() {
-                break;
+            match unsafe { wait_pid(now_process_id, &mut exit_code as *mut i32) } {
+                Ok(_) => break,
+                // It is no longer a child of the kernel, so waiting would never end
+                Err(WaitStatus::NotExist) => {
+                    println(format!("process {} was reaped elsewhere", now_process_id).as_str());
+                    break;
+                }
+                Err(_) => {}
             }
 
             yield_now_task();
@@ -259,4 +259,13 @@ impl GenericArmGic for GicV2 {
     fn end_interrupt(&self, intid: IntId) {
         self.gicc.regs().EOIR.set(intid.0 as u32);
     }
+
+    /// Sends the Software Generated Interrupt with the given ID to the target core.
+    fn send_sgi(&self, intid: IntId, target: usize) {
+        // TargetListFilter = 0: forward to the CPU interfaces in the target list.
+        self.gicd
+            .regs()
+            .SGIR
+            .set(((1 << (16 + target)) | intid.0) as u32);
+    }
 }
@@ -489,4 +489,22 @@ impl GenericArmGic for GicV3 {
         // SAFETY: Writing to this system register doesn't access memory in any way.
         unsafe { write_sysreg!(icc_eoir1_el1, intid.0 as u64) }
     }
+
+    /// Sends the Software Generated Interrupt with the given ID to the target core.
+    fn send_sgi(&self, intid: IntId, target: usize) {
+        let mpidr = target as u64;
+        let aff0 = mpidr & 0xff;
+        let aff1 = (mpidr >> 8) & 0xff;
+        let aff2 = (mpidr >> 16) & 0xff;
+        let aff3 = (mpidr >> 32) & 0xff;
+        // The target list covers 16 cores, selected by the range selector (RS).
+        let value = (aff3 << 48)
+            | ((aff0 >> 4) << 44)
+            | (aff2 << 32)
+            | ((intid.0 as u64) << 24)
+            | (aff1 << 16)
+            | (1 << (aff0 & 0xf));
+        // SAFETY: Writing to this system register doesn't access memory in any way.
+        unsafe { write_sysreg!(icc_sgi1r_el1, value) }
+    }
 }
@@ -199,4 +199,10 @@ pub trait GenericArmGic: Debug + Clone + Copy + Sync + Send + Sized {
     /// Informs the interrupt controller that the CPU has completed processing the given interrupt.
     /// This drops the interrupt priority and deactivates the interrupt.
     fn end_interrupt(&self, intid: IntId);
+
+    /// Sends the Software Generated Interrupt with the given ID to the target core.
+    ///
+    /// The target is the CPU interface number for GICv2, and the affinity value
+    /// (`MPIDR_EL1`) of the core for GICv3.
+    fn send_sgi(&self, intid: IntId, target: usize);
 }
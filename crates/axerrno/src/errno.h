@@ -146,5 +146,11 @@
 #define ERFKILL         132 /* Operation not possible due to RF-kill */
 #define EHWPOISON       133 /* Memory page has hardware error */
 
+/* Copy from include/linux/errno.h, these should never be seen by user programs */
+#define ERESTARTSYS     512 /* Interrupted system call, restart if SA_RESTART is set */
+#define ERESTARTNOINTR  513 /* Interrupted system call, always restart */
+#define ERESTARTNOHAND  514 /* Interrupted system call, restart if no handler is called */
+#define ERESTART_RESTARTBLOCK 516 /* Interrupted system call, restart by restart_syscall */
+
 
 #endif
@@ -89,6 +89,14 @@ pub enum AxError {
     Interrupted,
     /// Syscall timed out
     Timeout,
+    /// Too many levels of symbolic links were encountered while resolving a path.
+    FilesystemLoop,
+    /// The operation is not permitted, e.g. creating links on a filesystem without link support.
+    OperationNotPermitted,
+    /// The source and destination of a link or rename are on different filesystems.
+    CrossesDevices,
+    /// Writing to a pipe or a stream socket whose reading end has been closed.
+    BrokenPipe,
 }
 
 /// A specialized [`Result`] type with [`AxError`] as the error type.
@@ -216,6 +224,10 @@ impl AxError {
             WriteZero => "Write zero",
             Interrupted => "Interrupted",
             Timeout => "Timeout",
+            FilesystemLoop => "Too many levels of symbolic links",
+            OperationNotPermitted => "Operation not permitted",
+            CrossesDevices => "Cross-device link",
+            BrokenPipe => "Broken pipe",
         }
     }
 
@@ -269,6 +281,10 @@ impl From<AxError> for LinuxError {
             WouldBlock => LinuxError::EAGAIN,
             Interrupted => LinuxError::EINTR,
             Timeout => LinuxError::ETIME,
+            FilesystemLoop => LinuxError::ELOOP,
+            OperationNotPermitted => LinuxError::EPERM,
+            CrossesDevices => LinuxError::EXDEV,
+            BrokenPipe => LinuxError::EPIPE,
         }
     }
 }
@@ -291,13 +307,14 @@ mod tests {
     #[test]
     fn test_try_from() {
         let max_code = core::mem::variant_count::<AxError>() as i32;
-        assert_eq!(max_code, 24);
-        assert_eq!(max_code, AxError::Timeout.code());
+        assert_eq!(max_code, 28);
+        assert_eq!(max_code, AxError::BrokenPipe.code());
+        assert_eq!(AxError::Timeout.code(), 24);
 
         assert_eq!(AxError::AddrInUse.code(), 1);
         assert_eq!(Ok(AxError::AddrInUse), AxError::try_from(1));
         assert_eq!(Ok(AxError::AlreadyExists), AxError::try_from(2));
-        assert_eq!(Ok(AxError::Timeout), AxError::try_from(max_code));
+        assert_eq!(Ok(AxError::BrokenPipe), AxError::try_from(max_code));
         assert_eq!(Err(max_code + 1), AxError::try_from(max_code + 1));
         assert_eq!(Err(0), AxError::try_from(0));
         assert_eq!(Err(-1), AxError::try_from(-1));
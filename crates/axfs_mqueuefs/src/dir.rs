@@ -0,0 +1,182 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::{string::String, vec::Vec};
+
+use axfs_vfs::{VfsDirEntry, VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType};
+use axfs_vfs::{VfsError, VfsResult};
+use spin::RwLock;
+
+use crate::queue::MqueueNode;
+use crate::{NewWaitQueue, DFLT_MSGMAX, DFLT_MSGSIZEMAX};
+
+/// The root directory of the message queue filesystem.
+///
+/// It holds all message queues, and subdirectories are not allowed. It
+/// implements [`axfs_vfs::VfsNodeOps`].
+pub struct MqueueDir {
+    parent: RwLock<Weak<dyn VfsNodeOps>>,
+    children: RwLock<BTreeMap<String, Arc<MqueueNode>>>,
+    new_wait_queue: NewWaitQueue,
+}
+
+impl MqueueDir {
+    pub(super) fn new(new_wait_queue: NewWaitQueue) -> Arc<Self> {
+        Arc::new(Self {
+            parent: RwLock::new(Weak::<Self>::new()),
+            children: RwLock::new(BTreeMap::new()),
+            new_wait_queue,
+        })
+    }
+
+    pub(super) fn set_parent(&self, parent: Option<&VfsNodeRef>) {
+        *self.parent.write() = parent.map_or(Weak::<Self>::new() as _, Arc::downgrade);
+    }
+
+    /// Returns a string list of all queues in this directory.
+    pub fn get_entries(&self) -> Vec<String> {
+        self.children.read().keys().cloned().collect()
+    }
+
+    /// Returns the queue with the given name.
+    pub fn get_queue(&self, name: &str) -> Option<Arc<MqueueNode>> {
+        self.children.read().get(name).cloned()
+    }
+
+    /// Creates a new queue with the given name and limits.
+    ///
+    /// `mode` is the permission bits of the queue file.
+    pub fn create_queue(
+        &self,
+        name: &str,
+        mode: u16,
+        max_msg: usize,
+        msg_size: usize,
+    ) -> VfsResult<Arc<MqueueNode>> {
+        check_name(name)?;
+        let mut children = self.children.write();
+        if children.contains_key(name) {
+            return Err(VfsError::AlreadyExists);
+        }
+        let queue = Arc::new(MqueueNode::new(
+            mode,
+            max_msg,
+            msg_size,
+            (self.new_wait_queue)(),
+            (self.new_wait_queue)(),
+        ));
+        children.insert(name.into(), queue.clone());
+        Ok(queue)
+    }
+
+    /// Removes the queue with the given name.
+    ///
+    /// Processes which have opened the queue can still use it, and it is
+    /// destroyed after all of them close it.
+    pub fn remove_queue(&self, name: &str) -> VfsResult {
+        self.children
+            .write()
+            .remove(name)
+            .map(|_| ())
+            .ok_or(VfsError::NotFound)
+    }
+}
+
+impl VfsNodeOps for MqueueDir {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new_dir(4096, 0))
+    }
+
+    fn parent(&self) -> Option<VfsNodeRef> {
+        self.parent.read().upgrade()
+    }
+
+    fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
+        let (name, rest) = split_path(path);
+        let node = match name {
+            "" | "." => Ok(self.clone() as VfsNodeRef),
+            ".." => self.parent().ok_or(VfsError::NotFound),
+            _ => self
+                .get_queue(name)
+                .map(|queue| queue as VfsNodeRef)
+                .ok_or(VfsError::NotFound),
+        }?;
+        if let Some(rest) = rest {
+            node.lookup(rest)
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
+        let children = self.children.read();
+        let mut children = children.keys().skip(start_idx.max(2) - 2);
+        for (i, ent) in dirents.iter_mut().enumerate() {
+            match i + start_idx {
+                0 => *ent = VfsDirEntry::new(".", VfsNodeType::Dir),
+                1 => *ent = VfsDirEntry::new("..", VfsNodeType::Dir),
+                _ => {
+                    if let Some(name) = children.next() {
+                        *ent = VfsDirEntry::new(name, VfsNodeType::File);
+                    } else {
+                        return Ok(i);
+                    }
+                }
+            }
+        }
+        Ok(dirents.len())
+    }
+
+    fn create(&self, path: &str, ty: VfsNodeType) -> VfsResult {
+        log::debug!("create {:?} at mqueuefs: {}", ty, path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.create(rest, ty),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.create(rest, ty),
+                _ => Err(VfsError::NotADirectory),
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Ok(()) // already exists
+        } else if ty == VfsNodeType::File {
+            // a queue created by `open(O_CREAT)` has the default limits
+            self.create_queue(name, 0o644, DFLT_MSGMAX, DFLT_MSGSIZEMAX)
+                .map(|_| ())
+        } else {
+            Err(VfsError::OperationNotPermitted)
+        }
+    }
+
+    fn remove(&self, path: &str) -> VfsResult {
+        log::debug!("remove at mqueuefs: {}", path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.remove(rest),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.remove(rest),
+                _ => Err(VfsError::NotADirectory),
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::InvalidInput) // remove '.' or '..
+        } else {
+            self.remove_queue(name)
+        }
+    }
+
+    axfs_vfs::impl_vfs_dir_default! {}
+}
+
+/// Checks whether `name` can be used as the name of a queue.
+fn check_name(name: &str) -> VfsResult {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        Err(VfsError::InvalidInput)
+    } else {
+        Ok(())
+    }
+}
+
+fn split_path(path: &str) -> (&str, Option<&str>) {
+    let trimmed_path = path.trim_start_matches('/');
+    trimmed_path.find('/').map_or((trimmed_path, None), |n| {
+        (&trimmed_path[..n], Some(&trimmed_path[n + 1..]))
+    })
+}
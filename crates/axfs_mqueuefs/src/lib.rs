@@ -0,0 +1,96 @@
+//! POSIX message queue filesystem used by Starry.
+//!
+//! Every message queue is a file in the root directory of the filesystem, so
+//! the queues can be listed with `ls` and removed with `rm` once the
+//! filesystem is mounted (usually on `/dev/mqueue`). Reading a queue file
+//! gives a line of its status, in the same format as Linux.
+//!
+//! The implementation is based on [`axfs_vfs`]. Tasks blocked in sending to
+//! a full queue or receiving from an empty queue wait in the wait queues of
+//! the queue, and are woken up when the queue changes. The wait queues are
+//! provided by the kernel through [`WaitQueueOps`].
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+mod dir;
+mod queue;
+#[cfg(test)]
+mod tests;
+
+pub use self::dir::MqueueDir;
+pub use self::queue::{MqAttr, MqNotify, MqueueNode};
+use alloc::{boxed::Box, sync::Arc};
+use axfs_vfs::{VfsNodeRef, VfsOps, VfsResult};
+use core::time::Duration;
+use spin::once::Once;
+
+/// The default maximum number of messages in a queue.
+pub const DFLT_MSGMAX: usize = 10;
+/// The default maximum size of a message.
+pub const DFLT_MSGSIZEMAX: usize = 8192;
+/// The upper limit of `mq_maxmsg`.
+pub const HARD_MSGMAX: usize = 65536;
+/// The upper limit of `mq_msgsize`.
+pub const HARD_MSGSIZEMAX: usize = 16 * 1024 * 1024;
+/// Message priorities must be less than this value.
+pub const MQ_PRIO_MAX: u32 = 32768;
+
+/// A queue of tasks blocked on a message queue, provided by the kernel.
+pub trait WaitQueueOps: Send + Sync {
+    /// Blocks the current task if `condition` is true, until [`notify_all`]
+    /// is called or the task is unblocked directly (e.g. by a signal).
+    ///
+    /// The condition must be checked atomically with respect to
+    /// [`notify_all`], so that a notification is not lost.
+    ///
+    /// [`notify_all`]: WaitQueueOps::notify_all
+    fn wait_if(&self, condition: &dyn Fn() -> bool);
+
+    /// Like [`wait_if`](WaitQueueOps::wait_if), but also wakes up after `dur`.
+    fn wait_timeout_if(&self, dur: Duration, condition: &dyn Fn() -> bool);
+
+    /// Wakes up all tasks in the queue.
+    fn notify_all(&self);
+}
+
+/// Creates an empty wait queue for a new message queue.
+pub type NewWaitQueue = fn() -> Box<dyn WaitQueueOps>;
+
+/// A message queue filesystem that implements [`axfs_vfs::VfsOps`].
+pub struct MqueueFileSystem {
+    parent: Once<VfsNodeRef>,
+    root: Arc<MqueueDir>,
+}
+
+impl MqueueFileSystem {
+    /// Create a new instance, whose queues get their wait queues from
+    /// `new_wait_queue`.
+    pub fn new(new_wait_queue: NewWaitQueue) -> Self {
+        Self {
+            parent: Once::new(),
+            root: MqueueDir::new(new_wait_queue),
+        }
+    }
+
+    /// Returns the root directory node in [`Arc<MqueueDir>`](MqueueDir).
+    pub fn root_dir_node(&self) -> Arc<MqueueDir> {
+        self.root.clone()
+    }
+}
+
+impl VfsOps for MqueueFileSystem {
+    fn mount(&self, _path: &str, mount_point: VfsNodeRef) -> VfsResult {
+        if let Some(parent) = mount_point.parent() {
+            self.root.set_parent(Some(self.parent.call_once(|| parent)));
+        } else {
+            self.root.set_parent(None);
+        }
+        Ok(())
+    }
+
+    fn root_dir(&self) -> VfsNodeRef {
+        self.root.clone()
+    }
+}
@@ -0,0 +1,261 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use axfs_vfs::{impl_vfs_non_dir_default, VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType};
+use axfs_vfs::{VfsError, VfsResult};
+use core::time::Duration;
+use spin::Mutex;
+
+use crate::WaitQueueOps;
+
+/// The attributes of a message queue, i.e. `struct mq_attr`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MqAttr {
+    /// Flags of the open message queue description, only `O_NONBLOCK` is used
+    pub mq_flags: i64,
+    /// Maximum number of messages in the queue
+    pub mq_maxmsg: i64,
+    /// Maximum size of a message in bytes
+    pub mq_msgsize: i64,
+    /// Number of messages currently in the queue
+    pub mq_curmsgs: i64,
+    _reserved: [i64; 4],
+}
+
+/// The registration of a process to be notified, set by `mq_notify`.
+///
+/// The queue only records it, and the notification is delivered by the
+/// caller of [`MqueueNode::try_send`].
+#[derive(Clone, Copy, Debug)]
+pub struct MqNotify {
+    /// The process to be notified
+    pub pid: u64,
+    /// `sigev_notify` of the `struct sigevent`
+    pub notify: i32,
+    /// The signal to be sent
+    pub signo: i32,
+    /// The value passed along with the signal
+    pub value: usize,
+}
+
+struct MqueueInner {
+    /// Messages of the same priority are kept in FIFO order.
+    messages: BTreeMap<u32, VecDeque<Vec<u8>>>,
+    cur_msgs: usize,
+    /// Total size of all messages in bytes.
+    q_size: usize,
+    notify: Option<MqNotify>,
+    /// Number of tasks blocked in receiving from the queue.
+    waiting_receivers: usize,
+}
+
+/// A message queue, which is also a file in the message queue filesystem.
+///
+/// It implements [`axfs_vfs::VfsNodeOps`].
+pub struct MqueueNode {
+    mode: u16,
+    max_msg: usize,
+    msg_size: usize,
+    inner: Mutex<MqueueInner>,
+    /// Tasks waiting for the queue to become not full.
+    send_wq: Box<dyn WaitQueueOps>,
+    /// Tasks waiting for the queue to become not empty.
+    recv_wq: Box<dyn WaitQueueOps>,
+}
+
+impl MqueueNode {
+    pub(crate) fn new(
+        mode: u16,
+        max_msg: usize,
+        msg_size: usize,
+        send_wq: Box<dyn WaitQueueOps>,
+        recv_wq: Box<dyn WaitQueueOps>,
+    ) -> Self {
+        Self {
+            mode,
+            max_msg,
+            msg_size,
+            inner: Mutex::new(MqueueInner {
+                messages: BTreeMap::new(),
+                cur_msgs: 0,
+                q_size: 0,
+                notify: None,
+                waiting_receivers: 0,
+            }),
+            send_wq,
+            recv_wq,
+        }
+    }
+
+    /// The maximum size of a message in bytes.
+    pub fn msg_size(&self) -> usize {
+        self.msg_size
+    }
+
+    /// Returns the attributes of the queue, with `mq_flags` set to 0.
+    pub fn attr(&self) -> MqAttr {
+        MqAttr {
+            mq_maxmsg: self.max_msg as _,
+            mq_msgsize: self.msg_size as _,
+            mq_curmsgs: self.inner.lock().cur_msgs as _,
+            ..Default::default()
+        }
+    }
+
+    /// Whether a message can be received without blocking.
+    pub fn readable(&self) -> bool {
+        self.inner.lock().cur_msgs != 0
+    }
+
+    /// Whether a message can be sent without blocking.
+    pub fn writable(&self) -> bool {
+        self.inner.lock().cur_msgs < self.max_msg
+    }
+
+    /// Puts a message with the given priority into the queue.
+    ///
+    /// Returns [`VfsError::WouldBlock`] if the queue is full. If the queue
+    /// was empty and no task is waiting for messages, the registration of
+    /// `mq_notify` is removed and returned, and the caller should notify
+    /// the registered process.
+    pub fn try_send(&self, data: &[u8], priority: u32) -> VfsResult<Option<MqNotify>> {
+        if data.len() > self.msg_size {
+            return Err(VfsError::InvalidInput);
+        }
+        let mut inner = self.inner.lock();
+        if inner.cur_msgs >= self.max_msg {
+            return Err(VfsError::WouldBlock);
+        }
+        let notify = if inner.cur_msgs == 0 && inner.waiting_receivers == 0 {
+            inner.notify.take()
+        } else {
+            None
+        };
+        inner
+            .messages
+            .entry(priority)
+            .or_default()
+            .push_back(data.to_vec());
+        inner.cur_msgs += 1;
+        inner.q_size += data.len();
+        drop(inner);
+        self.recv_wq.notify_all();
+        Ok(notify)
+    }
+
+    /// Takes the oldest message of the highest priority from the queue.
+    ///
+    /// Returns the message and its priority, or [`VfsError::WouldBlock`] if
+    /// the queue is empty.
+    pub fn try_receive(&self) -> VfsResult<(Vec<u8>, u32)> {
+        let mut inner = self.inner.lock();
+        let mut entry = inner.messages.last_entry().ok_or(VfsError::WouldBlock)?;
+        let priority = *entry.key();
+        let data = entry.get_mut().pop_front().unwrap();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+        inner.cur_msgs -= 1;
+        inner.q_size -= data.len();
+        drop(inner);
+        self.send_wq.notify_all();
+        Ok((data, priority))
+    }
+
+    /// Blocks the current task while the queue is full, until another task
+    /// receives a message, the task is unblocked directly (e.g. by a signal),
+    /// or `timeout` has elapsed.
+    pub fn wait_writable(&self, timeout: Option<Duration>) {
+        let condition = || !self.writable();
+        match timeout {
+            Some(timeout) => self.send_wq.wait_timeout_if(timeout, &condition),
+            None => self.send_wq.wait_if(&condition),
+        }
+    }
+
+    /// Blocks the current task while the queue is empty, until another task
+    /// sends a message, the task is unblocked directly (e.g. by a signal), or
+    /// `timeout` has elapsed.
+    pub fn wait_readable(&self, timeout: Option<Duration>) {
+        let condition = || !self.readable();
+        match timeout {
+            Some(timeout) => self.recv_wq.wait_timeout_if(timeout, &condition),
+            None => self.recv_wq.wait_if(&condition),
+        }
+    }
+
+    /// Called before a task blocks to wait for messages.
+    ///
+    /// No notification is sent while any task is waiting.
+    pub fn inc_waiting_receivers(&self) {
+        self.inner.lock().waiting_receivers += 1;
+    }
+
+    /// Called after a task stops waiting for messages.
+    pub fn dec_waiting_receivers(&self) {
+        self.inner.lock().waiting_receivers -= 1;
+    }
+
+    /// Registers `notify` for the process `notify.pid`.
+    ///
+    /// Returns [`VfsError::ResourceBusy`] if a process has registered, even if
+    /// it is the same process.
+    pub fn register_notify(&self, notify: MqNotify) -> VfsResult {
+        let mut inner = self.inner.lock();
+        if inner.notify.is_some() {
+            return Err(VfsError::ResourceBusy);
+        }
+        inner.notify = Some(notify);
+        Ok(())
+    }
+
+    /// Removes the registration if it belongs to the process `pid`.
+    pub fn unregister_notify(&self, pid: u64) {
+        let mut inner = self.inner.lock();
+        if inner.notify.is_some_and(|notify| notify.pid == pid) {
+            inner.notify = None;
+        }
+    }
+
+    /// The status line shown when the queue file is read.
+    fn status(&self) -> String {
+        let inner = self.inner.lock();
+        let (notify, signo, pid) = inner.notify.map_or((0, 0, 0), |notify| {
+            (notify.notify, notify.signo, notify.pid)
+        });
+        format!(
+            "QSIZE:{:<10} NOTIFY:{:<5} SIGNO:{:<5} NOTIFY_PID:{:<6}\n",
+            inner.q_size, notify, signo, pid
+        )
+    }
+}
+
+impl VfsNodeOps for MqueueNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::from_bits_truncate(self.mode),
+            VfsNodeType::File,
+            self.status().len() as _,
+            0,
+        ))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let status = self.status();
+        let start = status.len().min(offset as usize);
+        let end = status.len().min(offset as usize + buf.len());
+        let src = &status.as_bytes()[start..end];
+        buf[..src.len()].copy_from_slice(src);
+        Ok(src.len())
+    }
+
+    fn write_at(&self, _offset: u64, _buf: &[u8]) -> VfsResult<usize> {
+        Err(VfsError::PermissionDenied)
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Ok(())
+    }
+
+    impl_vfs_non_dir_default! {}
+}
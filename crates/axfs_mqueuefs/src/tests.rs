@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axfs_vfs::{VfsError, VfsNodeType, VfsResult};
+
+use crate::*;
+
+/// The tests run without a scheduler, so waiting is only allowed when the
+/// queue is ready and the task would not block.
+struct NoWait;
+
+impl WaitQueueOps for NoWait {
+    fn wait_if(&self, condition: &dyn Fn() -> bool) {
+        assert!(!condition());
+    }
+
+    fn wait_timeout_if(&self, _dur: Duration, condition: &dyn Fn() -> bool) {
+        assert!(!condition());
+    }
+
+    fn notify_all(&self) {}
+}
+
+fn test_queue_ops(mqueuefs: &MqueueFileSystem) -> VfsResult {
+    let dir = mqueuefs.root_dir_node();
+    let queue = dir.create_queue("q1", 0o600, 3, 16)?;
+    assert_eq!(
+        dir.create_queue("q1", 0o600, 3, 16).err(),
+        Some(VfsError::AlreadyExists)
+    );
+    assert!(!queue.readable());
+    assert!(queue.writable());
+    assert_eq!(queue.try_receive().err(), Some(VfsError::WouldBlock));
+    assert_eq!(
+        queue.try_send(&[0; 17], 0).err(),
+        Some(VfsError::InvalidInput)
+    );
+
+    // higher priorities first, FIFO within the same priority
+    assert!(queue.try_send(b"low", 1)?.is_none());
+    queue.wait_readable(None);
+    queue.wait_writable(Some(Duration::from_millis(1)));
+    assert!(queue.try_send(b"high1", 5)?.is_none());
+    assert!(queue.try_send(b"high2", 5)?.is_none());
+    assert!(!queue.writable());
+    assert_eq!(queue.try_send(b"full", 9).err(), Some(VfsError::WouldBlock));
+    assert_eq!(queue.attr().mq_curmsgs, 3);
+    assert_eq!(queue.try_receive()?, (b"high1".to_vec(), 5));
+    assert_eq!(queue.try_receive()?, (b"high2".to_vec(), 5));
+    assert_eq!(queue.try_receive()?, (b"low".to_vec(), 1));
+    assert_eq!(queue.attr().mq_curmsgs, 0);
+
+    let node = mqueuefs.root_dir().lookup("/q1")?;
+    assert_eq!(node.get_attr()?.file_type(), VfsNodeType::File);
+    assert_eq!(node.get_attr()?.perm().bits(), 0o600);
+    assert!(Arc::ptr_eq(&node, &mqueuefs.root_dir().lookup("./q1")?));
+    Ok(())
+}
+
+fn test_notify(mqueuefs: &MqueueFileSystem) -> VfsResult {
+    let queue = mqueuefs.root_dir_node().create_queue("q2", 0o600, 4, 16)?;
+    let notify = MqNotify {
+        pid: 1,
+        notify: 0,
+        signo: 10,
+        value: 42,
+    };
+    queue.register_notify(notify)?;
+    assert_eq!(
+        queue.register_notify(MqNotify { pid: 2, ..notify }).err(),
+        Some(VfsError::ResourceBusy)
+    );
+    assert_eq!(
+        queue.register_notify(notify).err(),
+        Some(VfsError::ResourceBusy)
+    );
+
+    let mut buf = [0; 80];
+    let len = mqueuefs.root_dir().lookup("q2")?.read_at(0, &mut buf)?;
+    assert_eq!(
+        &buf[..len],
+        b"QSIZE:0          NOTIFY:0     SIGNO:10    NOTIFY_PID:1     \n"
+    );
+
+    // no notification while a receiver is waiting
+    queue.inc_waiting_receivers();
+    assert!(queue.try_send(b"a", 0)?.is_none());
+    queue.dec_waiting_receivers();
+    queue.try_receive()?;
+
+    // notified only on the empty-to-non-empty transition, and only once
+    let sent = queue.try_send(b"b", 0)?.unwrap();
+    assert_eq!((sent.pid, sent.signo, sent.value), (1, 10, 42));
+    queue.try_receive()?;
+    assert!(queue.try_send(b"c", 0)?.is_none());
+
+    queue.register_notify(notify)?;
+    queue.unregister_notify(2);
+    queue.try_receive()?;
+    assert!(queue.try_send(b"d", 0)?.is_some());
+    queue.register_notify(notify)?;
+    queue.unregister_notify(1);
+    queue.try_receive()?;
+    assert!(queue.try_send(b"e", 0)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_mqueuefs() {
+    let mqueuefs = MqueueFileSystem::new(|| Box::new(NoWait));
+    let root = mqueuefs.root_dir();
+    assert!(root.get_attr().unwrap().is_dir());
+    root.create("q0", VfsNodeType::File).unwrap();
+    assert_eq!(
+        root.create("d", VfsNodeType::Dir).err(),
+        Some(VfsError::OperationNotPermitted)
+    );
+    assert_eq!(
+        root.clone().lookup("q0/x").err(),
+        Some(VfsError::NotADirectory)
+    );
+
+    test_queue_ops(&mqueuefs).unwrap();
+    test_notify(&mqueuefs).unwrap();
+
+    let mut entries = mqueuefs.root_dir_node().get_entries();
+    entries.sort();
+    assert_eq!(entries, ["q0", "q1", "q2"]);
+
+    // opened queues are still usable after being unlinked
+    let queue = mqueuefs.root_dir_node().get_queue("q1").unwrap();
+    assert_eq!(root.remove("q1"), Ok(()));
+    assert_eq!(root.remove("q1").err(), Some(VfsError::NotFound));
+    assert!(queue.try_send(b"x", 0).is_ok());
+    assert_eq!(root.remove("/q0"), Ok(()));
+    assert_eq!(root.remove("./q2"), Ok(()));
+    assert!(mqueuefs.root_dir_node().get_entries().is_empty());
+}
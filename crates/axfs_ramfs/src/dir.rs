@@ -7,6 +7,8 @@ use axfs_vfs::{VfsError, VfsResult};
 use spin::RwLock;
 
 use crate::file::FileNode;
+use crate::special::SpecialNode;
+use crate::symlink::SymlinkNode;
 use crate::Interrupts;
 
 /// The directory node in the RAM filesystem.
@@ -16,14 +18,17 @@ pub struct DirNode {
     this: Weak<DirNode>,
     parent: RwLock<Weak<dyn VfsNodeOps>>,
     children: RwLock<BTreeMap<String, VfsNodeRef>>,
+    /// The id of the RAM filesystem which this directory belongs to.
+    fs_id: usize,
 }
 
 impl DirNode {
-    pub(super) fn new(parent: Option<Weak<dyn VfsNodeOps>>) -> Arc<Self> {
+    pub(super) fn new(parent: Option<Weak<dyn VfsNodeOps>>, fs_id: usize) -> Arc<Self> {
         Arc::new_cyclic(|this| Self {
             this: this.clone(),
             parent: RwLock::new(parent.unwrap_or_else(|| Weak::<Self>::new())),
             children: RwLock::new(BTreeMap::new()),
+            fs_id,
         })
     }
 
@@ -53,16 +58,71 @@ impl DirNode {
                 if name == "interrupts" {
                     Arc::new(Interrupts)
                 } else {
-                    Arc::new(FileNode::new())
+                    Arc::new(FileNode::new_in(self.fs_id))
                 }
             }
-            VfsNodeType::Dir => Self::new(Some(self.this.clone())),
+            VfsNodeType::Dir => Self::new(Some(self.this.clone()), self.fs_id),
+            VfsNodeType::Fifo | VfsNodeType::Socket => {
+                Arc::new(SpecialNode::new_in(ty, self.fs_id))
+            }
             _ => return Err(VfsError::Unsupported),
         };
         self.children.write().insert(name.into(), node);
         Ok(())
     }
 
+    /// Adds an existing node, which may not belong to any RAM filesystem, with
+    /// the given name to this directory.
+    pub fn add_node(&self, name: &str, node: VfsNodeRef) -> VfsResult {
+        if self.exist(name) {
+            return Err(VfsError::AlreadyExists);
+        }
+        self.children.write().insert(name.into(), node);
+        Ok(())
+    }
+
+    /// Creates a hard link with the given name to the `target` node in this directory.
+    pub fn link_node(&self, name: &str, target: &VfsNodeRef) -> VfsResult {
+        if self.exist(name) {
+            return Err(VfsError::AlreadyExists);
+        }
+        let target_any = target.as_any();
+        if target_any.is::<DirNode>() {
+            // hard links to directories are not allowed
+            return Err(VfsError::OperationNotPermitted);
+        }
+        let target_fs_id = if let Some(file) = target_any.downcast_ref::<FileNode>() {
+            file.fs_id()
+        } else if let Some(link) = target_any.downcast_ref::<SymlinkNode>() {
+            link.fs_id()
+        } else if let Some(special) = target_any.downcast_ref::<SpecialNode>() {
+            special.fs_id()
+        } else {
+            0
+        };
+        if target_fs_id != self.fs_id {
+            // the target does not belong to this RAM filesystem
+            return Err(VfsError::CrossesDevices);
+        }
+        if let Some(file) = target_any.downcast_ref::<FileNode>() {
+            file.inc_nlink();
+        }
+        self.children.write().insert(name.into(), target.clone());
+        Ok(())
+    }
+
+    /// Creates a symbolic link with the given name which points to `target` in this directory.
+    pub fn symlink_node(&self, name: &str, target: &str) -> VfsResult {
+        if self.exist(name) {
+            return Err(VfsError::AlreadyExists);
+        }
+        self.children.write().insert(
+            name.into(),
+            Arc::new(SymlinkNode::new_in(target, self.fs_id)),
+        );
+        Ok(())
+    }
+
     /// Removes a node by the given name in this directory.
     pub fn remove_node(&self, name: &str) -> VfsResult {
         let mut children = self.children.write();
@@ -71,6 +131,8 @@ impl DirNode {
             if !dir.children.read().is_empty() {
                 return Err(VfsError::DirectoryNotEmpty);
             }
+        } else if let Some(file) = node.as_any().downcast_ref::<FileNode>() {
+            file.dec_nlink();
         }
         children.remove(name);
         Ok(())
@@ -172,6 +234,57 @@ impl VfsNodeOps for DirNode {
         }
     }
 
+    fn link(&self, path: &str, target: &VfsNodeRef) -> VfsResult {
+        log::debug!("link at ramfs: {}", path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.link(rest, target),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.link(rest, target),
+                _ => {
+                    let subdir = self
+                        .children
+                        .read()
+                        .get(name)
+                        .ok_or(VfsError::NotFound)?
+                        .clone();
+                    subdir.link(rest, target)
+                }
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::AlreadyExists)
+        } else {
+            self.link_node(name, target)
+        }
+    }
+
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        log::debug!("symlink at ramfs: {} -> {}", path, target);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.symlink(rest, target),
+                ".." => self
+                    .parent()
+                    .ok_or(VfsError::NotFound)?
+                    .symlink(rest, target),
+                _ => {
+                    let subdir = self
+                        .children
+                        .read()
+                        .get(name)
+                        .ok_or(VfsError::NotFound)?
+                        .clone();
+                    subdir.symlink(rest, target)
+                }
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::AlreadyExists)
+        } else {
+            self.symlink_node(name, target)
+        }
+    }
+
     axfs_vfs::impl_vfs_dir_default! {}
 }
 
@@ -1,5 +1,6 @@
 use alloc::vec::Vec;
 use axfs_vfs::{impl_vfs_non_dir_default, VfsNodeAttr, VfsNodeOps, VfsResult};
+use core::sync::atomic::{AtomicU64, Ordering};
 use spin::RwLock;
 
 /// The file node in the RAM filesystem.
@@ -7,20 +8,47 @@ use spin::RwLock;
 /// It implements [`axfs_vfs::VfsNodeOps`].
 pub struct FileNode {
     content: RwLock<Vec<u8>>,
+    nlink: AtomicU64,
+    fs_id: usize,
 }
 
 impl FileNode {
     /// To get the environment variables of the application
     pub const fn new() -> Self {
+        Self::new_in(0)
+    }
+
+    /// Creates a file which belongs to the RAM filesystem with the given id.
+    pub(crate) const fn new_in(fs_id: usize) -> Self {
         Self {
             content: RwLock::new(Vec::new()),
+            nlink: AtomicU64::new(1),
+            fs_id,
         }
     }
+
+    /// The id of the RAM filesystem which this file belongs to, or 0 if it
+    /// does not belong to any.
+    pub(crate) fn fs_id(&self) -> usize {
+        self.fs_id
+    }
+
+    /// Called when a new hard link to this file is created.
+    pub(crate) fn inc_nlink(&self) {
+        self.nlink.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Called when a hard link to this file is removed.
+    pub(crate) fn dec_nlink(&self) {
+        self.nlink.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
 impl VfsNodeOps for FileNode {
     fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
-        Ok(VfsNodeAttr::new_file(self.content.read().len() as _, 0))
+        let mut attr = VfsNodeAttr::new_file(self.content.read().len() as _, 0);
+        attr.set_nlink(self.nlink.load(Ordering::Acquire));
+        Ok(attr)
     }
 
     fn truncate(&self, size: u64) -> VfsResult {
@@ -0,0 +1,50 @@
+use alloc::string::String;
+use axerrno::AxError;
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult};
+
+/// The read-only file node in the RAM filesystem, whose content is generated
+/// by the given function each time it is read.
+pub struct GeneratedFileNode {
+    generate: fn() -> String,
+}
+
+impl GeneratedFileNode {
+    /// Creates a new file node which reads the output of `generate`.
+    pub const fn new(generate: fn() -> String) -> Self {
+        Self { generate }
+    }
+}
+
+impl VfsNodeOps for GeneratedFileNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::from_bits_truncate(0o444),
+            VfsNodeType::File,
+            0,
+            0,
+        ))
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> VfsResult<usize> {
+        let content = (self.generate)();
+        let bytes = content.as_bytes();
+
+        let offset = offset as usize;
+        if offset > bytes.len() {
+            return Ok(0);
+        }
+        let len = buf.len().min(bytes.len() - offset);
+        buf[..len].copy_from_slice(&bytes[offset..offset + len]);
+        Ok(len)
+    }
+
+    fn write_at(&self, _offset: u64, _buf: &[u8]) -> VfsResult<usize> {
+        Err(AxError::PermissionDenied)
+    }
+
+    fn truncate(&self, _size: u64) -> VfsResult {
+        Err(AxError::PermissionDenied)
+    }
+
+    axfs_vfs::impl_vfs_non_dir_default! {}
+}
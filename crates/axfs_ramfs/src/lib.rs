@@ -8,15 +8,22 @@ extern crate alloc;
 
 mod dir;
 mod file;
+mod generated;
 mod interrupts;
+mod special;
+mod symlink;
 #[cfg(test)]
 mod tests;
 
 pub use self::dir::DirNode;
 pub use self::file::FileNode;
+pub use self::generated::GeneratedFileNode;
 pub use self::interrupts::{Interrupts, INTERRUPT};
+pub use self::special::SpecialNode;
+pub use self::symlink::SymlinkNode;
 use alloc::sync::Arc;
 use axfs_vfs::{VfsNodeRef, VfsOps, VfsResult};
+use core::sync::atomic::{AtomicUsize, Ordering};
 use spin::once::Once;
 
 /// A RAM filesystem that implements [`axfs_vfs::VfsOps`].
@@ -28,9 +35,11 @@ pub struct RamFileSystem {
 impl RamFileSystem {
     /// Create a new instance.
     pub fn new() -> Self {
+        // 0 is left for the nodes which do not belong to any RAM filesystem
+        static NEXT_FS_ID: AtomicUsize = AtomicUsize::new(1);
         Self {
             parent: Once::new(),
-            root: DirNode::new(None),
+            root: DirNode::new(None, NEXT_FS_ID.fetch_add(1, Ordering::Relaxed)),
         }
     }
 
@@ -0,0 +1,55 @@
+use core::sync::atomic::{AtomicU16, Ordering};
+
+use axfs_vfs::VfsResult;
+use axfs_vfs::{impl_vfs_non_dir_default, VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType};
+
+/// A node without content in the RAM filesystem, such as a FIFO or a socket.
+///
+/// It only marks a name in the filesystem, and the object behind it is kept
+/// by the kernel. It implements [`axfs_vfs::VfsNodeOps`].
+pub struct SpecialNode {
+    ty: VfsNodeType,
+    perm: AtomicU16,
+    fs_id: usize,
+}
+
+impl SpecialNode {
+    /// Creates a special node of the given type with the permission `0o755`.
+    pub const fn new(ty: VfsNodeType) -> Self {
+        Self::new_in(ty, 0)
+    }
+
+    /// Creates a special node which belongs to the RAM filesystem with the
+    /// given id.
+    pub(crate) const fn new_in(ty: VfsNodeType, fs_id: usize) -> Self {
+        Self {
+            ty,
+            perm: AtomicU16::new(0o755),
+            fs_id,
+        }
+    }
+
+    /// The id of the RAM filesystem which this node belongs to, or 0 if it
+    /// does not belong to any.
+    pub(crate) fn fs_id(&self) -> usize {
+        self.fs_id
+    }
+}
+
+impl VfsNodeOps for SpecialNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::from_bits_truncate(self.perm.load(Ordering::Relaxed)),
+            self.ty,
+            0,
+            0,
+        ))
+    }
+
+    fn set_perm(&self, perm: VfsNodePerm) -> VfsResult {
+        self.perm.store(perm.bits(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    impl_vfs_non_dir_default! {}
+}
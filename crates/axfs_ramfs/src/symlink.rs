@@ -0,0 +1,52 @@
+use alloc::string::String;
+use axfs_vfs::VfsResult;
+use axfs_vfs::{impl_vfs_non_dir_default, VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType};
+
+/// The symbolic link node in the RAM filesystem.
+///
+/// It implements [`axfs_vfs::VfsNodeOps`].
+pub struct SymlinkNode {
+    target: String,
+    fs_id: usize,
+}
+
+impl SymlinkNode {
+    /// Creates a symbolic link which points to `target`.
+    pub fn new(target: &str) -> Self {
+        Self::new_in(target, 0)
+    }
+
+    /// Creates a symbolic link which belongs to the RAM filesystem with the
+    /// given id.
+    pub(crate) fn new_in(target: &str, fs_id: usize) -> Self {
+        Self {
+            target: target.into(),
+            fs_id,
+        }
+    }
+
+    /// The id of the RAM filesystem which this link belongs to, or 0 if it
+    /// does not belong to any.
+    pub(crate) fn fs_id(&self) -> usize {
+        self.fs_id
+    }
+}
+
+impl VfsNodeOps for SymlinkNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::from_bits_truncate(0o777),
+            VfsNodeType::SymLink,
+            self.target.len() as _,
+            0,
+        ))
+    }
+
+    fn readlink(&self, buf: &mut [u8]) -> VfsResult<usize> {
+        let len = buf.len().min(self.target.len());
+        buf[..len].copy_from_slice(&self.target.as_bytes()[..len]);
+        Ok(len)
+    }
+
+    impl_vfs_non_dir_default! {}
+}
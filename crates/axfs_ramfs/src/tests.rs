@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use axfs_vfs::{VfsError, VfsNodeType, VfsResult};
+use axfs_vfs::{VfsError, VfsNodePerm, VfsNodeType, VfsResult};
 
 use crate::*;
 
@@ -88,6 +88,73 @@ fn test_get_parent(devfs: &RamFileSystem) -> VfsResult {
     Ok(())
 }
 
+fn test_links(ramfs: &RamFileSystem) -> VfsResult {
+    let root = ramfs.root_dir();
+    root.create("a", VfsNodeType::File)?;
+    root.create("d", VfsNodeType::Dir)?;
+
+    let a = root.clone().lookup("a")?;
+    root.link("d/b", &a)?;
+    assert!(Arc::ptr_eq(&a, &root.clone().lookup("d/b")?));
+    assert_eq!(a.get_attr()?.nlink(), 2);
+    assert_eq!(root.link("d/b", &a).err(), Some(VfsError::AlreadyExists));
+    let d = root.clone().lookup("d")?;
+    assert_eq!(
+        root.link("e", &d).err(),
+        Some(VfsError::OperationNotPermitted)
+    );
+    let other = RamFileSystem::new();
+    assert_eq!(
+        other.root_dir().link("b", &a).err(),
+        Some(VfsError::CrossesDevices)
+    );
+
+    let mut buf = [0; 16];
+    root.symlink("d/s", "../a")?;
+    let s = root.clone().lookup("d/s")?;
+    assert!(s.get_attr()?.is_symlink());
+    assert_eq!(s.readlink(&mut buf)?, 4);
+    assert_eq!(&buf[..4], b"../a");
+    assert_eq!(a.readlink(&mut buf).err(), Some(VfsError::InvalidInput));
+
+    root.remove("a")?;
+    assert_eq!(a.get_attr()?.nlink(), 1);
+    assert!(root.clone().lookup("d/b").is_ok());
+    root.remove("d/b")?;
+    root.remove("d/s")?;
+    root.remove("d")?;
+    Ok(())
+}
+
+fn test_special_nodes(ramfs: &RamFileSystem) -> VfsResult {
+    let root = ramfs.root_dir();
+    root.create("sock", VfsNodeType::Socket)?;
+    assert_eq!(
+        root.create("sock", VfsNodeType::Socket).err(),
+        Some(VfsError::AlreadyExists)
+    );
+    let sock = root.clone().lookup("sock")?;
+    assert!(sock.get_attr()?.file_type().is_socket());
+    assert_eq!(
+        sock.clone().lookup("x").err(),
+        Some(VfsError::NotADirectory)
+    );
+
+    root.link("sock2", &sock)?;
+    assert!(Arc::ptr_eq(&sock, &root.clone().lookup("sock2")?));
+    root.remove("sock")?;
+    root.remove("sock2")?;
+
+    root.create("fifo", VfsNodeType::Fifo)?;
+    let fifo = root.clone().lookup("fifo")?;
+    assert!(fifo.get_attr()?.file_type().is_fifo());
+    assert_eq!(fifo.get_attr()?.size(), 0);
+    fifo.set_perm(VfsNodePerm::from_bits_truncate(0o640))?;
+    assert_eq!(fifo.get_attr()?.perm().bits(), 0o640);
+    root.remove("fifo")?;
+    Ok(())
+}
+
 #[test]
 fn test_ramfs() {
     // .
@@ -133,4 +200,8 @@ fn test_ramfs() {
     assert_eq!(root.remove("./foo//.//f3"), Ok(()));
     assert_eq!(root.remove("./foo"), Ok(()));
     assert!(ramfs.root_dir_node().get_entries().is_empty());
+
+    test_links(&ramfs).unwrap();
+    test_special_nodes(&ramfs).unwrap();
+    assert!(ramfs.root_dir_node().get_entries().is_empty());
 }
@@ -1,7 +1,7 @@
 //! Virtual filesystem interfaces used by [ArceOS](https://github.com/rcore-os/arceos).
 //!
-//! A filesystem is a set of files and directories (symbol links are not
-//! supported currently), collectively referred to as **nodes**, which are
+//! A filesystem is a set of files, directories and symbolic links,
+//! collectively referred to as **nodes**, which are
 //! conceptually similar to [inodes] in Linux. A file system needs to implement
 //! the [`VfsOps`] trait, its files and directories need to implement the
 //! [`VfsNodeOps`] trait.
@@ -31,6 +31,9 @@
 //! | [`create()`](VfsNodeOps::create) | Create a new node with the given path | directory |
 //! | [`remove()`](VfsNodeOps::remove) | Remove the node with the given path | directory |
 //! | [`read_dir()`](VfsNodeOps::read_dir) | Read directory entries | directory |
+//! | [`link()`](VfsNodeOps::link) | Create a hard link to an existing node | directory |
+//! | [`symlink()`](VfsNodeOps::symlink) | Create a symbolic link | directory |
+//! | [`readlink()`](VfsNodeOps::readlink) | Read the target of a symbolic link | symlink |
 //!
 //! [inodes]: https://en.wikipedia.org/wiki/Inode
 
@@ -100,6 +103,11 @@ pub trait VfsNodeOps: Send + Sync {
         ax_err!(Unsupported)
     }
 
+    /// Set the permission mode of the node.
+    fn set_perm(&self, _perm: VfsNodePerm) -> VfsResult {
+        ax_err!(Unsupported)
+    }
+
     // file operations:
 
     /// Read data from the file at the given offset.
@@ -160,6 +168,31 @@ pub trait VfsNodeOps: Send + Sync {
         ax_err!(Unsupported)
     }
 
+    /// Create a hard link with the given `path` in the directory, which refers
+    /// to the existing node `target`.
+    ///
+    /// `target` must belong to the same filesystem and must not be a directory.
+    fn link(&self, _path: &str, _target: &VfsNodeRef) -> VfsResult {
+        ax_err!(Unsupported)
+    }
+
+    /// Create a symbolic link with the given `path` in the directory, whose
+    /// content is `target`.
+    fn symlink(&self, _path: &str, _target: &str) -> VfsResult {
+        ax_err!(Unsupported)
+    }
+
+    // symbolic link operations:
+
+    /// Read the target of the symbolic link into `buf`.
+    ///
+    /// Return the number of bytes read, the content is truncated if `buf` is
+    /// too small. Return [`InvalidInput`](AxError::InvalidInput) if the node
+    /// is not a symbolic link.
+    fn readlink(&self, _buf: &mut [u8]) -> VfsResult<usize> {
+        ax_err!(InvalidInput)
+    }
+
     /// Convert `&self` to [`&dyn Any`][1] that can use
     /// [`Any::downcast_ref`][2].
     ///
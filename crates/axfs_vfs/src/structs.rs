@@ -16,6 +16,8 @@ pub struct VfsNodeAttr {
     size: u64,
     /// Number of 512B blocks allocated.
     blocks: u64,
+    /// Number of hard links.
+    nlink: u64,
 }
 
 bitflags::bitflags! {
@@ -205,6 +207,7 @@ impl VfsNodeAttr {
             ty,
             size,
             blocks,
+            nlink: 1,
         }
     }
 
@@ -215,6 +218,7 @@ impl VfsNodeAttr {
             ty: VfsNodeType::File,
             size,
             blocks,
+            nlink: 1,
         }
     }
 
@@ -226,6 +230,7 @@ impl VfsNodeAttr {
             ty: VfsNodeType::Dir,
             size,
             blocks,
+            nlink: 1,
         }
     }
 
@@ -239,6 +244,16 @@ impl VfsNodeAttr {
         self.blocks
     }
 
+    /// Returns the number of hard links to the node.
+    pub const fn nlink(&self) -> u64 {
+        self.nlink
+    }
+
+    /// Sets the number of hard links to the node.
+    pub fn set_nlink(&mut self, nlink: u64) {
+        self.nlink = nlink
+    }
+
     /// Returns the permission of the node.
     pub const fn perm(&self) -> VfsNodePerm {
         self.mode
@@ -263,6 +278,11 @@ impl VfsNodeAttr {
     pub const fn is_dir(&self) -> bool {
         self.ty.is_dir()
     }
+
+    /// Whether the node is a symbolic link.
+    pub const fn is_symlink(&self) -> bool {
+        self.ty.is_symlink()
+    }
 }
 
 impl VfsDirEntry {
@@ -28,6 +28,15 @@ const NICE2WEIGHT_NEG: [isize; NICE_RANGE_NEG + 1] = [
     29154, 36291, 46273, 56483, 71755, 88761,
 ];
 
+/// Converts a nice value in `[-20, 19]` to the weight of the task.
+pub(crate) fn nice_to_weight(nice: isize) -> isize {
+    if nice >= 0 {
+        NICE2WEIGHT_POS[nice as usize]
+    } else {
+        NICE2WEIGHT_NEG[(-nice) as usize]
+    }
+}
+
 impl<T> CFSTask<T> {
     /// new with default values
     pub const fn new(inner: T) -> Self {
@@ -41,12 +50,7 @@ impl<T> CFSTask<T> {
     }
 
     fn get_weight(&self) -> isize {
-        let nice = self.nice.load(Ordering::Acquire);
-        if nice >= 0 {
-            NICE2WEIGHT_POS[nice as usize]
-        } else {
-            NICE2WEIGHT_NEG[(-nice) as usize]
-        }
+        nice_to_weight(self.nice.load(Ordering::Acquire))
     }
 
     fn get_id(&self) -> isize {
@@ -5,6 +5,8 @@
 //! - [`FifoScheduler`]: FIFO (First-In-First-Out) scheduler (cooperative).
 //! - [`RRScheduler`]: Round-robin scheduler (preemptive).
 //! - [`CFScheduler`]: Completely Fair Scheduler (preemptive).
+//! - [`RTScheduler`]: Real-time FIFO/RR classes layered above a fair class
+//!   (preemptive).
 
 #![cfg_attr(not(test), no_std)]
 #![feature(const_mut_refs)]
@@ -12,6 +14,7 @@
 mod cfs;
 mod fifo;
 mod round_robin;
+mod rt;
 
 #[cfg(test)]
 mod tests;
@@ -21,6 +24,10 @@ extern crate alloc;
 pub use cfs::{CFSTask, CFScheduler};
 pub use fifo::{FifoScheduler, FifoTask};
 pub use round_robin::{RRScheduler, RRTask};
+pub use rt::{
+    RTScheduler, RTTask, MAX_RT_PRIO, MIN_RT_PRIO, SCHED_BATCH, SCHED_FIFO, SCHED_IDLE,
+    SCHED_OTHER, SCHED_RR,
+};
 
 /// The base scheduler trait that all schedulers should implement.
 ///
@@ -66,4 +73,17 @@ pub trait BaseScheduler {
 
     /// set priority for a task
     fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool;
+
+    /// Sets the scheduling policy (in Linux numbering, e.g. [`SCHED_FIFO`])
+    /// and the real-time priority of a task, which may be in the scheduler or
+    /// not. Returns `false` if the scheduler does not support the policy.
+    fn set_policy(&mut self, _task: &Self::SchedItem, _policy: usize, _priority: usize) -> bool {
+        false
+    }
+
+    /// Whether `task`, which just became runnable, should preempt the
+    /// `current` running task immediately.
+    fn should_preempt(&self, _task: &Self::SchedItem, _current: &Self::SchedItem) -> bool {
+        false
+    }
 }
@@ -0,0 +1,310 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use core::ops::Deref;
+use core::sync::atomic::{AtomicIsize, AtomicU8, AtomicUsize, Ordering};
+
+use crate::cfs::nice_to_weight;
+use crate::BaseScheduler;
+
+/// The default time-sharing policy, scheduled by the fair class.
+pub const SCHED_OTHER: usize = 0;
+/// The first-in, first-out real-time policy.
+pub const SCHED_FIFO: usize = 1;
+/// The round-robin real-time policy.
+pub const SCHED_RR: usize = 2;
+/// The policy for CPU-intensive tasks, scheduled by the fair class.
+pub const SCHED_BATCH: usize = 3;
+/// The policy for very low priority background tasks, scheduled by the fair
+/// class with a minimal weight.
+pub const SCHED_IDLE: usize = 5;
+
+/// The lowest priority of real-time tasks.
+pub const MIN_RT_PRIO: usize = 1;
+/// The highest priority of real-time tasks.
+pub const MAX_RT_PRIO: usize = 99;
+
+/// The weight of `SCHED_IDLE` tasks, much lower than the weight of nice 19.
+const IDLE_WEIGHT: isize = 3;
+/// The virtual runtime a nice 0 task gains in one timer tick.
+const TICK_VRUNTIME: isize = 1024;
+
+const NOT_QUEUED: u8 = 0;
+const QUEUED_RT: u8 = 1;
+const QUEUED_FAIR: u8 = 2;
+
+/// A task wrapper for the [`RTScheduler`].
+///
+/// It holds the scheduling policy and the real-time priority of the task, a
+/// time slice counter for `SCHED_RR`, and the nice value and virtual runtime
+/// for the fair class. All of them can be changed at runtime.
+pub struct RTTask<T, const RR_TIME_SLICE: usize> {
+    inner: T,
+    policy: AtomicUsize,
+    rt_priority: AtomicUsize,
+    time_slice: AtomicIsize,
+    nice: AtomicIsize,
+    vruntime: AtomicIsize,
+    /// Which queue of the scheduler the task is in.
+    queued: AtomicU8,
+    /// The tie-breaker of the key in the fair queue.
+    seq: AtomicUsize,
+}
+
+impl<T, const S: usize> RTTask<T, S> {
+    /// Creates a new [`RTTask`] from the inner task struct, with the
+    /// `SCHED_OTHER` policy and nice 0.
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            policy: AtomicUsize::new(SCHED_OTHER),
+            rt_priority: AtomicUsize::new(0),
+            time_slice: AtomicIsize::new(S as isize),
+            nice: AtomicIsize::new(0),
+            vruntime: AtomicIsize::new(0),
+            queued: AtomicU8::new(NOT_QUEUED),
+            seq: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the scheduling policy of the task.
+    pub fn policy(&self) -> usize {
+        self.policy.load(Ordering::Acquire)
+    }
+
+    /// Returns the real-time priority of the task, 0 for non real-time tasks.
+    pub fn rt_priority(&self) -> usize {
+        self.rt_priority.load(Ordering::Acquire)
+    }
+
+    /// Whether the task is scheduled by the real-time class.
+    pub fn is_rt(&self) -> bool {
+        matches!(self.policy(), SCHED_FIFO | SCHED_RR)
+    }
+
+    fn weight(&self) -> isize {
+        if self.policy() == SCHED_IDLE {
+            IDLE_WEIGHT
+        } else {
+            nice_to_weight(self.nice.load(Ordering::Acquire))
+        }
+    }
+
+    fn vruntime(&self) -> isize {
+        self.vruntime.load(Ordering::Acquire)
+    }
+
+    fn time_slice(&self) -> isize {
+        self.time_slice.load(Ordering::Acquire)
+    }
+
+    fn reset_time_slice(&self) {
+        self.time_slice.store(S as isize, Ordering::Release);
+    }
+
+    /// Returns a reference to the inner task struct.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T, const S: usize> Deref for RTTask<T, S> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// A layered scheduler with a real-time class above a fair class.
+///
+/// `SCHED_FIFO` and `SCHED_RR` tasks are kept in per-priority FIFO queues, and
+/// a runnable real-time task always runs before any `SCHED_OTHER`,
+/// `SCHED_BATCH` or `SCHED_IDLE` task. Among real-time tasks, the one with the
+/// highest priority runs first. `SCHED_FIFO` tasks run until they block or
+/// yield, while `SCHED_RR` tasks are rotated within their priority every
+/// `RR_TIME_SLICE` ticks.
+///
+/// Other tasks are scheduled like the [`CFScheduler`]: the task with the
+/// smallest virtual runtime runs first, and the virtual runtime grows slower
+/// for tasks with higher weights. `SCHED_IDLE` tasks get a minimal weight.
+///
+/// [`CFScheduler`]: crate::CFScheduler
+pub struct RTScheduler<T, const RR_TIME_SLICE: usize> {
+    rt_queue: BTreeMap<usize, VecDeque<Arc<RTTask<T, RR_TIME_SLICE>>>>,
+    fair_queue: BTreeMap<(isize, usize), Arc<RTTask<T, RR_TIME_SLICE>>>,
+    min_vruntime: isize,
+    next_seq: usize,
+}
+
+impl<T, const S: usize> RTScheduler<T, S> {
+    /// Creates a new empty [`RTScheduler`].
+    pub const fn new() -> Self {
+        Self {
+            rt_queue: BTreeMap::new(),
+            fair_queue: BTreeMap::new(),
+            min_vruntime: 0,
+            next_seq: 0,
+        }
+    }
+
+    /// get the name of scheduler
+    pub fn scheduler_name() -> &'static str {
+        "Real-time"
+    }
+
+    fn highest_rt_priority(&self) -> Option<usize> {
+        self.rt_queue.last_key_value().map(|(&prio, _)| prio)
+    }
+
+    fn enqueue(&mut self, task: Arc<RTTask<T, S>>, front: bool) {
+        if task.is_rt() {
+            task.queued.store(QUEUED_RT, Ordering::Release);
+            let queue = self.rt_queue.entry(task.rt_priority()).or_default();
+            if front {
+                queue.push_front(task);
+            } else {
+                queue.push_back(task);
+            }
+        } else {
+            let seq = self.next_seq;
+            self.next_seq += 1;
+            task.seq.store(seq, Ordering::Release);
+            task.queued.store(QUEUED_FAIR, Ordering::Release);
+            self.fair_queue.insert((task.vruntime(), seq), task);
+        }
+    }
+}
+
+impl<T, const S: usize> BaseScheduler for RTScheduler<T, S> {
+    type SchedItem = Arc<RTTask<T, S>>;
+
+    fn init(&mut self) {}
+
+    fn add_task(&mut self, task: Self::SchedItem) {
+        // A newly runnable task should not get an unfair advantage from the
+        // time it was sleeping.
+        if task.vruntime() < self.min_vruntime {
+            task.vruntime.store(self.min_vruntime, Ordering::Release);
+        }
+        self.enqueue(task, false);
+    }
+
+    fn remove_task(&mut self, task: &Self::SchedItem) -> Option<Self::SchedItem> {
+        let removed = match task.queued.load(Ordering::Acquire) {
+            QUEUED_RT => {
+                let prio = task.rt_priority();
+                let queue = self.rt_queue.get_mut(&prio)?;
+                let removed = queue
+                    .iter()
+                    .position(|t| Arc::ptr_eq(t, task))
+                    .and_then(|idx| queue.remove(idx));
+                if queue.is_empty() {
+                    self.rt_queue.remove(&prio);
+                }
+                removed
+            }
+            QUEUED_FAIR => self
+                .fair_queue
+                .remove(&(task.vruntime(), task.seq.load(Ordering::Acquire))),
+            _ => None,
+        };
+        if removed.is_some() {
+            task.queued.store(NOT_QUEUED, Ordering::Release);
+        }
+        removed
+    }
+
+    fn pick_next_task(&mut self) -> Option<Self::SchedItem> {
+        let task = if let Some(mut entry) = self.rt_queue.last_entry() {
+            let task = entry.get_mut().pop_front();
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+            task
+        } else {
+            let (_, task) = self.fair_queue.pop_first()?;
+            self.min_vruntime = self.min_vruntime.max(task.vruntime());
+            Some(task)
+        }?;
+        task.queued.store(NOT_QUEUED, Ordering::Release);
+        Some(task)
+    }
+
+    fn put_prev_task(&mut self, prev: Self::SchedItem, preempt: bool) {
+        match prev.policy() {
+            // A preempted real-time task stays at the head of its priority,
+            // unless it used up its time slice.
+            SCHED_FIFO => self.enqueue(prev, preempt),
+            SCHED_RR => {
+                if prev.time_slice() > 0 {
+                    self.enqueue(prev, preempt);
+                } else {
+                    prev.reset_time_slice();
+                    self.enqueue(prev, false);
+                }
+            }
+            _ => self.enqueue(prev, false),
+        }
+    }
+
+    fn task_tick(&mut self, current: &Self::SchedItem) -> bool {
+        let preempted_by_rt = self
+            .highest_rt_priority()
+            .is_some_and(|prio| prio > current.rt_priority() || !current.is_rt());
+        match current.policy() {
+            SCHED_FIFO => preempted_by_rt,
+            SCHED_RR => {
+                let old_slice = current.time_slice.fetch_sub(1, Ordering::Release);
+                old_slice <= 1 || preempted_by_rt
+            }
+            _ => {
+                current
+                    .vruntime
+                    .fetch_add(TICK_VRUNTIME * 1024 / current.weight(), Ordering::Release);
+                preempted_by_rt
+                    || self
+                        .fair_queue
+                        .first_key_value()
+                        .is_some_and(|(&(vruntime, _), _)| vruntime < current.vruntime())
+            }
+        }
+    }
+
+    fn set_priority(&mut self, task: &Self::SchedItem, prio: isize) -> bool {
+        if (-20..=19).contains(&prio) {
+            // The key of a queued task does not depend on its weight.
+            task.nice.store(prio, Ordering::Release);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn set_policy(&mut self, task: &Self::SchedItem, policy: usize, priority: usize) -> bool {
+        let valid = match policy {
+            SCHED_FIFO | SCHED_RR => (MIN_RT_PRIO..=MAX_RT_PRIO).contains(&priority),
+            SCHED_OTHER | SCHED_BATCH | SCHED_IDLE => priority == 0,
+            _ => false,
+        };
+        if !valid {
+            return false;
+        }
+        // Move the task to the queue of its new class and priority.
+        let queued = self.remove_task(task);
+        task.policy.store(policy, Ordering::Release);
+        task.rt_priority.store(priority, Ordering::Release);
+        task.reset_time_slice();
+        if let Some(task) = queued {
+            self.add_task(task);
+        }
+        true
+    }
+
+    fn should_preempt(&self, task: &Self::SchedItem, current: &Self::SchedItem) -> bool {
+        if task.is_rt() {
+            !current.is_rt() || task.rt_priority() > current.rt_priority()
+        } else {
+            !current.is_rt() && current.policy() == SCHED_IDLE && task.policy() != SCHED_IDLE
+        }
+    }
+}
@@ -82,3 +82,93 @@ macro_rules! def_test_sched {
 def_test_sched!(fifo, FifoScheduler::<usize>, FifoTask::<usize>);
 def_test_sched!(rr, RRScheduler::<usize, 5>, RRTask::<usize, 5>);
 def_test_sched!(cfs, CFScheduler::<usize>, CFSTask::<usize>);
+def_test_sched!(rt, RTScheduler::<usize, 5>, RTTask::<usize, 5>);
+
+mod rt_class {
+    use crate::*;
+    use alloc::sync::Arc;
+
+    #[test]
+    fn test_rt_before_fair() {
+        let mut scheduler = RTScheduler::<usize, 5>::new();
+        let tasks: Vec<_> = (0..4)
+            .map(|i| Arc::new(RTTask::<usize, 5>::new(i)))
+            .collect();
+        assert!(scheduler.set_policy(&tasks[2], SCHED_FIFO, 10));
+        assert!(scheduler.set_policy(&tasks[3], SCHED_RR, 50));
+        assert!(!scheduler.set_policy(&tasks[1], SCHED_FIFO, 0));
+        assert!(!scheduler.set_policy(&tasks[1], SCHED_OTHER, 1));
+        for task in &tasks {
+            scheduler.add_task(task.clone());
+        }
+
+        let order: Vec<_> = core::iter::from_fn(|| scheduler.pick_next_task())
+            .map(|t| *t.inner())
+            .collect();
+        assert_eq!(order, [3, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_rt_preempt() {
+        let mut scheduler = RTScheduler::<usize, 5>::new();
+        let fair = Arc::new(RTTask::<usize, 5>::new(0));
+        let idle = Arc::new(RTTask::<usize, 5>::new(1));
+        let low = Arc::new(RTTask::<usize, 5>::new(2));
+        let high = Arc::new(RTTask::<usize, 5>::new(3));
+        assert!(scheduler.set_policy(&idle, SCHED_IDLE, 0));
+        assert!(scheduler.set_policy(&low, SCHED_FIFO, 1));
+        assert!(scheduler.set_policy(&high, SCHED_FIFO, 99));
+
+        assert!(scheduler.should_preempt(&low, &fair));
+        assert!(scheduler.should_preempt(&high, &low));
+        assert!(!scheduler.should_preempt(&low, &high));
+        assert!(!scheduler.should_preempt(&fair, &low));
+        assert!(scheduler.should_preempt(&fair, &idle));
+
+        // A FIFO task is not preempted by ticks, until a higher priority task
+        // becomes runnable.
+        for _ in 0..10 {
+            assert!(!scheduler.task_tick(&low));
+        }
+        scheduler.add_task(high.clone());
+        assert!(scheduler.task_tick(&low));
+    }
+
+    #[test]
+    fn test_rr_time_slice() {
+        let mut scheduler = RTScheduler::<usize, 5>::new();
+        let tasks: Vec<_> = (0..2)
+            .map(|i| Arc::new(RTTask::<usize, 5>::new(i)))
+            .collect();
+        for task in &tasks {
+            assert!(scheduler.set_policy(task, SCHED_RR, 10));
+            scheduler.add_task(task.clone());
+        }
+        for i in 0..6 {
+            let next = scheduler.pick_next_task().unwrap();
+            assert_eq!(*next.inner(), i % 2);
+            for _ in 0..4 {
+                assert!(!scheduler.task_tick(&next));
+            }
+            assert!(scheduler.task_tick(&next));
+            scheduler.put_prev_task(next, true);
+        }
+    }
+
+    #[test]
+    fn test_change_policy_when_queued() {
+        let mut scheduler = RTScheduler::<usize, 5>::new();
+        let tasks: Vec<_> = (0..3)
+            .map(|i| Arc::new(RTTask::<usize, 5>::new(i)))
+            .collect();
+        for task in &tasks {
+            scheduler.add_task(task.clone());
+        }
+        assert!(scheduler.set_policy(&tasks[2], SCHED_RR, 1));
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 2);
+        assert!(scheduler.set_policy(&tasks[2], SCHED_OTHER, 0));
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 0);
+        assert_eq!(*scheduler.pick_next_task().unwrap().inner(), 1);
+        assert!(scheduler.pick_next_task().is_none());
+    }
+}
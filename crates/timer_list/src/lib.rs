@@ -31,7 +31,7 @@
 
 extern crate alloc;
 
-use alloc::{boxed::Box, collections::BinaryHeap};
+use alloc::{boxed::Box, collections::BinaryHeap, vec::Vec};
 use core::cmp::{Ord, Ordering, PartialOrd};
 use core::time::Duration;
 
@@ -107,6 +107,25 @@ impl<E: TimerEvent> TimerList<E> {
         self.events.retain(|e| !condition(&e.event));
     }
 
+    /// Remove all events that meet the condition, and return them along with
+    /// their deadlines.
+    pub fn remove<F>(&mut self, condition: F) -> Vec<(TimeValue, E)>
+    where
+        F: Fn(&E) -> bool,
+    {
+        let (removed, kept): (Vec<_>, Vec<_>) = core::mem::take(&mut self.events)
+            .into_vec()
+            .into_iter()
+            .partition(|e| condition(&e.event));
+        self.events = kept.into();
+        removed.into_iter().map(|e| (e.deadline, e.event)).collect()
+    }
+
+    /// Iterate over all events along with their deadlines, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (TimeValue, &E)> {
+        self.events.iter().map(|e| (e.deadline, &e.event))
+    }
+
     /// Get the deadline of the most recent event.
     #[inline]
     pub fn next_deadline(&self) -> Option<TimeValue> {
@@ -208,6 +227,43 @@ mod tests {
         assert_eq!(COUNT.load(Ordering::SeqCst), 4);
     }
 
+    #[test]
+    fn test_timer_list_remove() {
+        struct TestTimerEvent(usize);
+
+        impl TimerEvent for TestTimerEvent {
+            fn callback(self, _now: TimeValue) {}
+        }
+
+        let mut timer_list = TimerList::new();
+        for i in 0..6 {
+            timer_list.set(Duration::from_secs(6 - i as u64), TestTimerEvent(i));
+        }
+
+        let mut removed = timer_list.remove(|e| e.0 % 2 == 0);
+        removed.sort_by_key(|(deadline, _)| *deadline);
+        let removed: Vec<_> = removed.iter().map(|(_, e)| e.0).collect();
+        assert_eq!(removed, [4, 2, 0]);
+
+        let mut rest: Vec<_> = timer_list.iter().map(|(ddl, e)| (ddl, e.0)).collect();
+        rest.sort();
+        assert_eq!(
+            rest,
+            [
+                (Duration::from_secs(1), 5),
+                (Duration::from_secs(3), 3),
+                (Duration::from_secs(5), 1)
+            ]
+        );
+
+        // the remaining events still expire in order
+        let now = Duration::from_secs(4);
+        assert_eq!(timer_list.expire_one(now).map(|(_, e)| e.0), Some(5));
+        assert_eq!(timer_list.expire_one(now).map(|(_, e)| e.0), Some(3));
+        assert!(timer_list.expire_one(now).is_none());
+        assert_eq!(timer_list.next_deadline(), Some(Duration::from_secs(5)));
+    }
+
     #[test]
     fn test_timer_list_fn() {
         let mut timer_list = TimerList::new();
@@ -66,6 +66,13 @@ impl OpenOptions {
         self
     }
 
+    /// Sets the option to fail if the last component of the path is a
+    /// symbolic link.
+    pub fn nofollow(&mut self, nofollow: bool) -> &mut Self {
+        self.0.nofollow(nofollow);
+        self
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     pub fn open(&self, path: &str) -> Result<File> {
         fops::File::open(path, &self.0).map(|inner| File { inner })
@@ -8,10 +8,13 @@ pub mod port;
 pub use self::dir::{DirBuilder, DirEntry, ReadDir};
 pub use self::file::{File, FileType, Metadata, OpenOptions, Permissions};
 use axerrno::AxResult;
-use axfs_vfs::VfsNodeRef;
+pub use axfs_vfs::VfsNodeRef;
 pub use axio::{Read, Seek, SeekFrom, Write};
 pub use port::*;
 
+#[cfg(feature = "monolithic")]
+pub use crate::root::{FsContext, FsContextIf, ProcInfoIf};
+
 use alloc::{string::String, vec::Vec};
 #[allow(unused_imports)]
 use axio::{self as io, prelude::*};
@@ -37,6 +40,11 @@ pub fn set_current_dir(path: &str) -> io::Result<()> {
     crate::root::set_current_dir(path)
 }
 
+/// Changes the root directory of the current process to the specified path.
+pub fn set_root_dir(path: &str) -> io::Result<()> {
+    crate::root::set_root_dir(path)
+}
+
 /// Read the entire contents of a file into a bytes vector.
 pub fn read(path: &str) -> io::Result<Vec<u8>> {
     let mut file = File::open(path)?;
@@ -77,6 +85,15 @@ pub fn create_dir_all(path: &str) -> io::Result<()> {
     DirBuilder::new().recursive(true).create(path)
 }
 
+/// Creates a special file such as a socket at the provided path with the
+/// permission `perm`.
+///
+/// Returns [`AlreadyExists`](axerrno::AxError::AlreadyExists) if the path exists,
+/// even if it is a dangling symbolic link.
+pub fn create_node(path: &str, ty: FileType, perm: Permissions) -> io::Result<()> {
+    crate::root::create_node(None, path, ty, perm)
+}
+
 /// Removes an empty directory.
 pub fn remove_dir(path: &str) -> io::Result<()> {
     crate::root::remove_dir(None, path)
@@ -95,6 +112,24 @@ pub fn rename(old: &str, new: &str) -> io::Result<()> {
     crate::root::rename(old, new)
 }
 
+/// Creates a new hard link `new` to the existing file `old`.
+///
+/// If `old` is a symbolic link, the link itself is linked unless `follow` is
+/// true. Directories can not be hard linked.
+pub fn hard_link(old: &str, new: &str, follow: bool) -> io::Result<()> {
+    crate::root::hard_link(old, new, follow)
+}
+
+/// Creates a new symbolic link `path` whose content is `target`.
+pub fn symlink(target: &str, path: &str) -> io::Result<()> {
+    crate::root::symlink(target, path)
+}
+
+/// Reads the target of the symbolic link `path`.
+pub fn read_link(path: &str) -> io::Result<String> {
+    crate::root::read_link(path)
+}
+
 /// Check if a path exists.
 pub fn path_exists(path: &str) -> bool {
     crate::root::lookup(None, path).is_ok()
@@ -104,3 +139,16 @@ pub fn path_exists(path: &str) -> bool {
 pub fn lookup(path: &str) -> AxResult<VfsNodeRef> {
     crate::root::lookup(None, path)
 }
+
+/// Returns the directory holding all POSIX message queues, i.e. the root of the
+/// message queue filesystem mounted on `/dev/mqueue`.
+#[cfg(feature = "mqueuefs")]
+pub fn mqueue_dir() -> alloc::sync::Arc<axfs_mqueuefs::MqueueDir> {
+    crate::mounts::MQUEUE_FS.root_dir_node()
+}
+
+/// Look up a file by a given path without following the symbolic link at the
+/// last component.
+pub fn lookup_nofollow(path: &str) -> AxResult<VfsNodeRef> {
+    crate::root::lookup_at(None, path, false)
+}
@@ -110,6 +110,8 @@ bitflags! {
         const NOCTTY = 1 << 8;
         /// 同上，在不同的库中可能会用到这个或者上一个
         const EXCL = 1 << 9;
+        /// 每次写入前都将读写指针移动到文件末尾
+        const APPEND = 1 << 10;
         /// 非阻塞读写?(虽然不知道为什么但 date.lua 也要)
         /// 在 socket 中使用得较多
         const NON_BLOCK = 1 << 11;
@@ -158,6 +160,16 @@ impl OpenFlags {
         self.contains(Self::EXCLUSIVE)
     }
 
+    /// 获取是否以追加模式写入
+    pub fn is_append(&self) -> bool {
+        self.contains(Self::APPEND)
+    }
+
+    /// 获取是否不跟随路径最后一级的符号链接
+    pub fn is_nofollow(&self) -> bool {
+        self.contains(Self::NOFOLLOW)
+    }
+
     /// 获取是否是目录
     pub fn is_dir(&self) -> bool {
         self.contains(Self::DIR)
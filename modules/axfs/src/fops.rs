@@ -45,6 +45,7 @@ pub struct OpenOptions {
     truncate: bool,
     create: bool,
     create_new: bool,
+    nofollow: bool,
     // system-specific
     _custom_flags: i32,
     _mode: u32,
@@ -61,6 +62,7 @@ impl OpenOptions {
             truncate: false,
             create: false,
             create_new: false,
+            nofollow: false,
             // system-specific
             _custom_flags: 0,
             _mode: 0o666,
@@ -90,6 +92,11 @@ impl OpenOptions {
     pub fn create_new(&mut self, create_new: bool) {
         self.create_new = create_new;
     }
+    /// Sets the option to fail if the last component of the path is a
+    /// symbolic link.
+    pub fn nofollow(&mut self, nofollow: bool) {
+        self.nofollow = nofollow;
+    }
 
     const fn is_valid(&self) -> bool {
         if !self.read && !self.write && !self.append {
@@ -119,7 +126,7 @@ impl File {
             return ax_err!(InvalidInput);
         }
 
-        let node_option = crate::root::lookup(dir, path);
+        let node_option = crate::root::lookup_at(dir, path, !opts.nofollow);
         let node = if opts.create || opts.create_new {
             match node_option {
                 Ok(node) => {
@@ -138,6 +145,10 @@ impl File {
             node_option?
         };
         let attr = node.get_attr()?;
+        if attr.is_symlink() {
+            // only reachable with `nofollow`
+            return ax_err!(FilesystemLoop);
+        }
         if attr.is_dir()
             && (opts.create || opts.create_new || opts.write || opts.append || opts.truncate)
         {
@@ -388,6 +399,7 @@ impl fmt::Debug for OpenOptions {
         fmt_opt!(truncate, "TRUNC");
         fmt_opt!(create, "CREATE");
         fmt_opt!(create_new, "CREATE_NEW");
+        fmt_opt!(nofollow, "NOFOLLOW");
         Ok(())
     }
 }
@@ -1,16 +1,82 @@
 use crate::alloc::string::String;
-use alloc::sync::Arc;
-use axerrno::AxError;
+use alloc::collections::BTreeMap;
+use alloc::ffi::CString;
+use alloc::sync::{Arc, Weak};
+use axerrno::{AxError, LinuxError};
 use axfs_vfs::{VfsDirEntry, VfsError, VfsNodePerm, VfsResult};
 use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps};
 use axsync::Mutex;
+use core::ffi::{c_char, CStr};
 use lwext4_rust::bindings::{
-    O_CREAT, O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY, SEEK_CUR, SEEK_END, SEEK_SET,
+    ext4_flink, ext4_fsymlink, ext4_inode, ext4_mknod, ext4_raw_inode_fill, ext4_readlink, O_CREAT,
+    O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY, SEEK_CUR, SEEK_END, SEEK_SET,
 };
 use lwext4_rust::{Ext4BlockWrapper, Ext4File, InodeTypes, KernelDevOp};
 
 use crate::dev::Disk;
 const BLOCK_SIZE: usize = 512;
+/// The maximum length of the target of a symbolic link.
+const SYMLINK_MAX: usize = 4096;
+
+/// Converts the return value of the lwext4 C API, which is an errno, into a [`VfsResult`].
+fn ext4_result(ret: i32) -> VfsResult {
+    if ret == 0 {
+        return Ok(());
+    }
+    Err(match LinuxError::try_from(ret) {
+        Ok(LinuxError::EPERM) => VfsError::OperationNotPermitted,
+        Ok(LinuxError::ENOENT) => VfsError::NotFound,
+        Ok(LinuxError::EEXIST) => VfsError::AlreadyExists,
+        Ok(LinuxError::ENOTDIR) => VfsError::NotADirectory,
+        Ok(LinuxError::EISDIR) => VfsError::IsADirectory,
+        Ok(LinuxError::ENOSPC) => VfsError::StorageFull,
+        Ok(LinuxError::ENOMEM) => VfsError::NoMemory,
+        Ok(LinuxError::EINVAL) => VfsError::InvalidInput,
+        Ok(LinuxError::ENOTSUP) => VfsError::Unsupported,
+        _ => VfsError::Io,
+    })
+}
+
+/// Reads the target of the symbolic link at `path` into `buf`.
+fn read_link_at(path: &CStr, buf: &mut [u8]) -> VfsResult<usize> {
+    let mut read_len = 0;
+    ext4_result(unsafe {
+        ext4_readlink(
+            path.as_ptr(),
+            buf.as_mut_ptr() as *mut c_char,
+            buf.len(),
+            &mut read_len,
+        )
+    })?;
+    Ok(read_len)
+}
+
+/// Reads the number of hard links to the inode at `path`.
+fn links_count_at(path: &CStr) -> VfsResult<u64> {
+    let mut ino = 0;
+    // SAFETY: `ext4_inode` is a plain C struct, for which all zeros is valid.
+    let mut inode: ext4_inode = unsafe { core::mem::zeroed() };
+    ext4_result(unsafe { ext4_raw_inode_fill(path.as_ptr(), &mut ino, &mut inode) })?;
+    Ok(u16::from_le(inode.links_count) as u64)
+}
+
+/// FIFO nodes which are still referenced, keyed by their full paths.
+///
+/// A new [`FileWrapper`] is created on every lookup, but the kernel tells FIFOs
+/// apart by their nodes, so the same node must be returned while it is in use.
+static FIFO_NODES: Mutex<BTreeMap<String, Weak<FileWrapper>>> = Mutex::new(BTreeMap::new());
+
+/// Returns the node of the FIFO at the full path `path`.
+fn fifo_node(path: &str) -> VfsNodeRef {
+    let mut nodes = FIFO_NODES.lock();
+    if let Some(node) = nodes.get(path).and_then(Weak::upgrade) {
+        return node;
+    }
+    nodes.retain(|_, node| node.strong_count() != 0);
+    let node = Arc::new(FileWrapper::new(path, InodeTypes::EXT4_DE_FIFO));
+    nodes.insert(path.into(), Arc::downgrade(&node));
+    node
+}
 
 #[allow(dead_code)]
 pub struct Ext4FileSystem {
@@ -124,6 +190,10 @@ impl VfsNodeOps for FileWrapper {
             let fsize = file.file_size();
             let _ = file.file_close();
             fsize
+        } else if vtype == VfsNodeType::SymLink {
+            // the size of a symbolic link is the length of its target
+            let mut buf = [0u8; SYMLINK_MAX];
+            read_link_at(&file.get_path(), &mut buf)? as u64
         } else {
             0 // DIR size ?
         };
@@ -137,7 +207,9 @@ impl VfsNodeOps for FileWrapper {
             blocks
         );
 
-        Ok(VfsNodeAttr::new(perm, vtype, size, blocks))
+        let mut attr = VfsNodeAttr::new(perm, vtype, size, blocks);
+        attr.set_nlink(links_count_at(&file.get_path())?);
+        Ok(attr)
     }
 
     fn create(&self, path: &str, ty: VfsNodeType) -> VfsResult {
@@ -166,6 +238,10 @@ impl VfsNodeOps for FileWrapper {
                 file.dir_mk(fpath)
                     .map(|_v| ())
                     .map_err(|e| e.try_into().unwrap())
+            } else if types == InodeTypes::EXT4_DE_FIFO || types == InodeTypes::EXT4_DE_SOCK {
+                // special files have no content and are created by mknod
+                let fpath = CString::new(fpath).map_err(|_| VfsError::InvalidInput)?;
+                ext4_result(unsafe { ext4_mknod(fpath.as_ptr(), types as i32, 0) })
             } else {
                 file.file_open(fpath, O_WRONLY | O_CREAT | O_TRUNC)
                     .expect("create file failed");
@@ -190,6 +266,8 @@ impl VfsNodeOps for FileWrapper {
                 .map(|_v| ())
                 .map_err(|e| e.try_into().unwrap())
         } else {
+            // a FIFO created later at the same path is a different one
+            FIFO_NODES.lock().remove(fpath);
             file.file_remove(fpath)
                 .map(|_v| ())
                 .map_err(|e| e.try_into().unwrap())
@@ -232,6 +310,10 @@ impl VfsNodeOps for FileWrapper {
                         VfsNodeType::File
                     } else if *t == InodeTypes::EXT4_DE_SYMLINK {
                         VfsNodeType::SymLink
+                    } else if *t == InodeTypes::EXT4_DE_FIFO {
+                        VfsNodeType::Fifo
+                    } else if *t == InodeTypes::EXT4_DE_SOCK {
+                        VfsNodeType::Socket
                     } else {
                         error!("unknown file type: {:?}", itypes);
                         unreachable!()
@@ -266,6 +348,15 @@ impl VfsNodeOps for FileWrapper {
         } else if file.check_inode_exist(fpath, InodeTypes::EXT4_DE_REG_FILE) {
             debug!("lookup new FILE FileWrapper");
             Ok(Arc::new(Self::new(fpath, InodeTypes::EXT4_DE_REG_FILE)))
+        } else if file.check_inode_exist(fpath, InodeTypes::EXT4_DE_SYMLINK) {
+            debug!("lookup new SYMLINK FileWrapper");
+            Ok(Arc::new(Self::new(fpath, InodeTypes::EXT4_DE_SYMLINK)))
+        } else if file.check_inode_exist(fpath, InodeTypes::EXT4_DE_SOCK) {
+            debug!("lookup new SOCKET FileWrapper");
+            Ok(Arc::new(Self::new(fpath, InodeTypes::EXT4_DE_SOCK)))
+        } else if file.check_inode_exist(fpath, InodeTypes::EXT4_DE_FIFO) {
+            debug!("lookup FIFO FileWrapper");
+            Ok(fifo_node(fpath))
         } else {
             Err(VfsError::NotFound)
         }
@@ -324,6 +415,50 @@ impl VfsNodeOps for FileWrapper {
             .map(|_v| ())
             .map_err(|e| e.try_into().unwrap())
     }
+
+    fn link(&self, path: &str, target: &VfsNodeRef) -> VfsResult {
+        info!("link on Ext4fs: {}", path);
+        let target = target
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or(VfsError::CrossesDevices)?;
+        let target_path = target.0.lock().get_path();
+        if target.get_attr()?.is_dir() {
+            // hard links to directories are not allowed
+            return Err(VfsError::OperationNotPermitted);
+        }
+        let fpath = self.path_deal_with(path);
+        if fpath.is_empty() {
+            return Err(VfsError::AlreadyExists);
+        }
+        let fpath = CString::new(fpath).map_err(|_| VfsError::InvalidInput)?;
+        let _file = self.0.lock();
+        ext4_result(unsafe { ext4_flink(target_path.as_ptr(), fpath.as_ptr()) })
+    }
+
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        info!("symlink on Ext4fs: {} -> {}", path, target);
+        let fpath = self.path_deal_with(path);
+        if fpath.is_empty() {
+            return Err(VfsError::AlreadyExists);
+        }
+        let fpath = CString::new(fpath).map_err(|_| VfsError::InvalidInput)?;
+        let target = CString::new(target).map_err(|_| VfsError::InvalidInput)?;
+        let _file = self.0.lock();
+        ext4_result(unsafe { ext4_fsymlink(target.as_ptr(), fpath.as_ptr()) })
+    }
+
+    fn readlink(&self, buf: &mut [u8]) -> VfsResult<usize> {
+        let mut file = self.0.lock();
+        if !matches!(file.file_type_get(), InodeTypes::EXT4_INODE_MODE_SOFTLINK) {
+            return Err(VfsError::InvalidInput);
+        }
+        read_link_at(&file.get_path(), buf)
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
 }
 
 impl Drop for FileWrapper {
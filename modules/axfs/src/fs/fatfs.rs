@@ -237,6 +237,16 @@ impl VfsNodeOps for DirWrapper<'static> {
             .rename(src_path, &self.0, dst_path)
             .map_err(as_vfs_err)
     }
+
+    fn link(&self, _path: &str, _target: &VfsNodeRef) -> VfsResult {
+        // FAT fs doesn't support hard links
+        Err(VfsError::OperationNotPermitted)
+    }
+
+    fn symlink(&self, _path: &str, _target: &str) -> VfsResult {
+        // FAT fs doesn't support symbolic links
+        Err(VfsError::OperationNotPermitted)
+    }
 }
 
 impl VfsOps for FatFileSystem {
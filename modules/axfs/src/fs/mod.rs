@@ -17,3 +17,6 @@ pub use axfs_devfs as devfs;
 
 #[cfg(feature = "ramfs")]
 pub use axfs_ramfs as ramfs;
+
+#[cfg(feature = "mqueuefs")]
+pub use axfs_mqueuefs as mqueuefs;
@@ -10,6 +10,9 @@
 //!    **enabled** by default.
 //! - `ramfs`: Mount [`axfs_ramfs::RamFileSystem`] on `/tmp`. This feature is
 //!    **enabled** by default.
+//! - `mqueuefs`: Mount [`axfs_mqueuefs::MqueueFileSystem`] on `/dev/mqueue`,
+//!    which holds the POSIX message queues. This feature is **enabled** by
+//!    default.
 //! - `myfs`: Allow users to define their custom filesystems to override the
 //!    default. In this case, [`MyFileSystemIf`] is required to be implemented
 //!    to create and initialize other filesystems. This feature is **disabled** by
@@ -36,6 +39,8 @@ pub mod api;
 pub mod fops;
 
 pub use axfs_devfs;
+#[cfg(feature = "mqueuefs")]
+pub use axfs_mqueuefs;
 pub use axfs_ramfs;
 
 use axdriver::{prelude::*, AxDeviceContainer};
@@ -1,5 +1,7 @@
 use alloc::sync::Arc;
 use axfs_vfs::{VfsNodeType, VfsOps, VfsResult};
+#[cfg(feature = "mqueuefs")]
+use lazy_init::LazyInit;
 
 use crate::fs;
 
@@ -38,6 +40,37 @@ pub(crate) fn ramfs() -> Arc<fs::ramfs::RamFileSystem> {
     Arc::new(fs::ramfs::RamFileSystem::new())
 }
 
+/// The only message queue filesystem, which is mounted on `/dev/mqueue`
+#[cfg(feature = "mqueuefs")]
+pub(crate) static MQUEUE_FS: LazyInit<Arc<fs::mqueuefs::MqueueFileSystem>> = LazyInit::new();
+
+/// Blocks the tasks waiting on a message queue with [`axtask::WaitQueue`]
+#[cfg(feature = "mqueuefs")]
+struct MqueueWaitQueue(axtask::WaitQueue);
+
+#[cfg(feature = "mqueuefs")]
+impl fs::mqueuefs::WaitQueueOps for MqueueWaitQueue {
+    fn wait_if(&self, condition: &dyn Fn() -> bool) {
+        self.0.wait_if(condition);
+    }
+
+    fn wait_timeout_if(&self, dur: core::time::Duration, condition: &dyn Fn() -> bool) {
+        self.0.wait_timeout_if(dur, condition);
+    }
+
+    fn notify_all(&self) {
+        self.0.notify_all(false);
+    }
+}
+
+#[cfg(feature = "mqueuefs")]
+pub(crate) fn mqueuefs() -> Arc<fs::mqueuefs::MqueueFileSystem> {
+    MQUEUE_FS.init_by(Arc::new(fs::mqueuefs::MqueueFileSystem::new(|| {
+        alloc::boxed::Box::new(MqueueWaitQueue(axtask::WaitQueue::new()))
+    })));
+    MQUEUE_FS.clone()
+}
+
 #[cfg(feature = "procfs")]
 pub(crate) fn procfs() -> VfsResult<Arc<fs::ramfs::RamFileSystem>> {
     let procfs = fs::ramfs::RamFileSystem::new();
@@ -68,6 +101,12 @@ pub(crate) fn procfs() -> VfsResult<Arc<fs::ramfs::RamFileSystem>> {
         proc_root.create("meminfo", VfsNodeType::File)?;
         proc_root.create("mounts", VfsNodeType::File)?;
         proc_root.create("interrupts", VfsNodeType::File)?;
+        procfs.root_dir_node().add_node(
+            "timer_list",
+            Arc::new(fs::ramfs::GeneratedFileNode::new(
+                crate::root::timer_list_info,
+            )),
+        )?;
         // procfs.mount("interrupts", Arc::new(fs::devfs::Interrupts::default()))?;
     }
     Ok(Arc::new(procfs))
@@ -5,17 +5,118 @@
 use alloc::{
     string::{String, ToString},
     sync::Arc,
+    vec,
     vec::Vec,
 };
 use axerrno::{ax_err, AxError, AxResult};
-use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps, VfsResult};
+use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeRef, VfsNodeType, VfsOps, VfsResult};
 use axsync::Mutex;
 use lazy_init::LazyInit;
 
 use crate::{api::FileType, fs, mounts};
 
-static CURRENT_DIR_PATH: Mutex<String> = Mutex::new(String::new());
-static CURRENT_DIR: LazyInit<Mutex<VfsNodeRef>> = LazyInit::new();
+/// The maximum number of symbolic links followed during one path lookup,
+/// which is the same as Linux.
+const MAX_SYMLINK_FOLLOWS: usize = 40;
+
+/// The maximum length of a path, including the target of a symbolic link.
+const PATH_MAX: usize = 4096;
+
+/// The filesystem context of a process, i.e. its root directory and current
+/// working directory.
+///
+/// Relative paths are looked up from `cwd`, and absolute paths are looked up
+/// from `root`. Processes created with `CLONE_FS` share the same context.
+#[derive(Clone)]
+pub struct FsContext {
+    root: VfsNodeRef,
+    cwd: VfsNodeRef,
+    /// The absolute path of `cwd`, always ends with '/'.
+    cwd_path: String,
+    /// Whether `cwd` is outside `root` after a chroot, in which case
+    /// `cwd_path` is relative to the previous root.
+    cwd_unreachable: bool,
+}
+
+impl Default for FsContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsContext {
+    /// Creates a context whose root and current directory are both the root
+    /// of the whole filesystem.
+    pub fn new() -> Self {
+        let root: VfsNodeRef = ROOT_DIR.clone();
+        Self {
+            root: root.clone(),
+            cwd: root,
+            cwd_path: "/".into(),
+            cwd_unreachable: false,
+        }
+    }
+
+    /// The root directory node of this context.
+    pub fn root(&self) -> VfsNodeRef {
+        self.root.clone()
+    }
+
+    /// The current working directory node of this context.
+    pub fn cwd(&self) -> VfsNodeRef {
+        self.cwd.clone()
+    }
+
+    /// The absolute path of the current working directory.
+    pub fn cwd_path(&self) -> &str {
+        &self.cwd_path
+    }
+
+    /// The path of the current working directory reported by `getcwd`, which
+    /// is prefixed with "(unreachable)" if it is outside the root directory.
+    pub fn display_cwd_path(&self) -> String {
+        if self.cwd_unreachable {
+            String::from("(unreachable)") + &self.cwd_path
+        } else {
+            self.cwd_path.clone()
+        }
+    }
+}
+
+/// The interface to get the filesystem context of the current process.
+///
+/// If it returns `None` (e.g. no process is running yet), the global default
+/// context will be used.
+#[cfg(feature = "monolithic")]
+#[crate_interface::def_interface]
+pub trait FsContextIf {
+    /// Returns the filesystem context of the current process.
+    fn current_fs_context() -> Option<Arc<Mutex<FsContext>>>;
+}
+
+/// The interface to generate the content of the files in procfs which are
+/// provided by other modules.
+#[cfg(feature = "monolithic")]
+#[crate_interface::def_interface]
+pub trait ProcInfoIf {
+    /// Returns the content of `/proc/timer_list`.
+    fn timer_list() -> String;
+}
+
+#[cfg(all(feature = "procfs", feature = "monolithic"))]
+pub(crate) fn timer_list_info() -> String {
+    crate_interface::call_interface!(ProcInfoIf::timer_list())
+}
+
+static DEFAULT_FS_CONTEXT: LazyInit<Arc<Mutex<FsContext>>> = LazyInit::new();
+
+fn current_fs_context() -> Arc<Mutex<FsContext>> {
+    #[cfg(feature = "monolithic")]
+    if let Some(context) = crate_interface::call_interface!(FsContextIf::current_fs_context()) {
+        return context;
+    }
+    DEFAULT_FS_CONTEXT.clone()
+}
 
 struct MountPoint {
     path: &'static str,
@@ -155,6 +256,26 @@ impl VfsNodeOps for RootDirectory {
             }
         })
     }
+
+    fn link(&self, path: &str, target: &VfsNodeRef) -> VfsResult {
+        self.lookup_mounted_fs(path, |fs, rest_path| {
+            if rest_path.is_empty() {
+                ax_err!(AlreadyExists)
+            } else {
+                fs.root_dir().link(rest_path, target)
+            }
+        })
+    }
+
+    fn symlink(&self, path: &str, target: &str) -> VfsResult {
+        self.lookup_mounted_fs(path, |fs, rest_path| {
+            if rest_path.is_empty() {
+                ax_err!(AlreadyExists)
+            } else {
+                fs.root_dir().symlink(rest_path, target)
+            }
+        })
+    }
 }
 
 pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
@@ -180,6 +301,12 @@ pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
         .mount("/dev", mounts::devfs())
         .expect("failed to mount devfs at /dev");
 
+    // Mount the message queue filesystem inside devfs, like Linux
+    #[cfg(feature = "mqueuefs")]
+    root_dir
+        .mount("/dev/mqueue", mounts::mqueuefs())
+        .expect("failed to mount mqueuefs at /dev/mqueue");
+
     #[cfg(feature = "ramfs")]
     root_dir
         .mount("/tmp", mounts::ramfs())
@@ -190,6 +317,15 @@ pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
         .mount("/var", mounts::ramfs())
         .expect("failed to mount ramfs at /tmp");
 
+    // FAT has no symbolic links, so keep the directories that hold the links
+    // to the dynamic loaders, shared libraries and busybox applets in memory
+    #[cfg(all(feature = "fatfs", feature = "ramfs", not(feature = "myfs")))]
+    for path in ["/bin", "/lib", "/lib64"] {
+        root_dir
+            .mount(path, mounts::ramfs())
+            .expect("failed to mount ramfs for links");
+    }
+
     // Mount another ramfs as procfs
     #[cfg(feature = "procfs")]
     root_dir // should not fail
@@ -203,15 +339,28 @@ pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
         .expect("fail to mount sysfs at /sys");
 
     ROOT_DIR.init_by(Arc::new(root_dir));
-    CURRENT_DIR.init_by(Mutex::new(ROOT_DIR.clone()));
-    *CURRENT_DIR_PATH.lock() = "/".into();
+    DEFAULT_FS_CONTEXT.init_by(Arc::new(Mutex::new(FsContext::new())));
 }
 
-fn parent_node_of(dir: Option<&VfsNodeRef>, path: &str) -> VfsNodeRef {
+/// Returns the node to look up `path` from and the path relative to it.
+///
+/// A relative path is looked up from the root with the path of the current
+/// directory prepended, so that `..` stops at the root of the process. Only
+/// if the current directory is outside the root after a chroot, it is looked
+/// up from the current directory node, from which `..` can leave the root
+/// as on Linux.
+fn start_of(dir: Option<&VfsNodeRef>, path: &str) -> (VfsNodeRef, String) {
+    if let Some(dir) = dir.filter(|_| !path.starts_with('/')) {
+        return (dir.clone(), path.into());
+    }
+    let context = current_fs_context();
+    let context = context.lock();
     if path.starts_with('/') {
-        ROOT_DIR.clone()
+        (context.root(), path.into())
+    } else if context.cwd_unreachable {
+        (context.cwd(), path.into())
     } else {
-        dir.cloned().unwrap_or_else(|| CURRENT_DIR.lock().clone())
+        (context.root(), context.cwd_path.clone() + path)
     }
 }
 
@@ -219,16 +368,111 @@ pub(crate) fn absolute_path(path: &str) -> AxResult<String> {
     if path.starts_with('/') {
         Ok(axfs_vfs::path::canonicalize(path))
     } else {
-        let path = CURRENT_DIR_PATH.lock().clone() + path;
+        let path = current_fs_context().lock().cwd_path().to_string() + path;
         Ok(axfs_vfs::path::canonicalize(&path))
     }
 }
 
+/// Resolves the symbolic links in `path`, which is looked up from `dir`, or
+/// from the root or the current directory of the current process if `dir` is
+/// `None`.
+///
+/// Returns the node to start from and a path relative to it which contains no
+/// symbolic links, except the last component if `follow` is false. The last
+/// component is always followed if `path` ends with '/', and it is allowed to
+/// be missing so that it can be created later.
+fn resolve_path(
+    dir: Option<&VfsNodeRef>,
+    path: &str,
+    follow: bool,
+) -> AxResult<(VfsNodeRef, String)> {
+    let follow = follow || path.ends_with('/');
+    let (mut base, path) = start_of(dir, path);
+    let mut from_root = path.starts_with('/');
+    let mut resolved: Vec<String> = Vec::new();
+    // components to be resolved, in reverse order
+    let mut pending: Vec<String> = path.rsplit('/').map(String::from).collect();
+    let mut follows = 0;
+    while let Some(name) = pending.pop() {
+        if name.is_empty() || name == "." {
+            continue;
+        } else if name == ".." {
+            if resolved.last().is_some_and(|last| last != "..") {
+                resolved.pop();
+            } else if !from_root {
+                resolved.push(name);
+            }
+            // `..` of the root directory is itself
+            continue;
+        }
+        resolved.push(name);
+        let is_last = pending.iter().all(String::is_empty);
+        if is_last && !follow {
+            break;
+        }
+        let node = match base.clone().lookup(&join_path(from_root, &resolved)) {
+            Ok(node) => node,
+            Err(AxError::NotFound) if is_last => break,
+            Err(e) => return Err(e),
+        };
+        let attr = node.get_attr()?;
+        if !attr.is_symlink() {
+            if !is_last && !attr.is_dir() {
+                return ax_err!(NotADirectory);
+            }
+            continue;
+        }
+        follows += 1;
+        if follows > MAX_SYMLINK_FOLLOWS {
+            return ax_err!(FilesystemLoop);
+        }
+        let target = read_link_node(&node)?;
+        // an absolute target restarts from the root, and a relative one
+        // from the directory containing the link
+        resolved.pop();
+        if target.starts_with('/') {
+            base = current_fs_context().lock().root();
+            from_root = true;
+            resolved.clear();
+        }
+        pending.extend(target.rsplit('/').map(String::from));
+    }
+    Ok((base, join_path(from_root, &resolved)))
+}
+
+fn join_path(from_root: bool, components: &[String]) -> String {
+    if from_root {
+        String::from("/") + &components.join("/")
+    } else if components.is_empty() {
+        String::from(".")
+    } else {
+        components.join("/")
+    }
+}
+
+fn read_link_node(node: &VfsNodeRef) -> AxResult<String> {
+    let mut buf = vec![0u8; PATH_MAX];
+    let len = node.readlink(&mut buf)?;
+    buf.truncate(len);
+    String::from_utf8(buf).map_err(|_| AxError::InvalidData)
+}
+
 pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+    lookup_at(dir, path, true)
+}
+
+/// Looks up `path`, the last component is not followed if it is a symbolic
+/// link and `follow` is false.
+pub(crate) fn lookup_at(
+    dir: Option<&VfsNodeRef>,
+    path: &str,
+    follow: bool,
+) -> AxResult<VfsNodeRef> {
     if path.is_empty() {
         return ax_err!(NotFound);
     }
-    let node = parent_node_of(dir, path).lookup(path)?;
+    let (base, resolved) = resolve_path(dir, path, follow)?;
+    let node = base.lookup(&resolved)?;
     if path.ends_with('/') && !node.get_attr()?.is_dir() {
         ax_err!(NotADirectory)
     } else {
@@ -242,28 +486,66 @@ pub(crate) fn create_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsN
     } else if path.ends_with('/') {
         return ax_err!(NotADirectory);
     }
-    let parent = parent_node_of(dir, path);
-    parent.create(path, VfsNodeType::File)?;
-    parent.lookup(path)
+    // like Linux, a dangling symbolic link creates its target
+    let (base, resolved) = resolve_path(dir, path, true)?;
+    base.create(&resolved, VfsNodeType::File)?;
+    base.lookup(&resolved)
 }
 
 pub(crate) fn create_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-    match lookup(dir, path) {
+    if path.is_empty() {
+        return ax_err!(NotFound);
+    }
+    let (base, resolved) = resolve_path(dir, path, false)?;
+    match base.clone().lookup(&resolved) {
         Ok(_) => ax_err!(AlreadyExists),
-        Err(AxError::NotFound) => parent_node_of(dir, path).create(path, VfsNodeType::Dir),
+        Err(AxError::NotFound) => base.create(&resolved, VfsNodeType::Dir),
+        Err(e) => Err(e),
+    }
+}
+
+/// Creates a node of type `ty` without content, such as a socket.
+///
+/// The permission `perm` is ignored if the filesystem does not keep it.
+pub(crate) fn create_node(
+    dir: Option<&VfsNodeRef>,
+    path: &str,
+    ty: VfsNodeType,
+    perm: VfsNodePerm,
+) -> AxResult {
+    if path.is_empty() {
+        return ax_err!(NotFound);
+    } else if path.ends_with('/') {
+        return ax_err!(NotADirectory);
+    }
+    let (base, resolved) = resolve_path(dir, path, false)?;
+    match base.clone().lookup(&resolved) {
+        Ok(_) => return ax_err!(AlreadyExists),
+        Err(AxError::NotFound) => base.create(&resolved, ty)?,
+        Err(e) => return Err(e),
+    }
+    match base.lookup(&resolved)?.set_perm(perm) {
+        Ok(()) | Err(AxError::Unsupported) => Ok(()),
         Err(e) => Err(e),
     }
 }
 
 pub(crate) fn remove_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-    let node = lookup(dir, path)?;
+    if path.is_empty() {
+        return ax_err!(NotFound);
+    }
+    // the link itself is removed instead of its target
+    let (base, resolved) = resolve_path(dir, path, false)?;
+    let node = base.clone().lookup(&resolved)?;
     let attr = node.get_attr()?;
     if attr.is_dir() {
         ax_err!(IsADirectory)
+    } else if path.ends_with('/') {
+        ax_err!(NotADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        base.remove(&resolved)
     }
 }
 
@@ -285,19 +567,33 @@ pub(crate) fn remove_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
         return ax_err!(PermissionDenied);
     }
 
-    let node = lookup(dir, path)?;
+    let (base, resolved) = resolve_path(dir, path.trim_end_matches('/'), false)?;
+    let node = base.clone().lookup(&resolved)?;
     let attr = node.get_attr()?;
     if !attr.is_dir() {
         ax_err!(NotADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        base.remove(&resolved)
     }
 }
 
 pub(crate) fn current_dir() -> AxResult<String> {
-    Ok(CURRENT_DIR_PATH.lock().clone())
+    Ok(current_fs_context().lock().display_cwd_path())
+}
+
+/// Looks up `path` and checks that it is a directory which can be entered.
+fn lookup_dir(path: &str) -> AxResult<VfsNodeRef> {
+    let node = lookup(None, path)?;
+    let attr = node.get_attr()?;
+    if !attr.is_dir() {
+        ax_err!(NotADirectory)
+    } else if !attr.perm().owner_executable() {
+        ax_err!(PermissionDenied)
+    } else {
+        Ok(node)
+    }
 }
 
 pub(crate) fn set_current_dir(path: &str) -> AxResult {
@@ -305,29 +601,87 @@ pub(crate) fn set_current_dir(path: &str) -> AxResult {
     if !abs_path.ends_with('/') {
         abs_path += "/";
     }
-    if abs_path == "/" {
-        *CURRENT_DIR.lock() = ROOT_DIR.clone();
-        *CURRENT_DIR_PATH.lock() = "/".into();
+    let context = current_fs_context();
+    if abs_path == "/" && !context.lock().cwd_unreachable {
+        let mut context = context.lock();
+        context.cwd = context.root();
+        context.cwd_path = "/".into();
         return Ok(());
     }
 
-    let node = lookup(None, &abs_path)?;
-    let attr = node.get_attr()?;
-    if !attr.is_dir() {
-        ax_err!(NotADirectory)
-    } else if !attr.perm().owner_executable() {
-        ax_err!(PermissionDenied)
+    let node = lookup_dir(path)?;
+    let mut context = context.lock();
+    context.cwd = node;
+    context.cwd_path = abs_path;
+    // an absolute path always leads into the root
+    if path.starts_with('/') {
+        context.cwd_unreachable = false;
+    }
+    Ok(())
+}
+
+pub(crate) fn set_root_dir(path: &str) -> AxResult {
+    let mut abs_path = absolute_path(path)?;
+    if !abs_path.ends_with('/') {
+        abs_path += "/";
+    }
+    let node = if abs_path == "/" {
+        current_fs_context().lock().root()
     } else {
-        *CURRENT_DIR.lock() = node;
-        *CURRENT_DIR_PATH.lock() = abs_path;
-        Ok(())
+        lookup_dir(path)?
+    };
+    // Like Linux, the current working directory is not changed by chroot,
+    // but its path is now relative to the new root if it is inside it.
+    let context = current_fs_context();
+    let mut context = context.lock();
+    context.root = node;
+    if abs_path != "/" && !context.cwd_unreachable {
+        match context.cwd_path.strip_prefix(abs_path.as_str()) {
+            Some(rest) => context.cwd_path = String::from("/") + rest,
+            None => context.cwd_unreachable = true,
+        }
     }
+    Ok(())
 }
 
 pub(crate) fn rename(old: &str, new: &str) -> AxResult {
-    if parent_node_of(None, new).lookup(new).is_ok() {
+    // rename moves the links themselves instead of their targets
+    let (new_base, new) = resolve_path(None, new, false)?;
+    if new_base.clone().lookup(&new).is_ok() {
         warn!("dst file already exist, now remove it");
-        remove_file(None, new)?;
+        new_base.remove(&new)?;
+    }
+    let (old_base, old) = resolve_path(None, old, false)?;
+    old_base.rename(&old, &new)
+}
+
+pub(crate) fn hard_link(old: &str, new: &str, follow: bool) -> AxResult {
+    let target = lookup_at(None, old, follow)?;
+    if target.get_attr()?.is_dir() {
+        return ax_err!(OperationNotPermitted);
+    }
+    let (base, new) = resolve_path(None, new, false)?;
+    if base.clone().lookup(&new).is_ok() {
+        return ax_err!(AlreadyExists);
+    }
+    base.link(&new, &target)
+}
+
+pub(crate) fn symlink(target: &str, path: &str) -> AxResult {
+    if target.is_empty() || path.is_empty() {
+        return ax_err!(NotFound);
+    }
+    let (base, path) = resolve_path(None, path, false)?;
+    if base.clone().lookup(&path).is_ok() {
+        return ax_err!(AlreadyExists);
+    }
+    base.symlink(&path, target)
+}
+
+pub(crate) fn read_link(path: &str) -> AxResult<String> {
+    let node = lookup_at(None, path, false)?;
+    if !node.get_attr()?.is_symlink() {
+        return ax_err!(InvalidInput);
     }
-    parent_node_of(None, old).rename(old, new)
+    read_link_node(&node)
 }
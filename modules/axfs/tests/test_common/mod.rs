@@ -231,8 +231,12 @@ fn test_devfs_ramfs() -> Result<()> {
     // parent of '/dev'
     assert_eq!(fs::create_dir("///dev//..//233//"), Ok(()));
     assert_eq!(fs::write(".///dev//..//233//.///test.txt", "test"), Ok(()));
-    assert_err!(fs::remove_file("./dev//../..//233//.///test.txt"), NotFound);
-    assert_eq!(fs::remove_file("./dev//..//233//../233/./test.txt"), Ok(()));
+    // `..` of the root directory is itself, even in a relative path
+    assert_eq!(fs::remove_file("./dev//../..//233//.///test.txt"), Ok(()));
+    assert_err!(
+        fs::remove_file("./dev//..//233//../233/./test.txt"),
+        NotFound
+    );
     assert_eq!(fs::remove_dir("dev//foo/../foo/../.././/233"), Ok(()));
     assert_err!(fs::remove_dir("very/../dev//"), PermissionDenied);
 
@@ -252,6 +256,32 @@ fn test_devfs_ramfs() -> Result<()> {
     Ok(())
 }
 
+fn test_chroot() -> Result<()> {
+    fs::create_dir("/jail")?;
+    fs::write("/outside.txt", "outside")?;
+    fs::write("/jail/inside.txt", "inside")?;
+
+    // the current directory is left outside the new root
+    fs::set_current_dir("/")?;
+    fs::set_root_dir("/jail")?;
+    assert_eq!(fs::current_dir()?, "(unreachable)/");
+    assert_eq!(fs::read("/inside.txt"), Ok("inside".into()));
+    assert_err!(fs::read("/outside.txt"), NotFound);
+
+    // `..` stops at the new root in both absolute and relative paths
+    fs::set_current_dir("/")?;
+    assert_eq!(fs::current_dir()?, "/");
+    assert_err!(fs::read("/../outside.txt"), NotFound);
+    assert_err!(fs::read("../outside.txt"), NotFound);
+    assert_eq!(fs::read("../inside.txt"), Ok("inside".into()));
+    fs::set_current_dir("..")?;
+    assert_eq!(fs::current_dir()?, "/");
+    assert_eq!(fs::read("inside.txt"), Ok("inside".into()));
+
+    println!("test_chroot() OK!");
+    Ok(())
+}
+
 pub fn test_all() {
     test_read_write_file().expect("test_read_write_file() failed");
     test_read_dir().expect("test_read_dir() failed");
@@ -259,4 +289,6 @@ pub fn test_all() {
     test_create_file_dir().expect("test_create_file_dir() failed");
     test_remove_file_dir().expect("test_remove_file_dir() failed");
     test_devfs_ramfs().expect("test_devfs_ramfs() failed");
+    // the root directory can not be restored, so this must be the last one
+    test_chroot().expect("test_chroot() failed");
 }
@@ -36,6 +36,28 @@ impl FpState {
     }
 }
 
+impl FpState {
+    /// Saves the FP/SIMD registers of the current CPU.
+    ///
+    /// It does nothing if the `fp_simd` feature is not enabled.
+    pub fn save(&mut self) {
+        #[cfg(feature = "fp_simd")]
+        unsafe {
+            fpstate_save(self)
+        }
+    }
+
+    /// Restores the FP/SIMD registers of the current CPU.
+    ///
+    /// It does nothing if the `fp_simd` feature is not enabled.
+    pub fn restore(&self) {
+        #[cfg(feature = "fp_simd")]
+        unsafe {
+            fpstate_restore(self)
+        }
+    }
+}
+
 impl TrapFrame {
     /// To set the stack pointer
     pub fn set_user_sp(&mut self, user_sp: usize) {
@@ -83,6 +105,16 @@ impl TrapFrame {
         self.r[30] = param;
     }
 
+    /// 返回用户态后重新执行刚刚完成的 syscall
+    ///
+    /// 返回值已经覆盖了 x0，因此需要传入原来的第一个参数
+    pub fn restart_syscall(&mut self, syscall_id: usize, arg0: usize) {
+        // svc 指令的长度为 4 字节，elr 指向其下一条指令
+        self.elr -= 4;
+        self.r[8] = syscall_id;
+        self.r[0] = arg0;
+    }
+
     /// 用于第一次进入应用程序时的初始化
     pub fn app_init_context(app_entry: usize, user_sp: usize) -> Self {
         let mut trap_frame = TrapFrame::default();
@@ -236,3 +268,64 @@ unsafe extern "C" fn fpstate_switch(_current_fpstate: &mut FpState, _next_fpstat
         options(noreturn),
     )
 }
+
+#[naked]
+#[cfg(feature = "fp_simd")]
+unsafe extern "C" fn fpstate_save(_fpstate: &mut FpState) {
+    asm!(
+        "
+        mrs     x9, fpcr
+        mrs     x10, fpsr
+        stp     q0, q1, [x0, 0 * 16]
+        stp     q2, q3, [x0, 2 * 16]
+        stp     q4, q5, [x0, 4 * 16]
+        stp     q6, q7, [x0, 6 * 16]
+        stp     q8, q9, [x0, 8 * 16]
+        stp     q10, q11, [x0, 10 * 16]
+        stp     q12, q13, [x0, 12 * 16]
+        stp     q14, q15, [x0, 14 * 16]
+        stp     q16, q17, [x0, 16 * 16]
+        stp     q18, q19, [x0, 18 * 16]
+        stp     q20, q21, [x0, 20 * 16]
+        stp     q22, q23, [x0, 22 * 16]
+        stp     q24, q25, [x0, 24 * 16]
+        stp     q26, q27, [x0, 26 * 16]
+        stp     q28, q29, [x0, 28 * 16]
+        stp     q30, q31, [x0, 30 * 16]
+        str     w9, [x0, 128 * 4]
+        str     w10, [x0, 129 * 4]
+        ret",
+        options(noreturn),
+    )
+}
+
+#[naked]
+#[cfg(feature = "fp_simd")]
+unsafe extern "C" fn fpstate_restore(_fpstate: &FpState) {
+    asm!(
+        "
+        ldp     q0, q1, [x0, 0 * 16]
+        ldp     q2, q3, [x0, 2 * 16]
+        ldp     q4, q5, [x0, 4 * 16]
+        ldp     q6, q7, [x0, 6 * 16]
+        ldp     q8, q9, [x0, 8 * 16]
+        ldp     q10, q11, [x0, 10 * 16]
+        ldp     q12, q13, [x0, 12 * 16]
+        ldp     q14, q15, [x0, 14 * 16]
+        ldp     q16, q17, [x0, 16 * 16]
+        ldp     q18, q19, [x0, 18 * 16]
+        ldp     q20, q21, [x0, 20 * 16]
+        ldp     q22, q23, [x0, 22 * 16]
+        ldp     q24, q25, [x0, 24 * 16]
+        ldp     q26, q27, [x0, 26 * 16]
+        ldp     q28, q29, [x0, 28 * 16]
+        ldp     q30, q31, [x0, 30 * 16]
+        ldr     w9, [x0, 128 * 4]
+        ldr     w10, [x0, 129 * 4]
+        msr     fpcr, x9
+        msr     fpsr, x10
+        isb
+        ret",
+        options(noreturn),
+    )
+}
@@ -55,6 +55,31 @@ pub struct TrapFrame {
     pub fs: [usize; 2],
 }
 
+/// Floating-point registers of RISC-V (F and D extensions).
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FpState {
+    /// Floating-point registers (f0..f31).
+    pub f: [u64; 32],
+    /// Floating-point Control and Status Register (fcsr).
+    pub fcsr: u32,
+}
+
+impl FpState {
+    /// Saves the floating-point registers of the current CPU.
+    ///
+    /// `fs0` and `fs1` are also saved in the [`TrapFrame`] on traps, and they
+    /// are restored from there when returning to the user space.
+    pub fn save(&mut self) {
+        unsafe { fpstate_save(self) }
+    }
+
+    /// Restores the floating-point registers of the current CPU.
+    pub fn restore(&self) {
+        unsafe { fpstate_restore(self) }
+    }
+}
+
 impl TrapFrame {
     pub fn set_user_sp(&mut self, user_sp: usize) {
         self.regs.sp = user_sp;
@@ -145,6 +170,16 @@ impl TrapFrame {
     pub fn get_syscall_num(&self) -> usize {
         self.regs.a7 as _
     }
+
+    /// 返回用户态后重新执行刚刚完成的 syscall
+    ///
+    /// 返回值已经覆盖了 a0，因此需要传入原来的第一个参数
+    pub fn restart_syscall(&mut self, syscall_id: usize, arg0: usize) {
+        // ecall 指令的长度为 4 字节
+        self.sepc -= 4;
+        self.regs.a7 = syscall_id;
+        self.regs.a0 = arg0;
+    }
 }
 
 /// Saved hardware states of a task.
@@ -259,3 +294,89 @@ unsafe extern "C" fn context_switch(_current_task: &mut TaskContext, _next_task:
         options(noreturn),
     )
 }
+
+#[naked]
+unsafe extern "C" fn fpstate_save(_fpstate: &mut FpState) {
+    asm!(
+        "
+        fsd     f0, 0 * 8(a0)
+        fsd     f1, 1 * 8(a0)
+        fsd     f2, 2 * 8(a0)
+        fsd     f3, 3 * 8(a0)
+        fsd     f4, 4 * 8(a0)
+        fsd     f5, 5 * 8(a0)
+        fsd     f6, 6 * 8(a0)
+        fsd     f7, 7 * 8(a0)
+        fsd     f8, 8 * 8(a0)
+        fsd     f9, 9 * 8(a0)
+        fsd     f10, 10 * 8(a0)
+        fsd     f11, 11 * 8(a0)
+        fsd     f12, 12 * 8(a0)
+        fsd     f13, 13 * 8(a0)
+        fsd     f14, 14 * 8(a0)
+        fsd     f15, 15 * 8(a0)
+        fsd     f16, 16 * 8(a0)
+        fsd     f17, 17 * 8(a0)
+        fsd     f18, 18 * 8(a0)
+        fsd     f19, 19 * 8(a0)
+        fsd     f20, 20 * 8(a0)
+        fsd     f21, 21 * 8(a0)
+        fsd     f22, 22 * 8(a0)
+        fsd     f23, 23 * 8(a0)
+        fsd     f24, 24 * 8(a0)
+        fsd     f25, 25 * 8(a0)
+        fsd     f26, 26 * 8(a0)
+        fsd     f27, 27 * 8(a0)
+        fsd     f28, 28 * 8(a0)
+        fsd     f29, 29 * 8(a0)
+        fsd     f30, 30 * 8(a0)
+        fsd     f31, 31 * 8(a0)
+        frcsr   t0
+        sw      t0, 32 * 8(a0)
+        ret",
+        options(noreturn),
+    )
+}
+
+#[naked]
+unsafe extern "C" fn fpstate_restore(_fpstate: &FpState) {
+    asm!(
+        "
+        fld     f0, 0 * 8(a0)
+        fld     f1, 1 * 8(a0)
+        fld     f2, 2 * 8(a0)
+        fld     f3, 3 * 8(a0)
+        fld     f4, 4 * 8(a0)
+        fld     f5, 5 * 8(a0)
+        fld     f6, 6 * 8(a0)
+        fld     f7, 7 * 8(a0)
+        fld     f8, 8 * 8(a0)
+        fld     f9, 9 * 8(a0)
+        fld     f10, 10 * 8(a0)
+        fld     f11, 11 * 8(a0)
+        fld     f12, 12 * 8(a0)
+        fld     f13, 13 * 8(a0)
+        fld     f14, 14 * 8(a0)
+        fld     f15, 15 * 8(a0)
+        fld     f16, 16 * 8(a0)
+        fld     f17, 17 * 8(a0)
+        fld     f18, 18 * 8(a0)
+        fld     f19, 19 * 8(a0)
+        fld     f20, 20 * 8(a0)
+        fld     f21, 21 * 8(a0)
+        fld     f22, 22 * 8(a0)
+        fld     f23, 23 * 8(a0)
+        fld     f24, 24 * 8(a0)
+        fld     f25, 25 * 8(a0)
+        fld     f26, 26 * 8(a0)
+        fld     f27, 27 * 8(a0)
+        fld     f28, 28 * 8(a0)
+        fld     f29, 29 * 8(a0)
+        fld     f30, 30 * 8(a0)
+        fld     f31, 31 * 8(a0)
+        lw      t0, 32 * 8(a0)
+        fscsr   t0
+        ret",
+        options(noreturn),
+    )
+}
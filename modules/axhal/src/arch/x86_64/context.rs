@@ -1,3 +1,4 @@
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use core::{arch::asm, fmt};
 use memory_addr::VirtAddr;
 
@@ -122,6 +123,15 @@ impl TrapFrame {
     pub fn get_syscall_num(&self) -> usize {
         self.rax as _
     }
+
+    /// 返回用户态后重新执行刚刚完成的 syscall
+    ///
+    /// 返回值已经覆盖了 rax 中的 syscall id，参数保存在其他寄存器中，不需要恢复
+    pub fn restart_syscall(&mut self, syscall_id: usize, _arg0: usize) {
+        // syscall 指令的长度为 2 字节
+        self.rip -= 2;
+        self.rax = syscall_id as _;
+    }
 }
 
 #[repr(C)]
@@ -159,22 +169,114 @@ pub struct FxsaveArea {
 
 static_assertions::const_assert_eq!(core::mem::size_of::<FxsaveArea>(), 512);
 
+/// Size of the XSAVE header and the AVX state following the legacy FXSAVE region.
+const XSAVE_EXT_SIZE: usize = 512;
+
+/// The state components enabled in `XCR0`, or 0 if XSAVE is not enabled.
+static XSAVE_FEATURES: AtomicU64 = AtomicU64::new(0);
+/// Size of the XSAVE area for the enabled state components.
+static XSAVE_SIZE: AtomicUsize = AtomicUsize::new(core::mem::size_of::<FxsaveArea>());
+
+/// Enables XSAVE and the AVX state on the current CPU if supported.
+///
+/// Only the x87, SSE and AVX states are enabled, so that the XSAVE area fits
+/// in [`ExtendedState`]. It must be called on every CPU during boot.
+#[cfg(feature = "fp_simd")]
+pub(crate) fn init_xsave() {
+    use x86_64::registers::control::{Cr4, Cr4Flags};
+    // State component bits in `XCR0`.
+    const XFEATURE_X87: u64 = 1 << 0;
+    const XFEATURE_SSE: u64 = 1 << 1;
+    const XFEATURE_AVX: u64 = 1 << 2;
+
+    let cpuid = raw_cpuid::CpuId::new();
+    let Some(finfo) = cpuid.get_feature_info() else {
+        return;
+    };
+    if !finfo.has_xsave() {
+        return;
+    }
+    let mut features = XFEATURE_X87 | XFEATURE_SSE;
+    if finfo.has_avx() {
+        features |= XFEATURE_AVX;
+    }
+    unsafe {
+        Cr4::update(|cr4| cr4.insert(Cr4Flags::OSXSAVE));
+        core::arch::x86_64::_xsetbv(0, features);
+    }
+    let size = cpuid
+        .get_extended_state_info()
+        .map_or(0, |info| info.xsave_area_size_enabled_features() as usize);
+    if size == 0 || size > core::mem::size_of::<ExtendedState>() {
+        unsafe { Cr4::update(|cr4| cr4.remove(Cr4Flags::OSXSAVE)) };
+        return;
+    }
+    XSAVE_SIZE.store(size, Ordering::Relaxed);
+    XSAVE_FEATURES.store(features, Ordering::Relaxed);
+}
+
+/// Returns the state components enabled in `XCR0`, or 0 if XSAVE is not
+/// enabled and only FXSAVE can be used.
+pub fn xsave_features() -> u64 {
+    XSAVE_FEATURES.load(Ordering::Relaxed)
+}
+
+/// Returns the size of the memory region needed by [`save_fp_state`].
+pub fn fp_state_size() -> usize {
+    if xsave_features() != 0 {
+        XSAVE_SIZE.load(Ordering::Relaxed)
+    } else {
+        core::mem::size_of::<FxsaveArea>()
+    }
+}
+
+/// Saves the FP/SIMD states of the current CPU to `area`, using XSAVE if it is
+/// enabled, or FXSAVE otherwise.
+///
+/// # Safety
+///
+/// `area` must be 64-byte aligned and at least [`fp_state_size`] bytes long.
+pub unsafe fn save_fp_state(area: *mut u8) {
+    match xsave_features() {
+        0 => core::arch::x86_64::_fxsave64(area),
+        features => core::arch::x86_64::_xsave64(area, features),
+    }
+}
+
+/// Restores the FP/SIMD states of the current CPU from `area`.
+///
+/// `features` are the state components saved in `area` by XSAVE. If it is 0,
+/// `area` is restored by FXRSTOR as a legacy FXSAVE region.
+///
+/// # Safety
+///
+/// `area` must be 64-byte aligned and hold a valid XSAVE or FXSAVE region.
+pub unsafe fn restore_fp_state(area: *const u8, features: u64) {
+    match features & xsave_features() {
+        0 => core::arch::x86_64::_fxrstor64(area),
+        features => core::arch::x86_64::_xrstor64(area, features),
+    }
+}
+
 /// Extended state of a task, such as FP/SIMD states.
+#[repr(C, align(64))]
 pub struct ExtendedState {
     /// Memory region for the FXSAVE/FXRSTOR instruction.
     pub fxsave_area: FxsaveArea,
+    /// The XSAVE header and the AVX state, only used if XSAVE is enabled.
+    xsave_ext: [u8; XSAVE_EXT_SIZE],
 }
 
 #[cfg(feature = "fp_simd")]
 impl ExtendedState {
     #[inline]
     fn save(&mut self) {
-        unsafe { core::arch::x86_64::_fxsave64(&mut self.fxsave_area as *mut _ as *mut u8) }
+        unsafe { save_fp_state(self as *mut _ as *mut u8) }
     }
 
     #[inline]
     fn restore(&self) {
-        unsafe { core::arch::x86_64::_fxrstor64(&self.fxsave_area as *const _ as *const u8) }
+        unsafe { restore_fp_state(self as *const _ as *const u8, xsave_features()) }
     }
 
     const fn default() -> Self {
@@ -182,7 +284,10 @@ impl ExtendedState {
         area.fcw = 0x37f;
         area.ftw = 0xffff;
         area.mxcsr = 0x1f80;
-        Self { fxsave_area: area }
+        Self {
+            fxsave_area: area,
+            xsave_ext: [0; XSAVE_EXT_SIZE],
+        }
     }
 }
 
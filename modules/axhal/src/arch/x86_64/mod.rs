@@ -14,7 +14,12 @@ use memory_addr::{PhysAddr, VirtAddr};
 use x86::{controlregs, msr, tlb};
 use x86_64::instructions::interrupts;
 
-pub use self::context::{ExtendedState, FxsaveArea, TaskContext, TrapFrame};
+#[cfg(feature = "fp_simd")]
+pub(crate) use self::context::init_xsave;
+pub use self::context::{
+    fp_state_size, restore_fp_state, save_fp_state, xsave_features, ExtendedState, FxsaveArea,
+    TaskContext, TrapFrame,
+};
 pub use self::gdt::GdtStruct;
 pub use self::idt::IdtStruct;
 pub use x86_64::structures::tss::TaskStateSegment;
@@ -8,6 +8,9 @@ pub const MAX_IRQ_COUNT: usize = IntId::GIC_MAX_IRQ;
 /// The timer IRQ number.
 pub const TIMER_IRQ_NUM: usize = translate_irq(14, InterruptType::PPI).unwrap();
 
+/// The inter-processor interrupt number.
+pub const IPI_IRQ_NUM: usize = translate_irq(0, InterruptType::SGI).unwrap();
+
 /// The UART IRQ number.
 pub const UART_IRQ_NUM: usize = translate_irq(axconfig::UART_IRQ, InterruptType::SPI).unwrap();
 
@@ -19,10 +22,20 @@ cfg_if::cfg_if! {
         use arm_gic::GicV3;
         static mut GIC: SpinNoIrq<GicV3> =
             SpinNoIrq::new(GicV3::new(phys_to_virt(GICD_BASE).as_mut_ptr(), phys_to_virt(GICC_BASE).as_mut_ptr()));
+
+        /// The affinity value of the given CPU, used as the SGI target.
+        fn sgi_target(cpu_id: usize) -> usize {
+            of::cpus().nth(cpu_id).expect("not correct cpu_id").ids().first()
+        }
     } else {
         use arm_gic::GicV2;
         static mut GIC: SpinNoIrq<GicV2> =
             SpinNoIrq::new(GicV2::new(phys_to_virt(GICD_BASE).as_mut_ptr(), phys_to_virt(GICC_BASE).as_mut_ptr()));
+
+        /// The CPU interface number of the given CPU, used as the SGI target.
+        fn sgi_target(cpu_id: usize) -> usize {
+            cpu_id
+        }
     }
 }
 
@@ -68,6 +81,15 @@ pub fn dispatch_irq(_unused: usize) {
     }
 }
 
+/// Sends an inter-processor interrupt to the given CPU.
+pub fn send_ipi(cpu_id: usize) {
+    // a single register write, no need lock
+    unsafe {
+        GIC.get_mut()
+            .send_sgi(IPI_IRQ_NUM.into(), sgi_target(cpu_id))
+    };
+}
+
 /// Initializes GICD, GICC on the primary CPU.
 pub(crate) fn init_primary() {
     info!("Initialize GICv2...");
@@ -79,4 +101,6 @@ pub(crate) fn init_primary() {
 pub(crate) fn init_secondary() {
     // per cpu handle, no need lock
     unsafe { GIC.get_mut().per_cpu_init() };
+    // SGIs are banked per CPU, enable the IPI on this CPU
+    set_enable(IPI_IRQ_NUM, true);
 }
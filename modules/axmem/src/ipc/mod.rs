@@ -0,0 +1,57 @@
+//! System V IPC 中除共享内存外的对象，目前包括消息队列与信号量
+
+mod msg;
+mod sem;
+pub use msg::*;
+pub use sem::*;
+
+/// 总是创建新的 IPC 对象的 key
+pub const IPC_PRIVATE: i32 = 0;
+/// 不存在时创建
+pub const IPC_CREAT: i32 = 0o1000;
+/// 与 `IPC_CREAT` 一起使用，已存在时返回 `EEXIST`
+pub const IPC_EXCL: i32 = 0o2000;
+/// 操作无法立即完成时不阻塞，直接返回错误
+pub const IPC_NOWAIT: i32 = 0o4000;
+
+/// 删除 IPC 对象
+pub const IPC_RMID: i32 = 0;
+/// 设置 IPC 对象的属性
+pub const IPC_SET: i32 = 1;
+/// 获取 IPC 对象的属性
+pub const IPC_STAT: i32 = 2;
+/// 部分 libc 会在 `ctl` 类调用的命令中带上该标志，表示使用 64 位的结构体
+pub const IPC_64: i32 = 0x100;
+
+/// IPC 对象的权限信息，即 `struct ipc64_perm`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IpcPerm {
+    /// 创建时使用的 key
+    pub key: i32,
+    /// 所有者的用户 id
+    pub uid: u32,
+    /// 所有者的用户组 id
+    pub gid: u32,
+    /// 创建者的用户 id
+    pub cuid: u32,
+    /// 创建者的用户组 id
+    pub cgid: u32,
+    /// 权限位，只使用低 9 位
+    pub mode: u32,
+    /// 序列号
+    pub seq: u16,
+    _pad: u16,
+    _unused: [usize; 2],
+}
+
+impl IpcPerm {
+    /// 以 `key` 与 `flags` 中的权限位创建权限信息，当前不区分用户，均视为 root
+    pub fn new(key: i32, flags: i32) -> Self {
+        Self {
+            key,
+            mode: (flags & 0o777) as u32,
+            ..Default::default()
+        }
+    }
+}
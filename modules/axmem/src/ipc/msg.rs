@@ -0,0 +1,309 @@
+//! System V 消息队列
+
+use alloc::{collections::BTreeMap, collections::VecDeque, sync::Arc, vec::Vec};
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::current_time;
+use axtask::WaitQueue;
+use core::sync::atomic::{AtomicI32, Ordering};
+use spinlock::SpinNoIrq;
+
+use super::{IpcPerm, IPC_CREAT, IPC_EXCL, IPC_PRIVATE};
+
+/// 单条消息的最大长度
+pub const MSGMAX: usize = 8192;
+/// 消息队列默认的最大字节数
+pub const MSGMNB: usize = 16384;
+
+/// 接收时消息过长则截断，而不是返回 `E2BIG`
+pub const MSG_NOERROR: i32 = 0o10000;
+/// 接收第一条类型不等于 `msgtyp` 的消息
+pub const MSG_EXCEPT: i32 = 0o20000;
+/// 复制而不取出消息，需要内核支持 checkpoint/restore
+pub const MSG_COPY: i32 = 0o40000;
+
+// TODO: a real allocator
+static MSQID: AtomicI32 = AtomicI32::new(1);
+
+/// 所有消息队列，包括以 `IPC_PRIVATE` 创建的消息队列
+pub static MSG_QUEUES: SpinNoIrq<BTreeMap<i32, Arc<MsgQueue>>> = SpinNoIrq::new(BTreeMap::new());
+
+/// 从 key 到消息队列 id 的映射
+pub static KEY_TO_MSQID: SpinNoIrq<BTreeMap<i32, i32>> = SpinNoIrq::new(BTreeMap::new());
+
+/// 消息队列的属性与统计信息，即 `struct msqid64_ds`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MsqidDs {
+    /// 权限信息
+    pub msg_perm: IpcPerm,
+    /// 最后一次发送消息的时间
+    pub msg_stime: usize,
+    /// 最后一次接收消息的时间
+    pub msg_rtime: usize,
+    /// 最后一次修改的时间
+    pub msg_ctime: usize,
+    /// 队列中所有消息的字节数
+    pub msg_cbytes: usize,
+    /// 队列中消息的数目
+    pub msg_qnum: usize,
+    /// 队列允许的最大字节数
+    pub msg_qbytes: usize,
+    /// 最后一次发送消息的进程
+    pub msg_lspid: i32,
+    /// 最后一次接收消息的进程
+    pub msg_lrpid: i32,
+    _unused: [usize; 2],
+}
+
+/// 队列中的一条消息
+pub struct Message {
+    /// 消息类型，总是为正数
+    pub mtype: i64,
+    /// 消息内容
+    pub data: Vec<u8>,
+}
+
+struct MsgQueueInner {
+    messages: VecDeque<Message>,
+    ds: MsqidDs,
+    /// 已经被 `IPC_RMID` 删除
+    removed: bool,
+}
+
+impl MsgQueueInner {
+    /// 能否放入长度为 `len` 的消息，消息数目同样受 `msg_qbytes` 限制
+    fn has_room(&self, len: usize) -> bool {
+        self.ds.msg_cbytes + len <= self.ds.msg_qbytes && self.ds.msg_qnum < self.ds.msg_qbytes
+    }
+
+    /// 根据 `msgtyp` 找到要接收的消息
+    ///
+    /// - `msgtyp` 为 0 时取第一条消息
+    /// - `msgtyp` 大于 0 时取第一条类型为 `msgtyp` 的消息，指定 `except` 时取第一条类型不为 `msgtyp` 的消息
+    /// - `msgtyp` 小于 0 时取类型不超过 `msgtyp` 绝对值的消息中类型最小的第一条
+    fn find(&self, msgtyp: i64, except: bool) -> Option<usize> {
+        let mut messages = self.messages.iter().enumerate();
+        match msgtyp.cmp(&0) {
+            core::cmp::Ordering::Equal => messages.next().map(|(index, _)| index),
+            core::cmp::Ordering::Greater => messages
+                .find(|(_, msg)| (msg.mtype == msgtyp) != except)
+                .map(|(index, _)| index),
+            core::cmp::Ordering::Less => messages
+                .filter(|(_, msg)| msg.mtype as u64 <= msgtyp.unsigned_abs())
+                .min_by_key(|(index, msg)| (msg.mtype, *index))
+                .map(|(index, _)| index),
+        }
+    }
+}
+
+/// System V 消息队列
+pub struct MsgQueue {
+    inner: SpinNoIrq<MsgQueueInner>,
+    /// 等待队列有空闲空间的发送者
+    send_wq: WaitQueue,
+    /// 等待新消息的接收者
+    recv_wq: WaitQueue,
+}
+
+impl MsgQueue {
+    fn new(key: i32, flags: i32) -> Self {
+        Self {
+            inner: SpinNoIrq::new(MsgQueueInner {
+                messages: VecDeque::new(),
+                ds: MsqidDs {
+                    msg_perm: IpcPerm::new(key, flags),
+                    msg_ctime: current_time().as_secs() as usize,
+                    msg_qbytes: MSGMNB,
+                    ..Default::default()
+                },
+                removed: false,
+            }),
+            send_wq: WaitQueue::new(),
+            recv_wq: WaitQueue::new(),
+        }
+    }
+
+    /// 发送一条消息，队列已满时若 `nowait` 为假则阻塞等待
+    ///
+    /// `interrupted` 在每次阻塞前调用，返回真时放弃等待并返回 `EINTR`
+    pub fn send<F>(
+        &self,
+        mtype: i64,
+        data: &[u8],
+        pid: i32,
+        nowait: bool,
+        interrupted: F,
+    ) -> LinuxResult<()>
+    where
+        F: Fn() -> bool,
+    {
+        loop {
+            match self.try_send(mtype, data, pid) {
+                Err(LinuxError::EAGAIN) if !nowait => {}
+                result => return result,
+            }
+            if interrupted() {
+                return Err(LinuxError::EINTR);
+            }
+            self.send_wq.wait_if(|| {
+                let inner = self.inner.lock();
+                !inner.removed && !inner.has_room(data.len())
+            });
+        }
+    }
+
+    fn try_send(&self, mtype: i64, data: &[u8], pid: i32) -> LinuxResult<()> {
+        let mut inner = self.inner.lock();
+        if inner.removed {
+            return Err(LinuxError::EIDRM);
+        }
+        if !inner.has_room(data.len()) {
+            return Err(LinuxError::EAGAIN);
+        }
+        inner.messages.push_back(Message {
+            mtype,
+            data: data.to_vec(),
+        });
+        inner.ds.msg_cbytes += data.len();
+        inner.ds.msg_qnum += 1;
+        inner.ds.msg_lspid = pid;
+        inner.ds.msg_stime = current_time().as_secs() as usize;
+        drop(inner);
+        self.recv_wq.notify_all(false);
+        Ok(())
+    }
+
+    /// 按照 `msgtyp` 与 `flags` 接收一条消息，没有符合条件的消息时若 `nowait` 为假则阻塞等待
+    ///
+    /// 消息长度超过 `max_size` 时，若 `flags` 含有 `MSG_NOERROR` 则截断，否则返回 `E2BIG`。
+    /// `interrupted` 在每次阻塞前调用，返回真时放弃等待并返回 `EINTR`
+    pub fn receive<F>(
+        &self,
+        msgtyp: i64,
+        max_size: usize,
+        flags: i32,
+        pid: i32,
+        nowait: bool,
+        interrupted: F,
+    ) -> LinuxResult<Message>
+    where
+        F: Fn() -> bool,
+    {
+        let except = flags & MSG_EXCEPT != 0;
+        loop {
+            match self.try_receive(msgtyp, max_size, flags, pid) {
+                Err(LinuxError::ENOMSG) if !nowait => {}
+                result => return result,
+            }
+            if interrupted() {
+                return Err(LinuxError::EINTR);
+            }
+            self.recv_wq.wait_if(|| {
+                let inner = self.inner.lock();
+                !inner.removed && inner.find(msgtyp, except).is_none()
+            });
+        }
+    }
+
+    fn try_receive(
+        &self,
+        msgtyp: i64,
+        max_size: usize,
+        flags: i32,
+        pid: i32,
+    ) -> LinuxResult<Message> {
+        let mut inner = self.inner.lock();
+        if inner.removed {
+            return Err(LinuxError::EIDRM);
+        }
+        let index = inner
+            .find(msgtyp, flags & MSG_EXCEPT != 0)
+            .ok_or(LinuxError::ENOMSG)?;
+        if inner.messages[index].data.len() > max_size && flags & MSG_NOERROR == 0 {
+            return Err(LinuxError::E2BIG);
+        }
+        let mut message = inner.messages.remove(index).unwrap();
+        inner.ds.msg_cbytes -= message.data.len();
+        inner.ds.msg_qnum -= 1;
+        inner.ds.msg_lrpid = pid;
+        inner.ds.msg_rtime = current_time().as_secs() as usize;
+        drop(inner);
+        message.data.truncate(max_size);
+        self.send_wq.notify_all(false);
+        Ok(message)
+    }
+
+    /// 获取消息队列的属性与统计信息
+    pub fn stat(&self) -> MsqidDs {
+        self.inner.lock().ds
+    }
+
+    /// 根据 `ds` 设置所有者、权限位与队列的最大字节数
+    pub fn set(&self, ds: &MsqidDs) -> LinuxResult<()> {
+        if ds.msg_qbytes == 0 || ds.msg_qbytes > i32::MAX as usize {
+            return Err(LinuxError::EINVAL);
+        }
+        let mut inner = self.inner.lock();
+        inner.ds.msg_perm.uid = ds.msg_perm.uid;
+        inner.ds.msg_perm.gid = ds.msg_perm.gid;
+        inner.ds.msg_perm.mode = ds.msg_perm.mode & 0o777;
+        inner.ds.msg_qbytes = ds.msg_qbytes;
+        inner.ds.msg_ctime = current_time().as_secs() as usize;
+        drop(inner);
+        // 队列容量可能增大，唤醒发送者重新尝试
+        self.send_wq.notify_all(false);
+        Ok(())
+    }
+
+    /// 标记消息队列已被删除，丢弃所有消息，并唤醒所有等待者使其返回 `EIDRM`
+    fn remove(&self) {
+        let mut inner = self.inner.lock();
+        inner.removed = true;
+        inner.messages.clear();
+        drop(inner);
+        self.send_wq.notify_all(false);
+        self.recv_wq.notify_all(false);
+    }
+}
+
+/// 根据 `key` 获取消息队列的 id，必要时按照 `flags` 创建
+///
+/// `flags` 的低 9 位为新消息队列的权限位
+pub fn get_or_create_msg_queue(key: i32, flags: i32) -> LinuxResult<i32> {
+    let mut key_map = KEY_TO_MSQID.lock();
+    if key != IPC_PRIVATE {
+        if let Some(&msqid) = key_map.get(&key) {
+            if flags & IPC_CREAT != 0 && flags & IPC_EXCL != 0 {
+                return Err(LinuxError::EEXIST);
+            }
+            return Ok(msqid);
+        }
+        if flags & IPC_CREAT == 0 {
+            return Err(LinuxError::ENOENT);
+        }
+    }
+    let msqid = MSQID.fetch_add(1, Ordering::Relaxed);
+    MSG_QUEUES
+        .lock()
+        .insert(msqid, Arc::new(MsgQueue::new(key, flags)));
+    if key != IPC_PRIVATE {
+        key_map.insert(key, msqid);
+    }
+    Ok(msqid)
+}
+
+/// 根据 id 获取消息队列
+pub fn get_msg_queue(msqid: i32) -> Option<Arc<MsgQueue>> {
+    MSG_QUEUES.lock().get(&msqid).cloned()
+}
+
+/// 删除消息队列，正在等待的发送者与接收者会返回 `EIDRM`
+pub fn remove_msg_queue(msqid: i32) -> LinuxResult<()> {
+    let queue = MSG_QUEUES.lock().remove(&msqid).ok_or(LinuxError::EINVAL)?;
+    let key = queue.stat().msg_perm.key;
+    if key != IPC_PRIVATE {
+        KEY_TO_MSQID.lock().remove(&key);
+    }
+    queue.remove();
+    Ok(())
+}
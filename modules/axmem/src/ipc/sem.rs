@@ -0,0 +1,395 @@
+//! System V 信号量
+
+use alloc::{collections::BTreeMap, sync::Arc, vec, vec::Vec};
+use axerrno::{LinuxError, LinuxResult};
+use axhal::time::current_time;
+use axtask::WaitQueue;
+use core::sync::atomic::{AtomicI32, Ordering};
+use core::time::Duration;
+use spinlock::SpinNoIrq;
+
+use super::{IpcPerm, IPC_CREAT, IPC_EXCL, IPC_NOWAIT, IPC_PRIVATE};
+
+/// 单个信号量集中信号量的最大数目
+pub const SEMMSL: usize = 32000;
+/// 单次 semop 中操作的最大数目
+pub const SEMOPM: usize = 500;
+/// 信号量的最大值
+pub const SEMVMX: i32 = 32767;
+/// 进程退出时撤销的调整值的最大绝对值
+pub const SEMAEM: i32 = SEMVMX;
+
+/// 进程退出时撤销该操作
+pub const SEM_UNDO: i16 = 0x1000;
+
+/// 获取最后一次操作信号量的进程
+pub const GETPID: i32 = 11;
+/// 获取信号量的值
+pub const GETVAL: i32 = 12;
+/// 获取信号量集中所有信号量的值
+pub const GETALL: i32 = 13;
+/// 获取等待信号量增加的进程数目
+pub const GETNCNT: i32 = 14;
+/// 获取等待信号量变为 0 的进程数目
+pub const GETZCNT: i32 = 15;
+/// 设置信号量的值
+pub const SETVAL: i32 = 16;
+/// 设置信号量集中所有信号量的值
+pub const SETALL: i32 = 17;
+
+// TODO: a real allocator
+static SEMID: AtomicI32 = AtomicI32::new(1);
+
+/// 所有信号量集，包括以 `IPC_PRIVATE` 创建的信号量集
+pub static SEM_SETS: SpinNoIrq<BTreeMap<i32, Arc<SemSet>>> = SpinNoIrq::new(BTreeMap::new());
+
+/// 从 key 到信号量集 id 的映射
+pub static KEY_TO_SEMID: SpinNoIrq<BTreeMap<i32, i32>> = SpinNoIrq::new(BTreeMap::new());
+
+/// semop 中的单个操作，即 `struct sembuf`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SemBuf {
+    /// 信号量在信号量集中的下标
+    pub sem_num: u16,
+    /// 为正数时增加信号量，为负数时减少信号量，为 0 时等待信号量变为 0
+    pub sem_op: i16,
+    /// `IPC_NOWAIT` 与 `SEM_UNDO`
+    pub sem_flg: i16,
+}
+
+/// 信号量集的属性，即 `struct semid64_ds`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SemidDs {
+    /// 权限信息
+    pub sem_perm: IpcPerm,
+    /// 最后一次 semop 的时间
+    pub sem_otime: usize,
+    #[cfg(target_arch = "x86_64")]
+    _unused1: usize,
+    /// 最后一次修改的时间
+    pub sem_ctime: usize,
+    #[cfg(target_arch = "x86_64")]
+    _unused2: usize,
+    /// 信号量的数目
+    pub sem_nsems: usize,
+    _unused: [usize; 2],
+}
+
+#[derive(Clone, Copy, Default)]
+struct Semaphore {
+    value: i32,
+    /// 最后一次操作该信号量的进程
+    pid: i32,
+    /// 等待信号量增加的任务数目
+    ncnt: usize,
+    /// 等待信号量变为 0 的任务数目
+    zcnt: usize,
+}
+
+struct SemSetInner {
+    sems: Vec<Semaphore>,
+    ds: SemidDs,
+    /// 已经被 `IPC_RMID` 删除
+    removed: bool,
+    /// 各个进程通过 `SEM_UNDO` 累计的调整值，进程退出时加到信号量上
+    undo: BTreeMap<u64, Vec<i32>>,
+}
+
+impl SemSetInner {
+    /// 按顺序模拟执行 `ops`
+    ///
+    /// 全部可以执行时返回执行后各个信号量的值，否则返回第一个需要阻塞的操作的下标
+    fn evaluate(&self, ops: &[SemBuf], pid: u64) -> LinuxResult<Result<Vec<i32>, usize>> {
+        let mut values: Vec<i32> = self.sems.iter().map(|sem| sem.value).collect();
+        let mut undo = self.undo.get(&pid).cloned();
+        for (index, op) in ops.iter().enumerate() {
+            let num = op.sem_num as usize;
+            let sem_op = op.sem_op as i32;
+            let value = values[num] + sem_op;
+            if value < 0 || (sem_op == 0 && values[num] != 0) {
+                return Ok(Err(index));
+            }
+            if value > SEMVMX {
+                return Err(LinuxError::ERANGE);
+            }
+            if op.sem_flg & SEM_UNDO != 0 {
+                let undo = undo.get_or_insert_with(|| vec![0; self.sems.len()]);
+                undo[num] -= sem_op;
+                if undo[num].abs() > SEMAEM {
+                    return Err(LinuxError::ERANGE);
+                }
+            }
+            values[num] = value;
+        }
+        Ok(Ok(values))
+    }
+
+    /// 将进程 `pid` 执行 `ops` 的结果写入信号量集
+    fn commit(&mut self, ops: &[SemBuf], values: Vec<i32>, pid: u64) {
+        for op in ops {
+            let num = op.sem_num as usize;
+            self.sems[num].pid = pid as i32;
+            if op.sem_flg & SEM_UNDO != 0 {
+                let nsems = self.sems.len();
+                self.undo.entry(pid).or_insert_with(|| vec![0; nsems])[num] -= op.sem_op as i32;
+            }
+        }
+        for (sem, value) in self.sems.iter_mut().zip(values) {
+            sem.value = value;
+        }
+        self.ds.sem_otime = current_time().as_secs() as usize;
+    }
+
+    /// 阻塞在 `op` 所操作的信号量上的任务数目加上 `delta`
+    fn update_waiters(&mut self, op: &SemBuf, delta: isize) {
+        let sem = &mut self.sems[op.sem_num as usize];
+        let count = if op.sem_op == 0 {
+            &mut sem.zcnt
+        } else {
+            &mut sem.ncnt
+        };
+        *count = count.wrapping_add_signed(delta);
+    }
+}
+
+/// System V 信号量集
+pub struct SemSet {
+    inner: SpinNoIrq<SemSetInner>,
+    /// 等待信号量变化的任务
+    wq: WaitQueue,
+}
+
+impl SemSet {
+    fn new(key: i32, nsems: usize, flags: i32) -> Self {
+        Self {
+            inner: SpinNoIrq::new(SemSetInner {
+                sems: vec![Semaphore::default(); nsems],
+                ds: SemidDs {
+                    sem_perm: IpcPerm::new(key, flags),
+                    sem_ctime: current_time().as_secs() as usize,
+                    sem_nsems: nsems,
+                    ..Default::default()
+                },
+                removed: false,
+                undo: BTreeMap::new(),
+            }),
+            wq: WaitQueue::new(),
+        }
+    }
+
+    /// 信号量的数目
+    pub fn nsems(&self) -> usize {
+        self.inner.lock().sems.len()
+    }
+
+    /// 以进程 `pid` 的身份原子地执行 `ops`，即要么全部执行，要么全部不执行
+    ///
+    /// 无法全部执行时阻塞等待，直到超过 `timeout`，或者 `interrupted` 返回真。
+    /// 导致阻塞的操作带有 `IPC_NOWAIT` 或等待超时时返回 `EAGAIN`，被打断时返回 `EINTR`，
+    /// 信号量集被删除时返回 `EIDRM`
+    pub fn semop<F>(
+        &self,
+        ops: &[SemBuf],
+        pid: u64,
+        timeout: Option<Duration>,
+        interrupted: F,
+    ) -> LinuxResult<()>
+    where
+        F: Fn() -> bool,
+    {
+        let deadline = timeout.map(|timeout| current_time() + timeout);
+        loop {
+            let mut inner = self.inner.lock();
+            if inner.removed {
+                return Err(LinuxError::EIDRM);
+            }
+            if ops.iter().any(|op| op.sem_num as usize >= inner.sems.len()) {
+                return Err(LinuxError::EFBIG);
+            }
+            let blocking = match inner.evaluate(ops, pid)? {
+                Ok(values) => {
+                    inner.commit(ops, values, pid);
+                    drop(inner);
+                    self.wq.notify_all(false);
+                    return Ok(());
+                }
+                Err(index) => &ops[index],
+            };
+            if blocking.sem_flg as i32 & IPC_NOWAIT != 0 {
+                return Err(LinuxError::EAGAIN);
+            }
+            drop(inner);
+            let now = current_time();
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                return Err(LinuxError::EAGAIN);
+            }
+            if interrupted() {
+                return Err(LinuxError::EINTR);
+            }
+            self.inner.lock().update_waiters(blocking, 1);
+            let condition = || {
+                let inner = self.inner.lock();
+                !inner.removed && matches!(inner.evaluate(ops, pid), Ok(Err(_)))
+            };
+            match deadline {
+                Some(deadline) => self.wq.wait_timeout_if(deadline - now, condition),
+                None => self.wq.wait_if(condition),
+            };
+            self.inner.lock().update_waiters(blocking, -1);
+        }
+    }
+
+    /// 获取信号量 `num` 的值、最后一次操作它的进程，以及等待它增加或变为 0 的任务数目
+    pub fn get(&self, num: usize, cmd: i32) -> LinuxResult<i32> {
+        let inner = self.inner.lock();
+        let sem = inner.sems.get(num).ok_or(LinuxError::EINVAL)?;
+        Ok(match cmd {
+            GETPID => sem.pid,
+            GETNCNT => sem.ncnt as i32,
+            GETZCNT => sem.zcnt as i32,
+            _ => sem.value,
+        })
+    }
+
+    /// 获取所有信号量的值
+    pub fn get_all(&self) -> Vec<u16> {
+        let inner = self.inner.lock();
+        inner.sems.iter().map(|sem| sem.value as u16).collect()
+    }
+
+    /// 设置信号量 `num` 的值，并清除所有进程对它的调整值
+    pub fn set(&self, num: usize, value: i32, pid: u64) -> LinuxResult<()> {
+        if !(0..=SEMVMX).contains(&value) {
+            return Err(LinuxError::ERANGE);
+        }
+        let mut inner = self.inner.lock();
+        let sem = inner.sems.get_mut(num).ok_or(LinuxError::EINVAL)?;
+        sem.value = value;
+        sem.pid = pid as i32;
+        for undo in inner.undo.values_mut() {
+            undo[num] = 0;
+        }
+        inner.ds.sem_ctime = current_time().as_secs() as usize;
+        drop(inner);
+        self.wq.notify_all(false);
+        Ok(())
+    }
+
+    /// 设置所有信号量的值，并清除所有进程的调整值
+    pub fn set_all(&self, values: &[u16], pid: u64) -> LinuxResult<()> {
+        if values.iter().any(|&value| value as i32 > SEMVMX) {
+            return Err(LinuxError::ERANGE);
+        }
+        let mut inner = self.inner.lock();
+        for (sem, &value) in inner.sems.iter_mut().zip(values) {
+            sem.value = value as i32;
+            sem.pid = pid as i32;
+        }
+        inner.undo.clear();
+        inner.ds.sem_ctime = current_time().as_secs() as usize;
+        drop(inner);
+        self.wq.notify_all(false);
+        Ok(())
+    }
+
+    /// 获取信号量集的属性
+    pub fn stat(&self) -> SemidDs {
+        self.inner.lock().ds
+    }
+
+    /// 根据 `ds` 设置所有者与权限位
+    pub fn set_stat(&self, ds: &SemidDs) {
+        let mut inner = self.inner.lock();
+        inner.ds.sem_perm.uid = ds.sem_perm.uid;
+        inner.ds.sem_perm.gid = ds.sem_perm.gid;
+        inner.ds.sem_perm.mode = ds.sem_perm.mode & 0o777;
+        inner.ds.sem_ctime = current_time().as_secs() as usize;
+    }
+
+    /// 将进程 `pid` 的调整值加到信号量上，结果限制在 `0..=SEMVMX` 范围内
+    fn undo(&self, pid: u64) {
+        let mut inner = self.inner.lock();
+        let Some(undo) = inner.undo.remove(&pid) else {
+            return;
+        };
+        for (sem, adj) in inner.sems.iter_mut().zip(undo) {
+            if adj != 0 {
+                sem.value = (sem.value + adj).clamp(0, SEMVMX);
+                sem.pid = pid as i32;
+            }
+        }
+        drop(inner);
+        self.wq.notify_all(false);
+    }
+
+    /// 标记信号量集已被删除，并唤醒所有等待者使其返回 `EIDRM`
+    fn remove(&self) {
+        let mut inner = self.inner.lock();
+        inner.removed = true;
+        inner.undo.clear();
+        drop(inner);
+        self.wq.notify_all(false);
+    }
+}
+
+/// 根据 `key` 获取信号量集的 id，必要时按照 `flags` 创建含有 `nsems` 个信号量的信号量集
+///
+/// `flags` 的低 9 位为新信号量集的权限位
+pub fn get_or_create_sem_set(key: i32, nsems: usize, flags: i32) -> LinuxResult<i32> {
+    if nsems > SEMMSL {
+        return Err(LinuxError::EINVAL);
+    }
+    let mut key_map = KEY_TO_SEMID.lock();
+    if key != IPC_PRIVATE {
+        if let Some(&semid) = key_map.get(&key) {
+            if flags & IPC_CREAT != 0 && flags & IPC_EXCL != 0 {
+                return Err(LinuxError::EEXIST);
+            }
+            let set = get_sem_set(semid).ok_or(LinuxError::EIDRM)?;
+            if nsems > set.nsems() {
+                return Err(LinuxError::EINVAL);
+            }
+            return Ok(semid);
+        }
+        if flags & IPC_CREAT == 0 {
+            return Err(LinuxError::ENOENT);
+        }
+    }
+    if nsems == 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    let semid = SEMID.fetch_add(1, Ordering::Relaxed);
+    SEM_SETS
+        .lock()
+        .insert(semid, Arc::new(SemSet::new(key, nsems, flags)));
+    if key != IPC_PRIVATE {
+        key_map.insert(key, semid);
+    }
+    Ok(semid)
+}
+
+/// 根据 id 获取信号量集
+pub fn get_sem_set(semid: i32) -> Option<Arc<SemSet>> {
+    SEM_SETS.lock().get(&semid).cloned()
+}
+
+/// 删除信号量集，正在等待的任务会返回 `EIDRM`
+pub fn remove_sem_set(semid: i32) -> LinuxResult<()> {
+    let set = SEM_SETS.lock().remove(&semid).ok_or(LinuxError::EINVAL)?;
+    let key = set.stat().sem_perm.key;
+    if key != IPC_PRIVATE {
+        KEY_TO_SEMID.lock().remove(&key);
+    }
+    set.remove();
+    Ok(())
+}
+
+/// 进程退出时撤销其通过 `SEM_UNDO` 对所有信号量所做的修改
+pub fn apply_sem_undo(pid: u64) {
+    let sets: Vec<Arc<SemSet>> = SEM_SETS.lock().values().cloned().collect();
+    for set in sets {
+        set.undo(pid);
+    }
+}
@@ -2,6 +2,7 @@
 #![cfg_attr(not(test), no_std)]
 mod area;
 mod backend;
+pub mod ipc;
 mod shared;
 pub use area::MapArea;
 use axerrno::{AxError, AxResult};
@@ -97,6 +98,19 @@ impl MemorySet {
             .unwrap_or_default()
     }
 
+    /// The total size of the areas in this memory set, used to check `RLIMIT_AS`.
+    pub fn total_size(&self) -> usize {
+        self.owned_mem.values().map(|area| area.size()).sum()
+    }
+
+    /// The size of the pages which have been loaded into physical memory, used as the RSS.
+    pub fn resident_size(&self) -> usize {
+        self.owned_mem
+            .values()
+            .map(|area| area.pages.iter().filter(|page| page.is_some()).count() * PAGE_SIZE_4K)
+            .sum()
+    }
+
     /// Allocate contiguous region. If no data, it will create a lazy load region.
     pub fn new_region(
         &mut self,
@@ -387,7 +401,11 @@ impl MemorySet {
     }
 
     /// It will map newly allocated page in the page table. You need to flush TLB after this.
-    pub fn handle_page_fault(&mut self, addr: VirtAddr, flags: MappingFlags) -> AxResult<()> {
+    ///
+    /// Returns whether the page was filled from a file backend, i.e. whether it is a major fault.
+    /// Returns `PermissionDenied` if the address is mapped but the access is not allowed, and
+    /// `BadAddress` if the address is not mapped.
+    pub fn handle_page_fault(&mut self, addr: VirtAddr, flags: MappingFlags) -> AxResult<bool> {
         match self
             .owned_mem
             .values_mut()
@@ -395,9 +413,10 @@ impl MemorySet {
         {
             Some(area) => {
                 if !area.handle_page_fault(addr, flags, &mut self.page_table) {
-                    return Err(AxError::BadAddress);
+                    // 地址已被映射，但访问权限不满足
+                    return Err(AxError::PermissionDenied);
                 }
-                Ok(())
+                Ok(area.backend.is_some())
             }
             None => {
                 error!("Page fault address {:?} not found in memory set ", addr);
@@ -1,35 +1,49 @@
 use core::ops::Deref;
 use core::ptr::copy_nonoverlapping;
 use core::str::from_utf8;
+use core::sync::atomic::{AtomicBool, Ordering};
 extern crate alloc;
 use alloc::sync::Arc;
 use alloc::{
+    collections::BTreeMap,
     string::{String, ToString},
     vec,
     vec::Vec,
 };
-use axconfig::{MAX_USER_HEAP_SIZE, MAX_USER_STACK_SIZE, USER_HEAP_BASE, USER_STACK_TOP};
+use axconfig::{MAX_USER_HEAP_SIZE, USER_HEAP_BASE, USER_STACK_TOP};
 use axerrno::{AxError, AxResult};
+use axhal::cpu::this_cpu_id;
 use axhal::mem::VirtAddr;
 use axhal::paging::MappingFlags;
+#[cfg(feature = "signal")]
+use axhal::time::MICROS_PER_SEC;
 use axhal::KERNEL_PROCESS_ID;
 use axlog::{debug, info};
 use axmem::MemorySet;
 #[cfg(feature = "signal")]
-use axsignal::signal_no::SignalNo;
+use axsignal::{
+    action::{SigActionFlags, SIG_IGN},
+    info::{SigInfo, CLD_EXITED, CLD_KILLED, SEGV_ACCERR, SEGV_MAPERR},
+    signal_no::SignalNo,
+};
 use axsync::Mutex;
 use axtask::{current, yield_now, CurrentTask, TaskId, TaskState, IDLE_TASK, RUN_QUEUE};
 use elf_parser::{
     get_app_stack_region, get_auxv_vector, get_elf_entry, get_elf_segments, get_relocate_pairs,
 };
+use lazy_static::lazy_static;
 use xmas_elf::program::SegmentData;
 
 use crate::flags::WaitStatus;
 use crate::futex::clear_wait;
 use crate::link::real_path;
 use crate::process::{Process, PID2PC, TID2TASK};
+use crate::rusage::ResourceUsage;
 #[cfg(feature = "signal")]
-use crate::signal::{send_signal_to_process, send_signal_to_thread};
+use crate::signal::{
+    send_signal_to_process, send_signal_to_process_with_info, send_signal_to_thread_with_info,
+    sigchld_action,
+};
 
 /// 初始化内核调度进程
 pub fn init_kernel_process() {
@@ -46,6 +60,7 @@ pub fn init_kernel_process() {
         IDLE_TASK.current_ref_raw().get_unchecked()
     }));
     PID2PC.lock().insert(kernel_process.pid(), kernel_process);
+    crate::timer::init_timer_worker();
 }
 
 /// return the `Arc<Process>` of the current process
@@ -74,8 +89,9 @@ pub fn exit_current_task(exit_code: i32) -> ! {
         current_task.is_leader(),
     );
     // 检查这个任务是否有sig_child信号
+    // 主线程退出时的 SIGCHLD 在进程退出完成后发送
     #[cfg(feature = "signal")]
-    if current_task.get_sig_child() || current_task.is_leader() {
+    if current_task.get_sig_child() && !current_task.is_leader() {
         let parent = process.get_parent();
         if parent != KERNEL_PROCESS_ID {
             // 发送sigchild
@@ -114,26 +130,55 @@ pub fn exit_current_task(exit_code: i32) -> ! {
         TID2TASK.lock().remove(&curr_id);
         process.set_exit_code(exit_code);
 
+        // 在释放线程之前，将所有线程的资源使用统计记入进程
+        process.update_max_rss();
+        for task in process.tasks.lock().iter() {
+            process.account_exited_task(task);
+        }
+        // 撤销进程通过 SEM_UNDO 对信号量所做的修改，需要在父进程可以回收进程之前完成
+        axmem::ipc::apply_sem_undo(process.pid());
         process.set_zombie(true);
 
         process.tasks.lock().clear();
+        process.timers.lock().clear(process.pid());
         process.fd_manager.fd_table.lock().clear();
         #[cfg(feature = "signal")]
         process.signal_modules.lock().clear();
 
         let mut pid2pc = PID2PC.lock();
-        let kernel_process = pid2pc.get(&KERNEL_PROCESS_ID).unwrap();
-        // 将子进程交给idle进程
-        // process.memory_set = Arc::clone(&kernel_process.memory_set);
-        for child in process.children.lock().deref() {
-            child.set_parent(KERNEL_PROCESS_ID);
-            kernel_process.children.lock().push(Arc::clone(child));
+        // 将子进程交给最近的收割者祖先，若不存在则交给 init 进程
+        let reaper_id = find_child_reaper(&pid2pc, &process);
+        let reaper = Arc::clone(pid2pc.get(&reaper_id).unwrap());
+        // 已经完成退出的子进程不会再通知新的父进程，需要在此向收割者补发 SIGCHLD。
+        // 尚未从进程表中移除的子进程会在退出时自行通知新的父进程
+        let mut adopted_zombies = Vec::new();
+        for child in process.children.lock().drain(..) {
+            child.set_parent(reaper_id);
+            if reaper_id == KERNEL_PROCESS_ID {
+                child.set_orphan(true);
+            }
+            if child.get_zombie() && !pid2pc.contains_key(&child.pid()) {
+                // init 进程会自动回收已经退出的孤儿进程
+                if reaper_id == KERNEL_PROCESS_ID {
+                    continue;
+                }
+                adopted_zombies.push(Arc::clone(&child));
+            }
+            reaper.children.lock().push(child);
         }
-        if let Some(parent_process) = pid2pc.get(&process.get_parent()) {
+        let parent_id = process.get_parent();
+        let parent = pid2pc.get(&parent_id).cloned();
+        if let Some(parent_process) = parent.as_ref() {
             parent_process.set_vfork_block(false);
         }
         pid2pc.remove(&process.pid());
         drop(pid2pc);
+        for child in adopted_zombies {
+            notify_parent(&reaper, &child, child.get_exit_code());
+        }
+        if let Some(parent) = parent {
+            notify_parent(&parent, &process, exit_code);
+        }
         drop(process);
     } else {
         TID2TASK.lock().remove(&curr_id);
@@ -147,6 +192,7 @@ pub fn exit_current_task(exit_code: i32) -> ! {
             }
         }
         drop(tasks);
+        process.account_exited_task(&current_task);
         #[cfg(feature = "signal")]
         process.signal_modules.lock().remove(&curr_id);
         drop(process);
@@ -154,16 +200,93 @@ pub fn exit_current_task(exit_code: i32) -> ! {
     RUN_QUEUE.lock().exit_current(exit_code);
 }
 
+/// 找到进程退出后接管其子进程的收割者
+///
+/// 沿父进程链向上寻找设置了 PR_SET_CHILD_SUBREAPER 的祖先，找不到时返回 init 进程
+fn find_child_reaper(pid2pc: &BTreeMap<u64, Arc<Process>>, process: &Process) -> u64 {
+    let mut ancestor_id = process.get_parent();
+    while ancestor_id != KERNEL_PROCESS_ID {
+        match pid2pc.get(&ancestor_id) {
+            Some(ancestor) if ancestor.get_child_subreaper() => return ancestor_id,
+            Some(ancestor) => ancestor_id = ancestor.get_parent(),
+            None => break,
+        }
+    }
+    KERNEL_PROCESS_ID
+}
+
+/// 进程退出后通知父进程
+///
+/// 被 init 进程收养的孤儿进程，或者父进程的 SIGCHLD 处理方式为 SIG_IGN 或设置了
+/// SA_NOCLDWAIT 时，退出的进程会被自动回收，不会成为僵尸进程。
+/// 由内核直接创建的进程留给内核通过 [`wait_pid`] 回收。
+/// 除了 init 进程与 SIG_IGN 之外都会向父进程发送 SIGCHLD
+#[allow(unused_variables)]
+fn notify_parent(parent: &Process, process: &Process, exit_code: i32) {
+    if parent.pid() == KERNEL_PROCESS_ID {
+        if process.get_orphan() {
+            parent
+                .children
+                .lock()
+                .retain(|child| child.pid() != process.pid());
+        }
+        return;
+    }
+    #[cfg(feature = "signal")]
+    {
+        let action = sigchld_action(parent);
+        let ignored = action.map_or(false, |action| action.sa_handler == SIG_IGN);
+        if ignored
+            || action.map_or(false, |action| {
+                action.sa_flags.contains(SigActionFlags::SA_NOCLDWAIT)
+            })
+        {
+            parent
+                .children
+                .lock()
+                .retain(|child| child.pid() != process.pid());
+        }
+        if ignored {
+            return;
+        }
+        let term_signal = process.get_term_signal();
+        let (code, status) = if term_signal != 0 {
+            (CLD_KILLED, term_signal)
+        } else {
+            (CLD_EXITED, exit_code)
+        };
+        // siginfo 中的 CPU 时间以时钟滴答为单位
+        let to_ticks = |us: usize| us * axconfig::TICKS_PER_SEC / MICROS_PER_SEC as usize;
+        let (utime, stime) = process.cpu_times_us();
+        let info = SigInfo::child(
+            code,
+            process.pid() as i32,
+            0,
+            status,
+            to_ticks(utime),
+            to_ticks(stime),
+        );
+        let _ = send_signal_to_process_with_info(
+            parent.pid() as isize,
+            SignalNo::SIGCHLD as isize,
+            Some(info),
+        );
+    }
+}
+
 /// 返回应用程序入口，用户栈底，用户堆底
+///
+/// `stack_size` 为用户栈的大小，由 RLIMIT_STACK 决定
 pub fn load_app(
     name: String,
     mut args: Vec<String>,
     envs: &Vec<String>,
     memory_set: &mut MemorySet,
+    stack_size: usize,
 ) -> AxResult<(VirtAddr, VirtAddr, VirtAddr)> {
     if name.ends_with(".sh") {
         args = [vec![String::from("busybox"), String::from("sh")], args].concat();
-        return load_app("busybox".to_string(), args, envs, memory_set);
+        return load_app("busybox".to_string(), args, envs, memory_set, stack_size);
     }
     let elf_data = if let Ok(ans) = axfs::api::read(name.as_str()) {
         ans
@@ -187,7 +310,7 @@ pub fn load_app(
         let interp_path = interp_path.trim_matches(char::from(0)).to_string();
         let real_interp_path = real_path(&interp_path);
         args = [vec![real_interp_path.clone()], args].concat();
-        return load_app(real_interp_path, args, envs, memory_set);
+        return load_app(real_interp_path, args, envs, memory_set, stack_size);
     }
     info!("args: {:?}", args);
     let elf_base_addr = Some(0x400_0000);
@@ -232,7 +355,6 @@ pub fn load_app(
     let auxv = get_auxv_vector(&elf, elf_base_addr);
 
     let stack_top = VirtAddr::from(USER_STACK_TOP);
-    let stack_size = MAX_USER_STACK_SIZE;
 
     let (stack_data, stack_bottom) = get_app_stack_region(args, envs, auxv, stack_top, stack_size);
     memory_set.new_region(
@@ -270,6 +392,56 @@ pub fn time_stat_output() -> (usize, usize, usize, usize) {
     curr_task.time_stat_output()
 }
 
+lazy_static! {
+    /// 各个 CPU 上是否有来自用户态的时钟中断，其对应的 CPU 时间检查尚未进行
+    static ref USER_TICK_PENDING: Vec<AtomicBool> =
+        (0..axconfig::SMP).map(|_| AtomicBool::new(false)).collect();
+}
+
+/// 记录一次来自用户态的时钟中断
+///
+/// 在时钟中断中调用。中断上下文中不能获取进程的锁，因此只做标记，
+/// 检查推迟到返回用户态之前的 [`handle_user_tick`] 中进行
+pub fn account_user_tick() {
+    USER_TICK_PENDING[this_cpu_id()].store(true, Ordering::Release);
+}
+
+/// 若本 CPU 上发生过来自用户态的时钟中断，则检查当前进程的 CPU 时间计时器与 CPU 时间限制
+///
+/// 在返回用户态之前于任务上下文中调用
+pub fn handle_user_tick() {
+    if !USER_TICK_PENDING[this_cpu_id()].swap(false, Ordering::AcqRel) {
+        return;
+    }
+    crate::timer::check_cpu_timers();
+    #[cfg(feature = "signal")]
+    check_cpu_rlimit();
+}
+
+/// 检查当前进程占用的 CPU 时间是否超过了 RLIMIT_CPU
+///
+/// 超过软上限时每秒发送一次 SIGXCPU，超过硬上限时发送 SIGKILL
+#[cfg(feature = "signal")]
+fn check_cpu_rlimit() {
+    let pid = current().get_process_id();
+    if pid == KERNEL_PROCESS_ID {
+        return;
+    }
+    let process = if let Some(process) = PID2PC.lock().get(&pid) {
+        Arc::clone(process)
+    } else {
+        return;
+    };
+    let cpu_secs = (process.cpu_time_us() / 1_000_000) as u64;
+    let signal = match process.rlimits.lock().check_cpu_time(cpu_secs) {
+        Some(true) => SignalNo::SIGKILL,
+        Some(false) => SignalNo::SIGXCPU,
+        None => return,
+    };
+    info!("process {} exceeds RLIMIT_CPU, send {:?}", pid, signal);
+    let _ = send_signal_to_process(pid as isize, signal as isize);
+}
+
 /// To deal with the page fault
 pub fn handle_page_fault(addr: VirtAddr, flags: MappingFlags) {
     axlog::debug!("'page fault' addr: {:?}, flags: {:?}", addr, flags);
@@ -279,17 +451,36 @@ pub fn handle_page_fault(addr: VirtAddr, flags: MappingFlags) {
         current_process.memory_set.lock().lock().page_table_token()
     );
 
-    if current_process
+    let result = current_process
         .memory_set
         .lock()
         .lock()
-        .handle_page_fault(addr, flags)
-        .is_ok()
-    {
-        axhal::arch::flush_tlb(None);
-    } else {
+        .handle_page_fault(addr, flags);
+    match result {
+        Ok(major) => {
+            current().record_page_fault(major);
+            axhal::arch::flush_tlb(None);
+        }
         #[cfg(feature = "signal")]
-        let _ = send_signal_to_thread(current().id().as_u64() as isize, SignalNo::SIGSEGV as isize);
+        Err(err) => {
+            // 访问已映射但权限不足的地址为 SEGV_ACCERR，访问未映射的地址为 SEGV_MAPERR
+            let code = if err == AxError::PermissionDenied {
+                SEGV_ACCERR
+            } else {
+                SEGV_MAPERR
+            };
+            let _ = send_signal_to_thread_with_info(
+                current().id().as_u64() as isize,
+                SignalNo::SIGSEGV as isize,
+                Some(SigInfo::fault(
+                    SignalNo::SIGSEGV as i32,
+                    code,
+                    addr.as_usize(),
+                )),
+            );
+        }
+        #[cfg(not(feature = "signal"))]
+        Err(_) => {}
     }
 }
 
@@ -301,6 +492,18 @@ pub fn handle_page_fault(addr: VirtAddr, flags: MappingFlags) {
 ///
 /// 保证传入的 ptr 是有效的
 pub unsafe fn wait_pid(pid: isize, exit_code_ptr: *mut i32) -> Result<u64, WaitStatus> {
+    wait_pid_with_usage(pid, exit_code_ptr).map(|(pid, _)| pid)
+}
+
+/// 与 [`wait_pid`] 相同，但同时返回被回收的子进程的资源使用统计，供 sys_wait4 使用
+///
+/// # Safety
+///
+/// 保证传入的 ptr 是有效的
+pub unsafe fn wait_pid_with_usage(
+    pid: isize,
+    exit_code_ptr: *mut i32,
+) -> Result<(u64, ResourceUsage), WaitStatus> {
     // 获取当前进程
     let curr_process = current_process();
     let mut exit_task_id: usize = 0;
@@ -344,8 +547,9 @@ pub unsafe fn wait_pid(pid: isize, exit_code_ptr: *mut i32) -> Result<u64, WaitS
     }
     // 若进程成功结束，需要将其从父进程的children中删除
     if answer_status == WaitStatus::Exited {
-        curr_process.children.lock().remove(exit_task_id);
-        return Ok(answer_id);
+        let child = curr_process.children.lock().remove(exit_task_id);
+        let usage = curr_process.account_reaped_child(&child);
+        return Ok((answer_id, usage));
     }
     Err(answer_status)
 }
@@ -2,7 +2,7 @@
 extern crate alloc;
 use core::sync::atomic::{AtomicI32, AtomicU64};
 
-use alloc::string::String;
+use alloc::collections::BTreeSet;
 use alloc::sync::Arc;
 use axfs::api::{FileIO, OpenFlags};
 use axlog::info;
@@ -18,7 +18,10 @@ pub struct FdManager {
     pub limit: AtomicU64,
     /// 创建文件时的mode的掩码
     umask: AtomicI32,
-    pub cwd: Mutex<String>,
+    /// 设置了 `FD_CLOEXEC` 的文件描述符
+    ///
+    /// 该标志属于文件描述符而不是打开的文件，因此不能保存在共享的文件对象中
+    cloexec: Mutex<BTreeSet<usize>>,
 }
 
 impl FdManager {
@@ -27,7 +30,7 @@ impl FdManager {
             fd_table: Mutex::new(fd_table),
             limit: AtomicU64::new(limit as u64),
             umask: AtomicI32::new(0o022),
-            cwd: Mutex::new(String::from("/")),
+            cloexec: Mutex::new(BTreeSet::new()),
         }
     }
 
@@ -40,7 +43,6 @@ impl FdManager {
             .store(new_limit, core::sync::atomic::Ordering::Release)
     }
 
-    #[allow(unused)]
     pub fn get_mask(&self) -> i32 {
         self.umask.load(core::sync::atomic::Ordering::Acquire)
     }
@@ -53,17 +55,39 @@ impl FdManager {
         old_mask
     }
 
+    /// 设置或清除文件描述符的 `FD_CLOEXEC` 标志
+    pub fn set_cloexec(&self, fd: usize, cloexec: bool) {
+        let mut fds = self.cloexec.lock();
+        if cloexec {
+            fds.insert(fd);
+        } else {
+            fds.remove(&fd);
+        }
+    }
+
+    /// 查询文件描述符是否设置了 `FD_CLOEXEC` 标志
+    pub fn is_cloexec(&self, fd: usize) -> bool {
+        self.cloexec.lock().contains(&fd)
+    }
+
+    /// fork 时从父进程继承各个文件描述符的 `FD_CLOEXEC` 标志
+    pub fn inherit_cloexec(&self, parent: &FdManager) {
+        *self.cloexec.lock() = parent.cloexec.lock().clone();
+    }
+
     /// 在执行 `exec()` 时关闭标记为 `CLOEXEC` 的文件
     pub fn close_on_exec(&self) {
         let mut fd_table = self.fd_table.lock();
+        let mut cloexec = self.cloexec.lock();
         for (index, fd) in fd_table.iter_mut().enumerate() {
             if let Some(f) = fd {
-                if f.get_status().is_close_on_exec() {
+                if cloexec.contains(&index) || f.get_status().is_close_on_exec() {
                     info!("close fd: {} on exec", index);
                     fd.take();
                 }
             }
         }
+        cloexec.clear();
         if fd_table[0].is_none() {
             fd_table[0] = Some(Arc::new(Stdin {
                 flags: Mutex::new(OpenFlags::empty()),
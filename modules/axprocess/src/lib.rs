@@ -9,7 +9,11 @@ pub use process::{Process, PID2PC, TID2TASK};
 pub mod flags;
 pub mod futex;
 pub mod link;
+pub mod rlimit;
+pub mod rusage;
+pub mod seccomp;
 mod stdio;
+pub mod timer;
 
 mod fd_manager;
 #[cfg(feature = "signal")]
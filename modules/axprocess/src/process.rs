@@ -5,7 +5,7 @@ use alloc::vec;
 use alloc::vec::Vec;
 use alloc::{collections::BTreeMap, string::String};
 use axerrno::{AxError, AxResult};
-use axfs::api::{FileIO, OpenFlags};
+use axfs::api::{FileIO, FsContext, FsContextIf, OpenFlags, ProcInfoIf};
 use axhal::arch::{write_page_table_root0, TrapFrame};
 use axhal::mem::{phys_to_virt, VirtAddr};
 
@@ -19,9 +19,16 @@ use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
 use crate::fd_manager::FdManager;
 use crate::flags::CloneFlags;
 use crate::futex::FutexRobustList;
+use crate::rlimit::{
+    RLimit, ResourceLimits, FD_LIMIT_ORIGIN, RLIMIT_NOFILE, RLIMIT_NPROC, RLIMIT_STACK,
+    RLIM_INFINITY,
+};
+use crate::rusage::ResourceUsage;
+use crate::seccomp::SeccompState;
 #[cfg(feature = "signal")]
-use crate::signal::SignalModule;
+use crate::signal::{SigPending, SignalModule};
 use crate::stdio::{Stderr, Stdin, Stdout};
+use crate::timer::TimerTable;
 use crate::{load_app, yield_now_task};
 
 /// Map from task id to arc pointer of task
@@ -29,7 +36,6 @@ pub static TID2TASK: Mutex<BTreeMap<u64, AxTaskRef>> = Mutex::new(BTreeMap::new(
 
 /// Map from process id to arc pointer of process
 pub static PID2PC: Mutex<BTreeMap<u64, Arc<Process>>> = Mutex::new(BTreeMap::new());
-const FD_LIMIT_ORIGIN: usize = 1025;
 
 #[cfg(feature = "signal")]
 extern "C" {
@@ -53,6 +59,9 @@ pub struct Process {
     /// 文件描述符管理器
     pub fd_manager: FdManager,
 
+    /// 根目录与当前工作目录，CLONE_FS 时在进程间共享
+    pub fs_context: Mutex<Arc<Mutex<FsContext>>>,
+
     /// 进程状态
     pub is_zombie: AtomicBool,
 
@@ -73,6 +82,12 @@ pub struct Process {
     /// 第一维代表TaskID，第二维代表对应的信号处理模块
     pub signal_modules: Mutex<BTreeMap<u64, SignalModule>>,
 
+    #[cfg(feature = "signal")]
+    /// 发送给进程的未决信号，由任意一个没有阻塞该信号的线程处理
+    ///
+    /// 需要同时持有 `signal_modules` 的锁时，先获取 `signal_modules` 的锁
+    pub shared_pending: Mutex<SigPending>,
+
     /// robust list存储模块
     /// 用来存储线程对共享变量的使用地址
     /// 具体使用交给了用户空间
@@ -83,6 +98,37 @@ pub struct Process {
 
     /// 该进程可执行文件所在的路径
     pub file_path: Mutex<String>,
+
+    /// 资源限制表，fork 时继承
+    pub rlimits: Mutex<ResourceLimits>,
+
+    /// 是否为子进程收割者，由 PR_SET_CHILD_SUBREAPER 设置
+    ///
+    /// 后代进程成为孤儿时会被交给最近的收割者祖先，而不是 init 进程
+    pub is_child_subreaper: AtomicBool,
+
+    /// 是否为被 init 进程收养的孤儿进程，退出时会被自动回收
+    ///
+    /// 由内核直接创建的进程的父进程同样是 init 进程，但需要由内核等待其退出
+    pub is_orphan: AtomicBool,
+
+    /// 导致进程终止的信号，正常退出时为 0
+    pub term_signal: AtomicI32,
+
+    /// seccomp 过滤状态，fork 与 exec 时继承
+    pub seccomp: Mutex<SeccompState>,
+
+    /// 是否设置了 PR_SET_NO_NEW_PRIVS，一旦设置不能取消
+    pub no_new_privs: AtomicBool,
+
+    /// 已经退出的线程的资源使用统计之和，同时记录进程的最大常驻内存
+    pub exited_usage: Mutex<ResourceUsage>,
+
+    /// 已经被回收的子进程及其后代的资源使用统计之和
+    pub children_usage: Mutex<ResourceUsage>,
+
+    /// 间隔计时器与 POSIX 计时器，fork 时不继承，exec 时只保留间隔计时器
+    pub timers: Mutex<TimerTable>,
 }
 
 impl Process {
@@ -121,6 +167,36 @@ impl Process {
         self.is_zombie.store(status, Ordering::Release)
     }
 
+    /// whether the process is a child subreaper
+    pub fn get_child_subreaper(&self) -> bool {
+        self.is_child_subreaper.load(Ordering::Acquire)
+    }
+
+    /// set the process as a child subreaper
+    pub fn set_child_subreaper(&self, status: bool) {
+        self.is_child_subreaper.store(status, Ordering::Release)
+    }
+
+    /// whether the process is an orphan adopted by the init process
+    pub fn get_orphan(&self) -> bool {
+        self.is_orphan.load(Ordering::Acquire)
+    }
+
+    /// set the process as an orphan adopted by the init process
+    pub fn set_orphan(&self, status: bool) {
+        self.is_orphan.store(status, Ordering::Release)
+    }
+
+    /// get the signal which terminated the process, 0 if it exited normally
+    pub fn get_term_signal(&self) -> i32 {
+        self.term_signal.load(Ordering::Acquire)
+    }
+
+    /// set the signal which terminated the process
+    pub fn set_term_signal(&self, signal: i32) {
+        self.term_signal.store(signal, Ordering::Release)
+    }
+
     /// get the heap top of the process
     pub fn get_heap_top(&self) -> u64 {
         self.heap_top.load(Ordering::Acquire)
@@ -157,6 +233,125 @@ impl Process {
         (*self.file_path.lock()).clone()
     }
 
+    /// 获取某一项资源限制
+    pub fn get_rlimit(&self, resource: i32) -> AxResult<RLimit> {
+        if resource == RLIMIT_NOFILE {
+            // fd 上限以 fd_manager 中记录的为准
+            let mut limit = self.rlimits.lock().get(resource)?;
+            limit.rlim_cur = self.fd_manager.get_limit();
+            return Ok(limit);
+        }
+        self.rlimits.lock().get(resource)
+    }
+
+    /// 设置某一项资源限制
+    pub fn set_rlimit(&self, resource: i32, new_limit: RLimit) -> AxResult<()> {
+        self.rlimits.lock().set(resource, new_limit)?;
+        if resource == RLIMIT_NOFILE {
+            self.fd_manager.set_limit(new_limit.rlim_cur);
+        }
+        Ok(())
+    }
+
+    /// 从父进程继承资源限制
+    pub fn inherit_rlimits(&self, parent: &Process) {
+        *self.rlimits.lock() = parent.rlimits.lock().clone();
+        self.fd_manager.set_limit(parent.fd_manager.get_limit());
+    }
+
+    /// 根据 RLIMIT_STACK 得到加载程序时的用户栈大小
+    ///
+    /// 用户栈区域的大小不能超过 MAX_USER_STACK_SIZE
+    pub fn user_stack_size(&self) -> usize {
+        let limit = self
+            .rlimits
+            .lock()
+            .get(RLIMIT_STACK)
+            .map(|limit| limit.rlim_cur)
+            .unwrap_or(RLIM_INFINITY);
+        if limit == RLIM_INFINITY {
+            axconfig::MAX_USER_STACK_SIZE
+        } else {
+            (limit as usize).clamp(axhal::mem::PAGE_SIZE_4K, axconfig::MAX_USER_STACK_SIZE)
+        }
+    }
+
+    /// 进程所有线程占用的 CPU 时间之和，单位为微秒
+    pub fn cpu_time_us(&self) -> usize {
+        let usage = self.threads_usage();
+        usage.utime_us + usage.stime_us
+    }
+
+    /// 进程所有线程的用户态与内核态运行时间，单位为微秒
+    pub fn cpu_times_us(&self) -> (usize, usize) {
+        let usage = self.threads_usage();
+        (usage.utime_us, usage.stime_us)
+    }
+
+    /// 进程所有尚未被回收的后代进程
+    ///
+    /// 在实现进程组与多用户之前，进程自身及其后代进程被视为调用者的进程组与用户所拥有的进程
+    pub fn descendants(&self) -> Vec<Arc<Process>> {
+        let mut descendants = Vec::new();
+        let mut stack = self.children.lock().clone();
+        while let Some(process) = stack.pop() {
+            stack.extend(process.children.lock().iter().cloned());
+            descendants.push(process);
+        }
+        descendants
+    }
+
+    /// RLIMIT_NPROC 所统计的属于调用者的进程数目，即进程自身及其后代进程的数目
+    pub fn owned_process_count(&self) -> u64 {
+        1 + self.descendants().len() as u64
+    }
+
+    /// 根据当前地址空间的常驻内存更新进程的最大常驻内存
+    pub fn update_max_rss(&self) {
+        let rss_kb = self.memory_set.lock().lock().resident_size() / 1024;
+        let mut usage = self.exited_usage.lock();
+        usage.maxrss_kb = usage.maxrss_kb.max(rss_kb);
+    }
+
+    /// 线程退出时，将其资源使用统计累加到进程中
+    pub fn account_exited_task(&self, task: &TaskInner) {
+        self.exited_usage
+            .lock()
+            .add(&ResourceUsage::from_task(task));
+    }
+
+    /// 进程自身的资源使用统计，包括所有存活与已经退出的线程
+    pub fn self_usage(&self) -> ResourceUsage {
+        if !self.get_zombie() {
+            self.update_max_rss();
+        }
+        self.threads_usage()
+    }
+
+    /// 累加所有线程的资源使用统计，不更新内存信息，可以在时钟中断中调用
+    fn threads_usage(&self) -> ResourceUsage {
+        let mut usage = *self.exited_usage.lock();
+        for task in self.tasks.lock().iter() {
+            usage.add(&ResourceUsage::from_task(task));
+        }
+        usage
+    }
+
+    /// 已经被回收的子进程的资源使用统计
+    pub fn children_usage(&self) -> ResourceUsage {
+        *self.children_usage.lock()
+    }
+
+    /// 回收子进程时，将子进程及其已回收后代的资源使用统计累加到当前进程
+    ///
+    /// 返回被回收的子进程的资源使用统计
+    pub fn account_reaped_child(&self, child: &Process) -> ResourceUsage {
+        let mut usage = child.self_usage();
+        usage.add(&child.children_usage());
+        self.children_usage.lock().add(&usage);
+        usage
+    }
+
     /// 若进程运行完成，则获取其返回码
     /// 若正在运行（可能上锁或没有上锁），则返回None
     pub fn get_code_if_exit(&self) -> Option<i32> {
@@ -187,11 +382,23 @@ impl Process {
             heap_bottom: AtomicU64::new(heap_bottom),
             heap_top: AtomicU64::new(heap_bottom),
             fd_manager: FdManager::new(fd_table, FD_LIMIT_ORIGIN),
+            fs_context: Mutex::new(Arc::new(Mutex::new(FsContext::new()))),
             #[cfg(feature = "signal")]
             signal_modules: Mutex::new(BTreeMap::new()),
+            #[cfg(feature = "signal")]
+            shared_pending: Mutex::new(SigPending::default()),
             robust_list: Mutex::new(BTreeMap::new()),
             blocked_by_vfork: Mutex::new(false),
             file_path: Mutex::new(String::new()),
+            rlimits: Mutex::new(ResourceLimits::new()),
+            is_child_subreaper: AtomicBool::new(false),
+            is_orphan: AtomicBool::new(false),
+            term_signal: AtomicI32::new(0),
+            seccomp: Mutex::new(SeccompState::default()),
+            no_new_privs: AtomicBool::new(false),
+            exited_usage: Mutex::new(ResourceUsage::default()),
+            children_usage: Mutex::new(ResourceUsage::default()),
+            timers: Mutex::new(TimerTable::new()),
         }
     }
     /// 根据给定参数创建一个新的进程，作为应用程序初始进程
@@ -223,13 +430,18 @@ impl Process {
             };
         }
 
-        let (entry, user_stack_bottom, heap_bottom) =
-            if let Ok(ans) = load_app(path.clone(), args, envs, &mut memory_set) {
-                ans
-            } else {
-                error!("Failed to load app {}", path);
-                return Err(AxError::NotFound);
-            };
+        let (entry, user_stack_bottom, heap_bottom) = if let Ok(ans) = load_app(
+            path.clone(),
+            args,
+            envs,
+            &mut memory_set,
+            axconfig::MAX_USER_STACK_SIZE,
+        ) {
+            ans
+        } else {
+            error!("Failed to load app {}", path);
+            return Err(AxError::NotFound);
+        };
         let new_process = Arc::new(Self::new(
             TaskId::new().as_u64(),
             KERNEL_PROCESS_ID,
@@ -263,6 +475,7 @@ impl Process {
             .lock()
             .insert(new_task.id().as_u64(), Arc::clone(&new_task));
         new_task.set_leader(true);
+        new_task.set_fs_context(new_process.fs_context.lock().clone());
         let new_trap_frame =
             TrapFrame::app_init_context(entry.as_usize(), user_stack_bottom.as_usize());
         new_task.set_trap_context(new_trap_frame);
@@ -273,7 +486,7 @@ impl Process {
         new_process
             .signal_modules
             .lock()
-            .insert(new_task.id().as_u64(), SignalModule::init_signal(None));
+            .insert(new_task.id().as_u64(), SignalModule::init_signal(None, 0));
         new_process
             .robust_list
             .lock()
@@ -322,6 +535,28 @@ impl VforkSet for VforkHandler {
     }
 }
 
+struct FsContextHandler;
+
+#[crate_interface::impl_interface]
+impl FsContextIf for FsContextHandler {
+    /// 获取当前进程的根目录与工作目录，供 axfs 解析路径使用
+    fn current_fs_context() -> Option<Arc<Mutex<FsContext>>> {
+        current()
+            .fs_context()
+            .and_then(|context| context.downcast::<Mutex<FsContext>>().ok())
+    }
+}
+
+struct ProcInfoHandler;
+
+#[crate_interface::impl_interface]
+impl ProcInfoIf for ProcInfoHandler {
+    /// 由任务管理模块生成 /proc/timer_list 的内容
+    fn timer_list() -> String {
+        axtask::timer_list_info()
+    }
+}
+
 impl Process {
     /// 将当前进程替换为指定的用户程序
     /// args为传入的参数
@@ -333,6 +568,7 @@ impl Process {
         // 处理分配的页帧
         // 之后加入额外的东西之后再处理其他的包括信号等因素
         // 不是直接删除原有地址空间，否则构建成本较高。
+        self.update_max_rss();
 
         if Arc::strong_count(&self.memory_set.lock()) == 1 {
             self.memory_set.lock().lock().unmap_user_areas();
@@ -373,12 +609,18 @@ impl Process {
             } else {
                 TID2TASK.lock().remove(&task.id().as_u64());
                 RUN_QUEUE.lock().remove_task(&task);
+                self.account_exited_task(&task);
             }
         }
         // 当前任务被设置为主线程
         current_task.set_leader(true);
-        // 重置统计时间
+        // 重置统计时间，CPU 时间计时器的到期时间随之调整
+        let (_, utime_us, _, stime_us) = current_task.time_stat_output();
         current_task.time_stat_clear();
+        let mut timers = self.timers.lock();
+        timers.clear_posix(self.pid);
+        timers.rewind_cpu_clocks(utime_us as u64 * 1000, (utime_us + stime_us) as u64 * 1000);
+        drop(timers);
         current_task.set_name(name.split('/').last().unwrap());
         assert!(tasks.len() == 1);
         drop(tasks);
@@ -387,9 +629,14 @@ impl Process {
         } else {
             args
         };
-        let (entry, user_stack_bottom, heap_bottom) = if let Ok(ans) =
-            load_app(name.clone(), args, envs, &mut self.memory_set.lock().lock())
-        {
+        let stack_size = self.user_stack_size();
+        let (entry, user_stack_bottom, heap_bottom) = if let Ok(ans) = load_app(
+            name.clone(),
+            args,
+            envs,
+            &mut self.memory_set.lock().lock(),
+            stack_size,
+        ) {
             ans
         } else {
             error!("Failed to load app {}", name);
@@ -421,11 +668,19 @@ impl Process {
             use axhal::mem::virt_to_phys;
             use axhal::paging::MappingFlags;
             // 重置信号处理模块
-            // 此时只会留下一个线程
-            self.signal_modules.lock().clear();
-            self.signal_modules
-                .lock()
-                .insert(current_task.id().as_u64(), SignalModule::init_signal(None));
+            // 此时只会留下一个线程，信号掩码与未决信号在 exec 后保留
+            let mut signal_modules = self.signal_modules.lock();
+            let old_module = signal_modules.remove(&current_task.id().as_u64());
+            signal_modules.clear();
+            let mut signal_module = SignalModule::init_signal(
+                None,
+                old_module.as_ref().map_or(0, |module| module.mask),
+            );
+            if let Some(old_module) = old_module {
+                signal_module.pending = old_module.pending;
+            }
+            signal_modules.insert(current_task.id().as_u64(), signal_module);
+            drop(signal_modules);
 
             // 生成信号跳板
             let signal_trampoline_vaddr: VirtAddr = (axconfig::SIGNAL_TRAMPOLINE).into();
@@ -468,6 +723,15 @@ impl Process {
         //     // 任务过多，手动特判结束，用来作为QEMU内存不足的应对方法
         //     return Err(AxError::NoMemory);
         // }
+        if !flags.contains(CloneFlags::CLONE_THREAD) {
+            // 检查 RLIMIT_NPROC，只统计调用者自己的进程
+            let nproc_limit = self.get_rlimit(RLIMIT_NPROC)?.rlim_cur;
+            let nproc = self.owned_process_count();
+            if nproc_limit != RLIM_INFINITY && nproc >= nproc_limit {
+                debug!("too many processes: {}", nproc);
+                return Err(AxError::WouldBlock);
+            }
+        }
         // 是否共享虚拟地址空间
         let new_memory_set = if flags.contains(CloneFlags::CLONE_VM) {
             Mutex::new(Arc::clone(&self.memory_set.lock()))
@@ -552,6 +816,13 @@ impl Process {
             ))
             // info!("curr_id: {:X}", (&curr_id as *const _ as usize));
         };
+        // 新的线程或进程继承当前线程的信号掩码
+        #[cfg(feature = "signal")]
+        let mask = self
+            .signal_modules
+            .lock()
+            .get(&current().id().as_u64())
+            .map_or(0, |signal_module| signal_module.mask);
         // 检查是否在父任务中写入当前新任务的tid
         if flags.contains(CloneFlags::CLONE_PARENT_SETTID)
             & self.manual_alloc_for_lazy(ptid.into()).is_ok()
@@ -630,10 +901,13 @@ impl Process {
             //     (&Arc::clone(&new_task)) as *const _ as usize
             // );
             self.tasks.lock().push(Arc::clone(&new_task));
+            new_task.set_fs_context(self.fs_context.lock().clone());
+            // 先加入任务列表再读取模式，这样并发安装的过滤器一定会同步到新线程
+            new_task.set_seccomp_mode(self.seccomp.lock().mode);
             #[cfg(feature = "signal")]
             self.signal_modules.lock().insert(
                 new_task.id().as_u64(),
-                SignalModule::init_signal(Some(new_handler)),
+                SignalModule::init_signal(Some(new_handler), mask),
             );
             self.robust_list
                 .lock()
@@ -649,15 +923,42 @@ impl Process {
                 self.get_heap_bottom(),
                 self.fd_manager.fd_table.lock().clone(),
             ));
+            new_process.inherit_rlimits(self);
+            new_process.fd_manager.inherit_cloexec(&self.fd_manager);
+            *new_process.seccomp.lock() = self.seccomp.lock().clone();
+            new_task.set_seccomp_mode(new_process.seccomp.lock().mode);
+            new_process
+                .no_new_privs
+                .store(self.no_new_privs.load(Ordering::Acquire), Ordering::Release);
+            // CLONE_FS 时共享根目录与工作目录，否则复制一份
+            let fs_context = if flags.contains(CloneFlags::CLONE_FS) {
+                Arc::clone(&self.fs_context.lock())
+            } else {
+                Arc::new(Mutex::new(self.fs_context.lock().lock().clone()))
+            };
+            new_task.set_fs_context(fs_context.clone());
+            *new_process.fs_context.lock() = fs_context;
             // 记录该进程，防止被回收
             PID2PC.lock().insert(process_id, Arc::clone(&new_process));
             new_process.tasks.lock().push(Arc::clone(&new_task));
             // 若是新建了进程，那么需要把进程的父子关系进行记录
             #[cfg(feature = "signal")]
-            new_process.signal_modules.lock().insert(
-                new_task.id().as_u64(),
-                SignalModule::init_signal(Some(new_handler)),
-            );
+            {
+                let mut signal_module = SignalModule::init_signal(Some(new_handler), mask);
+                // 不共享地址空间时，子进程继承备用信号栈
+                if !flags.contains(CloneFlags::CLONE_VM) || flags.contains(CloneFlags::CLONE_VFORK)
+                {
+                    if let Some(parent_module) =
+                        self.signal_modules.lock().get(&current().id().as_u64())
+                    {
+                        signal_module.alternate_stack = parent_module.alternate_stack;
+                    }
+                }
+                new_process
+                    .signal_modules
+                    .lock()
+                    .insert(new_task.id().as_u64(), signal_module);
+            }
 
             new_process
                 .robust_list
@@ -697,6 +998,9 @@ impl Process {
         }
         new_task.set_trap_context(trap_frame);
         new_task.set_trap_in_kernel_stack();
+        // 子任务继承调度策略与优先级
+        axtask::set_sched_status(&new_task, current_task.get_sched_status());
+        axtask::set_task_priority(&new_task, current_task.nice());
         RUN_QUEUE.lock().add_task(new_task);
         // 判断是否为VFORK
         if flags.contains(CloneFlags::CLONE_VFORK) {
@@ -735,8 +1039,10 @@ impl Process {
 impl Process {
     /// 为进程分配一个文件描述符
     pub fn alloc_fd(&self, fd_table: &mut Vec<Option<Arc<dyn FileIO>>>) -> AxResult<usize> {
+        // 新分配的文件描述符不继承之前使用者的 FD_CLOEXEC 标志
         for (i, fd) in fd_table.iter().enumerate() {
             if fd.is_none() {
+                self.fd_manager.set_cloexec(i, false);
                 return Ok(i);
             }
         }
@@ -745,25 +1051,31 @@ impl Process {
             return Err(AxError::StorageFull);
         }
         fd_table.push(None);
-        Ok(fd_table.len() - 1)
+        let fd = fd_table.len() - 1;
+        self.fd_manager.set_cloexec(fd, false);
+        Ok(fd)
     }
 
     /// 获取当前进程的工作目录
     pub fn get_cwd(&self) -> String {
-        self.fd_manager.cwd.lock().clone()
+        self.fs_context.lock().lock().display_cwd_path()
     }
 }
 #[cfg(feature = "signal")]
 /// 与信号相关的方法
 impl Process {
-    /// 查询当前任务是否存在未决信号
+    /// 查询当前任务是否存在没有被阻塞的未决信号，包括发送给进程的信号
     pub fn have_signals(&self) -> Option<usize> {
         let current_task = current();
-        self.signal_modules
-            .lock()
-            .get(&current_task.id().as_u64())
-            .unwrap()
-            .signal_set
-            .find_signal()
+        let signal_modules = self.signal_modules.lock();
+        let signal_module = signal_modules.get(&current_task.id().as_u64()).unwrap();
+        signal_module
+            .pending
+            .find_unblocked(signal_module.mask)
+            .or_else(|| {
+                self.shared_pending
+                    .lock()
+                    .find_unblocked(signal_module.mask)
+            })
     }
 }
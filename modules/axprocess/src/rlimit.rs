@@ -0,0 +1,152 @@
+//! 进程的资源限制，对应 Linux 的 `RLIMIT_*` 系列
+//!
+//! 每个进程持有一张资源限制表，fork 时由子进程继承，exec 时保持不变
+use axconfig::MAX_USER_STACK_SIZE;
+use axerrno::{AxError, AxResult};
+
+/// sys_prlimit64 / sys_getrlimit / sys_setrlimit 使用的结构体
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RLimit {
+    /// 软上限
+    pub rlim_cur: u64,
+    /// 硬上限
+    pub rlim_max: u64,
+}
+
+impl RLimit {
+    /// 构造一个软硬上限分别为给定值的资源限制
+    pub const fn new(rlim_cur: u64, rlim_max: u64) -> Self {
+        Self { rlim_cur, rlim_max }
+    }
+
+    /// 构造一个没有限制的资源限制
+    pub const fn infinity() -> Self {
+        Self::new(RLIM_INFINITY, RLIM_INFINITY)
+    }
+}
+
+/// 表示不作限制
+pub const RLIM_INFINITY: u64 = u64::MAX;
+
+/// 进程可以使用的 CPU 时间，单位为秒
+pub const RLIMIT_CPU: i32 = 0;
+/// 进程可以创建的文件的最大大小
+pub const RLIMIT_FSIZE: i32 = 1;
+/// 数据段(堆)的最大大小
+pub const RLIMIT_DATA: i32 = 2;
+/// 用户栈大小
+pub const RLIMIT_STACK: i32 = 3;
+/// core dump 文件的最大大小，为 0 时不产生 core dump
+pub const RLIMIT_CORE: i32 = 4;
+/// 常驻内存的最大大小
+pub const RLIMIT_RSS: i32 = 5;
+/// 用户可以创建的最大进程数
+pub const RLIMIT_NPROC: i32 = 6;
+/// 可以打开的 fd 数
+pub const RLIMIT_NOFILE: i32 = 7;
+/// 可以锁定的内存大小
+pub const RLIMIT_MEMLOCK: i32 = 8;
+/// 用户地址空间的最大大小
+pub const RLIMIT_AS: i32 = 9;
+/// 文件锁的最大数目
+pub const RLIMIT_LOCKS: i32 = 10;
+/// 可以排队的信号数目
+pub const RLIMIT_SIGPENDING: i32 = 11;
+/// POSIX 消息队列可以使用的字节数
+pub const RLIMIT_MSGQUEUE: i32 = 12;
+/// nice 值的上限
+pub const RLIMIT_NICE: i32 = 13;
+/// 实时优先级的上限
+pub const RLIMIT_RTPRIO: i32 = 14;
+/// 实时调度下不进行阻塞调用时可以占用的 CPU 时间，单位为微秒
+pub const RLIMIT_RTTIME: i32 = 15;
+/// 资源限制的种类数
+pub const RLIM_NLIMITS: usize = 16;
+
+/// 默认可以打开的 fd 数
+pub const FD_LIMIT_ORIGIN: usize = 1025;
+
+/// 进程的资源限制表
+#[derive(Clone)]
+pub struct ResourceLimits {
+    limits: [RLimit; RLIM_NLIMITS],
+    /// 下一次发送 SIGXCPU 时对应的 CPU 时间，单位为秒
+    ///
+    /// 超过软上限后每经过一秒发送一次 SIGXCPU，直到到达硬上限
+    next_xcpu_sec: u64,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceLimits {
+    /// 新建进程时的默认资源限制
+    pub fn new() -> Self {
+        let mut limits = [RLimit::infinity(); RLIM_NLIMITS];
+        limits[RLIMIT_STACK as usize] = RLimit::new(MAX_USER_STACK_SIZE as u64, RLIM_INFINITY);
+        limits[RLIMIT_CORE as usize] = RLimit::new(0, RLIM_INFINITY);
+        limits[RLIMIT_NOFILE as usize] =
+            RLimit::new(FD_LIMIT_ORIGIN as u64, FD_LIMIT_ORIGIN as u64);
+        limits[RLIMIT_MEMLOCK as usize] = RLimit::new(0x10000, 0x10000);
+        limits[RLIMIT_SIGPENDING as usize] = RLimit::new(0x1000, 0x1000);
+        limits[RLIMIT_MSGQUEUE as usize] = RLimit::new(819200, 819200);
+        limits[RLIMIT_NICE as usize] = RLimit::new(0, 0);
+        limits[RLIMIT_RTPRIO as usize] = RLimit::new(0, 0);
+        Self {
+            limits,
+            next_xcpu_sec: 0,
+        }
+    }
+
+    /// 获取某一项资源限制
+    ///
+    /// 资源编号不合法时返回 `InvalidInput`
+    pub fn get(&self, resource: i32) -> AxResult<RLimit> {
+        if resource < 0 || resource as usize >= RLIM_NLIMITS {
+            return Err(AxError::InvalidInput);
+        }
+        Ok(self.limits[resource as usize])
+    }
+
+    /// 设置某一项资源限制
+    ///
+    /// 软上限大于硬上限时返回 `InvalidInput`。
+    /// 当前所有进程都视为 root 用户，因此允许提高硬上限。
+    pub fn set(&mut self, resource: i32, new_limit: RLimit) -> AxResult<()> {
+        if resource < 0 || resource as usize >= RLIM_NLIMITS {
+            return Err(AxError::InvalidInput);
+        }
+        if new_limit.rlim_cur > new_limit.rlim_max {
+            return Err(AxError::InvalidInput);
+        }
+        if resource == RLIMIT_CPU {
+            // 重新开始计算 SIGXCPU 的发送时机
+            self.next_xcpu_sec = 0;
+        }
+        self.limits[resource as usize] = new_limit;
+        Ok(())
+    }
+
+    /// 根据进程已经占用的 CPU 时间判断应当发送的信号
+    ///
+    /// 超过硬上限时返回 `Some(true)`，表示需要 SIGKILL；
+    /// 超过软上限时每经过一秒返回一次 `Some(false)`，表示需要 SIGXCPU
+    pub fn check_cpu_time(&mut self, cpu_secs: u64) -> Option<bool> {
+        let limit = self.limits[RLIMIT_CPU as usize];
+        if limit.rlim_max != RLIM_INFINITY && cpu_secs >= limit.rlim_max {
+            return Some(true);
+        }
+        if limit.rlim_cur == RLIM_INFINITY || cpu_secs < limit.rlim_cur {
+            return None;
+        }
+        if cpu_secs >= self.next_xcpu_sec {
+            self.next_xcpu_sec = cpu_secs + 1;
+            return Some(false);
+        }
+        None
+    }
+}
@@ -0,0 +1,52 @@
+//! 进程的资源使用统计，对应 Linux 的 `struct rusage`
+//!
+//! 线程的运行时间、上下文切换与缺页次数由调度器与缺页处理函数记录在任务中，
+//! 线程退出时累加到所属进程，子进程被回收时累加到父进程
+use axtask::TaskInner;
+
+/// 资源使用统计
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResourceUsage {
+    /// 用户态运行时间，单位为微秒
+    pub utime_us: usize,
+    /// 内核态运行时间，单位为微秒
+    pub stime_us: usize,
+    /// 最大常驻内存大小，单位为 KB
+    pub maxrss_kb: usize,
+    /// 不需要进行 I/O 的缺页次数
+    pub minflt: usize,
+    /// 需要从文件中读入数据的缺页次数
+    pub majflt: usize,
+    /// 主动上下文切换次数
+    pub nvcsw: usize,
+    /// 被动上下文切换次数
+    pub nivcsw: usize,
+}
+
+impl ResourceUsage {
+    /// 获取单个线程的资源使用统计，不包含内存信息
+    pub fn from_task(task: &TaskInner) -> Self {
+        let (_, utime_us, _, stime_us) = task.time_stat_output();
+        let (nvcsw, nivcsw, minflt, majflt) = task.rusage_counters_output();
+        Self {
+            utime_us,
+            stime_us,
+            maxrss_kb: 0,
+            minflt,
+            majflt,
+            nvcsw,
+            nivcsw,
+        }
+    }
+
+    /// 将另一份统计累加到当前统计中，最大常驻内存取两者的较大值
+    pub fn add(&mut self, other: &Self) {
+        self.utime_us += other.utime_us;
+        self.stime_us += other.stime_us;
+        self.maxrss_kb = self.maxrss_kb.max(other.maxrss_kb);
+        self.minflt += other.minflt;
+        self.majflt += other.majflt;
+        self.nvcsw += other.nvcsw;
+        self.nivcsw += other.nivcsw;
+    }
+}
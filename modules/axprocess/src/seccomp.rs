@@ -0,0 +1,585 @@
+//! seccomp 系统调用过滤
+//!
+//! 支持 SECCOMP_MODE_STRICT 与 SECCOMP_MODE_FILTER 两种模式。
+//! FILTER 模式下使用经典 BPF(cBPF) 程序对 `seccomp_data` 进行判断，得到对应的处理动作。
+//!
+//! 当前 seccomp 状态以进程为单位保存，同一进程下的线程共享过滤器，fork 与 exec 时均会继承
+extern crate alloc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use axerrno::{AxError, AxResult};
+
+/// 未开启 seccomp
+pub const SECCOMP_MODE_DISABLED: u8 = 0;
+/// 只允许 read、write、exit 与 rt_sigreturn
+pub const SECCOMP_MODE_STRICT: u8 = 1;
+/// 使用 BPF 程序过滤系统调用
+pub const SECCOMP_MODE_FILTER: u8 = 2;
+
+/// 终止整个进程
+pub const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+/// 终止当前线程
+pub const SECCOMP_RET_KILL_THREAD: u32 = 0x0000_0000;
+/// 发送 SIGSYS 信号
+pub const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+/// 不执行系统调用，返回 data 中指定的错误码
+pub const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+/// 交给用户态的监听者处理
+pub const SECCOMP_RET_USER_NOTIF: u32 = 0x7fc0_0000;
+/// 交给 ptrace 的跟踪者处理
+pub const SECCOMP_RET_TRACE: u32 = 0x7ff0_0000;
+/// 记录后允许执行
+pub const SECCOMP_RET_LOG: u32 = 0x7ffc_0000;
+/// 允许执行
+pub const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+/// 返回值中表示动作的部分
+pub const SECCOMP_RET_ACTION_FULL: u32 = 0xffff_0000;
+/// 返回值中表示附加数据的部分
+pub const SECCOMP_RET_DATA: u32 = 0x0000_ffff;
+
+/// 单个 BPF 程序的最大指令数
+pub const BPF_MAXINSNS: usize = 4096;
+/// BPF 程序的暂存区大小
+const BPF_MEMWORDS: usize = 16;
+
+/// BPF 过滤器的输入，对应 Linux 的 `struct seccomp_data`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SeccompData {
+    /// 系统调用号
+    pub nr: i32,
+    /// 系统调用约定对应的 AUDIT_ARCH_* 值
+    pub arch: u32,
+    /// 发起系统调用的指令地址
+    pub instruction_pointer: u64,
+    /// 系统调用参数
+    pub args: [u64; 6],
+}
+
+/// 当前架构对应的 AUDIT_ARCH_* 值
+#[cfg(target_arch = "riscv64")]
+pub const AUDIT_ARCH_CURRENT: u32 = 0xc000_00f3;
+/// 当前架构对应的 AUDIT_ARCH_* 值
+#[cfg(target_arch = "aarch64")]
+pub const AUDIT_ARCH_CURRENT: u32 = 0xc000_00b7;
+/// 当前架构对应的 AUDIT_ARCH_* 值
+#[cfg(target_arch = "x86_64")]
+pub const AUDIT_ARCH_CURRENT: u32 = 0xc000_003e;
+/// 当前架构对应的 AUDIT_ARCH_* 值
+#[cfg(not(any(
+    target_arch = "riscv64",
+    target_arch = "aarch64",
+    target_arch = "x86_64"
+)))]
+pub const AUDIT_ARCH_CURRENT: u32 = 0;
+
+impl SeccompData {
+    /// 将 `seccomp_data` 中偏移为 `offset` 的 32 位字读出
+    ///
+    /// 偏移不合法时返回 None
+    fn load_word(&self, offset: usize) -> Option<u32> {
+        if offset % 4 != 0 || offset + 4 > core::mem::size_of::<Self>() {
+            return None;
+        }
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        };
+        Some(u32::from_ne_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ]))
+    }
+}
+
+/// 一条 cBPF 指令，对应 Linux 的 `struct sock_filter`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SockFilter {
+    /// 操作码
+    pub code: u16,
+    /// 条件成立时的跳转偏移
+    pub jt: u8,
+    /// 条件不成立时的跳转偏移
+    pub jf: u8,
+    /// 立即数
+    pub k: u32,
+}
+
+/// 用户传入的 BPF 程序，对应 Linux 的 `struct sock_fprog`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SockFprog {
+    /// 指令数
+    pub len: u16,
+    /// 指令数组的地址
+    pub filter: *const SockFilter,
+}
+
+// 指令类别
+const BPF_LD: u16 = 0x00;
+const BPF_LDX: u16 = 0x01;
+const BPF_ST: u16 = 0x02;
+const BPF_STX: u16 = 0x03;
+const BPF_ALU: u16 = 0x04;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+const BPF_MISC: u16 = 0x07;
+
+// 读取的大小
+const BPF_W: u16 = 0x00;
+
+// 寻址方式
+const BPF_IMM: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_MEM: u16 = 0x60;
+const BPF_LEN: u16 = 0x80;
+
+// 运算
+const BPF_ADD: u16 = 0x00;
+const BPF_SUB: u16 = 0x10;
+const BPF_MUL: u16 = 0x20;
+const BPF_DIV: u16 = 0x30;
+const BPF_OR: u16 = 0x40;
+const BPF_AND: u16 = 0x50;
+const BPF_LSH: u16 = 0x60;
+const BPF_RSH: u16 = 0x70;
+const BPF_NEG: u16 = 0x80;
+const BPF_MOD: u16 = 0x90;
+const BPF_XOR: u16 = 0xa0;
+
+// 跳转
+const BPF_JA: u16 = 0x00;
+const BPF_JEQ: u16 = 0x10;
+const BPF_JGT: u16 = 0x20;
+const BPF_JGE: u16 = 0x30;
+const BPF_JSET: u16 = 0x40;
+
+// 操作数来源
+const BPF_K: u16 = 0x00;
+const BPF_X: u16 = 0x08;
+const BPF_A: u16 = 0x10;
+
+// MISC
+const BPF_TAX: u16 = 0x00;
+const BPF_TXA: u16 = 0x80;
+
+/// 经过检查的 BPF 过滤程序
+pub struct SeccompFilter {
+    insns: Vec<SockFilter>,
+}
+
+impl SeccompFilter {
+    /// 检查 BPF 程序的合法性并创建过滤器
+    ///
+    /// 只接受 seccomp 可以使用的指令，跳转必须向前且不越界，程序必须以返回指令结束
+    pub fn new(insns: Vec<SockFilter>) -> AxResult<Self> {
+        let len = insns.len();
+        if len == 0 || len > BPF_MAXINSNS {
+            return Err(AxError::InvalidInput);
+        }
+        for (pc, insn) in insns.iter().enumerate() {
+            let code = insn.code;
+            let valid = match code & 0x07 {
+                BPF_LD => match code {
+                    c if c == BPF_LD | BPF_W | BPF_ABS => {
+                        let offset = insn.k as usize;
+                        offset % 4 == 0 && offset + 4 <= core::mem::size_of::<SeccompData>()
+                    }
+                    c if c == BPF_LD | BPF_W | BPF_LEN || c == BPF_LD | BPF_IMM => true,
+                    c if c == BPF_LD | BPF_MEM => (insn.k as usize) < BPF_MEMWORDS,
+                    _ => false,
+                },
+                BPF_LDX => match code {
+                    c if c == BPF_LDX | BPF_W | BPF_LEN || c == BPF_LDX | BPF_IMM => true,
+                    c if c == BPF_LDX | BPF_MEM => (insn.k as usize) < BPF_MEMWORDS,
+                    _ => false,
+                },
+                BPF_ST | BPF_STX => (insn.k as usize) < BPF_MEMWORDS,
+                BPF_ALU => match code & 0xf0 {
+                    BPF_DIV | BPF_MOD => code & BPF_X != 0 || insn.k != 0,
+                    BPF_ADD | BPF_SUB | BPF_MUL | BPF_OR | BPF_AND | BPF_LSH | BPF_RSH
+                    | BPF_NEG | BPF_XOR => true,
+                    _ => false,
+                },
+                BPF_JMP => match code & 0xf0 {
+                    BPF_JA => pc + 1 + (insn.k as usize) < len,
+                    BPF_JEQ | BPF_JGT | BPF_JGE | BPF_JSET => {
+                        pc + 1 + (insn.jt as usize) < len && pc + 1 + (insn.jf as usize) < len
+                    }
+                    _ => false,
+                },
+                BPF_RET => matches!(code & 0x18, BPF_K | BPF_A),
+                BPF_MISC => matches!(code & 0xf8, BPF_TAX | BPF_TXA),
+                _ => false,
+            };
+            if !valid {
+                return Err(AxError::InvalidInput);
+            }
+        }
+        if insns[len - 1].code & 0x07 != BPF_RET {
+            return Err(AxError::InvalidInput);
+        }
+        Ok(Self { insns })
+    }
+
+    /// 以 `data` 为输入运行 BPF 程序，返回程序给出的动作
+    pub fn run(&self, data: &SeccompData) -> u32 {
+        let mut acc: u32 = 0;
+        let mut x: u32 = 0;
+        let mut mem = [0u32; BPF_MEMWORDS];
+        let mut pc = 0;
+        // 构造时已经检查过跳转目标，因此程序一定会在某条返回指令处结束
+        while pc < self.insns.len() {
+            let insn = self.insns[pc];
+            let code = insn.code;
+            let k = insn.k;
+            pc += 1;
+            match code & 0x07 {
+                BPF_LD => {
+                    acc = match code & 0xe0 {
+                        BPF_ABS => match data.load_word(k as usize) {
+                            Some(word) => word,
+                            None => return SECCOMP_RET_KILL_THREAD,
+                        },
+                        BPF_LEN => core::mem::size_of::<SeccompData>() as u32,
+                        BPF_MEM => mem[k as usize],
+                        _ => k,
+                    }
+                }
+                BPF_LDX => {
+                    x = match code & 0xe0 {
+                        BPF_LEN => core::mem::size_of::<SeccompData>() as u32,
+                        BPF_MEM => mem[k as usize],
+                        _ => k,
+                    }
+                }
+                BPF_ST => mem[k as usize] = acc,
+                BPF_STX => mem[k as usize] = x,
+                BPF_ALU => {
+                    let src = if code & BPF_X != 0 { x } else { k };
+                    acc = match code & 0xf0 {
+                        BPF_ADD => acc.wrapping_add(src),
+                        BPF_SUB => acc.wrapping_sub(src),
+                        BPF_MUL => acc.wrapping_mul(src),
+                        BPF_DIV => {
+                            if src == 0 {
+                                return SECCOMP_RET_KILL_THREAD;
+                            }
+                            acc / src
+                        }
+                        BPF_MOD => {
+                            if src == 0 {
+                                return SECCOMP_RET_KILL_THREAD;
+                            }
+                            acc % src
+                        }
+                        BPF_OR => acc | src,
+                        BPF_AND => acc & src,
+                        BPF_LSH => acc.checked_shl(src).unwrap_or(0),
+                        BPF_RSH => acc.checked_shr(src).unwrap_or(0),
+                        BPF_NEG => acc.wrapping_neg(),
+                        _ => acc ^ src,
+                    }
+                }
+                BPF_JMP => {
+                    let src = if code & BPF_X != 0 { x } else { k };
+                    let cond = match code & 0xf0 {
+                        BPF_JA => {
+                            pc += k as usize;
+                            continue;
+                        }
+                        BPF_JEQ => acc == src,
+                        BPF_JGT => acc > src,
+                        BPF_JGE => acc >= src,
+                        _ => acc & src != 0,
+                    };
+                    let offset = if cond { insn.jt } else { insn.jf };
+                    pc += offset as usize;
+                }
+                BPF_RET => {
+                    return if code & 0x18 == BPF_A { acc } else { k };
+                }
+                _ => {
+                    if code & 0xf8 == BPF_TAX {
+                        x = acc;
+                    } else {
+                        acc = x;
+                    }
+                }
+            }
+        }
+        SECCOMP_RET_KILL_THREAD
+    }
+}
+
+/// 按照优先级比较两个动作，返回更严格的一个
+///
+/// 与 Linux 相同，将动作部分视为有符号数，越小越严格，因此 KILL_PROCESS 优先级最高
+fn stricter_action(a: u32, b: u32) -> u32 {
+    if ((a & SECCOMP_RET_ACTION_FULL) as i32) < ((b & SECCOMP_RET_ACTION_FULL) as i32) {
+        a
+    } else {
+        b
+    }
+}
+
+/// 进程的 seccomp 状态
+#[derive(Clone, Default)]
+pub struct SeccompState {
+    /// 当前的模式
+    pub mode: u8,
+    /// 已经安装的过滤器，按照安装顺序排列
+    pub filters: Vec<Arc<SeccompFilter>>,
+}
+
+impl SeccompState {
+    /// 进入 STRICT 模式
+    ///
+    /// 已经处于 FILTER 模式时不能切换
+    pub fn set_strict(&mut self) -> AxResult<()> {
+        if self.mode == SECCOMP_MODE_FILTER {
+            return Err(AxError::InvalidInput);
+        }
+        self.mode = SECCOMP_MODE_STRICT;
+        Ok(())
+    }
+
+    /// 安装一个新的过滤器，并进入 FILTER 模式
+    ///
+    /// 已经处于 STRICT 模式时不能切换
+    pub fn add_filter(&mut self, filter: SeccompFilter) -> AxResult<()> {
+        if self.mode == SECCOMP_MODE_STRICT {
+            return Err(AxError::InvalidInput);
+        }
+        self.mode = SECCOMP_MODE_FILTER;
+        self.filters.push(Arc::new(filter));
+        Ok(())
+    }
+
+    /// 运行所有过滤器，得到对系统调用的处理动作
+    ///
+    /// 多个过滤器的结果取其中最严格的一个；STRICT 模式的判断由调用者完成
+    pub fn run_filters(&self, data: &SeccompData) -> u32 {
+        self.filters
+            .iter()
+            .rev()
+            .fold(SECCOMP_RET_ALLOW, |action, filter| {
+                stricter_action(filter.run(data), action)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const fn stmt(code: u16, k: u32) -> SockFilter {
+        SockFilter {
+            code,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    const fn jump(code: u16, k: u32, jt: u8, jf: u8) -> SockFilter {
+        SockFilter { code, jt, jf, k }
+    }
+
+    fn data(nr: i32, arg0: u64) -> SeccompData {
+        SeccompData {
+            nr,
+            arch: AUDIT_ARCH_CURRENT,
+            instruction_pointer: 0x1000,
+            args: [arg0, 0, 0, 0, 0, 0],
+        }
+    }
+
+    /// 检查并运行程序，返回程序给出的结果
+    fn run(insns: Vec<SockFilter>, data: &SeccompData) -> u32 {
+        SeccompFilter::new(insns).unwrap().run(data)
+    }
+
+    #[test]
+    fn test_alu() {
+        let ret_a = stmt(BPF_RET | BPF_A, 0);
+        let cases = [
+            (BPF_ADD, 7, 12),
+            (BPF_SUB, 7, u32::MAX - 1),
+            (BPF_MUL, 3, 15),
+            (BPF_DIV, 2, 2),
+            (BPF_MOD, 3, 2),
+            (BPF_OR, 0x10, 0x15),
+            (BPF_AND, 0x4, 0x4),
+            (BPF_LSH, 4, 0x50),
+            (BPF_RSH, 1, 2),
+            (BPF_XOR, 0x1, 0x4),
+        ];
+        for (op, k, expected) in cases {
+            let insns = vec![
+                stmt(BPF_LD | BPF_IMM, 5),
+                stmt(BPF_ALU | op | BPF_K, k),
+                ret_a,
+            ];
+            assert_eq!(run(insns, &data(0, 0)), expected, "op {:#x}", op);
+        }
+        // 以 X 寄存器为操作数，并检查 NEG 与超出位宽的移位
+        let insns = vec![
+            stmt(BPF_LDX | BPF_IMM, 3),
+            stmt(BPF_LD | BPF_IMM, 10),
+            stmt(BPF_ALU | BPF_SUB | BPF_X, 0),
+            stmt(BPF_ALU | BPF_NEG, 0),
+            ret_a,
+        ];
+        assert_eq!(run(insns, &data(0, 0)), (-7i32) as u32);
+        let insns = vec![
+            stmt(BPF_LD | BPF_IMM, 1),
+            stmt(BPF_ALU | BPF_LSH | BPF_K, 32),
+            ret_a,
+        ];
+        assert_eq!(run(insns, &data(0, 0)), 0);
+    }
+
+    #[test]
+    fn test_divide_by_zero() {
+        // 立即数为 0 的除法在检查时被拒绝
+        assert!(SeccompFilter::new(vec![
+            stmt(BPF_ALU | BPF_DIV | BPF_K, 0),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+        ])
+        .is_err());
+        // X 为 0 时在运行时终止
+        let insns = vec![
+            stmt(BPF_LD | BPF_IMM, 1),
+            stmt(BPF_ALU | BPF_MOD | BPF_X, 0),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+        ];
+        assert_eq!(run(insns, &data(0, 0)), SECCOMP_RET_KILL_THREAD);
+    }
+
+    #[test]
+    fn test_jumps() {
+        // 系统调用号为 64 时返回 ERRNO(1)，否则允许
+        let filter = SeccompFilter::new(vec![
+            stmt(BPF_LD | BPF_W | BPF_ABS, 0),
+            jump(BPF_JMP | BPF_JEQ | BPF_K, 64, 0, 1),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_ERRNO | 1),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+        ])
+        .unwrap();
+        assert_eq!(filter.run(&data(64, 0)), SECCOMP_RET_ERRNO | 1);
+        assert_eq!(filter.run(&data(63, 0)), SECCOMP_RET_ALLOW);
+
+        // 读取第一个参数的低 32 位，依次检查 JGT、JGE、JSET 与 JA
+        let filter = SeccompFilter::new(vec![
+            stmt(BPF_LD | BPF_W | BPF_ABS, 16),
+            jump(BPF_JMP | BPF_JGT | BPF_K, 100, 4, 0),
+            jump(BPF_JMP | BPF_JGE | BPF_K, 50, 4, 0),
+            jump(BPF_JMP | BPF_JSET | BPF_K, 0x1, 0, 1),
+            stmt(BPF_JMP | BPF_JA, 3),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_LOG),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_TRAP),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_ERRNO),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+        ])
+        .unwrap();
+        assert_eq!(filter.run(&data(0, 101)), SECCOMP_RET_TRAP);
+        assert_eq!(filter.run(&data(0, 50)), SECCOMP_RET_ERRNO);
+        assert_eq!(filter.run(&data(0, 3)), SECCOMP_RET_ALLOW);
+        assert_eq!(filter.run(&data(0, 2)), SECCOMP_RET_LOG);
+
+        // 以 X 寄存器为比较对象
+        let filter = SeccompFilter::new(vec![
+            stmt(BPF_LDX | BPF_IMM, 7),
+            stmt(BPF_LD | BPF_W | BPF_ABS, 0),
+            jump(BPF_JMP | BPF_JEQ | BPF_X, 0, 0, 1),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_KILL_PROCESS),
+            stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+        ])
+        .unwrap();
+        assert_eq!(filter.run(&data(7, 0)), SECCOMP_RET_KILL_PROCESS);
+        assert_eq!(filter.run(&data(8, 0)), SECCOMP_RET_ALLOW);
+    }
+
+    #[test]
+    fn test_invalid_programs() {
+        let ret = stmt(BPF_RET | BPF_K, SECCOMP_RET_ALLOW);
+        // 空程序与超长程序
+        assert!(SeccompFilter::new(vec![]).is_err());
+        assert!(SeccompFilter::new(vec![ret; BPF_MAXINSNS + 1]).is_err());
+        // 越界、未对齐的读取
+        let size = core::mem::size_of::<SeccompData>() as u32;
+        assert!(SeccompFilter::new(vec![stmt(BPF_LD | BPF_W | BPF_ABS, size), ret]).is_err());
+        assert!(SeccompFilter::new(vec![stmt(BPF_LD | BPF_W | BPF_ABS, 2), ret]).is_err());
+        assert!(SeccompFilter::new(vec![stmt(BPF_LD | BPF_W | BPF_ABS, size - 4), ret]).is_ok());
+        // 越界的暂存区访问
+        assert!(SeccompFilter::new(vec![stmt(BPF_ST, BPF_MEMWORDS as u32), ret]).is_err());
+        // 跳出程序末尾
+        assert!(SeccompFilter::new(vec![stmt(BPF_JMP | BPF_JA, 1), ret]).is_err());
+        assert!(SeccompFilter::new(vec![jump(BPF_JMP | BPF_JEQ | BPF_K, 0, 0, 1), ret]).is_err());
+        // 没有以返回指令结束
+        assert!(SeccompFilter::new(vec![stmt(BPF_LD | BPF_IMM, 0)]).is_err());
+    }
+
+    #[test]
+    fn test_memory_and_misc() {
+        let insns = vec![
+            stmt(BPF_LD | BPF_W | BPF_LEN, 0),
+            stmt(BPF_ST, 3),
+            stmt(BPF_LD | BPF_IMM, 0),
+            stmt(BPF_LDX | BPF_MEM, 3),
+            stmt(BPF_MISC | BPF_TXA, 0),
+            stmt(BPF_MISC | BPF_TAX, 0),
+            stmt(BPF_STX, 4),
+            stmt(BPF_LD | BPF_MEM, 4),
+            stmt(BPF_RET | BPF_A, 0),
+        ];
+        assert_eq!(
+            run(insns, &data(0, 0)),
+            core::mem::size_of::<SeccompData>() as u32
+        );
+    }
+
+    #[test]
+    fn test_action_precedence() {
+        let actions = [
+            SECCOMP_RET_KILL_PROCESS,
+            SECCOMP_RET_KILL_THREAD,
+            SECCOMP_RET_TRAP,
+            SECCOMP_RET_ERRNO,
+            SECCOMP_RET_USER_NOTIF,
+            SECCOMP_RET_TRACE,
+            SECCOMP_RET_LOG,
+            SECCOMP_RET_ALLOW,
+        ];
+        for (i, &a) in actions.iter().enumerate() {
+            for &b in &actions[i..] {
+                assert_eq!(stricter_action(a, b), a);
+                assert_eq!(stricter_action(b, a), a);
+            }
+        }
+        // 动作相同时保留先得到的结果，即最后安装的过滤器给出的数据
+        assert_eq!(
+            stricter_action(SECCOMP_RET_ERRNO | 1, SECCOMP_RET_ERRNO | 2),
+            SECCOMP_RET_ERRNO | 2
+        );
+
+        let mut state = SeccompState::default();
+        let ret = |action| SeccompFilter::new(vec![stmt(BPF_RET | BPF_K, action)]).unwrap();
+        state.add_filter(ret(SECCOMP_RET_ALLOW)).unwrap();
+        state.add_filter(ret(SECCOMP_RET_ERRNO | 1)).unwrap();
+        state.add_filter(ret(SECCOMP_RET_ERRNO | 2)).unwrap();
+        state.add_filter(ret(SECCOMP_RET_LOG)).unwrap();
+        assert_eq!(state.run_filters(&data(0, 0)), SECCOMP_RET_ERRNO | 2);
+        state.add_filter(ret(SECCOMP_RET_KILL_PROCESS)).unwrap();
+        assert_eq!(state.run_filters(&data(0, 0)), SECCOMP_RET_KILL_PROCESS);
+        // FILTER 模式下不能再进入 STRICT 模式
+        assert!(state.set_strict().is_err());
+    }
+}
@@ -1,53 +1,234 @@
 //! 负责处理进程中与信号相关的内容
 extern crate alloc;
-use alloc::sync::Arc;
-use axerrno::{AxError, AxResult};
-use axhal::{arch::TrapFrame, cpu::this_cpu_id, KERNEL_PROCESS_ID};
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
+use axerrno::{AxError, AxResult, LinuxError};
+use axhal::{arch::TrapFrame, cpu::this_cpu_id, time::TimeValue, KERNEL_PROCESS_ID};
 use axlog::{info, warn};
 use axsignal::{
-    action::{SigActionFlags, SignalDefault, SIG_IGN},
-    info::SigInfo,
+    action::{SigAction, SigActionFlags, SignalDefault, SIG_IGN},
+    info::{SigInfo, SI_KERNEL, SI_TIMER},
     signal_no::SignalNo,
-    ucontext::SignalUserContext,
+    ucontext::{fp_state_size, SignalStack, SignalUserContext, SS_AUTODISARM},
     SignalHandler, SignalSet,
 };
 use axsync::Mutex;
-use axtask::{SignalCaller, TaskState, RUN_QUEUE};
+use axtask::{AxTaskRef, TaskState, RUN_QUEUE};
 
-/// 信号处理模块，进程间不共享
+/// 信号处理模块，每个线程各有一个，进程间不共享
 pub struct SignalModule {
     /// 是否存在siginfo
     pub sig_info: bool,
     /// 保存的trap上下文
     pub last_trap_frame_for_signal: Option<TrapFrame>,
+    /// 调用信号处理函数前的信号掩码，信号处理函数返回时恢复
+    pub last_mask_for_signal: usize,
     /// 信号处理函数集
     pub signal_handler: Arc<Mutex<SignalHandler>>,
-    /// 未决信号集
-    pub signal_set: SignalSet,
+    /// 线程的信号掩码
+    pub mask: usize,
+    /// sigsuspend 等调用临时修改信号掩码前的掩码，处理完信号后恢复
+    pub saved_mask: Option<usize>,
+    /// 发送给该线程的未决信号
+    pub pending: SigPending,
+    /// 线程的备用信号栈，由 sigaltstack 设置
+    pub alternate_stack: SignalStack,
+    /// 刚刚被信号打断的系统调用，返回用户态前决定重新执行还是返回 EINTR
+    syscall_restart: Option<SyscallRestart>,
+    /// 通过 restart_syscall 继续执行被打断的系统调用时使用的状态
+    restart_block: Option<RestartBlock>,
+}
+
+/// 被信号打断、返回了 `ERESTART*` 错误码的系统调用
+#[derive(Clone, Copy, Debug)]
+struct SyscallRestart {
+    errno: LinuxError,
+    /// 重新执行时使用的 syscall id
+    syscall_id: usize,
+    /// 原来的第一个参数，它所在的寄存器已经被返回值覆盖
+    arg0: usize,
+}
+
+/// 通过 restart_syscall 继续执行被打断的系统调用时使用的状态，即 Linux 中的 `restart_block`
+#[derive(Clone, Copy, Debug)]
+pub enum RestartBlock {
+    /// 被打断的相对时间的睡眠，继续睡眠到原来的截止时间
+    Nanosleep {
+        /// 原来的截止时间
+        deadline: TimeValue,
+        /// 写回剩余时间的用户地址，可以为空
+        rem: usize,
+    },
 }
 
 impl SignalModule {
-    /// 初始化信号模块
-    pub fn init_signal(signal_handler: Option<Arc<Mutex<SignalHandler>>>) -> Self {
+    /// 初始化信号模块，`mask` 为继承的信号掩码
+    pub fn init_signal(signal_handler: Option<Arc<Mutex<SignalHandler>>>, mask: usize) -> Self {
         let signal_handler =
             signal_handler.unwrap_or_else(|| Arc::new(Mutex::new(SignalHandler::new())));
-        let signal_set = SignalSet::new();
         let last_trap_frame_for_signal = None;
         let sig_info = false;
         Self {
             sig_info,
             last_trap_frame_for_signal,
+            last_mask_for_signal: mask,
             signal_handler,
-            signal_set,
+            mask: mask & !UNBLOCKABLE_SIGNALS,
+            saved_mask: None,
+            pending: SigPending::default(),
+            alternate_stack: SignalStack::default(),
+            syscall_restart: None,
+            restart_block: None,
+        }
+    }
+
+    /// 设置线程的信号掩码，SIGKILL 与 SIGSTOP 不能被阻塞
+    pub fn set_mask(&mut self, mask: usize) {
+        self.mask = mask & !UNBLOCKABLE_SIGNALS;
+    }
+
+    /// 线程是否阻塞了指定信号
+    pub fn blocks(&self, signum: usize) -> bool {
+        self.mask & sig_bit(signum) != 0
+    }
+}
+
+/// 不能被阻塞、忽略或者捕获的信号，即 SIGKILL 与 SIGSTOP
+const UNBLOCKABLE_SIGNALS: usize =
+    (1 << (SignalNo::SIGKILL as usize - 1)) | (1 << (SignalNo::SIGSTOP as usize - 1));
+
+/// 信号在信号集中对应的位
+fn sig_bit(signum: usize) -> usize {
+    1 << (signum - 1)
+}
+
+/// 未决信号集与排队中的信号附带的信息，即 Linux 中的 `struct sigpending`
+///
+/// 每个线程有自己的未决信号，发送给进程的信号则保存在进程共享的未决信号中，由任意一个没有阻塞该信号的线程处理
+#[derive(Default)]
+pub struct SigPending {
+    /// 未决信号集
+    pub signals: usize,
+    /// 未决信号附带的信息，如 SIGCHLD 的 si_code 与子进程号
+    ///
+    /// 实时信号的每一个实例分别排队，标准信号至多保留一个
+    pub infos: BTreeMap<usize, VecDeque<SigInfo>>,
+}
+
+impl SigPending {
+    /// 排队中的信号实例数目
+    pub fn queued_count(&self) -> usize {
+        self.infos.values().map(|queue| queue.len()).sum()
+    }
+
+    /// 指定信号是否处于未决状态
+    pub fn contains(&self, signum: usize) -> bool {
+        self.signals & sig_bit(signum) != 0
+    }
+
+    /// 将信号加入未决信号集
+    ///
+    /// 标准信号若已经处于未决状态则被丢弃。`queued` 为进程中已经排队的信号数目，
+    /// 达到 RLIMIT_SIGPENDING 时，实时信号若来自 `sigqueue` 等调用则返回 `WouldBlock`，
+    /// 否则与标准信号一样只记录未决状态而不保存附带的信息
+    pub fn queue(
+        &mut self,
+        signum: usize,
+        info: Option<SigInfo>,
+        queued: usize,
+        limit: u64,
+    ) -> AxResult<()> {
+        let mut info = info.unwrap_or_else(|| SigInfo::new(signum as i32, SI_KERNEL));
+        info.si_signo = signum as i32;
+        let is_rt = signum >= SignalNo::SIGRTMIN as usize;
+        if !is_rt && self.contains(signum) {
+            return Ok(());
+        }
+        if queued as u64 >= limit {
+            if is_rt && info.si_code < 0 {
+                return Err(AxError::WouldBlock);
+            }
+        } else {
+            self.infos.entry(signum).or_default().push_back(info);
+        }
+        self.signals |= sig_bit(signum);
+        Ok(())
+    }
+
+    /// 不在 `mask` 中的编号最低的未决信号，SIGKILL 与 SIGSTOP 不受 `mask` 影响
+    pub fn find_unblocked(&self, mask: usize) -> Option<usize> {
+        SignalSet {
+            mask,
+            pending: self.signals,
+        }
+        .find_signal()
+    }
+
+    /// 取出 `set` 中编号最低的未决信号，返回其附带的信息
+    ///
+    /// 若同一实时信号还有实例在排队，则该信号仍然处于未决状态
+    pub fn dequeue_in(&mut self, set: usize) -> Option<SigInfo> {
+        let pending = self.signals & set;
+        if pending == 0 {
+            return None;
+        }
+        let signum = pending.trailing_zeros() as usize + 1;
+        let info = self
+            .infos
+            .get_mut(&signum)
+            .and_then(|queue| queue.pop_front());
+        match self.infos.get(&signum) {
+            Some(queue) if !queue.is_empty() => {}
+            Some(_) => {
+                self.infos.remove(&signum);
+                self.signals &= !sig_bit(signum);
+            }
+            None => self.signals &= !sig_bit(signum),
         }
+        Some(info.unwrap_or_else(|| SigInfo::new(signum as i32, SI_KERNEL)))
     }
 }
 
+/// 进程中排队的信号数目与 RLIMIT_SIGPENDING 的限制
+fn queued_and_limit(
+    process: &Process,
+    signal_modules: &BTreeMap<u64, SignalModule>,
+) -> (usize, u64) {
+    let limit = process
+        .get_rlimit(crate::rlimit::RLIMIT_SIGPENDING)
+        .map(|limit| limit.rlim_cur)
+        .unwrap_or(0);
+    let queued = signal_modules
+        .values()
+        .map(|signal_module| signal_module.pending.queued_count())
+        .sum::<usize>()
+        + process.shared_pending.lock().queued_count();
+    (queued, limit)
+}
+
+/// 向进程中指定线程的未决信号加入信号，排队的信号数目受进程的 RLIMIT_SIGPENDING 限制
+fn queue_signal_to(
+    process: &Process,
+    signal_modules: &mut BTreeMap<u64, SignalModule>,
+    tid: u64,
+    signum: usize,
+    info: Option<SigInfo>,
+) -> AxResult<()> {
+    let (queued, limit) = queued_and_limit(process, signal_modules);
+    signal_modules
+        .get_mut(&tid)
+        .ok_or(AxError::NotFound)?
+        .pending
+        .queue(signum, info, queued, limit)
+}
+
 const USER_SIGNAL_PROTECT: usize = 512;
 
 use crate::{
     current_process, current_task, exit_current_task,
-    process::{PID2PC, TID2TASK},
+    process::{Process, PID2PC, TID2TASK},
 };
 
 /// 将保存的trap上下文填入内核栈中
@@ -70,9 +251,28 @@ pub fn load_trap_for_signal() -> bool {
             // 所以此时sp的位置应该是SignalUserContext的位置
             let sp = (*now_trap_frame).get_sp();
             *now_trap_frame = old_trap_frame;
+            let ucontext = &*(sp as *const SignalUserContext);
+            // 恢复调用信号处理函数前的浮点状态
+            ucontext.restore_fp_state(&mut *now_trap_frame, |start, size| {
+                current_process
+                    .manual_alloc_range_for_lazy(start.into(), (start + size - 1).into())
+                    .is_ok()
+            });
+            // 恢复因 SS_AUTODISARM 而在信号处理期间被清除的备用信号栈，
+            // 无论是否带有 SA_SIGINFO，ucontext 中总是保存了原先的备用信号栈
+            if !signal_module.alternate_stack.on_stack(sp) {
+                let mut stack = ucontext.get_stack();
+                if stack.is_enabled() {
+                    stack.flags &= SS_AUTODISARM;
+                    signal_module.alternate_stack = stack;
+                }
+            }
             if signal_module.sig_info {
-                let pc = (*(sp as *const SignalUserContext)).get_pc();
-                (*now_trap_frame).set_pc(pc);
+                (*now_trap_frame).set_pc(ucontext.get_pc());
+                // 信号处理函数可能修改了 ucontext 中的信号掩码
+                signal_module.set_mask(ucontext.get_mask());
+            } else {
+                signal_module.mask = signal_module.last_mask_for_signal;
             }
         }
         true
@@ -85,6 +285,8 @@ pub fn load_trap_for_signal() -> bool {
 fn terminate_process(signal: SignalNo) {
     let current_task = current_task();
     warn!("Terminate process: {}", current_task.get_process_id());
+    // 记录导致进程终止的信号，以便通知父进程
+    current_process().set_term_signal(signal as i32);
     if current_task.is_leader() {
         exit_current_task(signal as i32);
     } else {
@@ -116,12 +318,27 @@ pub fn handle_signals() {
     let mut signal_modules = process.signal_modules.lock();
 
     let signal_module = signal_modules.get_mut(&current_task.id().as_u64()).unwrap();
-    let signal_set = &mut signal_module.signal_set;
-    let sig_num = if let Some(sig_num) = signal_set.get_one_signal() {
-        sig_num
+    let restart = signal_module.syscall_restart.take();
+    // 先处理发送给线程的信号，再处理发送给进程的信号
+    let mask = signal_module.mask;
+    let info = if let Some(sig_num) = signal_module.pending.find_unblocked(mask) {
+        signal_module.pending.dequeue_in(sig_bit(sig_num))
+    } else {
+        let mut shared_pending = process.shared_pending.lock();
+        shared_pending
+            .find_unblocked(mask)
+            .and_then(|sig_num| shared_pending.dequeue_in(sig_bit(sig_num)))
+    };
+    // sigsuspend 等调用临时修改的信号掩码在处理完信号后恢复
+    let old_mask = signal_module.saved_mask.take().unwrap_or(mask);
+    let info = if let Some(info) = info {
+        info
     } else {
+        signal_module.mask = old_mask;
+        restart_syscall(restart, None);
         return;
     };
+    let sig_num = info.si_signo as usize;
     info!(
         "cpu: {}, task: {}, handler signal: {}",
         this_cpu_id(),
@@ -129,7 +346,6 @@ pub fn handle_signals() {
         sig_num
     );
     let signal = SignalNo::from(sig_num);
-    let mask = signal_set.mask;
     // 存在未被处理的信号
     if signal_module.last_trap_frame_for_signal.is_some() {
         // 之前的trap frame还未被处理
@@ -139,18 +355,26 @@ pub fn handle_signals() {
             drop(signal_modules);
             exit_current_task(-1);
         }
+        signal_module.mask = old_mask;
+        restart_syscall(restart, None);
         return;
     }
+    // 调取处理函数
+    let signal_handler = signal_module.signal_handler.lock();
+    let action = signal_handler.get_action(sig_num);
+    // 被打断的系统调用需要在保存trap上下文之前处理，使得信号处理函数返回后能够重新执行
+    restart_syscall(
+        restart,
+        action.filter(|action| action.sa_handler != SIG_IGN),
+    );
     // 之前的trap frame已经被处理
     // 说明之前的信号处理函数已经返回，即没有信号嵌套。
     // 此时可以将当前的trap frame保存起来
     signal_module.last_trap_frame_for_signal =
         Some(unsafe { *current_task.get_first_trap_frame() });
+    signal_module.last_mask_for_signal = old_mask;
     // current_task.set_siginfo(false);
     signal_module.sig_info = false;
-    // 调取处理函数
-    let signal_handler = signal_module.signal_handler.lock();
-    let action = signal_handler.get_action(sig_num);
     if action.is_none() {
         drop(signal_handler);
         drop(signal_modules);
@@ -158,7 +382,11 @@ pub fn handle_signals() {
         match SignalDefault::get_action(signal) {
             SignalDefault::Ignore => {
                 // 忽略，此时相当于已经完成了处理，所以要把trap上下文清空
-                load_trap_for_signal();
+                // 由于没有在用户栈上构造ucontext，不能通过load_trap_for_signal恢复
+                let mut signal_modules = process.signal_modules.lock();
+                let signal_module = signal_modules.get_mut(&current_task.id().as_u64()).unwrap();
+                signal_module.last_trap_frame_for_signal = None;
+                signal_module.mask = old_mask;
             }
             SignalDefault::Terminate => {
                 terminate_process(signal);
@@ -170,6 +398,14 @@ pub fn handle_signals() {
                 unimplemented!();
             }
             SignalDefault::Core => {
+                // RLIMIT_CORE 为 0 时不产生 core dump
+                let core_limit = process
+                    .get_rlimit(crate::rlimit::RLIMIT_CORE)
+                    .map(|limit| limit.rlim_cur)
+                    .unwrap_or(0);
+                if core_limit != 0 {
+                    warn!("core dump is not supported, limit: {:#x}", core_limit);
+                }
                 terminate_process(signal);
             }
         }
@@ -177,9 +413,17 @@ pub fn handle_signals() {
     }
     let action = action.unwrap();
     if action.sa_handler == SIG_IGN {
-        // 忽略处理
+        // 忽略处理，同样清空保存的trap上下文
+        signal_module.last_trap_frame_for_signal = None;
+        signal_module.mask = old_mask;
         return;
     }
+    // 信号处理函数执行期间阻塞 sa_mask 中的信号，未指定 SA_NODEFER 时还阻塞当前信号
+    let mut handler_mask = old_mask | action.sa_mask;
+    if !action.sa_flags.contains(SigActionFlags::SA_NODEFER) {
+        handler_mask |= sig_bit(sig_num);
+    }
+    signal_module.mask = handler_mask & !UNBLOCKABLE_SIGNALS;
     // 此时需要调用信号处理函数，注意调用的方式是：
     // 通过修改trap上下文的pc指针，使得trap返回之后，直接到达信号处理函数
     // 因此需要处理一系列的trap上下文，使得正确传参与返回。
@@ -190,8 +434,21 @@ pub fn handle_signals() {
     // 注意是直接修改内核栈上的内容
     let trap_frame = unsafe { &mut *(current_task.get_first_trap_frame()) };
 
-    // // 新的trap上下文的sp指针位置，由于SIGINFO会存放内容，所以需要开个保护区域
-    let mut sp = trap_frame.get_sp() - USER_SIGNAL_PROTECT;
+    // 指定了 SA_ONSTACK 且设置了备用信号栈时，在备用信号栈上处理信号，嵌套的信号继续使用当前栈
+    let old_sp = trap_frame.get_sp();
+    let alternate_stack = signal_module.alternate_stack;
+    let mut sp = if action.sa_flags.contains(SigActionFlags::SA_ONSTACK)
+        && alternate_stack.is_enabled()
+        && !alternate_stack.on_stack(old_sp)
+    {
+        if alternate_stack.flags & SS_AUTODISARM != 0 {
+            signal_module.alternate_stack = SignalStack::default();
+        }
+        alternate_stack.sp + alternate_stack.size
+    } else {
+        // 新的trap上下文的sp指针位置，由于SIGINFO会存放内容，所以需要开个保护区域
+        old_sp - USER_SIGNAL_PROTECT
+    };
     let restorer = if let Some(addr) = action.get_storer() {
         addr
     } else {
@@ -210,28 +467,35 @@ pub fn handle_signals() {
     trap_frame.set_pc(action.sa_handler);
     // 传参
     trap_frame.set_arg0(sig_num);
+    // 浮点状态保存在ucontext之外时，需要预留64字节对齐的空间
+    let fp_area = if fp_state_size() != 0 {
+        sp = (sp - fp_state_size()) & !0x3f;
+        sp
+    } else {
+        0
+    };
     // 若带有SIG_INFO参数，则函数原型为fn(sig: SignalNo, info: &SigInfo, ucontext: &mut UContext)
-    if action.sa_flags.contains(SigActionFlags::SA_SIGINFO) {
+    let sig_info = action.sa_flags.contains(SigActionFlags::SA_SIGINFO);
+    if sig_info {
         // current_task.set_siginfo(true);
         signal_module.sig_info = true;
         // 注意16字节对齐
         sp = (sp - core::mem::size_of::<SigInfo>()) & !0xf;
-        let info = SigInfo {
-            si_signo: sig_num as i32,
-            ..Default::default()
-        };
         unsafe {
             *(sp as *mut SigInfo) = info;
         }
         trap_frame.set_arg1(sp);
+    }
 
-        // 接下来存储ucontext
-        sp = (sp - core::mem::size_of::<SignalUserContext>()) & !0xf;
+    // 接下来存储ucontext，信号处理函数返回时需要从中恢复浮点状态，因此总是存储
+    sp = (sp - core::mem::size_of::<SignalUserContext>()) & !0xf;
 
-        let ucontext = SignalUserContext::init(old_pc, mask);
-        unsafe {
-            *(sp as *mut SignalUserContext) = ucontext;
-        }
+    let ucontext = SignalUserContext::init(old_pc, old_mask, alternate_stack.output(old_sp));
+    unsafe {
+        *(sp as *mut SignalUserContext) = ucontext;
+        (*(sp as *mut SignalUserContext)).save_fp_state(fp_area, trap_frame);
+    }
+    if sig_info {
         trap_frame.set_arg2(sp);
     }
 
@@ -247,6 +511,62 @@ pub fn handle_signals() {
     drop(signal_modules);
 }
 
+/// 根据信号的处理方式，决定被信号打断的系统调用是重新执行还是返回 EINTR
+///
+/// `action` 为即将调用的信号处理函数，为 `None` 时表示不会调用信号处理函数，此时系统调用总是重新执行。
+/// 否则只有 `ERESTARTSYS` 在处理函数指定了 `SA_RESTART` 时与 `ERESTARTNOINTR` 会重新执行
+fn restart_syscall(restart: Option<SyscallRestart>, action: Option<&SigAction>) {
+    let Some(restart) = restart else {
+        return;
+    };
+    let restart_now = match restart.errno {
+        LinuxError::ERESTARTSYS => action.map_or(true, |action| {
+            action.sa_flags.contains(SigActionFlags::SA_RESTART)
+        }),
+        LinuxError::ERESTARTNOINTR => true,
+        _ => action.is_none(),
+    };
+    let trap_frame = unsafe { &mut *current_task().get_first_trap_frame() };
+    if restart_now {
+        trap_frame.restart_syscall(restart.syscall_id, restart.arg0);
+    } else {
+        trap_frame.set_ret_code(-(LinuxError::EINTR.code() as isize) as usize);
+    }
+}
+
+/// 记录当前线程被信号打断的系统调用，由返回用户态前的信号处理决定重新执行还是返回 EINTR
+///
+/// `errno` 为 `ERESTART_RESTARTBLOCK` 时，`syscall_id` 应为 restart_syscall 的 id
+pub fn set_syscall_restart(errno: LinuxError, syscall_id: usize, arg0: usize) {
+    let process = current_process();
+    let mut signal_modules = process.signal_modules.lock();
+    if let Some(signal_module) = signal_modules.get_mut(&current_task().id().as_u64()) {
+        signal_module.syscall_restart = Some(SyscallRestart {
+            errno,
+            syscall_id,
+            arg0,
+        });
+    }
+}
+
+/// 设置当前线程通过 restart_syscall 继续执行时使用的状态
+pub fn set_restart_block(block: RestartBlock) {
+    let process = current_process();
+    let mut signal_modules = process.signal_modules.lock();
+    if let Some(signal_module) = signal_modules.get_mut(&current_task().id().as_u64()) {
+        signal_module.restart_block = Some(block);
+    }
+}
+
+/// 取出当前线程通过 restart_syscall 继续执行时使用的状态
+pub fn take_restart_block() -> Option<RestartBlock> {
+    let process = current_process();
+    let mut signal_modules = process.signal_modules.lock();
+    signal_modules
+        .get_mut(&current_task().id().as_u64())
+        .and_then(|signal_module| signal_module.restart_block.take())
+}
+
 /// 从信号处理函数返回
 ///
 /// 返回的值与原先syscall应当返回的值相同，即返回原先保存的trap上下文的a0的值
@@ -265,29 +585,52 @@ pub fn signal_return() -> isize {
 
 /// 发送信号到指定的进程
 ///
-/// 默认发送到该进程下的主线程
+/// 信号加入进程共享的未决信号，由主线程或任意一个没有阻塞该信号的线程处理
 pub fn send_signal_to_process(pid: isize, signum: isize) -> AxResult<()> {
-    let mut pid2pc = PID2PC.lock();
-    if !pid2pc.contains_key(&(pid as u64)) {
-        return Err(axerrno::AxError::NotFound);
-    }
-    let process = pid2pc.get_mut(&(pid as u64)).unwrap();
-    let mut now_id: Option<u64> = None;
-    for task in process.tasks.lock().iter_mut() {
-        if task.is_leader() {
-            now_id = Some(task.id().as_u64());
-            break;
-        }
-    }
-    if now_id.is_some() {
-        let mut signal_modules = process.signal_modules.lock();
-        let signal_module = signal_modules.get_mut(&now_id.unwrap()).unwrap();
-        signal_module.signal_set.try_add_signal(signum as usize);
-        let tid2task = TID2TASK.lock();
-        let main_task = Arc::clone(tid2task.get(&now_id.unwrap()).unwrap());
-        // 如果这个时候对应的线程是处于休眠状态的，则唤醒之，进入信号处理阶段
-        if main_task.state() == TaskState::Blocked {
-            RUN_QUEUE.lock().unblock_task(main_task, false);
+    send_signal_to_process_with_info(pid, signum, None)
+}
+
+/// 发送带有附加信息的信号到指定的进程
+///
+/// 信号处理函数指定了 SA_SIGINFO 时，`info` 会被传递给用户。实时信号排队数目达到上限时返回 `WouldBlock`
+pub fn send_signal_to_process_with_info(
+    pid: isize,
+    signum: isize,
+    info: Option<SigInfo>,
+) -> AxResult<()> {
+    let pid2pc = PID2PC.lock();
+    let process = if let Some(process) = pid2pc.get(&(pid as u64)) {
+        Arc::clone(process)
+    } else {
+        return Err(AxError::NotFound);
+    };
+    drop(pid2pc);
+    let signum = signum as usize;
+    let tasks = process.tasks.lock().clone();
+    let signal_modules = process.signal_modules.lock();
+    let (queued, limit) = queued_and_limit(&process, &signal_modules);
+    process
+        .shared_pending
+        .lock()
+        .queue(signum, info, queued, limit)?;
+    // 优先选择主线程，若主线程阻塞了该信号，则选择其他没有阻塞该信号的线程。
+    // 所有线程都阻塞了该信号时，信号保持未决，直到某个线程解除阻塞
+    let unblocked = |task: &&AxTaskRef| {
+        signal_modules
+            .get(&task.id().as_u64())
+            .map_or(false, |signal_module| !signal_module.blocks(signum))
+    };
+    let target = tasks
+        .iter()
+        .filter(|task| task.is_leader())
+        .find(unblocked)
+        .or_else(|| tasks.iter().find(unblocked))
+        .cloned();
+    drop(signal_modules);
+    // 如果这个时候对应的线程是处于休眠状态的，则唤醒之，进入信号处理阶段
+    if let Some(task) = target {
+        if task.state() == TaskState::Blocked {
+            RUN_QUEUE.lock().unblock_task(task, false);
         }
     }
     Ok(())
@@ -295,6 +638,15 @@ pub fn send_signal_to_process(pid: isize, signum: isize) -> AxResult<()> {
 
 /// 发送信号到指定的线程
 pub fn send_signal_to_thread(tid: isize, signum: isize) -> AxResult<()> {
+    send_signal_to_thread_with_info(tid, signum, None)
+}
+
+/// 发送带有附加信息的信号到指定的线程
+pub fn send_signal_to_thread_with_info(
+    tid: isize,
+    signum: isize,
+    info: Option<SigInfo>,
+) -> AxResult<()> {
     let tid2task = TID2TASK.lock();
     let task = if let Some(task) = tid2task.get(&(tid as u64)) {
         Arc::clone(task)
@@ -311,11 +663,14 @@ pub fn send_signal_to_thread(tid: isize, signum: isize) -> AxResult<()> {
     };
     drop(pid2pc);
     let mut signal_modules = process.signal_modules.lock();
-    if !signal_modules.contains_key(&(tid as u64)) {
-        return Err(axerrno::AxError::NotFound);
-    }
-    let signal_module = signal_modules.get_mut(&(tid as u64)).unwrap();
-    signal_module.signal_set.try_add_signal(signum as usize);
+    queue_signal_to(
+        &process,
+        &mut signal_modules,
+        tid as u64,
+        signum as usize,
+        info,
+    )?;
+    drop(signal_modules);
     // 如果这个时候对应的线程是处于休眠状态的，则唤醒之，进入信号处理阶段
     if task.state() == TaskState::Blocked {
         RUN_QUEUE.lock().unblock_task(task, false);
@@ -323,10 +678,76 @@ pub fn send_signal_to_thread(tid: isize, signum: isize) -> AxResult<()> {
     Ok(())
 }
 
-struct SignalCallerImpl;
-#[crate_interface::impl_interface]
-impl SignalCaller for SignalCallerImpl {
-    fn send_signal(tid: isize, signum: isize) {
-        send_signal_to_thread(tid, signum).unwrap();
+/// 从当前线程与进程的未决信号中取出一个属于 `set` 的信号，不考虑信号掩码
+///
+/// 用于 signalfd 与 rt_sigtimedwait 同步接收信号，取出的信号不会再被信号处理函数处理
+pub fn dequeue_signal_in(set: usize) -> Option<SigInfo> {
+    let process = current_process();
+    let mut signal_modules = process.signal_modules.lock();
+    let signal_module = signal_modules.get_mut(&current_task().id().as_u64())?;
+    signal_module
+        .pending
+        .dequeue_in(set)
+        .or_else(|| process.shared_pending.lock().dequeue_in(set))
+}
+
+/// 当前线程与进程是否有属于 `set` 的未决信号
+pub fn has_pending_in(set: usize) -> bool {
+    pending_signals() & set != 0
+}
+
+/// 当前线程与进程的所有未决信号，包括被阻塞的信号
+pub fn pending_signals() -> usize {
+    let process = current_process();
+    let signal_modules = process.signal_modules.lock();
+    let thread_pending = signal_modules
+        .get(&current_task().id().as_u64())
+        .map_or(0, |signal_module| signal_module.pending.signals);
+    let shared_pending = process.shared_pending.lock().signals;
+    thread_pending | shared_pending
+}
+
+/// 获取进程对 SIGCHLD 设置的处理方式
+///
+/// 同一进程下的线程共享信号处理函数，因此任取一个线程的信号模块即可
+pub fn sigchld_action(process: &Process) -> Option<SigAction> {
+    let signal_modules = process.signal_modules.lock();
+    let signal_module = signal_modules.values().next()?;
+    let signal_handler = signal_module.signal_handler.lock();
+    signal_handler.handlers[SignalNo::SIGCHLD as usize - 1]
+}
+
+/// 若指定信号仍处于未决状态，则更新其附带的计时器超时次数并返回 true
+///
+/// `tid` 为 `None` 时查找进程共享的未决信号，否则查找对应线程的未决信号。
+/// 计时器到期时若上一次发出的信号尚未被处理，则不再重复发送，而是累加超时次数
+pub fn update_pending_timer_signal(
+    process: &Process,
+    tid: Option<u64>,
+    signum: usize,
+    overrun: i32,
+) -> bool {
+    let mut signal_modules = process.signal_modules.lock();
+    let mut shared_pending = process.shared_pending.lock();
+    let pending = match tid {
+        Some(tid) => match signal_modules.get_mut(&tid) {
+            Some(signal_module) => &mut signal_module.pending,
+            None => return false,
+        },
+        None => &mut *shared_pending,
+    };
+    if !pending.contains(signum) {
+        return false;
+    }
+    let timer_info = pending
+        .infos
+        .get_mut(&signum)
+        .and_then(|queue| queue.iter_mut().find(|info| info.si_code == SI_TIMER));
+    if let Some(info) = timer_info {
+        info.set_timer_overrun(overrun);
+        true
+    } else {
+        // 排队中的实时信号并非由计时器产生时，计时器仍然需要发出自己的信号
+        signum < SignalNo::SIGRTMIN as usize
     }
 }
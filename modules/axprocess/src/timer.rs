@@ -0,0 +1,498 @@
+//! 进程的间隔计时器（setitimer）与 POSIX 计时器（timer_create）
+//!
+//! 两类计时器统一保存在进程的计时器表中。墙上时钟计时器的到期时间同时记录在设置它的 CPU 的计时器队列中，
+//! 由该 CPU 的时钟中断取出，CPU 时间计时器则在发生过时钟中断的线程返回用户态前根据进程已经占用的 CPU 时间检查。
+//!
+//! 中断上下文中不能获取进程的锁，因此到期的墙上时钟计时器交给内核任务 `timer_worker` 处理
+extern crate alloc;
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    vec::Vec,
+};
+use axerrno::{AxError, AxResult};
+use axhal::{cpu::this_cpu_id, time::current_time_nanos, KERNEL_PROCESS_ID};
+use axtask::{current, WaitQueue};
+use core::time::Duration;
+use kernel_guard::NoPreemptIrqSave;
+use lazy_static::lazy_static;
+use spinlock::SpinNoIrq;
+use timer_list::{TimeValue, TimerEvent, TimerList};
+
+use crate::process::{Process, PID2PC};
+
+/// 每个进程最多可以创建的 POSIX 计时器数目
+pub const MAX_POSIX_TIMERS: usize = 1024;
+
+/// setitimer 的 ITIMER_REAL
+pub const ITIMER_REAL: usize = 0;
+/// setitimer 的 ITIMER_VIRTUAL
+pub const ITIMER_VIRTUAL: usize = 1;
+/// setitimer 的 ITIMER_PROF
+pub const ITIMER_PROF: usize = 2;
+
+/// 计时器所依据的时钟
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerClock {
+    /// 墙上时钟，CLOCK_REALTIME 与 CLOCK_MONOTONIC 在本内核中一致
+    Real,
+    /// 进程所有线程的用户态运行时间
+    Virtual,
+    /// 进程所有线程的用户态与内核态运行时间，即 CLOCK_PROCESS_CPUTIME_ID
+    Prof,
+}
+
+/// 计时器到期时的通知方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerNotify {
+    /// 不发送信号，对应 SIGEV_NONE
+    None,
+    /// 向进程发送信号，对应 SIGEV_SIGNAL
+    Signal(usize),
+    /// 向指定线程发送信号，对应 SIGEV_THREAD_ID，参数为线程号与信号
+    Thread(u64, usize),
+}
+
+/// 计时器在计时器表中的编号
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimerId {
+    /// setitimer 的计时器，参数为 which
+    ITimer(usize),
+    /// timer_create 创建的计时器，参数为返回给用户的编号
+    Posix(usize),
+}
+
+/// 一个计时器
+pub struct ProcessTimer {
+    /// 所依据的时钟
+    pub clock: TimerClock,
+    /// 到期时的通知方式
+    pub notify: TimerNotify,
+    /// 信号附带的 sigev_value
+    pub value: usize,
+    /// 周期，为 0 时只触发一次
+    interval_ns: u64,
+    /// 下一次到期时对应时钟的读数，为 0 时表示计时器未启动
+    expire_ns: u64,
+    /// 最近一次发出信号之后额外到期的次数
+    overrun: usize,
+}
+
+impl ProcessTimer {
+    fn new(clock: TimerClock, notify: TimerNotify, value: usize) -> Self {
+        Self {
+            clock,
+            notify,
+            value,
+            interval_ns: 0,
+            expire_ns: 0,
+            overrun: 0,
+        }
+    }
+
+    /// 计时器是否已经启动
+    pub fn is_armed(&self) -> bool {
+        self.expire_ns != 0
+    }
+
+    /// 以 (剩余时间, 周期) 的形式输出，单位为纳秒
+    pub fn output(&self, now_ns: u64) -> (u64, u64) {
+        if !self.is_armed() {
+            return (0, self.interval_ns);
+        }
+        // 已经到期但尚未处理的计时器仍然视为启动状态
+        (
+            self.expire_ns.saturating_sub(now_ns).max(1),
+            self.interval_ns,
+        )
+    }
+
+    /// 检查计时器是否到期，若到期则返回在这之前错过的周期数，并计算下一次到期时间
+    fn expire(&mut self, now_ns: u64) -> Option<usize> {
+        if !self.is_armed() || now_ns < self.expire_ns {
+            return None;
+        }
+        if self.interval_ns == 0 {
+            self.expire_ns = 0;
+            return Some(0);
+        }
+        let missed = (now_ns - self.expire_ns) / self.interval_ns;
+        self.expire_ns += (missed + 1) * self.interval_ns;
+        Some(missed as usize)
+    }
+}
+
+/// 进程的计时器表
+#[derive(Default)]
+pub struct TimerTable {
+    timers: BTreeMap<TimerId, ProcessTimer>,
+}
+
+impl TimerTable {
+    /// 新建一个空的计时器表
+    pub const fn new() -> Self {
+        Self {
+            timers: BTreeMap::new(),
+        }
+    }
+
+    /// 创建一个 POSIX 计时器，返回最小的可用编号
+    ///
+    /// `value` 为 None 时，信号附带的值为计时器编号
+    pub fn create(
+        &mut self,
+        clock: TimerClock,
+        notify: TimerNotify,
+        value: Option<usize>,
+    ) -> AxResult<usize> {
+        let id = (0..MAX_POSIX_TIMERS)
+            .find(|id| !self.timers.contains_key(&TimerId::Posix(*id)))
+            .ok_or(AxError::WouldBlock)?;
+        self.timers.insert(
+            TimerId::Posix(id),
+            ProcessTimer::new(clock, notify, value.unwrap_or(id)),
+        );
+        Ok(id)
+    }
+
+    /// 获取一个计时器
+    pub fn get(&self, id: TimerId) -> Option<&ProcessTimer> {
+        self.timers.get(&id)
+    }
+
+    /// 删除一个 POSIX 计时器
+    pub fn delete(&mut self, pid: u64, id: usize) -> AxResult<()> {
+        self.timers
+            .remove(&TimerId::Posix(id))
+            .ok_or(AxError::InvalidInput)?;
+        cancel_real_timer(pid, TimerId::Posix(id));
+        Ok(())
+    }
+
+    /// 设置计时器，返回原先的 (剩余时间, 周期)，单位为纳秒
+    ///
+    /// `now_ns` 为计时器所依据的时钟的当前读数。`value_ns` 为 0 时停止计时器，
+    /// `absolute` 为 true 时 `value_ns` 表示到期时刻而非剩余时间。
+    /// setitimer 的计时器在第一次设置时创建
+    pub fn set(
+        &mut self,
+        pid: u64,
+        id: TimerId,
+        value_ns: u64,
+        interval_ns: u64,
+        absolute: bool,
+        now_ns: u64,
+    ) -> AxResult<(u64, u64)> {
+        if let TimerId::ITimer(which) = id {
+            let (clock, signum) = itimer_clock_and_signal(which).ok_or(AxError::InvalidInput)?;
+            self.timers
+                .entry(id)
+                .or_insert_with(|| ProcessTimer::new(clock, TimerNotify::Signal(signum), 0));
+        }
+        let timer = self.timers.get_mut(&id).ok_or(AxError::InvalidInput)?;
+        let old = timer.output(now_ns);
+        timer.interval_ns = interval_ns;
+        timer.overrun = 0;
+        timer.expire_ns = if value_ns == 0 {
+            0
+        } else if absolute {
+            value_ns
+        } else {
+            now_ns + value_ns
+        };
+        if timer.clock == TimerClock::Real {
+            cancel_real_timer(pid, id);
+            if timer.is_armed() {
+                arm_real_timer(pid, id, timer.expire_ns);
+            }
+        }
+        Ok(old)
+    }
+
+    /// 获取 POSIX 计时器最近一次发出信号之后额外到期的次数
+    pub fn overrun(&self, id: usize) -> AxResult<usize> {
+        self.timers
+            .get(&TimerId::Posix(id))
+            .map(|timer| timer.overrun)
+            .ok_or(AxError::InvalidInput)
+    }
+
+    /// 是否存在已经启动的 CPU 时间计时器
+    fn has_cpu_timers(&self) -> bool {
+        self.timers
+            .values()
+            .any(|timer| timer.clock != TimerClock::Real && timer.is_armed())
+    }
+
+    /// 清除 POSIX 计时器，setitimer 的计时器保持不变，用于 exec
+    pub fn clear_posix(&mut self, pid: u64) {
+        self.timers.retain(|id, _| matches!(id, TimerId::ITimer(_)));
+        for list in WALL_TIMERS.iter() {
+            list.lock()
+                .cancel(|event| event.pid == pid && matches!(event.id, TimerId::Posix(_)));
+        }
+    }
+
+    /// 清除所有计时器，用于进程退出
+    pub fn clear(&mut self, pid: u64) {
+        self.timers.clear();
+        for list in WALL_TIMERS.iter() {
+            list.lock().cancel(|event| event.pid == pid);
+        }
+    }
+
+    /// 进程的 CPU 时间统计被重置时，同步调整 CPU 时间计时器的到期时间
+    ///
+    /// `virtual_ns` 与 `prof_ns` 分别为用户态时间与总 CPU 时间减少的量
+    pub fn rewind_cpu_clocks(&mut self, virtual_ns: u64, prof_ns: u64) {
+        for timer in self.timers.values_mut().filter(|timer| timer.is_armed()) {
+            let delta = match timer.clock {
+                TimerClock::Real => continue,
+                TimerClock::Virtual => virtual_ns,
+                TimerClock::Prof => prof_ns,
+            };
+            timer.expire_ns = timer.expire_ns.saturating_sub(delta).max(1);
+        }
+    }
+}
+
+/// setitimer 的 which 对应的时钟与信号
+fn itimer_clock_and_signal(which: usize) -> Option<(TimerClock, usize)> {
+    match which {
+        // SIGALRM
+        ITIMER_REAL => Some((TimerClock::Real, 14)),
+        // SIGVTALRM
+        ITIMER_VIRTUAL => Some((TimerClock::Virtual, 26)),
+        // SIGPROF
+        ITIMER_PROF => Some((TimerClock::Prof, 27)),
+        _ => None,
+    }
+}
+
+/// 进程在指定时钟下的当前读数，单位为纳秒
+pub fn clock_now_ns(process: &Process, clock: TimerClock) -> u64 {
+    match clock {
+        TimerClock::Real => current_time_nanos(),
+        TimerClock::Virtual => process.cpu_times_us().0 as u64 * 1000,
+        TimerClock::Prof => {
+            let (utime_us, stime_us) = process.cpu_times_us();
+            (utime_us + stime_us) as u64 * 1000
+        }
+    }
+}
+
+/// 墙上时钟计时器的一次到期事件
+///
+/// 计时器被重新设置或者删除时，对应的事件会被取消，因此到期时只需要再确认一次到期时间
+struct RealTimerEvent {
+    pid: u64,
+    id: TimerId,
+    expire_ns: u64,
+}
+
+impl TimerEvent for RealTimerEvent {
+    /// 在时钟中断中调用，只将事件交给 `timer_worker` 处理
+    fn callback(self, _now: TimeValue) {
+        EXPIRED_TIMERS.lock().push_back(self);
+        TIMER_WORKER_WQ.notify_one(true);
+    }
+}
+
+impl RealTimerEvent {
+    /// 在任务上下文中处理到期事件
+    fn handle(self) {
+        let process = if let Some(process) = PID2PC.lock().get(&self.pid) {
+            Arc::clone(process)
+        } else {
+            return;
+        };
+        let is_current = process
+            .timers
+            .lock()
+            .get(self.id)
+            .map_or(false, |timer| timer.expire_ns == self.expire_ns);
+        if is_current {
+            fire_timer(&process, self.id, current_time_nanos());
+        }
+    }
+}
+
+lazy_static! {
+    /// 所有进程的墙上时钟计时器的到期事件，按照设置计时器的 CPU 划分
+    static ref WALL_TIMERS: Vec<SpinNoIrq<TimerList<RealTimerEvent>>> = (0..axconfig::SMP)
+        .map(|_| SpinNoIrq::new(TimerList::new()))
+        .collect();
+}
+
+/// 已经到期、等待 `timer_worker` 处理的墙上时钟计时器事件
+static EXPIRED_TIMERS: SpinNoIrq<VecDeque<RealTimerEvent>> = SpinNoIrq::new(VecDeque::new());
+
+/// `timer_worker` 在此等待到期事件
+static TIMER_WORKER_WQ: WaitQueue = WaitQueue::new();
+
+/// 创建处理到期的墙上时钟计时器的内核任务
+pub fn init_timer_worker() {
+    axtask::spawn_raw(
+        timer_worker,
+        "timer_worker".into(),
+        axconfig::TASK_STACK_SIZE,
+    );
+}
+
+fn timer_worker() {
+    loop {
+        TIMER_WORKER_WQ.wait_until(|| !EXPIRED_TIMERS.lock().is_empty());
+        loop {
+            let event = EXPIRED_TIMERS.lock().pop_front();
+            if let Some(event) = event {
+                event.handle();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+fn arm_real_timer(pid: u64, id: TimerId, expire_ns: u64) {
+    let _guard = NoPreemptIrqSave::new();
+    let deadline = Duration::from_nanos(expire_ns);
+    WALL_TIMERS[this_cpu_id()]
+        .lock()
+        .set(deadline, RealTimerEvent { pid, id, expire_ns });
+    axtask::request_timer_irq(deadline);
+}
+
+fn cancel_real_timer(pid: u64, id: TimerId) {
+    for list in WALL_TIMERS.iter() {
+        list.lock()
+            .cancel(|event| event.pid == pid && event.id == id);
+    }
+}
+
+/// 处理一个到期的计时器：计算下一次到期时间，并按照通知方式发送信号
+///
+/// 若上一次发出的信号仍未被处理，则不再重复发送，只累加超时次数
+fn fire_timer(process: &Process, id: TimerId, now_ns: u64) {
+    let mut timers = process.timers.lock();
+    let timer = if let Some(timer) = timers.timers.get_mut(&id) {
+        timer
+    } else {
+        return;
+    };
+    let missed = if let Some(missed) = timer.expire(now_ns) {
+        missed
+    } else {
+        return;
+    };
+    if timer.clock == TimerClock::Real && timer.is_armed() {
+        arm_real_timer(process.pid(), id, timer.expire_ns);
+    }
+    let (tid, signum) = match timer.notify {
+        TimerNotify::None => {
+            timer.overrun = timer.overrun.saturating_add(missed);
+            return;
+        }
+        // 发送给进程的信号保存在进程共享的未决信号中
+        TimerNotify::Signal(signum) => (None, signum),
+        TimerNotify::Thread(tid, signum) => (Some(tid), signum),
+    };
+    #[cfg(feature = "signal")]
+    {
+        use crate::signal::{
+            send_signal_to_process_with_info, send_signal_to_thread_with_info,
+            update_pending_timer_signal,
+        };
+        use axsignal::info::SigInfo;
+
+        let overrun = timer.overrun.saturating_add(1 + missed);
+        if update_pending_timer_signal(process, tid, signum, overrun.min(i32::MAX as usize) as i32)
+        {
+            timer.overrun = overrun;
+            return;
+        }
+        timer.overrun = missed;
+        let info = match id {
+            TimerId::Posix(timer_id) => Some(SigInfo::timer(
+                signum as i32,
+                timer_id as i32,
+                missed.min(i32::MAX as usize) as i32,
+                timer.value,
+            )),
+            TimerId::ITimer(_) => None,
+        };
+        let notify = timer.notify;
+        drop(timers);
+        let _ = match notify {
+            TimerNotify::Thread(tid, _) => {
+                send_signal_to_thread_with_info(tid as isize, signum as isize, info)
+            }
+            _ => send_signal_to_process_with_info(process.pid() as isize, signum as isize, info),
+        };
+    }
+    #[cfg(not(feature = "signal"))]
+    {
+        let _ = (tid, signum);
+        timer.overrun = missed;
+    }
+}
+
+/// 保证本 CPU 在其队列中最早的墙上时钟计时器到期时产生时钟中断
+///
+/// axtask 只根据自己的定时事件设置下一次时钟中断，因此每次时钟中断之后都需要调用
+pub fn rearm_timer_irq() {
+    let deadline = WALL_TIMERS[this_cpu_id()].lock().next_deadline();
+    if let Some(deadline) = deadline {
+        axtask::request_timer_irq(deadline);
+    }
+}
+
+/// 在时钟中断中取出本 CPU 队列中已经到期的墙上时钟计时器，交给 `timer_worker` 处理
+pub fn expire_real_timers() {
+    loop {
+        let now = Duration::from_nanos(current_time_nanos());
+        let event = WALL_TIMERS[this_cpu_id()].lock().expire_one(now);
+        if let Some((_deadline, event)) = event {
+            event.callback(now);
+        } else {
+            break;
+        }
+    }
+}
+
+/// 检查当前进程的 CPU 时间计时器是否到期
+///
+/// 在发生过来自用户态的时钟中断之后、返回用户态之前于任务上下文中调用，
+/// 此时当前线程的用户态时间已经统计完毕
+pub fn check_cpu_timers() {
+    let pid = current().get_process_id();
+    if pid == KERNEL_PROCESS_ID {
+        return;
+    }
+    let process = if let Some(process) = PID2PC.lock().get(&pid) {
+        Arc::clone(process)
+    } else {
+        return;
+    };
+    if !process.timers.lock().has_cpu_timers() {
+        return;
+    }
+    let virtual_ns = clock_now_ns(&process, TimerClock::Virtual);
+    let prof_ns = clock_now_ns(&process, TimerClock::Prof);
+    let expired: Vec<(TimerId, u64)> = process
+        .timers
+        .lock()
+        .timers
+        .iter()
+        .filter_map(|(id, timer)| {
+            let now_ns = match timer.clock {
+                TimerClock::Real => return None,
+                TimerClock::Virtual => virtual_ns,
+                TimerClock::Prof => prof_ns,
+            };
+            (timer.is_armed() && timer.expire_ns <= now_ns).then_some((*id, now_ns))
+        })
+        .collect();
+    for (id, now_ns) in expired {
+        fire_timer(&process, id, now_ns);
+    }
+}
@@ -293,28 +293,35 @@ fn init_interrupt() {
     use axhal::time::TIMER_IRQ_NUM;
 
     // Setup timer interrupt handler
-    const PERIODIC_INTERVAL_NANOS: u64 =
-        axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
-
-    #[percpu::def_percpu]
-    static NEXT_DEADLINE: u64 = 0;
-
-    fn update_timer() {
-        let now_ns = axhal::time::current_time_nanos();
-        // Safety: we have disabled preemption in IRQ handler.
-        let mut deadline = unsafe { NEXT_DEADLINE.read_current_raw() };
-        if now_ns >= deadline {
-            deadline = now_ns + PERIODIC_INTERVAL_NANOS;
+    #[cfg(not(feature = "multitask"))]
+    {
+        const PERIODIC_INTERVAL_NANOS: u64 =
+            axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
+
+        #[percpu::def_percpu]
+        static NEXT_DEADLINE: u64 = 0;
+
+        fn update_timer() {
+            let now_ns = axhal::time::current_time_nanos();
+            // Safety: we have disabled preemption in IRQ handler.
+            let mut deadline = unsafe { NEXT_DEADLINE.read_current_raw() };
+            if now_ns >= deadline {
+                deadline = now_ns + PERIODIC_INTERVAL_NANOS;
+            }
+            unsafe { NEXT_DEADLINE.write_current_raw(deadline + PERIODIC_INTERVAL_NANOS) };
+            axhal::time::set_oneshot_timer(deadline);
         }
-        unsafe { NEXT_DEADLINE.write_current_raw(deadline + PERIODIC_INTERVAL_NANOS) };
-        axhal::time::set_oneshot_timer(deadline);
+
+        axhal::irq::register_handler(TIMER_IRQ_NUM, update_timer);
     }
 
-    axhal::irq::register_handler(TIMER_IRQ_NUM, || {
-        update_timer();
-        #[cfg(feature = "multitask")]
-        axtask::on_timer_tick();
-    });
+    // The task manager programs the next timer interrupt by itself, according
+    // to the scheduler tick and the earliest timed event (tickless when idle).
+    #[cfg(feature = "multitask")]
+    {
+        axhal::irq::register_handler(TIMER_IRQ_NUM, axtask::on_timer_tick);
+        axhal::irq::register_handler(axhal::irq::IPI_IRQ_NUM, axtask::on_ipi);
+    }
 
     // Enable IRQs before starting app
     axhal::arch::enable_irqs();
@@ -1,10 +1,24 @@
 //! 触发信号时的信息，当SigAction指定需要信息时，将其返回给用户
 //!
 //! 错误信息：详细定义见 `https://man7.org/linux/man-pages/man2/rt_sigaction.2.html`
+//!
+//! 布局与 Linux 的 `siginfo_t` 一致。当前支持的 riscv64、aarch64 与 x86_64 均为 64 位架构，
+//! 三者的 `siginfo_t` 布局相同：`si_signo`、`si_errno`、`si_code` 之后是按 8 字节对齐的联合体，
+//! 整个结构体大小为 128 字节
+
+use crate::signal_no::SignalNo;
+
+/// `siginfo_t` 的大小
+pub const SI_MAX_SIZE: usize = 128;
+
+/// 联合体部分按 4 字节划分的长度
+const SI_FIELDS_LEN: usize = (SI_MAX_SIZE - 4 * core::mem::size_of::<i32>()) / 4;
 
 /// The information of the signal
 ///
 /// When the `SigAction` specifies that it needs information, it will return it to the user
+#[repr(C, align(8))]
+#[derive(Clone, Copy, Debug)]
 pub struct SigInfo {
     /// The signal number
     pub si_signo: i32,
@@ -12,14 +26,262 @@ pub struct SigInfo {
     pub si_errno: i32,
     /// The code of the signal
     pub si_code: i32,
+    /// Padding to align the following union as `siginfo_t` does
+    _pad: i32,
+    /// The union of `siginfo_t`, whose meaning depends on the signal and `si_code`
+    ///
+    /// Offsets below are in units of 4 bytes from the start of the union.
+    fields: [u32; SI_FIELDS_LEN],
 }
 
+/// `si_pid`, `si_tid` of timers, `si_addr` of faults and `si_call_addr` of `SIGSYS`
+const FIELD_PID: usize = 0;
+/// `si_uid` and `si_overrun` of timers
+const FIELD_UID: usize = 1;
+/// `si_value`, `si_status` of `SIGCHLD` and `si_syscall` of `SIGSYS`
+const FIELD_VALUE: usize = 2;
+/// `si_arch` of `SIGSYS`
+const FIELD_ARCH: usize = 3;
+/// `si_utime` of `SIGCHLD`
+const FIELD_UTIME: usize = 4;
+/// `si_stime` of `SIGCHLD`
+const FIELD_STIME: usize = 6;
+
 impl Default for SigInfo {
     fn default() -> Self {
         Self {
             si_signo: 0,
             si_errno: 0,
-            si_code: -6, // SI_TKILL
+            si_code: SI_KERNEL,
+            _pad: 0,
+            fields: [0; SI_FIELDS_LEN],
         }
     }
 }
+
+impl SigInfo {
+    /// The information of a signal with only the signal number and `si_code`
+    pub fn new(signo: i32, code: i32) -> Self {
+        Self {
+            si_signo: signo,
+            si_code: code,
+            ..Default::default()
+        }
+    }
+
+    /// The information of a signal sent by a process, such as `kill`, `tkill` and `sigqueue`
+    pub fn user(signo: i32, code: i32, pid: i32, uid: u32) -> Self {
+        let mut info = Self::new(signo, code);
+        info.set_u32(FIELD_PID, pid as u32);
+        info.set_u32(FIELD_UID, uid);
+        info
+    }
+
+    /// The information of a signal sent by `sigqueue`, which carries `si_value`
+    pub fn queue(signo: i32, pid: i32, uid: u32, value: usize) -> Self {
+        let mut info = Self::user(signo, SI_QUEUE, pid, uid);
+        info.set_u64(FIELD_VALUE, value as u64);
+        info
+    }
+
+    /// The information of a signal sent by `mq_notify` when a message arrives at an empty
+    /// message queue, which carries `si_value` and the sender
+    pub fn mesgq(signo: i32, pid: i32, uid: u32, value: usize) -> Self {
+        let mut info = Self::user(signo, SI_MESGQ, pid, uid);
+        info.set_u64(FIELD_VALUE, value as u64);
+        info
+    }
+
+    /// The information of a signal generated by the expiration of a POSIX timer
+    pub fn timer(signo: i32, timer_id: i32, overrun: i32, value: usize) -> Self {
+        let mut info = Self::new(signo, SI_TIMER);
+        info.set_u32(FIELD_PID, timer_id as u32);
+        info.set_u32(FIELD_UID, overrun as u32);
+        info.set_u64(FIELD_VALUE, value as u64);
+        info
+    }
+
+    /// The information of `SIGCHLD`
+    ///
+    /// `status` is the exit code or the signal of the child, and `utime` and `stime` are
+    /// the CPU times of the child in clock ticks.
+    pub fn child(code: i32, pid: i32, uid: u32, status: i32, utime: usize, stime: usize) -> Self {
+        let mut info = Self::user(SignalNo::SIGCHLD as i32, code, pid, uid);
+        info.set_u32(FIELD_VALUE, status as u32);
+        info.set_u64(FIELD_UTIME, utime as u64);
+        info.set_u64(FIELD_STIME, stime as u64);
+        info
+    }
+
+    /// The information of a signal generated by a hardware fault, such as `SIGSEGV` and `SIGBUS`
+    pub fn fault(signo: i32, code: i32, addr: usize) -> Self {
+        let mut info = Self::new(signo, code);
+        info.set_u64(FIELD_PID, addr as u64);
+        info
+    }
+
+    /// The information of `SIGSYS` triggered by a seccomp filter
+    ///
+    /// `call_addr` is the address of the system call instruction and `errno` is the
+    /// `SECCOMP_RET_DATA` part of the filter result.
+    pub fn seccomp(call_addr: usize, syscall: i32, arch: u32, errno: i32) -> Self {
+        let mut info = Self::new(SignalNo::SIGSYS as i32, SYS_SECCOMP);
+        info.si_errno = errno;
+        info.set_u64(FIELD_PID, call_addr as u64);
+        info.set_u32(FIELD_VALUE, syscall as u32);
+        info.set_u32(FIELD_ARCH, arch);
+        info
+    }
+
+    /// The process id of the sender, or the child process for `SIGCHLD`
+    pub fn si_pid(&self) -> i32 {
+        self.fields[FIELD_PID] as i32
+    }
+
+    /// The real user id of the sender
+    pub fn si_uid(&self) -> u32 {
+        self.fields[FIELD_UID]
+    }
+
+    /// The value carried by `sigqueue` or a POSIX timer
+    pub fn si_value(&self) -> usize {
+        self.get_u64(FIELD_VALUE) as usize
+    }
+
+    /// The exit code or the signal of the child process for `SIGCHLD`
+    pub fn si_status(&self) -> i32 {
+        self.fields[FIELD_VALUE] as i32
+    }
+
+    /// The faulting address for `SIGSEGV`, `SIGBUS`, `SIGILL` and `SIGFPE`,
+    /// or `si_call_addr` for `SIGSYS`
+    pub fn si_addr(&self) -> usize {
+        self.get_u64(FIELD_PID) as usize
+    }
+
+    /// The id of the POSIX timer
+    pub fn si_timerid(&self) -> i32 {
+        self.fields[FIELD_PID] as i32
+    }
+
+    /// The overrun count of the POSIX timer
+    pub fn si_overrun(&self) -> i32 {
+        self.fields[FIELD_UID] as i32
+    }
+
+    /// The user CPU time of the child process for `SIGCHLD`
+    pub fn si_utime(&self) -> usize {
+        self.get_u64(FIELD_UTIME) as usize
+    }
+
+    /// The system CPU time of the child process for `SIGCHLD`
+    pub fn si_stime(&self) -> usize {
+        self.get_u64(FIELD_STIME) as usize
+    }
+
+    /// The number of the attempted system call for `SIGSYS`
+    pub fn si_syscall(&self) -> i32 {
+        self.fields[FIELD_VALUE] as i32
+    }
+
+    /// The `AUDIT_ARCH_*` value of the attempted system call for `SIGSYS`
+    pub fn si_arch(&self) -> u32 {
+        self.fields[FIELD_ARCH]
+    }
+
+    /// Which member of the union is valid, as `siginfo_layout` in Linux does
+    pub fn layout(&self) -> SigInfoLayout {
+        use crate::signal_no::SignalNo::*;
+        match self.si_code {
+            SI_TIMER => SigInfoLayout::Timer,
+            SI_QUEUE | SI_MESGQ => SigInfoLayout::Rt,
+            code if code > 0 && code < SI_KERNEL => match SignalNo::from(self.si_signo as usize) {
+                SIGCHLD => SigInfoLayout::Chld,
+                SIGSEGV | SIGBUS | SIGILL | SIGFPE | SIGTRAP => SigInfoLayout::Fault,
+                SIGSYS => SigInfoLayout::Sys,
+                _ => SigInfoLayout::Kill,
+            },
+            _ => SigInfoLayout::Kill,
+        }
+    }
+
+    /// Set the overrun count if it is the information of a timer signal
+    pub fn set_timer_overrun(&mut self, overrun: i32) {
+        if self.si_code == SI_TIMER {
+            self.set_u32(FIELD_UID, overrun as u32);
+        }
+    }
+
+    fn set_u32(&mut self, index: usize, value: u32) {
+        self.fields[index] = value;
+    }
+
+    /// The union is 8-byte aligned, so `index` must be even
+    fn set_u64(&mut self, index: usize, value: u64) {
+        self.fields[index] = value as u32;
+        self.fields[index + 1] = (value >> 32) as u32;
+    }
+
+    fn get_u64(&self, index: usize) -> u64 {
+        self.fields[index] as u64 | (self.fields[index + 1] as u64) << 32
+    }
+}
+
+/// The valid member of the union in `siginfo_t`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigInfoLayout {
+    /// `si_pid` and `si_uid`
+    Kill,
+    /// `si_timerid`, `si_overrun` and `si_value`
+    Timer,
+    /// `si_pid`, `si_uid` and `si_value`
+    Rt,
+    /// `si_pid`, `si_uid`, `si_status`, `si_utime` and `si_stime`
+    Chld,
+    /// `si_addr`
+    Fault,
+    /// `si_call_addr`, `si_syscall` and `si_arch`
+    Sys,
+}
+
+/// `si_code`: sent by `kill`
+pub const SI_USER: i32 = 0;
+/// `si_code`: sent by the kernel
+pub const SI_KERNEL: i32 = 0x80;
+/// `si_code`: sent by `sigqueue`
+pub const SI_QUEUE: i32 = -1;
+/// `si_code`: sent by the expiration of a POSIX timer
+pub const SI_TIMER: i32 = -2;
+/// `si_code`: sent by the arrival of a message on an empty POSIX message queue
+pub const SI_MESGQ: i32 = -3;
+/// `si_code`: sent by `tkill` and `tgkill`
+pub const SI_TKILL: i32 = -6;
+/// `si_code` of `SIGSEGV`: address not mapped to object
+pub const SEGV_MAPERR: i32 = 1;
+/// `si_code` of `SIGSEGV`: invalid permissions for mapped object
+pub const SEGV_ACCERR: i32 = 2;
+/// `si_code` of `SIGCHLD`: the child has exited
+pub const CLD_EXITED: i32 = 1;
+/// `si_code` of `SIGCHLD`: the child was killed
+pub const CLD_KILLED: i32 = 2;
+/// `si_code` of `SIGCHLD`: the child terminated abnormally and dumped core
+pub const CLD_DUMPED: i32 = 3;
+/// `si_code` of `SIGSYS`: triggered by a seccomp filter
+pub const SYS_SECCOMP: i32 = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_siginfo_layout() {
+        assert_eq!(core::mem::size_of::<SigInfo>(), SI_MAX_SIZE);
+        let info = SigInfo::queue(40, 3, 0, 0x1234_5678_9abc);
+        let raw = unsafe { &*(&info as *const SigInfo as *const [u32; SI_MAX_SIZE / 4]) };
+        // si_pid 与 si_value 分别位于偏移 16 与 24 字节处
+        assert_eq!(raw[4], 3);
+        assert_eq!(raw[6], 0x5678_9abc);
+        assert_eq!(raw[7], 0x1234);
+        assert_eq!(info.si_value(), 0x1234_5678_9abc);
+    }
+}
@@ -1,25 +1,26 @@
 //! 信号处理时保存的用户上下文。
 
-/// 处理信号时使用的栈
-///
-/// 详细信息见`https://man7.org/linux/man-pages/man2/sigaltstack.2.html`
+use axhal::arch::{FpState, TrapFrame};
+
+use super::SignalStack;
+
+/// Magic number of the `fpsimd_context` record in `__reserved`
+const FPSIMD_MAGIC: u32 = 0x4650_8001;
+
+/// The `fpsimd_context` record saved in the `__reserved` area of the `mcontext`
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct SignalStack {
-    sp: usize,
-    flags: u32,
-    size: usize,
+#[derive(Clone, Copy)]
+struct FpsimdContext {
+    magic: u32,
+    size: u32,
+    fpsr: u32,
+    fpcr: u32,
+    vregs: [u128; 32],
 }
 
-impl Default for SignalStack {
-    fn default() -> Self {
-        Self {
-            sp: 0,
-            // 代表SS_DISABLE，即不使用栈
-            flags: 2,
-            size: 0,
-        }
-    }
+/// get the size of the FP state area outside the user context in the signal frame
+pub fn fp_state_size() -> usize {
+    0
 }
 
 #[repr(C)]
@@ -31,6 +32,8 @@ pub struct MContext {
     sp: usize,
     pc: usize,
     pstate: usize,
+    // `__reserved` is 16-byte aligned
+    _pad: usize,
     reserved: [usize; 256 * 2],
 }
 
@@ -42,6 +45,7 @@ impl Default for MContext {
             sp: 0,
             pc: 0,
             pstate: 0,
+            _pad: 0,
             reserved: [0; 512],
         }
     }
@@ -55,6 +59,7 @@ impl MContext {
             sp: 0,
             pc,
             pstate: 0,
+            _pad: 0,
             reserved: [0; 512],
         }
     }
@@ -72,19 +77,77 @@ pub struct SignalUserContext {
 }
 
 impl SignalUserContext {
-    /// init the user context by the pc and the mask
-    pub fn init(pc: usize, _mask: usize) -> Self {
+    /// init the user context by the pc, the mask and the alternate signal stack
+    pub fn init(pc: usize, mask: usize, stack: SignalStack) -> Self {
+        let mut sigmask = [0; 17];
+        sigmask[0] = mask;
         Self {
             flags: 0,
             link: 0,
-            stack: SignalStack::default(),
+            stack,
             mcontext: MContext::init_by_pc(pc),
-            sigmask: [0; 17],
+            sigmask,
         }
     }
 
+    /// get the alternate signal stack saved in the user context
+    pub fn get_stack(&self) -> SignalStack {
+        self.stack
+    }
+
     /// get the pc from the user context
     pub fn get_pc(&self) -> usize {
         self.mcontext.pc
     }
+
+    /// get the signal mask to be restored when the signal handler returns
+    pub fn get_mask(&self) -> usize {
+        self.sigmask[0]
+    }
+
+    /// save the FP/SIMD states of the current CPU as a `fpsimd_context` record
+    ///
+    /// # Safety
+    ///
+    /// The user context must be 16-byte aligned.
+    pub unsafe fn save_fp_state(&mut self, _area: usize, _trap_frame: &TrapFrame) {
+        let mut fp_state = FpState::default();
+        fp_state.save();
+        let record = self.mcontext.reserved.as_mut_ptr() as *mut FpsimdContext;
+        *record = FpsimdContext {
+            magic: FPSIMD_MAGIC,
+            size: core::mem::size_of::<FpsimdContext>() as u32,
+            fpsr: fp_state.fpsr,
+            fpcr: fp_state.fpcr,
+            vregs: fp_state.regs,
+        };
+        // the record list is terminated by a null header
+        *(record.add(1) as *mut u64) = 0;
+    }
+
+    /// restore the FP/SIMD states of the current CPU from the `fpsimd_context` record
+    ///
+    /// The record is saved in the user context itself, so `_is_user_range` is not needed.
+    ///
+    /// # Safety
+    ///
+    /// The user context must be 16-byte aligned.
+    pub unsafe fn restore_fp_state(
+        &self,
+        _trap_frame: &mut TrapFrame,
+        _is_user_range: impl FnOnce(usize, usize) -> bool,
+    ) {
+        let record = &*(self.mcontext.reserved.as_ptr() as *const FpsimdContext);
+        if record.magic != FPSIMD_MAGIC
+            || record.size as usize != core::mem::size_of::<FpsimdContext>()
+        {
+            return;
+        }
+        let fp_state = FpState {
+            regs: record.vregs,
+            fpcr: record.fpcr,
+            fpsr: record.fpsr,
+        };
+        fp_state.restore();
+    }
 }
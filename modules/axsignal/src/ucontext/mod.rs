@@ -12,3 +12,63 @@ cfg_if::cfg_if! {
         pub use self::aarch64::*;
     }
 }
+
+/// 当前正在备用信号栈上运行
+pub const SS_ONSTACK: u32 = 1;
+/// 不使用备用信号栈
+pub const SS_DISABLE: u32 = 2;
+/// 在备用信号栈上开始处理信号时清除备用信号栈的设置，信号处理函数返回时恢复
+pub const SS_AUTODISARM: u32 = 1 << 31;
+
+/// 备用信号栈的最小大小
+#[cfg(target_arch = "aarch64")]
+pub const MINSIGSTKSZ: usize = 5120;
+/// 备用信号栈的最小大小
+#[cfg(not(target_arch = "aarch64"))]
+pub const MINSIGSTKSZ: usize = 2048;
+
+/// 处理信号时使用的栈，即 `stack_t`
+///
+/// 详细信息见`https://man7.org/linux/man-pages/man2/sigaltstack.2.html`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SignalStack {
+    /// 栈的起始地址
+    pub sp: usize,
+    /// `SS_DISABLE` 或 `SS_AUTODISARM`，查询时还可能带有 `SS_ONSTACK`
+    pub flags: u32,
+    /// 栈的大小
+    pub size: usize,
+}
+
+impl Default for SignalStack {
+    fn default() -> Self {
+        Self {
+            sp: 0,
+            // 代表SS_DISABLE，即不使用栈
+            flags: SS_DISABLE,
+            size: 0,
+        }
+    }
+}
+
+impl SignalStack {
+    /// 是否设置了备用信号栈
+    pub fn is_enabled(&self) -> bool {
+        self.flags & SS_DISABLE == 0
+    }
+
+    /// 用户栈指针 `sp` 是否位于备用信号栈上
+    pub fn on_stack(&self, sp: usize) -> bool {
+        self.is_enabled() && sp > self.sp && sp - self.sp <= self.size
+    }
+
+    /// 返回给用户的备用信号栈信息，`sp` 为当前的用户栈指针
+    pub fn output(&self, sp: usize) -> Self {
+        let mut stack = *self;
+        if self.on_stack(sp) {
+            stack.flags |= SS_ONSTACK;
+        }
+        stack
+    }
+}
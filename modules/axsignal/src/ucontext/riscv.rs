@@ -1,25 +1,12 @@
 //! 信号处理时保存的用户上下文。
 
-/// 处理信号时使用的栈
-///
-/// 详细信息见`https://man7.org/linux/man-pages/man2/sigaltstack.2.html`
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct SignalStack {
-    sp: usize,
-    flags: u32,
-    size: usize,
-}
+use axhal::arch::{FpState, TrapFrame};
 
-impl Default for SignalStack {
-    fn default() -> Self {
-        Self {
-            sp: 0,
-            // 代表SS_DISABLE，即不使用栈
-            flags: 2,
-            size: 0,
-        }
-    }
+use super::SignalStack;
+
+/// get the size of the FP state area outside the user context in the signal frame
+pub fn fp_state_size() -> usize {
+    0
 }
 
 #[repr(C)]
@@ -28,7 +15,9 @@ impl Default for SignalStack {
 pub struct MContext {
     reserved1: [usize; 16],
     pc: usize,
-    reserved2: [usize; 17],
+    // x1..x31
+    reserved2: [usize; 31],
+    // `__riscv_fp_state`, the F/D states are saved as `__riscv_d_ext_state`
     fpstate: [usize; 66],
 }
 
@@ -37,7 +26,7 @@ impl Default for MContext {
         Self {
             reserved1: [0; 16],
             pc: 0,
-            reserved2: [0; 17],
+            reserved2: [0; 31],
             fpstate: [0; 66],
         }
     }
@@ -48,7 +37,7 @@ impl MContext {
         Self {
             reserved1: [0; 16],
             pc,
-            reserved2: [0; 17],
+            reserved2: [0; 31],
             fpstate: [0; 66],
         }
     }
@@ -70,19 +59,63 @@ pub struct SignalUserContext {
 }
 
 impl SignalUserContext {
-    /// init the user context by the pc and the mask
-    pub fn init(pc: usize, mask: usize) -> Self {
+    /// init the user context by the pc, the mask and the alternate signal stack
+    pub fn init(pc: usize, mask: usize, stack: SignalStack) -> Self {
         Self {
             flags: 0,
             link: 0,
-            stack: SignalStack::default(),
+            stack,
             mcontext: MContext::init_by_pc(pc),
             sigmask: mask as u64,
         }
     }
 
+    /// get the alternate signal stack saved in the user context
+    pub fn get_stack(&self) -> SignalStack {
+        self.stack
+    }
+
     /// get the pc from the user context
     pub fn get_pc(&self) -> usize {
         self.mcontext.get_pc()
     }
+
+    /// get the signal mask to be restored when the signal handler returns
+    pub fn get_mask(&self) -> usize {
+        self.sigmask as usize
+    }
+
+    /// save the F/D states of the current CPU in the `mcontext`
+    ///
+    /// `fs0` and `fs1` of the user are taken from the trap frame.
+    ///
+    /// # Safety
+    ///
+    /// It must be called in the context of the trap to be handled.
+    pub unsafe fn save_fp_state(&mut self, _area: usize, trap_frame: &TrapFrame) {
+        let fp_state = &mut *(self.mcontext.fpstate.as_mut_ptr() as *mut FpState);
+        fp_state.save();
+        fp_state.f[8] = trap_frame.fs[0] as u64;
+        fp_state.f[9] = trap_frame.fs[1] as u64;
+    }
+
+    /// restore the F/D states of the current CPU from the `mcontext`
+    ///
+    /// `fs0` and `fs1` are written to the trap frame, since they are restored
+    /// from there when returning to the user space. The states are saved in the
+    /// user context itself, so `_is_user_range` is not needed.
+    ///
+    /// # Safety
+    ///
+    /// It must be called in the context of the trap to be handled.
+    pub unsafe fn restore_fp_state(
+        &self,
+        trap_frame: &mut TrapFrame,
+        _is_user_range: impl FnOnce(usize, usize) -> bool,
+    ) {
+        let fp_state = &*(self.mcontext.fpstate.as_ptr() as *const FpState);
+        fp_state.restore();
+        trap_frame.fs[0] = fp_state.f[8] as usize;
+        trap_frame.fs[1] = fp_state.f[9] as usize;
+    }
 }
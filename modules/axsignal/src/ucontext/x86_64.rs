@@ -1,26 +1,42 @@
 //! 信号处理时保存的用户上下文。
 
-/// 处理信号时使用的栈
-///
-/// 详细信息见`https://man7.org/linux/man-pages/man2/sigaltstack.2.html`
+use axhal::arch::{fp_state_size as xstate_size, xsave_features, ExtendedState, TrapFrame};
+
+use super::SignalStack;
+
+/// `uc_flags` bit indicating that `fpstate` is in the XSAVE format
+const UC_FP_XSTATE: usize = 1;
+/// Magic number in `_fpx_sw_bytes` indicating the presence of extended states
+const FP_XSTATE_MAGIC1: u32 = 0x4650_5853;
+/// Magic number at the end of the extended states
+const FP_XSTATE_MAGIC2: u32 = 0x4650_5845;
+/// Offset of the software-available `_fpx_sw_bytes` in the FXSAVE region
+const FPX_SW_BYTES_OFFSET: usize = 464;
+/// Offset of `mxcsr` in the FXSAVE region
+const MXCSR_OFFSET: usize = 24;
+/// Offset of `mxcsr_mask` in the FXSAVE region
+const MXCSR_MASK_OFFSET: usize = 28;
+/// Offset of the XSAVE header in the XSAVE region
+const XSAVE_HEADER_OFFSET: usize = 512;
+/// Size of the XSAVE header
+const XSAVE_HEADER_SIZE: usize = 64;
+
+/// The `_fpx_sw_bytes` in the FXSAVE region, describing the extended states following it
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct SignalStack {
-    sp: usize,
-    flags: u32,
-    size: usize,
+#[derive(Clone, Copy, Default)]
+struct FpxSwBytes {
+    magic1: u32,
+    extended_size: u32,
+    xfeatures: u64,
+    xstate_size: u32,
+    padding: [u32; 7],
 }
 
-impl Default for SignalStack {
-    fn default() -> Self {
-        Self {
-            sp: 0,
-            // 代表SS_DISABLE，即不使用栈
-            flags: 2,
-            size: 0,
-        }
-    }
+/// get the size of the FP state area in the signal frame, including the trailing magic number
+pub fn fp_state_size() -> usize {
+    xstate_size() + core::mem::size_of::<u32>()
 }
+
 #[repr(C)]
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 /// The `mcontext` struct for the signal action
@@ -53,7 +69,7 @@ pub struct MContext {
     oldmask: usize,
     cr2: usize,
     // fpregs
-    // TODO
+    // pointer to the saved FXSAVE/XSAVE region, 0 if no FP state is saved
     fpstate: usize,
     // reserved
     _reserved1: [usize; 8],
@@ -85,20 +101,109 @@ pub struct SignalUserContext {
 }
 
 impl SignalUserContext {
-    /// init the user context by the pc and the mask
-    pub fn init(pc: usize, mask: usize) -> Self {
+    /// init the user context by the pc, the mask and the alternate signal stack
+    pub fn init(pc: usize, mask: usize, stack: SignalStack) -> Self {
         Self {
             flags: 0,
             link: 0,
-            stack: SignalStack::default(),
+            stack,
             mcontext: MContext::init_by_pc(pc),
             sigmask: mask as u64,
             _fpregs: [0; 64],
         }
     }
 
+    /// get the alternate signal stack saved in the user context
+    pub fn get_stack(&self) -> SignalStack {
+        self.stack
+    }
+
     /// get the pc from the user context
     pub fn get_pc(&self) -> usize {
         self.mcontext.get_pc()
     }
+
+    /// get the signal mask to be restored when the signal handler returns
+    pub fn get_mask(&self) -> usize {
+        self.sigmask as usize
+    }
+
+    /// save the FP states of the current CPU to `area` and point `fpstate` to it
+    ///
+    /// # Safety
+    ///
+    /// `area` must be 64-byte aligned and at least [`fp_state_size`] bytes long
+    pub unsafe fn save_fp_state(&mut self, area: usize, _trap_frame: &TrapFrame) {
+        let buf = area as *mut u8;
+        core::ptr::write_bytes(buf, 0, xstate_size());
+        axhal::arch::save_fp_state(buf);
+        let xfeatures = xsave_features();
+        if xfeatures != 0 {
+            let size = xstate_size();
+            let sw_bytes = FpxSwBytes {
+                magic1: FP_XSTATE_MAGIC1,
+                extended_size: fp_state_size() as u32,
+                xfeatures,
+                xstate_size: size as u32,
+                padding: [0; 7],
+            };
+            *(buf.add(FPX_SW_BYTES_OFFSET) as *mut FpxSwBytes) = sw_bytes;
+            *(buf.add(size) as *mut u32) = FP_XSTATE_MAGIC2;
+            self.flags |= UC_FP_XSTATE;
+        }
+        self.mcontext.fpstate = area;
+    }
+
+    /// restore the FP states of the current CPU from the area pointed to by `fpstate`
+    ///
+    /// `fpstate` is controlled by the user, so the area is restored only if
+    /// `is_user_range(start, size)` accepts it, and it is copied into a kernel buffer
+    /// before being checked, so that other threads cannot change it afterwards.
+    ///
+    /// The area is restored in the XSAVE format if `_fpx_sw_bytes` and the trailing
+    /// magic number are valid, otherwise only the FXSAVE region is restored. Reserved
+    /// bits that may have been modified by the user are cleared before restoring.
+    ///
+    /// # Safety
+    ///
+    /// It must be called in the address space of the user context.
+    pub unsafe fn restore_fp_state(
+        &self,
+        _trap_frame: &mut TrapFrame,
+        is_user_range: impl FnOnce(usize, usize) -> bool,
+    ) {
+        let area = self.mcontext.fpstate;
+        let size = xstate_size();
+        if area == 0
+            || area & 0x3f != 0
+            || size > core::mem::size_of::<ExtendedState>()
+            || area.checked_add(fp_state_size()).is_none()
+            || !is_user_range(area, fp_state_size())
+        {
+            return;
+        }
+        let mut state = core::mem::MaybeUninit::<ExtendedState>::zeroed();
+        let buf = state.as_mut_ptr() as *mut u8;
+        core::ptr::copy_nonoverlapping(area as *const u8, buf, size);
+        let magic2 = core::ptr::read_unaligned((area + size) as *const u32);
+        let mxcsr_mask = match *(buf.add(MXCSR_MASK_OFFSET) as *const u32) {
+            0 => 0xffbf,
+            mask => mask,
+        };
+        *(buf.add(MXCSR_OFFSET) as *mut u32) &= mxcsr_mask;
+        let sw_bytes = *(buf.add(FPX_SW_BYTES_OFFSET) as *const FpxSwBytes);
+        let mut xfeatures = 0;
+        if self.flags & UC_FP_XSTATE != 0
+            && sw_bytes.magic1 == FP_XSTATE_MAGIC1
+            && sw_bytes.xstate_size as usize == size
+            && sw_bytes.extended_size as usize == fp_state_size()
+            && magic2 == FP_XSTATE_MAGIC2
+        {
+            xfeatures = sw_bytes.xfeatures & xsave_features();
+            // XSTATE_BV must be a subset of XCR0, XCOMP_BV and the reserved bytes must be 0
+            *(buf.add(XSAVE_HEADER_OFFSET) as *mut u64) &= xfeatures;
+            core::ptr::write_bytes(buf.add(XSAVE_HEADER_OFFSET + 8), 0, XSAVE_HEADER_SIZE - 8);
+        }
+        axhal::arch::restore_fp_state(buf, xfeatures);
+    }
 }
@@ -5,6 +5,7 @@ use alloc::{string::String, sync::Arc};
 use axhal::KERNEL_PROCESS_ID;
 
 pub(crate) use crate::run_queue::{AxRunQueue, RUN_QUEUE};
+use crate::task::SchedStatus;
 
 #[doc(cfg(feature = "multitask"))]
 pub use crate::task::{CurrentTask, TaskId, TaskInner};
@@ -20,10 +21,16 @@ cfg_if::cfg_if! {
         pub(crate) type AxTask = scheduler::RRTask<TaskInner, MAX_TIME_SLICE>;
         pub(crate) type Scheduler = scheduler::RRScheduler<TaskInner, MAX_TIME_SLICE>;
     } else if #[cfg(feature = "sched_cfs")] {
+        const MAX_TIME_SLICE: usize = 0;
         pub(crate) type AxTask = scheduler::CFSTask<TaskInner>;
         pub(crate) type Scheduler = scheduler::CFScheduler<TaskInner>;
+    } else if #[cfg(feature = "sched_rt")] {
+        const MAX_TIME_SLICE: usize = 10;
+        pub(crate) type AxTask = scheduler::RTTask<TaskInner, MAX_TIME_SLICE>;
+        pub(crate) type Scheduler = scheduler::RTScheduler<TaskInner, MAX_TIME_SLICE>;
     } else {
         // If no scheduler features are set, use FIFO as the default.
+        const MAX_TIME_SLICE: usize = 0;
         pub(crate) type AxTask = scheduler::FifoTask<TaskInner>;
         pub(crate) type Scheduler = scheduler::FifoScheduler<TaskInner>;
     }
@@ -79,14 +86,44 @@ pub fn init_scheduler_secondary() {
     crate::run_queue::init_secondary();
 }
 
-/// Handles periodic timer ticks for the task manager.
+/// Handles timer interrupts for the task manager.
 ///
-/// For example, advance scheduler states, checks timed events, etc.
+/// For example, checks timed events, advances scheduler states when the
+/// scheduler tick is due, and programs the next timer interrupt of the
+/// current CPU. The scheduler tick is stopped while the CPU is idle.
 #[cfg(feature = "irq")]
 #[doc(cfg(feature = "irq"))]
 pub fn on_timer_tick() {
-    crate::timers::check_events();
-    RUN_QUEUE.lock().scheduler_timer_tick();
+    crate::timers::on_timer_irq();
+}
+
+/// Handles the rescheduling inter-processor interrupt.
+///
+/// Other CPUs send it after putting ready tasks into the run queue of the
+/// current CPU, so that an idle CPU starts running them immediately. Timed
+/// events of the migrated tasks may also require an earlier timer interrupt.
+#[cfg(feature = "irq")]
+#[doc(cfg(feature = "irq"))]
+pub fn on_ipi() {
+    RUN_QUEUE.lock().handle_resched_ipi();
+    crate::timers::sync_next_event();
+}
+
+/// Makes sure that a timer interrupt occurs on the current CPU at the given
+/// deadline, for timed events managed outside the task manager.
+///
+/// Deadlines that have already passed are ignored.
+#[cfg(feature = "irq")]
+#[doc(cfg(feature = "irq"))]
+pub fn request_timer_irq(deadline: axhal::time::TimeValue) {
+    crate::timers::request_timer_irq(deadline);
+}
+
+/// Dumps the timer interrupt states and the timed events of all CPUs, in the
+/// format of Linux's `/proc/timer_list`.
+#[cfg(all(feature = "irq", feature = "monolithic"))]
+pub fn timer_list_info() -> String {
+    crate::timers::timer_list_info()
 }
 
 /// Spawns a new task with the given parameters.
@@ -134,7 +171,58 @@ where
 ///
 /// [CFS]: https://en.wikipedia.org/wiki/Completely_Fair_Scheduler
 pub fn set_priority(prio: isize) -> bool {
-    RUN_QUEUE.lock().set_current_priority(prio)
+    set_task_priority(crate::current().as_task_ref(), prio)
+}
+
+/// Set the priority for the given task, which may be ready on any CPU.
+///
+/// The priority is the nice value for schedulers that support priorities,
+/// ranging from -20 to 19. A valid nice value is always recorded in the task
+/// (see [`TaskInner::nice`]), even if the scheduler ignores it.
+///
+/// Returns `true` if the priority is set successfully.
+pub fn set_task_priority(task: &AxTaskRef, prio: isize) -> bool {
+    if (-20..=19).contains(&prio) {
+        task.set_nice(prio);
+    }
+    crate::run_queue::set_task_priority(task, prio)
+}
+
+/// Sets the scheduling policy and the real-time priority of the given task.
+///
+/// The policy takes effect immediately if the underlying scheduler supports
+/// it (e.g. the [real-time scheduler][1]). Otherwise non-real-time policies
+/// are only recorded in the task, and real-time policies are left untouched.
+///
+/// Returns `true` if the scheduler accepts the policy.
+///
+/// [1]: scheduler::RTScheduler
+pub fn set_sched_status(task: &AxTaskRef, status: SchedStatus) -> bool {
+    let policy = isize::from(status.policy) as usize;
+    if crate::run_queue::set_task_policy(task, policy, status.priority) {
+        #[cfg(feature = "monolithic")]
+        task.set_sched_status(status);
+        return true;
+    }
+    #[cfg(feature = "monolithic")]
+    {
+        use crate::task::SchedPolicy;
+        if !matches!(
+            status.policy,
+            SchedPolicy::SCHED_FIFO | SchedPolicy::SCHED_RR
+        ) {
+            task.set_sched_status(status);
+        }
+    }
+    false
+}
+
+/// Returns the time slice of round-robin tasks, or zero if the underlying
+/// scheduler does not rotate tasks by time slices.
+pub fn rr_interval() -> core::time::Duration {
+    core::time::Duration::from_nanos(
+        (MAX_TIME_SLICE as u64) * axhal::time::NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64,
+    )
 }
 
 /// Current task gives up the CPU time voluntarily, and switches to another
@@ -20,10 +20,14 @@
 //!   the `multitask` and `preempt` features if it is enabled.
 //! - `sched_cfs`: Use the [Completely Fair Scheduler][3]. It also enables the
 //!   the `multitask` and `preempt` features if it is enabled.
+//! - `sched_rt`: Use the [real-time scheduler][4], where `SCHED_FIFO` and
+//!   `SCHED_RR` tasks always preempt the fair-scheduled tasks. It also enables
+//!   the `multitask` and `preempt` features if it is enabled.
 //!
 //! [1]: scheduler::FifoScheduler
 //! [2]: scheduler::RRScheduler
 //! [3]: scheduler::CFScheduler
+//! [4]: scheduler::RTScheduler
 
 #![cfg_attr(not(test), no_std)]
 #![feature(doc_cfg)]
@@ -43,8 +47,6 @@ cfg_if::cfg_if! {
         mod wait_queue;
         mod stat;
 
-        #[cfg(feature = "signal")]
-        pub use stat::SignalCaller;
         pub use task::{SchedPolicy, SchedStatus};
 
         #[cfg(feature = "irq")]
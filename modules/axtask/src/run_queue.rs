@@ -1,20 +1,29 @@
-use alloc::collections::VecDeque;
+use alloc::collections::{BTreeSet, VecDeque};
 use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
+use axconfig::SMP;
+use axhal::cpu::this_cpu_id;
 #[cfg(feature = "monolithic")]
 use axhal::KERNEL_PROCESS_ID;
+use kernel_guard::NoPreemptIrqSave;
 use lazy_init::LazyInit;
 use scheduler::BaseScheduler;
 use spinlock::SpinNoIrq;
 
 use crate::task::{CurrentTask, TaskState};
-use crate::{AxTaskRef, Scheduler, TaskInner, WaitQueue};
+use crate::{AxTask, AxTaskRef, Scheduler, TaskInner, WaitQueue};
 #[cfg(feature = "monolithic")]
 use crate_interface::call_interface;
 
-// TODO: per-CPU
-/// The running task-queue of the kernel.
-pub static RUN_QUEUE: LazyInit<SpinNoIrq<AxRunQueue>> = LazyInit::new();
+/// 周期性负载均衡的间隔，单位为时钟中断次数
+#[cfg(feature = "irq")]
+const BALANCE_INTERVAL_TICKS: usize = 4;
+
+/// The running task-queue of the current CPU.
+pub static RUN_QUEUE: CurrentRunQueue = CurrentRunQueue;
 
 // TODO: per-CPU
 /// The exited task-queue of the kernel.
@@ -26,9 +35,144 @@ static WAIT_FOR_EXIT: WaitQueue = WaitQueue::new();
 /// The idle task of the kernel.
 pub static IDLE_TASK: LazyInit<AxTaskRef> = LazyInit::new();
 
+#[allow(clippy::declare_interior_mutable_const)]
+const PER_CPU_RUN_QUEUE_INIT: PerCpuRunQueue = PerCpuRunQueue::new();
+
+/// 各个 CPU 的运行队列
+static RUN_QUEUES: [PerCpuRunQueue; SMP] = [PER_CPU_RUN_QUEUE_INIT; SMP];
+
+/// 单个 CPU 的运行队列及其负载信息
+///
+/// 运行队列的锁会跨越任务切换：切换前由原任务加锁，切换后由新任务解锁，
+/// 因此这里不使用带守卫的自旋锁，而是手动维护锁的状态
+struct PerCpuRunQueue {
+    locked: AtomicBool,
+    rq: LazyInit<UnsafeCell<AxRunQueue>>,
+    /// 其他 CPU 放入本 CPU 的就绪任务，在本 CPU 下一次调度或时钟中断时移入调度器
+    remote: SpinNoIrq<VecDeque<AxTaskRef>>,
+    /// 等待运行的就绪任务数，包括 `remote` 中的任务
+    nr_ready: AtomicUsize,
+    /// 是否正在运行非 idle 任务
+    busy: AtomicBool,
+    /// 是否已经完成调度初始化
+    online: AtomicBool,
+    /// 刚被切换掉的任务，切换完成后清除其 on_cpu 标志
+    prev_task: AtomicUsize,
+}
+
+unsafe impl Sync for PerCpuRunQueue {}
+
+impl PerCpuRunQueue {
+    const fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            rq: LazyInit::new(),
+            remote: SpinNoIrq::new(VecDeque::new()),
+            nr_ready: AtomicUsize::new(0),
+            busy: AtomicBool::new(false),
+            online: AtomicBool::new(false),
+            prev_task: AtomicUsize::new(0),
+        }
+    }
+
+    fn lock(&self) {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            while self.locked.load(Ordering::Relaxed) {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    /// # Safety
+    ///
+    /// 调用者必须持有该运行队列的锁
+    unsafe fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+
+    /// # Safety
+    ///
+    /// 调用者必须持有该运行队列的锁
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get_mut(&self) -> &mut AxRunQueue {
+        &mut *self.rq.get()
+    }
+
+    fn is_online(&self) -> bool {
+        self.online.load(Ordering::Acquire)
+    }
+
+    fn nr_ready(&self) -> usize {
+        self.nr_ready.load(Ordering::Acquire)
+    }
+}
+
+/// 当前 CPU 的运行队列
+pub struct CurrentRunQueue;
+
+impl CurrentRunQueue {
+    /// 关闭中断与抢占后锁住当前 CPU 的运行队列
+    pub fn lock(&self) -> AxRunQueueGuard {
+        let irq_guard = NoPreemptIrqSave::new();
+        RUN_QUEUES[this_cpu_id()].lock();
+        AxRunQueueGuard {
+            _irq_guard: irq_guard,
+        }
+    }
+
+    /// 强制释放当前 CPU 的运行队列的锁
+    ///
+    /// # Safety
+    ///
+    /// 仅用于新任务第一次运行时，释放切换到它之前加上的锁
+    pub unsafe fn force_unlock(&self) {
+        RUN_QUEUES[this_cpu_id()].unlock();
+    }
+}
+
+/// 运行队列的锁守卫
+///
+/// 持有守卫的任务被切换掉后可能在其他 CPU 上恢复运行，
+/// 因此每次访问与释放时都重新获取当前 CPU 的运行队列
+pub struct AxRunQueueGuard {
+    _irq_guard: NoPreemptIrqSave,
+}
+
+impl Deref for AxRunQueueGuard {
+    type Target = AxRunQueue;
+    fn deref(&self) -> &AxRunQueue {
+        unsafe { RUN_QUEUES[this_cpu_id()].get_mut() }
+    }
+}
+
+impl DerefMut for AxRunQueueGuard {
+    fn deref_mut(&mut self) -> &mut AxRunQueue {
+        unsafe { RUN_QUEUES[this_cpu_id()].get_mut() }
+    }
+}
+
+impl Drop for AxRunQueueGuard {
+    fn drop(&mut self) {
+        unsafe { RUN_QUEUES[this_cpu_id()].unlock() };
+    }
+}
+
 /// The struct to define the running task-queue of the kernel.
 pub struct AxRunQueue {
+    cpu_id: usize,
     scheduler: Scheduler,
+    #[cfg(feature = "irq")]
+    ticks: usize,
 }
 
 #[crate_interface::def_interface]
@@ -39,31 +183,29 @@ pub trait VforkSet {
 }
 
 impl AxRunQueue {
-    pub fn new() -> SpinNoIrq<Self> {
-        let gc_task = TaskInner::new(
-            gc_entry,
-            "gc".into(),
-            axconfig::TASK_STACK_SIZE,
-            #[cfg(feature = "monolithic")]
-            KERNEL_PROCESS_ID,
-            #[cfg(feature = "monolithic")]
-            0,
-            #[cfg(feature = "signal")]
-            false,
-        );
-        let mut scheduler = Scheduler::new();
-        scheduler.add_task(gc_task);
-        SpinNoIrq::new(Self { scheduler })
+    fn new(cpu_id: usize) -> Self {
+        Self {
+            cpu_id,
+            scheduler: Scheduler::new(),
+            #[cfg(feature = "irq")]
+            ticks: 0,
+        }
     }
 
     pub fn add_task(&mut self, task: AxTaskRef) {
         debug!("task spawn: {}", task.id_name());
         assert!(task.is_ready());
-        self.scheduler.add_task(task);
+        let cpu_id = select_cpu(&task, self.cpu_id);
+        self.enqueue_on(cpu_id, task);
     }
 
     #[cfg(feature = "irq")]
     pub fn scheduler_timer_tick(&mut self) {
+        self.fetch_remote_tasks();
+        self.ticks += 1;
+        if self.ticks % BALANCE_INTERVAL_TICKS == 0 {
+            self.balance();
+        }
         let curr = crate::current();
         if !curr.is_idle() && self.scheduler.task_tick(curr.as_task_ref()) {
             #[cfg(feature = "preempt")]
@@ -71,6 +213,23 @@ impl AxRunQueue {
         }
     }
 
+    /// 处理调度核间中断：取走其他 CPU 放入的就绪任务，
+    /// 本 CPU 空闲或者新任务应当抢占当前任务时，在中断返回前重新调度
+    #[cfg(feature = "irq")]
+    pub fn handle_resched_ipi(&mut self) {
+        let mut remote = RUN_QUEUES[self.cpu_id].remote.lock();
+        while let Some(task) = remote.pop_front() {
+            #[cfg(feature = "preempt")]
+            {
+                let curr = crate::current();
+                if curr.is_idle() || self.scheduler.should_preempt(&task, curr.as_task_ref()) {
+                    curr.set_preempt_pending(true);
+                }
+            }
+            self.scheduler.add_task(task);
+        }
+    }
+
     pub fn yield_current(&mut self) {
         let curr = crate::current();
         trace!("task yield: {}", curr.id_name());
@@ -78,11 +237,6 @@ impl AxRunQueue {
         self.resched(false);
     }
 
-    pub fn set_current_priority(&mut self, prio: isize) -> bool {
-        self.scheduler
-            .set_priority(crate::current().as_task_ref(), prio)
-    }
-
     #[cfg(feature = "preempt")]
     pub fn preempt_resched(&mut self) {
         let curr = crate::current();
@@ -130,16 +284,14 @@ impl AxRunQueue {
 
     #[cfg(feature = "monolithic")]
     /// 仅用于exec与exit时清除其他后台线程
+    ///
+    /// 就绪的任务可能位于其他 CPU 的运行队列中，因此这里只将其标记为已退出，
+    /// 由所在的 CPU 在选取下一个任务时丢弃。正在其他 CPU 上运行或处于阻塞态的任务不做处理
     pub fn remove_task(&mut self, task: &AxTaskRef) {
         debug!("task remove: {}", task.id_name());
-        // 当前任务不予清除
-        // assert!(!task.is_running());
-        assert!(!task.is_running());
         assert!(!task.is_idle());
-        if task.is_ready() {
-            task.set_state(TaskState::Exited);
+        if task.transition_state(TaskState::Ready, TaskState::Exited) {
             EXITED_TASKS.lock().push_back(task.clone());
-            self.scheduler.remove_task(task);
         }
     }
 
@@ -163,9 +315,10 @@ impl AxRunQueue {
 
     pub fn unblock_task(&mut self, task: AxTaskRef, resched: bool) {
         debug!("task unblock: {}", task.id_name());
-        if task.is_blocked() {
-            task.set_state(TaskState::Ready);
-            self.scheduler.add_task(task); // TODO: priority
+        // 多个 CPU 可能同时唤醒同一个任务，只有一个能将其置为就绪
+        if task.transition_state(TaskState::Blocked, TaskState::Ready) {
+            let cpu_id = select_cpu(&task, self.cpu_id);
+            self.enqueue_on(cpu_id, task); // TODO: priority
             if resched {
                 #[cfg(feature = "preempt")]
                 crate::current().set_preempt_pending(true);
@@ -190,6 +343,153 @@ impl AxRunQueue {
 }
 
 impl AxRunQueue {
+    /// 将就绪任务放入指定 CPU 的运行队列，其他 CPU 的任务先放入其 `remote` 队列
+    fn enqueue_on(&mut self, cpu_id: usize, task: AxTaskRef) {
+        #[cfg(feature = "irq")]
+        crate::timers::migrate_alarm(&task, cpu_id);
+        task.set_cpu_id(cpu_id);
+        let rq = &RUN_QUEUES[cpu_id];
+        rq.nr_ready.fetch_add(1, Ordering::AcqRel);
+        if cpu_id == self.cpu_id {
+            // 例如实时任务就绪时，立即抢占本 CPU 上优先级更低的任务
+            #[cfg(feature = "preempt")]
+            {
+                let curr = crate::current();
+                if !curr.is_idle() && self.scheduler.should_preempt(&task, curr.as_task_ref()) {
+                    curr.set_preempt_pending(true);
+                }
+            }
+            self.scheduler.add_task(task);
+        } else {
+            rq.remote.lock().push_back(task);
+            // 通知对方 CPU 取走任务，空闲的 CPU 可能已经停止了调度时钟
+            #[cfg(feature = "irq")]
+            axhal::irq::send_ipi(cpu_id);
+        }
+    }
+
+    /// 将从本 CPU 的调度器中取出的任务放回
+    fn requeue(&mut self, task: AxTaskRef, preempt: bool) {
+        RUN_QUEUES[self.cpu_id]
+            .nr_ready
+            .fetch_add(1, Ordering::AcqRel);
+        self.scheduler.put_prev_task(task, preempt);
+    }
+
+    fn dequeue(&mut self) -> Option<AxTaskRef> {
+        let task = self.scheduler.pick_next_task()?;
+        RUN_QUEUES[self.cpu_id]
+            .nr_ready
+            .fetch_sub(1, Ordering::AcqRel);
+        Some(task)
+    }
+
+    /// 将其他 CPU 放入的就绪任务移入本 CPU 的调度器
+    fn fetch_remote_tasks(&mut self) {
+        let mut remote = RUN_QUEUES[self.cpu_id].remote.lock();
+        while let Some(task) = remote.pop_front() {
+            self.scheduler.add_task(task);
+        }
+    }
+
+    /// 选出下一个在本 CPU 上运行的任务
+    ///
+    /// 已被移除的任务直接丢弃，不允许在本 CPU 上运行的任务转交给其他 CPU
+    fn pick_next_task(&mut self) -> Option<AxTaskRef> {
+        let mut skipped = BTreeSet::new();
+        while let Some(task) = self.dequeue() {
+            if !task.allowed_on(self.cpu_id) {
+                let cpu_id = select_cpu(&task, self.cpu_id);
+                if cpu_id != self.cpu_id {
+                    self.enqueue_on(cpu_id, task);
+                    continue;
+                }
+                // 没有其他可以运行它的在线 CPU，仍在本 CPU 上运行
+            }
+            // 刚在其他 CPU 上被切换掉的任务要等到其上下文保存完成后才能运行。
+            // 这里不原地等待，否则两个 CPU 互相等待对方切换掉的任务时会死锁
+            let skip = task.on_cpu() && !crate::current().ptr_eq(&task);
+            // 如果所属进程被 vfork 阻塞，暂不运行
+            #[cfg(feature = "monolithic")]
+            let skip = skip || task.is_vfork();
+            if skip {
+                let first_seen = skipped.insert(task.id().as_u64());
+                self.requeue(task, false);
+                if first_seen {
+                    continue;
+                }
+                // 队列中剩余的任务都暂时不能运行
+                return None;
+            }
+            if task.transition_state(TaskState::Ready, TaskState::Running) {
+                return Some(task);
+            }
+        }
+        None
+    }
+
+    /// 从就绪任务最多的其他 CPU 上迁移一个可以在本 CPU 上运行的任务
+    ///
+    /// 只有对方的就绪任务数不少于 `min_ready` 时才会迁移。
+    /// 为了避免两个 CPU 互相等待对方的锁，获取对方的锁失败时直接放弃本次迁移
+    fn steal_task(&mut self, min_ready: usize) -> bool {
+        let Some(victim) = (0..SMP)
+            .filter(|&cpu| cpu != self.cpu_id && RUN_QUEUES[cpu].is_online())
+            .max_by_key(|&cpu| RUN_QUEUES[cpu].nr_ready())
+        else {
+            return false;
+        };
+        let victim_rq = &RUN_QUEUES[victim];
+        if victim_rq.nr_ready() < min_ready || !victim_rq.try_lock() {
+            return false;
+        }
+        // Safety: 已经持有对方运行队列的锁
+        let other = unsafe { victim_rq.get_mut() };
+        other.fetch_remote_tasks();
+        let mut skipped = alloc::vec::Vec::new();
+        let mut stolen = None;
+        for _ in 0..victim_rq.nr_ready() {
+            let Some(task) = other.dequeue() else {
+                break;
+            };
+            if !task.is_ready() {
+                // 已被移除的任务
+                continue;
+            }
+            // 刚被切换掉的任务仍在对方 CPU 上保存上下文，不迁移
+            if task.allowed_on(self.cpu_id) && !task.on_cpu() {
+                stolen = Some(task);
+                break;
+            }
+            skipped.push(task);
+        }
+        for task in skipped {
+            other.requeue(task, false);
+        }
+        unsafe { victim_rq.unlock() };
+
+        match stolen {
+            Some(task) => {
+                debug!(
+                    "task migrate: {}, cpu {} -> {}",
+                    task.id_name(),
+                    victim,
+                    self.cpu_id
+                );
+                self.enqueue_on(self.cpu_id, task);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 周期性的负载均衡：其他 CPU 的就绪任务比本 CPU 多出两个及以上时迁移一个过来
+    #[cfg(feature = "irq")]
+    fn balance(&mut self) {
+        let nr_ready = RUN_QUEUES[self.cpu_id].nr_ready();
+        self.steal_task(nr_ready + 2);
+    }
+
     /// Common reschedule subroutine. If `preempt`, keep current task's time
     /// slice, otherwise reset it.
     fn resched(&mut self, preempt: bool) {
@@ -197,49 +497,31 @@ impl AxRunQueue {
         if prev.is_running() {
             prev.set_state(TaskState::Ready);
             if !prev.is_idle() {
-                self.scheduler.put_prev_task(prev.clone(), preempt);
+                if prev.allowed_on(self.cpu_id) {
+                    self.requeue(prev.clone(), preempt);
+                } else {
+                    // 亲和性被修改后不再允许在本 CPU 上运行，迁移到其他 CPU
+                    let cpu_id = select_cpu(prev.as_task_ref(), self.cpu_id);
+                    self.enqueue_on(cpu_id, prev.clone());
+                }
             }
         }
-        #[cfg(feature = "monolithic")]
-        {
-            use alloc::collections::BTreeSet;
-            use axhal::cpu::this_cpu_id;
-            let mut task_set = BTreeSet::new();
-            let next = loop {
-                let task = self.scheduler.pick_next_task();
-                if task.is_none() {
-                    break unsafe {
-                        // Safety: IRQs must be disabled at this time.
-                        IDLE_TASK.current_ref_raw().get_unchecked().clone()
-                    };
-                }
-                let task = task.unwrap();
-                // 原先队列有任务，但是全部不满足CPU适配集，则还是返回IDLE
-                if task_set.contains(&task.id().as_u64()) {
-                    break unsafe {
-                        // Safety: IRQs must be disabled at this time.
-                        IDLE_TASK.current_ref_raw().get_unchecked().clone()
-                    };
-                }
-                let mask = task.get_cpu_set();
-                let curr_cpu = this_cpu_id();
-                // 如果当前进程没有被 vfork 阻塞，弹出任务
-                if mask & (1 << curr_cpu) != 0 && !task.is_vfork() {
-                    break task;
+        self.fetch_remote_tasks();
+        let next = self
+            .pick_next_task()
+            .or_else(|| {
+                // 本 CPU 即将空闲，尝试从其他 CPU 上迁移任务
+                if self.steal_task(1) {
+                    self.pick_next_task()
+                } else {
+                    None
                 }
-                task_set.insert(task.id().as_u64());
-                self.scheduler.put_prev_task(task, false);
-            };
-            self.switch_to(prev, next);
-        }
-        #[cfg(not(feature = "monolithic"))]
-        {
-            let next = self.scheduler.pick_next_task().unwrap_or_else(|| unsafe {
+            })
+            .unwrap_or_else(|| unsafe {
                 // Safety: IRQs must be disabled at this time.
                 IDLE_TASK.current_ref_raw().get_unchecked().clone()
             });
-            self.switch_to(prev, next);
-        }
+        self.switch_to(prev, next);
     }
 
     fn switch_to(&mut self, prev_task: CurrentTask, next_task: AxTaskRef) {
@@ -254,11 +536,21 @@ impl AxRunQueue {
         if prev_task.ptr_eq(&next_task) {
             return;
         }
+        next_task.set_on_cpu(true);
+        next_task.set_cpu_id(self.cpu_id);
+        let rq = &RUN_QUEUES[self.cpu_id];
+        rq.busy.store(!next_task.is_idle(), Ordering::Release);
+        #[cfg(feature = "irq")]
+        if !next_task.is_idle() {
+            crate::timers::restart_tick();
+        }
         // 当任务进行切换时，更新两个任务的时间统计信息
         #[cfg(feature = "monolithic")]
         {
             next_task.time_stat_when_switch_to();
             prev_task.time_stat_when_switch_from();
+            // 被切换掉时仍处于就绪态，说明是被抢占或者主动 yield，记为被动切换
+            prev_task.record_context_switch(!prev_task.is_ready());
         }
         unsafe {
             let prev_ctx_ptr = prev_task.ctx_mut_ptr();
@@ -276,9 +568,79 @@ impl AxRunQueue {
                 }
             }
 
+            rq.prev_task.store(
+                Arc::as_ptr(prev_task.as_task_ref()) as usize,
+                Ordering::Release,
+            );
             CurrentTask::set_current(prev_task, next_task);
             (*prev_ctx_ptr).switch_to(&*next_ctx_ptr);
         }
+        // 此时可能已经在其他 CPU 上恢复运行，不能再访问 `self`
+        finish_task_switch();
+    }
+}
+
+/// 为就绪任务选择运行的 CPU
+///
+/// 在允许运行的在线 CPU 中选择负载最轻的一个，负载相同时依次优先选择任务上次所在的 CPU 与本 CPU。
+/// 本 CPU 正在运行的是发起调度的任务本身，不计入负载
+fn select_cpu(task: &AxTaskRef, this_cpu: usize) -> usize {
+    let prev_cpu = task.cpu_id();
+    (0..SMP)
+        .filter(|&cpu| RUN_QUEUES[cpu].is_online() && task.allowed_on(cpu))
+        .min_by_key(|&cpu| {
+            let rq = &RUN_QUEUES[cpu];
+            let busy = cpu != this_cpu && rq.busy.load(Ordering::Acquire);
+            let preference = if cpu == prev_cpu {
+                0
+            } else if cpu == this_cpu {
+                1
+            } else {
+                2
+            };
+            (rq.nr_ready() + busy as usize, preference)
+        })
+        .unwrap_or(this_cpu)
+}
+
+/// 锁住任务所在的运行队列并对其进行操作
+///
+/// 就绪的任务可能位于其他 CPU 的运行队列中，不能直接使用当前 CPU 的运行队列
+fn with_task_run_queue<R>(task: &AxTaskRef, f: impl FnOnce(&mut AxRunQueue) -> R) -> R {
+    let _guard = NoPreemptIrqSave::new();
+    loop {
+        let cpu_id = task.cpu_id();
+        let rq = &RUN_QUEUES[cpu_id];
+        rq.lock();
+        // 加锁期间任务可能被迁移到了其他 CPU
+        if task.cpu_id() == cpu_id {
+            // Safety: 已经持有该运行队列的锁
+            let ret = f(unsafe { rq.get_mut() });
+            unsafe { rq.unlock() };
+            return ret;
+        }
+        unsafe { rq.unlock() };
+    }
+}
+
+/// 修改任务的调度策略与实时优先级，返回调度器是否支持该策略
+pub(crate) fn set_task_policy(task: &AxTaskRef, policy: usize, priority: usize) -> bool {
+    with_task_run_queue(task, |rq| rq.scheduler.set_policy(task, policy, priority))
+}
+
+/// 修改任务的优先级，返回调度器是否支持该优先级
+pub(crate) fn set_task_priority(task: &AxTaskRef, prio: isize) -> bool {
+    with_task_run_queue(task, |rq| rq.scheduler.set_priority(task, prio))
+}
+
+/// 完成任务切换：清除被切换掉的任务的 on_cpu 标志，此后它才可以在其他 CPU 上运行
+pub(crate) fn finish_task_switch() {
+    let prev = RUN_QUEUES[this_cpu_id()]
+        .prev_task
+        .swap(0, Ordering::AcqRel) as *const AxTask;
+    if !prev.is_null() {
+        // Safety: 任务在 on_cpu 标志被清除之前不会被回收
+        unsafe { (*prev).set_on_cpu(false) };
     }
 }
 
@@ -290,7 +652,7 @@ fn gc_entry() {
             // Do not do the slow drops in the critical section.
             let task = EXITED_TASKS.lock().pop_front();
             if let Some(task) = task {
-                if Arc::strong_count(&task) == 1 {
+                if Arc::strong_count(&task) == 1 && !task.on_cpu() {
                     // If I'm the last holder of the task, drop it immediately.
                     drop(task);
                 } else {
@@ -304,8 +666,23 @@ fn gc_entry() {
     }
 }
 
+/// 将当前 CPU 标记为在线，此后其他 CPU 可以将任务放入它的运行队列
+fn set_online(cpu_id: usize, init_task: &AxTaskRef) {
+    init_task.set_on_cpu(true);
+    init_task.set_cpu_id(cpu_id);
+    RUN_QUEUES[cpu_id]
+        .busy
+        .store(!init_task.is_idle(), Ordering::Release);
+    RUN_QUEUES[cpu_id].online.store(true, Ordering::Release);
+}
+
 pub(crate) fn init() {
     const IDLE_TASK_STACK_SIZE: usize = 4096;
+    let cpu_id = this_cpu_id();
+    for (id, rq) in RUN_QUEUES.iter().enumerate() {
+        rq.rq.init_by(UnsafeCell::new(AxRunQueue::new(id)));
+    }
+
     let idle_task = TaskInner::new(
         || crate::run_idle(),
         "idle".into(), // FIXME: name 现已被用作 prctl 使用的程序名，应另选方式判断 idle 进程
@@ -321,14 +698,27 @@ pub(crate) fn init() {
 
     let main_task = TaskInner::new_init("main".into());
     main_task.set_state(TaskState::Running);
+    set_online(cpu_id, &main_task);
 
-    RUN_QUEUE.init_by(AxRunQueue::new());
+    let gc_task = TaskInner::new(
+        gc_entry,
+        "gc".into(),
+        axconfig::TASK_STACK_SIZE,
+        #[cfg(feature = "monolithic")]
+        KERNEL_PROCESS_ID,
+        #[cfg(feature = "monolithic")]
+        0,
+        #[cfg(feature = "signal")]
+        false,
+    );
     unsafe { CurrentTask::init_current(main_task) }
+    RUN_QUEUE.lock().add_task(gc_task);
 }
 
 pub(crate) fn init_secondary() {
     let idle_task = TaskInner::new_init("idle".into()); // FIXME: name 现已被用作 prctl 使用的程序名，应另选方式判断 idle 进程
     idle_task.set_state(TaskState::Running);
     IDLE_TASK.with_current(|i| i.init_by(idle_task.clone()));
+    set_online(this_cpu_id(), &idle_task);
     unsafe { CurrentTask::init_current(idle_task) }
 }
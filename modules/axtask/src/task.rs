@@ -4,14 +4,17 @@ use axconfig::SMP;
 
 #[cfg(feature = "monolithic")]
 use axhal::KERNEL_PROCESS_ID;
+#[cfg(feature = "monolithic")]
+use lazy_init::LazyInit;
 
+#[cfg(feature = "monolithic")]
+use core::any::Any;
 use core::ops::Deref;
-use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, Ordering};
+use core::sync::atomic::{
+    AtomicBool, AtomicI32, AtomicIsize, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+};
 use core::{alloc::Layout, cell::UnsafeCell, fmt, ptr::NonNull};
 
-#[cfg(feature = "preempt")]
-use core::sync::atomic::AtomicUsize;
-
 #[cfg(feature = "tls")]
 use axhal::tls::TlsArea;
 
@@ -107,6 +110,13 @@ pub struct TaskInner {
     entry: Option<*mut dyn FnOnce()>,
     state: AtomicU8,
 
+    /// 是否正在某个 CPU 上运行，包括正在被切换掉、尚未保存完上下文的情况
+    on_cpu: AtomicBool,
+    /// 最近一次运行或所在运行队列的 CPU
+    cpu_id: AtomicUsize,
+    /// nice 值，范围为 -20 到 19，决定任务在公平调度类中的权重
+    nice: AtomicIsize,
+
     in_wait_queue: AtomicBool,
     #[cfg(feature = "irq")]
     in_timer_list: AtomicBool,
@@ -161,6 +171,14 @@ pub struct TaskInner {
     #[cfg(feature = "monolithic")]
     /// The scheduler status of the task, which defines the scheduling policy and priority
     pub sched_status: UnsafeCell<SchedStatus>,
+
+    #[cfg(feature = "monolithic")]
+    /// 所属进程的文件系统上下文，由 axprocess 在创建任务时设置，解析路径时不需要再查找进程表
+    fs_context: LazyInit<Arc<dyn Any + Send + Sync>>,
+
+    #[cfg(feature = "monolithic")]
+    /// 所属进程 seccomp 模式的缓存，为 0 时系统调用入口不需要查找进程表
+    seccomp_mode: AtomicU8,
 }
 static ID_COUNTER: AtomicU64 = AtomicU64::new(1);
 impl TaskId {
@@ -291,7 +309,7 @@ impl TaskInner {
     pub fn time_stat_from_user_to_kernel(&self) {
         let time = self.time.get();
         unsafe {
-            (*time).switch_into_kernel_mode(self.id.as_u64() as isize);
+            (*time).switch_into_kernel_mode();
         }
     }
 
@@ -300,7 +318,7 @@ impl TaskInner {
     pub fn time_stat_from_kernel_to_user(&self) {
         let time = self.time.get();
         unsafe {
-            (*time).switch_into_user_mode(self.id.as_u64() as isize);
+            (*time).switch_into_user_mode();
         }
     }
 
@@ -309,7 +327,7 @@ impl TaskInner {
     pub fn time_stat_when_switch_from(&self) {
         let time = self.time.get();
         unsafe {
-            (*time).swtich_from_old_task(self.id.as_u64() as isize);
+            (*time).swtich_from_old_task();
         }
     }
 
@@ -318,7 +336,7 @@ impl TaskInner {
     pub fn time_stat_when_switch_to(&self) {
         let time = self.time.get();
         unsafe {
-            (*time).switch_to_new_task(self.id.as_u64() as isize);
+            (*time).switch_to_new_task();
         }
     }
 
@@ -331,26 +349,29 @@ impl TaskInner {
     }
 
     #[inline]
-    /// 输出计时器信息
-    /// (计时器周期，当前计时器剩余时间)
-    /// 单位为us
-    pub fn timer_output(&self) -> (usize, usize) {
+    /// 记录一次上下文切换，`voluntary` 表示任务是否主动让出 CPU
+    pub fn record_context_switch(&self, voluntary: bool) {
         let time = self.time.get();
-        unsafe { (*time).output_timer_as_us() }
+        unsafe {
+            (*time).record_context_switch(voluntary);
+        }
     }
 
     #[inline]
-    /// 设置计时器信息
-    ///
-    /// 若type不为None则返回成功
-    pub fn set_timer(
-        &self,
-        timer_interval_ns: usize,
-        timer_remained_ns: usize,
-        timer_type: usize,
-    ) -> bool {
+    /// 记录一次缺页，`major` 表示是否需要从文件中读入数据
+    pub fn record_page_fault(&self, major: bool) {
+        let time = self.time.get();
+        unsafe {
+            (*time).record_page_fault(major);
+        }
+    }
+
+    #[inline]
+    /// 输出上下文切换与缺页次数
+    /// (主动切换次数，被动切换次数，次缺页次数，主缺页次数)
+    pub fn rusage_counters_output(&self) -> (usize, usize, usize, usize) {
         let time = self.time.get();
-        unsafe { (*time).set_timer(timer_interval_ns, timer_remained_ns, timer_type) }
+        unsafe { (*time).output_counters() }
     }
 
     #[inline]
@@ -412,13 +433,16 @@ impl TaskInner {
         }
     }
     /// 设置CPU set，其中set_size为bytes长度
+    ///
+    /// 超出 CPU 数目的位被忽略。若任务正在运行且不再允许在当前 CPU 上运行，
+    /// 会在下一次调度时迁移到其他 CPU
     pub fn set_cpu_set(&self, mask: usize, set_size: usize) {
-        let len = if set_size * 4 > SMP {
-            SMP
+        let len = set_size.saturating_mul(8).min(SMP);
+        let now_mask = if len >= usize::BITS as usize {
+            mask
         } else {
-            set_size * 4
+            mask & ((1 << len) - 1)
         };
-        let now_mask = mask & 1 << ((len) - 1);
         self.cpu_set.store(now_mask as u64, Ordering::Release)
     }
 
@@ -441,6 +465,30 @@ impl TaskInner {
         unsafe { *status }
     }
 
+    #[cfg(feature = "monolithic")]
+    /// 设置所属进程的文件系统上下文，只能在创建任务时设置一次
+    pub fn set_fs_context(&self, context: Arc<dyn Any + Send + Sync>) {
+        self.fs_context.init_by(context);
+    }
+
+    #[cfg(feature = "monolithic")]
+    /// 获取所属进程的文件系统上下文，内核任务没有设置时返回 `None`
+    pub fn fs_context(&self) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.fs_context.try_get().cloned()
+    }
+
+    #[cfg(feature = "monolithic")]
+    /// 设置所属进程 seccomp 模式的缓存，由 axprocess 在进程切换模式与创建任务时维护
+    pub fn set_seccomp_mode(&self, mode: u8) {
+        self.seccomp_mode.store(mode, Ordering::Release);
+    }
+
+    #[cfg(feature = "monolithic")]
+    /// 获取所属进程 seccomp 模式的缓存
+    pub fn seccomp_mode(&self) -> u8 {
+        self.seccomp_mode.load(Ordering::Acquire)
+    }
+
     /// get the task context for task switch
     pub fn get_ctx(&self) -> &TaskContext {
         unsafe { self.ctx.get().as_ref().unwrap() }
@@ -481,6 +529,9 @@ impl TaskInner {
             is_init: false,
             entry: None,
             state: AtomicU8::new(TaskState::Ready as u8),
+            on_cpu: AtomicBool::new(false),
+            cpu_id: AtomicUsize::new(0),
+            nice: AtomicIsize::new(0),
             in_wait_queue: AtomicBool::new(false),
             #[cfg(feature = "irq")]
             in_timer_list: AtomicBool::new(false),
@@ -522,12 +573,18 @@ impl TaskInner {
 
             #[cfg(feature = "monolithic")]
             sched_status: UnsafeCell::new(SchedStatus {
-                policy: SchedPolicy::SCHED_FIFO,
-                priority: 1,
+                policy: SchedPolicy::SCHED_OTHER,
+                priority: 0,
             }),
 
             #[cfg(feature = "signal")]
             send_sigchld_when_exit: false,
+
+            #[cfg(feature = "monolithic")]
+            fs_context: LazyInit::new(),
+
+            #[cfg(feature = "monolithic")]
+            seccomp_mode: AtomicU8::new(0),
         }
     }
 
@@ -611,6 +668,58 @@ impl TaskInner {
         self.state.store(state as u8, Ordering::Release)
     }
 
+    #[inline]
+    /// 仅当任务处于 `from` 状态时将其设置为 `to` 状态，返回是否设置成功
+    pub(crate) fn transition_state(&self, from: TaskState, to: TaskState) -> bool {
+        self.state
+            .compare_exchange(from as u8, to as u8, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    #[inline]
+    pub(crate) fn on_cpu(&self) -> bool {
+        self.on_cpu.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_on_cpu(&self, on_cpu: bool) {
+        self.on_cpu.store(on_cpu, Ordering::Release);
+    }
+
+    #[inline]
+    /// 任务最近一次运行或所在运行队列的 CPU
+    pub fn cpu_id(&self) -> usize {
+        self.cpu_id.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_cpu_id(&self, cpu_id: usize) {
+        self.cpu_id.store(cpu_id, Ordering::Release);
+    }
+
+    #[inline]
+    /// 任务的 nice 值
+    pub fn nice(&self) -> isize {
+        self.nice.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_nice(&self, nice: isize) {
+        self.nice.store(nice, Ordering::Release);
+    }
+
+    #[cfg(feature = "monolithic")]
+    /// 任务是否允许在指定的 CPU 上运行
+    pub fn allowed_on(&self, cpu_id: usize) -> bool {
+        self.get_cpu_set() & (1 << cpu_id) != 0
+    }
+
+    #[cfg(not(feature = "monolithic"))]
+    /// 任务是否允许在指定的 CPU 上运行
+    pub fn allowed_on(&self, _cpu_id: usize) -> bool {
+        true
+    }
+
     #[inline]
     pub(crate) fn is_running(&self) -> bool {
         matches!(self.state(), TaskState::Running)
@@ -621,18 +730,14 @@ impl TaskInner {
         matches!(self.state(), TaskState::Ready)
     }
 
-    #[inline]
-    pub(crate) fn is_blocked(&self) -> bool {
-        matches!(self.state(), TaskState::Blocked)
-    }
-
     #[inline]
     pub(crate) const fn is_init(&self) -> bool {
         self.is_init
     }
 
     #[inline]
-    pub(crate) const fn is_idle(&self) -> bool {
+    /// 是否为空闲任务
+    pub const fn is_idle(&self) -> bool {
         self.is_idle
     }
 
@@ -809,6 +914,7 @@ impl Deref for CurrentTask {
 }
 
 extern "C" fn task_entry() -> ! {
+    crate::run_queue::finish_task_switch();
     // release the lock that was implicitly held across the reschedule
     unsafe { crate::RUN_QUEUE.force_unlock() };
     #[cfg(feature = "irq")]
@@ -1,13 +1,73 @@
 use alloc::sync::Arc;
-use axhal::time::current_time;
+#[cfg(feature = "monolithic")]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "monolithic")]
+use core::fmt::Write;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use axconfig::SMP;
+use axhal::cpu::this_cpu_id;
+use axhal::time::{current_time, current_time_nanos, NANOS_PER_SEC};
+use kernel_guard::NoPreemptIrqSave;
 use lazy_init::LazyInit;
 use spinlock::SpinNoIrq;
 use timer_list::{TimeValue, TimerEvent, TimerList};
 
 use crate::{AxTaskRef, RUN_QUEUE};
 
-// TODO: per-CPU
-static TIMER_LIST: LazyInit<SpinNoIrq<TimerList<TaskWakeupEvent>>> = LazyInit::new();
+/// 调度时钟的周期
+const TICK_NANOS: u64 = NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64;
+
+/// 调度时钟停止后两次时钟中断之间的最长间隔，避免超出定时器计数器的范围
+const MAX_IDLE_NANOS: u64 = NANOS_PER_SEC;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const PER_CPU_TIMERS_INIT: PerCpuTimers = PerCpuTimers::new();
+
+/// 各个 CPU 的定时事件与时钟中断状态
+static CPU_TIMERS: [PerCpuTimers; SMP] = [PER_CPU_TIMERS_INIT; SMP];
+
+/// 单个 CPU 的定时事件与时钟中断状态
+///
+/// 定时唤醒事件保存在任务所在 CPU 的队列中，到期时由该 CPU 处理，任务迁移时随之迁移。
+/// 因此空闲的 CPU 不会因为其他 CPU 上的定时事件而被唤醒
+struct PerCpuTimers {
+    events: LazyInit<SpinNoIrq<TimerList<TaskWakeupEvent>>>,
+    /// 下一次调度时钟的时刻，为 0 表示调度时钟已停止
+    next_tick_ns: AtomicU64,
+    /// 已经设置的下一次时钟中断的时刻，为 `u64::MAX` 表示尚未设置
+    next_event_ns: AtomicU64,
+}
+
+impl PerCpuTimers {
+    const fn new() -> Self {
+        Self {
+            events: LazyInit::new(),
+            next_tick_ns: AtomicU64::new(0),
+            next_event_ns: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    /// 若 `deadline` 早于已经设置的时钟中断，则提前时钟中断
+    ///
+    /// 只能在本 CPU 上关闭中断后调用
+    fn set_next_event_before(&self, deadline: u64) {
+        if deadline < self.next_event_ns.load(Ordering::Relaxed) {
+            self.set_next_event(deadline);
+        }
+    }
+
+    /// 设置下一次时钟中断，只能在本 CPU 上关闭中断后调用
+    fn set_next_event(&self, deadline: u64) {
+        self.next_event_ns.store(deadline, Ordering::Relaxed);
+        axhal::time::set_oneshot_timer(deadline);
+    }
+}
+
+/// 本 CPU 的定时事件与时钟中断状态，调用时需要关闭中断或抢占
+fn this_cpu_timers() -> &'static PerCpuTimers {
+    &CPU_TIMERS[this_cpu_id()]
+}
 
 struct TaskWakeupEvent(AxTaskRef);
 
@@ -19,22 +79,67 @@ impl TimerEvent for TaskWakeupEvent {
     }
 }
 
+/// 为当前任务设置定时唤醒事件，事件保存在本 CPU 的队列中
 pub fn set_alarm_wakeup(deadline: TimeValue, task: AxTaskRef) {
-    let mut timers = TIMER_LIST.lock();
+    let _guard = NoPreemptIrqSave::new();
+    let timers = this_cpu_timers();
+    let mut events = timers.events.lock();
     task.set_in_timer_list(true);
-    timers.set(deadline, TaskWakeupEvent(task));
+    events.set(deadline, TaskWakeupEvent(task));
+    timers.set_next_event_before(deadline.as_nanos() as u64);
 }
 
+/// 取消当前任务的定时唤醒事件
 pub fn cancel_alarm(task: &AxTaskRef) {
-    let mut timers = TIMER_LIST.lock();
+    let _guard = NoPreemptIrqSave::new();
+    let mut events = CPU_TIMERS[task.cpu_id()].events.lock();
     task.set_in_timer_list(false);
-    timers.cancel(|t| Arc::ptr_eq(&t.0, task));
+    events.cancel(|t| Arc::ptr_eq(&t.0, task));
+}
+
+/// 任务被放入其他 CPU 的运行队列时，将其定时唤醒事件迁移到目标 CPU 的队列中
+///
+/// 调用时任务所在的 CPU 尚未更新。迁移到其他 CPU 的事件由目标 CPU 在处理调度核间中断时重新设置时钟中断
+pub fn migrate_alarm(task: &AxTaskRef, to: usize) {
+    let from = task.cpu_id();
+    if from == to || !task.in_timer_list() {
+        return;
+    }
+    let moved = CPU_TIMERS[from]
+        .events
+        .lock()
+        .remove(|t| Arc::ptr_eq(&t.0, task));
+    if moved.is_empty() {
+        // 已经到期，正在被原来的 CPU 处理
+        return;
+    }
+    let timers = &CPU_TIMERS[to];
+    let mut events = timers.events.lock();
+    for (deadline, event) in moved {
+        events.set(deadline, event);
+    }
+    if to == this_cpu_id() {
+        if let Some(deadline) = events.next_deadline() {
+            timers.set_next_event_before(deadline.as_nanos() as u64);
+        }
+    }
 }
 
+/// 其他 CPU 迁移过来的定时事件可能早于本 CPU 已经设置的时钟中断，此时提前时钟中断
+pub fn sync_next_event() {
+    let timers = this_cpu_timers();
+    let next = timers.events.lock().next_deadline();
+    if let Some(deadline) = next {
+        timers.set_next_event_before(deadline.as_nanos() as u64);
+    }
+}
+
+/// 处理本 CPU 上到期的定时事件
 pub fn check_events() {
+    let timers = this_cpu_timers();
     loop {
         let now = current_time();
-        let event = TIMER_LIST.lock().expire_one(now);
+        let event = timers.events.lock().expire_one(now);
         if let Some((_deadline, event)) = event {
             event.callback(now);
         } else {
@@ -43,6 +148,112 @@ pub fn check_events() {
     }
 }
 
+/// 处理本 CPU 的时钟中断
+///
+/// 先唤醒到期的任务，调度时钟到期时再推进调度器的状态，最后按照最近的定时事件设置下一次时钟中断。
+/// 开启抢占时，空闲的 CPU 停止调度时钟，只在定时事件到期时产生时钟中断；
+/// 否则空闲任务无法在中断返回时被抢占，仍需依靠调度时钟取走新的就绪任务
+pub fn on_timer_irq() {
+    check_events();
+    let timers = this_cpu_timers();
+    let now = current_time_nanos();
+    let mut next_tick = timers.next_tick_ns.load(Ordering::Relaxed);
+    if next_tick != 0 && now >= next_tick {
+        RUN_QUEUE.lock().scheduler_timer_tick();
+        next_tick += TICK_NANOS;
+        if next_tick <= now {
+            next_tick = now + TICK_NANOS;
+        }
+    }
+    if cfg!(feature = "preempt") && crate::current().is_idle() {
+        next_tick = 0;
+    } else if next_tick == 0 {
+        next_tick = now + TICK_NANOS;
+    }
+    timers.next_tick_ns.store(next_tick, Ordering::Relaxed);
+
+    let mut deadline = now + MAX_IDLE_NANOS;
+    if next_tick != 0 {
+        deadline = deadline.min(next_tick);
+    }
+    if let Some(next) = timers.events.lock().next_deadline() {
+        deadline = deadline.min(next.as_nanos() as u64);
+    }
+    timers.set_next_event(deadline);
+}
+
+/// 本 CPU 开始运行任务时，重新启动已经停止的调度时钟
+///
+/// 调用者需要持有运行队列的锁
+pub fn restart_tick() {
+    let timers = this_cpu_timers();
+    if timers.next_tick_ns.load(Ordering::Relaxed) == 0 {
+        let deadline = current_time_nanos() + TICK_NANOS;
+        timers.next_tick_ns.store(deadline, Ordering::Relaxed);
+        timers.set_next_event_before(deadline);
+    }
+}
+
+/// 保证本 CPU 在 `deadline` 时产生一次时钟中断
+///
+/// 已经过去的时刻会被忽略：此时 CPU 正在运行任务，到期的事件由下一次调度时钟处理
+pub fn request_timer_irq(deadline: TimeValue) {
+    let _guard = NoPreemptIrqSave::new();
+    let deadline = deadline.as_nanos() as u64;
+    if deadline > current_time_nanos() {
+        this_cpu_timers().set_next_event_before(deadline);
+    }
+}
+
+/// 输出所有 CPU 的时钟中断状态与定时事件，格式参考 Linux 的 /proc/timer_list
+#[cfg(feature = "monolithic")]
+pub(crate) fn timer_list_info() -> String {
+    let mut info = String::new();
+    let _ = writeln!(info, "Timer List Version: v0.9");
+    let _ = writeln!(info, "now at {} nsecs", current_time_nanos());
+    for (cpu_id, timers) in CPU_TIMERS.iter().enumerate() {
+        if !timers.events.is_init() {
+            continue;
+        }
+        let _ = writeln!(info, "\ncpu: {}", cpu_id);
+        match timers.next_tick_ns.load(Ordering::Relaxed) {
+            0 => {
+                let _ = writeln!(info, " tick_stopped     : 1");
+            }
+            next_tick => {
+                let _ = writeln!(info, " tick_stopped     : 0");
+                let _ = writeln!(info, " next_tick        : {} nsecs", next_tick);
+            }
+        }
+        match timers.next_event_ns.load(Ordering::Relaxed) {
+            u64::MAX => {}
+            next_event => {
+                let _ = writeln!(info, " next_event       : {} nsecs", next_event);
+            }
+        }
+        let mut events: Vec<(TimeValue, String)> = timers
+            .events
+            .lock()
+            .iter()
+            .map(|(deadline, event)| (deadline, event.0.id_name()))
+            .collect();
+        events.sort_by_key(|(deadline, _)| *deadline);
+        let _ = writeln!(info, " active timers:");
+        for (i, (deadline, name)) in events.iter().enumerate() {
+            let _ = writeln!(
+                info,
+                " #{}: <wakeup>, {}, expires at {} nsecs",
+                i,
+                name,
+                deadline.as_nanos()
+            );
+        }
+    }
+    info
+}
+
 pub fn init() {
-    TIMER_LIST.init_by(SpinNoIrq::new(TimerList::new()));
+    for timers in CPU_TIMERS.iter() {
+        timers.events.init_by(SpinNoIrq::new(TimerList::new()));
+    }
 }
@@ -76,24 +76,88 @@ impl WaitQueue {
     /// `condition` becomes true.
     ///
     /// Note that even other tasks notify this task, it will not wake up until
-    /// the condition becomes true.
+    /// the condition becomes true. The condition is checked with the wait queue
+    /// locked, so a task that changes it and then notifies this queue from
+    /// another CPU either makes the check succeed or finds this task queued.
     pub fn wait_until<F>(&self, condition: F)
     where
         F: Fn() -> bool,
     {
         loop {
             let mut rq = RUN_QUEUE.lock();
+            let mut wq = self.queue.lock();
             if condition() {
                 break;
             }
-            rq.block_current(|task| {
+            rq.block_current(move |task| {
                 task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
+                wq.push_back(task);
             });
         }
         self.cancel_events(crate::current());
     }
 
+    /// Blocks the current task and put it into the wait queue if the given
+    /// `condition` is true, until other tasks notify it or unblock it directly.
+    ///
+    /// The `condition` is checked with the wait queue locked and the task is
+    /// queued before the lock is released, so a notifier that changes the
+    /// condition before calling `notify_*()` either makes the check fail or
+    /// finds the task in the queue. Returns whether the task was blocked.
+    pub fn wait_if<F>(&self, condition: F) -> bool
+    where
+        F: FnOnce() -> bool,
+    {
+        let mut rq = RUN_QUEUE.lock();
+        let mut wq = self.queue.lock();
+        if !condition() {
+            return false;
+        }
+        rq.block_current(move |task| {
+            task.set_in_wait_queue(true);
+            wq.push_back(task);
+        });
+        drop(rq);
+        self.cancel_events(crate::current());
+        true
+    }
+
+    /// Blocks the current task and put it into the wait queue if the given
+    /// `condition` is true, until other tasks notify it or unblock it directly,
+    /// or the given duration has elapsed.
+    ///
+    /// Like [`WaitQueue::wait_if`], returns whether the task was blocked.
+    #[cfg(feature = "irq")]
+    pub fn wait_timeout_if<F>(&self, dur: core::time::Duration, condition: F) -> bool
+    where
+        F: FnOnce() -> bool,
+    {
+        let curr = crate::current();
+        let deadline = axhal::time::current_time() + dur;
+        debug!(
+            "task wait_timeout_if: {}, deadline={:?}",
+            curr.id_name(),
+            deadline
+        );
+        crate::timers::set_alarm_wakeup(deadline, curr.clone());
+
+        let mut rq = RUN_QUEUE.lock();
+        let mut wq = self.queue.lock();
+        // the alarm may have been triggered before the run queue is locked
+        let blocked = axhal::time::current_time() < deadline && condition();
+        if blocked {
+            rq.block_current(move |task| {
+                task.set_in_wait_queue(true);
+                wq.push_back(task);
+            });
+        } else {
+            drop(wq);
+        }
+        drop(rq);
+        self.cancel_events(curr);
+        blocked
+    }
+
     /// Blocks the current task and put it into the wait queue, until other tasks
     /// notify it, or the given duration has elapsed.
     #[cfg(feature = "irq")]
@@ -138,13 +202,14 @@ impl WaitQueue {
         let mut timeout = true;
         while axhal::time::current_time() < deadline {
             let mut rq = RUN_QUEUE.lock();
+            let mut wq = self.queue.lock();
             if condition() {
                 timeout = false;
                 break;
             }
-            rq.block_current(|task| {
+            rq.block_current(move |task| {
                 task.set_in_wait_queue(true);
-                self.queue.lock().push_back(task);
+                wq.push_back(task);
             });
         }
         self.cancel_events(curr);
@@ -2,6 +2,7 @@
 #include <signal.h>
 #include <stddef.h>
 #include <stdio.h>
+#include <unistd.h>
 
 int sigaction_helper(int signum, const struct sigaction *act, struct sigaction *oldact,
                      size_t sigsetsize)
@@ -40,6 +41,31 @@ int kill(pid_t __pid, int __sig)
     return 0;
 }
 
+// There is no signal delivery in axlibc: handlers are never run and signals are
+// never queued. sigqueue() therefore only checks its arguments, accepts signal 0
+// as a probe of the calling process, and fails with ENOSYS for real signals.
+int sigqueue(pid_t pid, int sig, const union sigval value)
+{
+    (void)value;
+
+    if (sig < 0 || sig >= _NSIG) {
+        errno = EINVAL;
+        return -1;
+    }
+
+    // The current process is the only one that exists
+    if (pid != getpid()) {
+        errno = ESRCH;
+        return -1;
+    }
+
+    if (sig == 0)
+        return 0;
+
+    errno = ENOSYS;
+    return -1;
+}
+
 int sigemptyset(sigset_t *set)
 {
     set->__bits[0] = 0;
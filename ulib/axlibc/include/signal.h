@@ -139,6 +139,9 @@ typedef struct {
 #define SIGSYS    31
 #define SIGUNUSED SIGSYS
 
+#define SIGRTMIN  35
+#define SIGRTMAX  (_NSIG - 1)
+
 #define _NSIG 65
 
 typedef void (*sighandler_t)(int);
@@ -171,6 +174,7 @@ int sigaddset(sigset_t *, int);
 int pthread_sigmask(int, const sigset_t *__restrict, sigset_t *__restrict);
 
 int kill(pid_t, int);
+int sigqueue(pid_t, int, const union sigval);
 
 #ifdef AX_CONFIG_MULTITASK
 int pthread_kill(pthread_t t, int sig);
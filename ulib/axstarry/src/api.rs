@@ -18,8 +18,10 @@ pub fn new_file(path: &str, flags: &OpenFlags) -> AxResult<File> {
     let mut file = File::options();
     file.read(flags.readable());
     file.write(flags.writable());
+    file.append(flags.is_append() && flags.writable());
     file.create(flags.creatable());
     file.create_new(flags.new_creatable());
+    file.nofollow(flags.is_nofollow());
     file.open(path)
 }
 /// 在完成一次系统调用之后，恢复全局目录
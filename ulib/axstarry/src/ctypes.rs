@@ -2,6 +2,7 @@ use axhal::{
     paging::MappingFlags,
     time::{current_time_nanos, nanos_to_ticks, MICROS_PER_SEC, NANOS_PER_MICROS, NANOS_PER_SEC},
 };
+use axprocess::rusage::ResourceUsage;
 use bitflags::*;
 use core::panic;
 /// The nano seconds number per second
@@ -20,19 +21,19 @@ bitflags! {
 /// sys_times 中指定的结构体类型
 #[repr(C)]
 pub struct Tms {
-    /// 进程用户态执行时间，单位为us
+    /// 进程用户态执行时间，单位为时钟滴答
     pub tms_utime: usize,
-    /// 进程内核态执行时间，单位为us
+    /// 进程内核态执行时间，单位为时钟滴答
     pub tms_stime: usize,
-    /// 子进程用户态执行时间和，单位为us
+    /// 子进程用户态执行时间和，单位为时钟滴答
     pub tms_cutime: usize,
-    /// 子进程内核态执行时间和，单位为us
+    /// 子进程内核态执行时间和，单位为时钟滴答
     pub tms_cstime: usize,
 }
 
 /// sys_gettimeofday 中指定的类型
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct TimeVal {
     /// seconds
     pub sec: usize,
@@ -71,6 +72,62 @@ pub struct ITimerVal {
     pub it_value: TimeVal,
 }
 
+impl ITimerVal {
+    /// create an ITimerVal from the remaining time and the cycle in nano seconds
+    pub fn from_nanos(value_ns: u64, interval_ns: u64) -> Self {
+        // 向上取整到微秒，避免仍在运行的计时器被报告为已停止
+        ITimerVal {
+            it_interval: TimeVal::from_micro((interval_ns as usize + 999) / 1000),
+            it_value: TimeVal::from_micro((value_ns as usize + 999) / 1000),
+        }
+    }
+}
+
+/// sys_timer_settime / sys_timer_gettime 指定的类型
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ITimerSpec {
+    /// The cycle of the timer
+    pub it_interval: TimeSecs,
+    /// The remaining time of the timer, or the absolute expiration time when
+    /// `TIMER_ABSTIME` is set
+    pub it_value: TimeSecs,
+}
+
+impl ITimerSpec {
+    /// create an ITimerSpec from the remaining time and the cycle in nano seconds
+    pub fn from_nanos(value_ns: u64, interval_ns: u64) -> Self {
+        ITimerSpec {
+            it_interval: TimeSecs::from_nanos(interval_ns as usize),
+            it_value: TimeSecs::from_nanos(value_ns as usize),
+        }
+    }
+}
+
+/// sys_timer_settime 的 flags：it_value 为绝对时间
+pub const TIMER_ABSTIME: usize = 1;
+
+/// sys_timer_create 指定的结构体类型，只包含内核用到的部分
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SigEvent {
+    /// The value passed along with the signal
+    pub sigev_value: usize,
+    /// The signal to send
+    pub sigev_signo: i32,
+    /// The notification method
+    pub sigev_notify: i32,
+    /// The thread to receive the signal when `sigev_notify` is `SIGEV_THREAD_ID`
+    pub sigev_notify_thread_id: i32,
+}
+
+/// sigevent 的通知方式：发送信号给进程
+pub const SIGEV_SIGNAL: i32 = 0;
+/// sigevent 的通知方式：不进行通知
+pub const SIGEV_NONE: i32 = 1;
+/// sigevent 的通知方式：发送信号给指定线程
+pub const SIGEV_THREAD_ID: i32 = 4;
+
 /// sys_nanosleep指定的结构体类型
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default)]
@@ -98,6 +155,14 @@ impl TimeSecs {
         self.tv_sec * NSEC_PER_SEC + self.tv_nsec
     }
 
+    /// create a TimeSecs from nano seconds
+    pub fn from_nanos(nano: usize) -> Self {
+        TimeSecs {
+            tv_sec: nano / NSEC_PER_SEC,
+            tv_nsec: nano % NSEC_PER_SEC,
+        }
+    }
+
     /// turn the TimeSecs to cpu ticks, which is related to cpu frequency
     pub fn get_ticks(&self) -> usize {
         self.tv_sec * axconfig::TIMER_FREQUENCY + (nanos_to_ticks(self.tv_nsec as u64) as usize)
@@ -232,21 +297,8 @@ impl SigMaskFlag {
     }
 }
 
-/// sys_prlimit64 使用的数组
-#[repr(C)]
-pub struct RLimit {
-    /// 软上限
-    pub rlim_cur: u64,
-    /// 硬上限
-    pub rlim_max: u64,
-}
-// sys_prlimit64 使用的选项
-/// 用户栈大小
-pub const RLIMIT_STACK: i32 = 3;
-/// 可以打开的 fd 数
-pub const RLIMIT_NOFILE: i32 = 7;
-/// 用户地址空间的最大大小
-pub const RLIMIT_AS: i32 = 9;
+// sys_prlimit64 / sys_getrlimit / sys_setrlimit 使用的结构体与选项
+pub use axprocess::rlimit::{RLimit, RLIMIT_AS, RLIMIT_DATA, RLIMIT_FSIZE, RLIM_INFINITY};
 
 /// robust list
 #[repr(C)]
@@ -363,6 +415,59 @@ impl RusageFlags {
     }
 }
 
+/// sys_getrusage / sys_wait4 使用的资源统计结构体
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct Rusage {
+    /// 用户态运行时间
+    pub ru_utime: TimeVal,
+    /// 内核态运行时间
+    pub ru_stime: TimeVal,
+    /// 最大常驻内存大小，单位为 KB
+    pub ru_maxrss: isize,
+    /// 共享内存大小，未使用
+    pub ru_ixrss: isize,
+    /// 非共享数据段大小，未使用
+    pub ru_idrss: isize,
+    /// 非共享栈大小，未使用
+    pub ru_isrss: isize,
+    /// 不需要进行 I/O 的缺页次数
+    pub ru_minflt: isize,
+    /// 需要进行 I/O 的缺页次数
+    pub ru_majflt: isize,
+    /// 换出次数，未使用
+    pub ru_nswap: isize,
+    /// 文件系统读入次数，未使用
+    pub ru_inblock: isize,
+    /// 文件系统写出次数，未使用
+    pub ru_oublock: isize,
+    /// 发送的 IPC 消息数，未使用
+    pub ru_msgsnd: isize,
+    /// 接收的 IPC 消息数，未使用
+    pub ru_msgrcv: isize,
+    /// 接收的信号数，未使用
+    pub ru_nsignals: isize,
+    /// 主动上下文切换次数
+    pub ru_nvcsw: isize,
+    /// 被动上下文切换次数
+    pub ru_nivcsw: isize,
+}
+
+impl From<ResourceUsage> for Rusage {
+    fn from(usage: ResourceUsage) -> Self {
+        Self {
+            ru_utime: TimeVal::from_micro(usage.utime_us),
+            ru_stime: TimeVal::from_micro(usage.stime_us),
+            ru_maxrss: usage.maxrss_kb as isize,
+            ru_minflt: usage.minflt as isize,
+            ru_majflt: usage.majflt as isize,
+            ru_nvcsw: usage.nvcsw as isize,
+            ru_nivcsw: usage.nivcsw as isize,
+            ..Default::default()
+        }
+    }
+}
+
 #[allow(unused)]
 /// sched_setscheduler时指定子进程是否继承父进程的调度策略
 pub const SCHED_RESET_ON_FORK: usize = 0x40000000;
@@ -375,6 +480,40 @@ pub struct SchedParam {
     pub sched_priority: usize,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+/// sys_sched_setattr / sys_sched_getattr 使用的结构体
+///
+/// 仅支持第一个版本的字段，SCHED_DEADLINE 使用的字段被忽略
+pub struct SchedAttr {
+    /// The size of the structure
+    pub size: u32,
+    /// The scheduling policy
+    pub sched_policy: u32,
+    /// The scheduling flags
+    pub sched_flags: u64,
+    /// The nice value, for SCHED_OTHER and SCHED_BATCH
+    pub sched_nice: i32,
+    /// The real-time priority, for SCHED_FIFO and SCHED_RR
+    pub sched_priority: u32,
+    /// The runtime of SCHED_DEADLINE
+    pub sched_runtime: u64,
+    /// The deadline of SCHED_DEADLINE
+    pub sched_deadline: u64,
+    /// The period of SCHED_DEADLINE
+    pub sched_period: u64,
+}
+
+/// 第一个版本的 sched_attr 的大小
+pub const SCHED_ATTR_SIZE_VER0: u32 = 48;
+
+/// getpriority / setpriority 的 which：指定线程
+pub const PRIO_PROCESS: usize = 0;
+/// getpriority / setpriority 的 which：指定进程组
+pub const PRIO_PGRP: usize = 1;
+/// getpriority / setpriority 的 which：指定用户
+pub const PRIO_USER: usize = 2;
+
 numeric_enum_macro::numeric_enum! {
     #[repr(usize)]
     #[allow(non_camel_case_types)]
@@ -385,6 +524,8 @@ numeric_enum_macro::numeric_enum! {
         CLOCK_REALTIME = 0,
         /// monotonic clock
         CLOCK_MONOTONIC = 1,
+        /// cpu time consumed by all threads of the process
+        CLOCK_PROCESS_CPUTIME_ID = 2,
     }
 }
 
@@ -512,6 +653,14 @@ bitflags! {
         const S_IFDIR = 1 << 14;
         /// character device
         const S_IFCHR = 1 << 13;
+        /// symbolic link
+        const S_IFLNK = (1 << 15) | (1 << 13);
+        /// socket
+        const S_IFSOCK = (1 << 15) | (1 << 14);
+        /// block device
+        const S_IFBLK = (1 << 14) | (1 << 13);
+        /// FIFO
+        const S_IFIFO = 1 << 12;
         /// 是否设置 uid/gid/sticky
         //const S_ISUID = 1 << 14;
         //const S_ISGID = 1 << 13;
@@ -560,6 +709,18 @@ numeric_enum_macro::numeric_enum! {
         PR_SET_NAME = 15,
         /// get the name of the process
         PR_GET_NAME = 16,
+        /// get the seccomp mode of the process
+        PR_GET_SECCOMP = 21,
+        /// set the seccomp mode of the process
+        PR_SET_SECCOMP = 22,
+        /// set the process as a child subreaper
+        PR_SET_CHILD_SUBREAPER = 36,
+        /// get whether the process is a child subreaper
+        PR_GET_CHILD_SUBREAPER = 37,
+        /// forbid the process and its children to gain new privileges
+        PR_SET_NO_NEW_PRIVS = 38,
+        /// get the no_new_privs flag of the process
+        PR_GET_NO_NEW_PRIVS = 39,
     }
 }
 
@@ -25,6 +25,7 @@ mod ctypes;
 use ctypes::*;
 
 pub use axprocess::{
+    flags::WaitStatus,
     link::{create_link, FilePath},
     wait_pid, Process,
 };
@@ -45,6 +46,35 @@ pub(crate) fn deal_result(result: SyscallResult) -> isize {
     }
 }
 
+/// 向读端已经关闭的管道或者已经断开的流式套接字写入时返回 EPIPE
+///
+/// `no_signal` 为假时还会向当前线程发送 SIGPIPE，MSG_NOSIGNAL 与 SO_NOSIGPIPE 可以抑制该信号
+pub(crate) fn broken_pipe(no_signal: bool) -> SyscallError {
+    #[cfg(feature = "signal")]
+    if !no_signal {
+        let tid = axtask::current().id().as_u64() as isize;
+        let _ = axprocess::signal::send_signal_to_thread(
+            tid,
+            axsignal::signal_no::SignalNo::SIGPIPE as isize,
+        );
+    }
+    #[cfg(not(feature = "signal"))]
+    let _ = no_signal;
+    SyscallError::EPIPE
+}
+
+/// 阻塞等待时是否被信号打断
+pub(crate) fn interrupted() -> bool {
+    #[cfg(feature = "signal")]
+    {
+        axprocess::current_process().have_signals().is_some()
+    }
+    #[cfg(not(feature = "signal"))]
+    {
+        false
+    }
+}
+
 /// The error of a syscall, which is a `LinuxError`
 pub type SyscallError = axerrno::LinuxError;
 
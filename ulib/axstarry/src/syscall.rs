@@ -1,10 +1,14 @@
-use crate::{deal_result, SyscallResult};
+use crate::{deal_result, SyscallError, SyscallResult};
 use axlog::info;
 
 #[no_mangle]
 pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
     #[cfg(feature = "futex")]
     crate::syscall_task::check_dead_wait();
+    // 在分发之前进行 seccomp 过滤
+    if let Some(ans) = crate::syscall_task::seccomp_check(syscall_id, args) {
+        return ans;
+    }
     #[allow(unused_mut, unused_assignments)]
     let mut ans: Option<SyscallResult> = None;
 
@@ -48,7 +52,7 @@ pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
     if ans.is_none() {
         panic!("unknown syscall id: {}", syscall_id);
     }
-    let ans = deal_result(ans.unwrap());
+    let ans = deal_result(check_restart(syscall_id, args, ans.unwrap()));
     if syscall_id != 96 && syscall_id != 98 {
         info!(
             "[syscall] id = {}, args = {:?}, return {}",
@@ -57,3 +61,35 @@ pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
     }
     ans
 }
+
+/// 系统调用被信号打断而返回 `ERESTART*` 时，记录重新执行所需的信息
+///
+/// 返回用户态前的信号处理会根据信号的处理方式决定重新执行还是返回 EINTR，未启用信号时直接返回 EINTR
+fn check_restart(syscall_id: usize, args: [usize; 6], result: SyscallResult) -> SyscallResult {
+    match result {
+        Err(
+            errno @ (SyscallError::ERESTARTSYS
+            | SyscallError::ERESTARTNOINTR
+            | SyscallError::ERESTARTNOHAND
+            | SyscallError::ERESTART_RESTARTBLOCK),
+        ) => {
+            #[cfg(feature = "signal")]
+            {
+                // 通过 restart_syscall 继续执行
+                let syscall_id = if errno == SyscallError::ERESTART_RESTARTBLOCK {
+                    crate::syscall_task::TaskSyscallId::RESTART_SYSCALL as usize
+                } else {
+                    syscall_id
+                };
+                axprocess::signal::set_syscall_restart(errno, syscall_id, args[0]);
+                Err(errno)
+            }
+            #[cfg(not(feature = "signal"))]
+            {
+                let _ = (errno, syscall_id, args);
+                Err(SyscallError::EINTR)
+            }
+        }
+        result => result,
+    }
+}
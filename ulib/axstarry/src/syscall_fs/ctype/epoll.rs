@@ -1,4 +1,4 @@
-use axhal::time::current_ticks;
+use axhal::time::current_time_nanos;
 use bitflags::bitflags;
 extern crate alloc;
 use alloc::{
@@ -144,7 +144,7 @@ impl EpollFile {
 
     /// 实现epoll wait，在规定超时时间内收集达到触发条件的事件
     ///
-    /// 实现原理和ppoll很像
+    /// 实现原理和ppoll很像，`expire_time` 为超时的时刻，单位为纳秒
     pub fn epoll_wait(&self, expire_time: usize) -> AxResult<Vec<EpollEvent>> {
         let events = self.get_events();
         let mut ret_events = Vec::new();
@@ -189,14 +189,14 @@ impl EpollFile {
                 return Ok(ret_events);
             }
             // 否则直接block
-            if current_ticks() as usize > expire_time {
+            if current_time_nanos() as usize >= expire_time {
                 return Ok(ret_events);
             }
             yield_now_task();
 
             #[cfg(feature = "signal")]
             if current_process.have_signals().is_some() {
-                return Err(AxError::Timeout);
+                return Err(AxError::Interrupted);
             }
         }
     }
@@ -11,7 +11,6 @@ use axfs::api::{File, FileIO, FileIOType, Kstat, OpenFlags, Read, Seek, SeekFrom
 use axlog::debug;
 
 use crate::{new_file, normal_file_mode, StMode, TimeSecs};
-use axprocess::link::get_link_count;
 use axsync::Mutex;
 
 pub static INODE_NAME_MAP: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
@@ -50,10 +49,11 @@ impl FileIO for FileDesc {
 
     fn write(&self, buf: &[u8]) -> AxResult<usize> {
         // 如果seek时超出了文件原有大小，则在write的时候进行补零操作
+        // 追加模式下总是写到文件末尾，不需要补零
         let mut file = self.file.lock();
         let old_offset = file.seek(SeekFrom::Current(0)).unwrap();
         let size = file.metadata().unwrap().size();
-        if old_offset > size {
+        if old_offset > size && !self.flags.lock().is_append() {
             file.seek(SeekFrom::Start(size)).unwrap();
             let temp_buf: Vec<u8> = vec![0u8; (old_offset - size) as usize];
             file.write(&temp_buf)?;
@@ -112,7 +112,7 @@ impl FileIO for FileDesc {
             st_dev: 1,
             st_ino: inode_number,
             st_mode: normal_file_mode(StMode::S_IFREG).bits() | 0o644,
-            st_nlink: get_link_count(&(self.path.as_str().to_string())) as _,
+            st_nlink: attr.nlink() as _,
             st_uid: 0,
             st_gid: 0,
             st_rdev: 0,
@@ -4,6 +4,8 @@ pub mod file;
 
 pub mod mount;
 
+pub mod mqueue;
+
 pub mod pipe;
 
 pub use file::FileDesc;
@@ -11,3 +13,6 @@ pub use file::FileDesc;
 pub mod epoll;
 
 pub mod eventfd;
+
+#[cfg(feature = "signal")]
+pub mod signalfd;
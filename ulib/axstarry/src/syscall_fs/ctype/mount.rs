@@ -2,7 +2,7 @@ extern crate alloc;
 use crate::{normal_file_mode, StMode, SyscallError};
 use alloc::string::ToString;
 use alloc::vec::Vec;
-use axfs::api::{lookup, path_exists, FileIO, Kstat, OpenFlags};
+use axfs::api::{lookup, lookup_nofollow, path_exists, FileIO, Kstat, OpenFlags};
 use axlog::{debug, info};
 use axprocess::link::FilePath;
 use axsync::Mutex;
@@ -98,12 +98,42 @@ pub fn check_mounted(path: &FilePath) -> bool {
 }
 
 /// 根据给定的路径获取对应的文件stat
-pub fn get_stat_in_fs(path: &FilePath) -> Result<Kstat, SyscallError> {
+///
+/// 若 `nofollow` 为 true 且路径最后一级为符号链接，则返回链接本身的信息
+pub fn get_stat_in_fs(path: &FilePath, nofollow: bool) -> Result<Kstat, SyscallError> {
     // 根目录算作一个简单的目录文件，不使用特殊的stat
     // 否则在fat32中查找
     let real_path = path.path();
     let mut ans = Kstat::default();
     info!("get_stat_in_fs: {}", real_path);
+    if nofollow {
+        if let Ok(node) = lookup_nofollow(real_path) {
+            let attr = node.get_attr()?;
+            if attr.is_symlink() {
+                ans.st_nlink = attr.nlink() as _;
+                ans.st_mode = StMode::S_IFLNK.bits() | 0o777;
+                // 符号链接的大小为其指向的路径的长度
+                ans.st_size = attr.size();
+                return Ok(ans);
+            }
+        }
+    }
+    // 命名管道与套接字文件只是在文件系统中的标记，没有内容
+    if let Ok(node) = lookup(real_path) {
+        let attr = node.get_attr()?;
+        let kind = if attr.file_type().is_fifo() {
+            Some(StMode::S_IFIFO)
+        } else if attr.file_type().is_socket() {
+            Some(StMode::S_IFSOCK)
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            ans.st_nlink = attr.nlink() as _;
+            ans.st_mode = kind.bits() | attr.perm().bits() as u32;
+            return Ok(ans);
+        }
+    }
     if real_path.starts_with("/var")
         || real_path.starts_with("/dev")
         || real_path.starts_with("/tmp")
@@ -0,0 +1,86 @@
+use alloc::sync::Arc;
+use axfs::api::{FileIO, FileIOType, OpenFlags};
+use axfs::axfs_mqueuefs::MqueueNode;
+use axprocess::current_process;
+use axsync::Mutex;
+
+/// POSIX 消息队列的打开描述，即 mq_open 返回的描述符
+///
+/// 队列本身是 mqueue 文件系统中的文件，被 mq_unlink 删除后仍然可以通过已打开的描述符使用
+pub struct MessageQueueDesc {
+    queue: Arc<MqueueNode>,
+    flags: Mutex<OpenFlags>,
+}
+
+impl MessageQueueDesc {
+    pub fn new(queue: Arc<MqueueNode>, flags: OpenFlags) -> Self {
+        Self {
+            queue,
+            flags: Mutex::new(flags),
+        }
+    }
+
+    /// 描述符对应的消息队列
+    pub fn queue(&self) -> &Arc<MqueueNode> {
+        &self.queue
+    }
+
+    /// 是否设置了 O_NONBLOCK
+    pub fn is_non_block(&self) -> bool {
+        self.flags.lock().contains(OpenFlags::NON_BLOCK)
+    }
+}
+
+impl FileIO for MessageQueueDesc {
+    fn readable(&self) -> bool {
+        self.flags.lock().readable()
+    }
+
+    fn writable(&self) -> bool {
+        self.flags.lock().writable()
+    }
+
+    fn executable(&self) -> bool {
+        false
+    }
+
+    fn get_type(&self) -> FileIOType {
+        FileIOType::Other
+    }
+
+    // 队列中有消息时可读，队列未满时可写
+    fn ready_to_read(&self) -> bool {
+        self.readable() && self.queue.readable()
+    }
+
+    fn ready_to_write(&self) -> bool {
+        self.writable() && self.queue.writable()
+    }
+
+    /// F_SETFL 与 mq_setattr 只能修改 O_NONBLOCK
+    fn set_status(&self, flags: OpenFlags) -> bool {
+        let mut status = self.flags.lock();
+        status.set(OpenFlags::NON_BLOCK, flags.contains(OpenFlags::NON_BLOCK));
+        true
+    }
+
+    fn get_status(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_close_on_exec(&self, is_set: bool) -> bool {
+        if is_set {
+            *self.flags.lock() |= OpenFlags::CLOEXEC;
+        } else {
+            *self.flags.lock() &= !OpenFlags::CLOEXEC;
+        }
+        true
+    }
+}
+
+impl Drop for MessageQueueDesc {
+    /// 注册了 mq_notify 的进程关闭描述符时，取消注册
+    fn drop(&mut self) {
+        self.queue.unregister_notify(current_process().pid());
+    }
+}
@@ -1,11 +1,14 @@
-use axfs::api::{FileIO, FileIOType, OpenFlags};
+use axfs::api::{FileIO, FileIOType, OpenFlags, VfsNodeRef};
 extern crate alloc;
+use alloc::collections::BTreeMap;
 use alloc::sync::{Arc, Weak};
-use axerrno::AxResult;
+use axerrno::{AxError, AxResult};
 use axlog::{info, trace};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
 
 use axsync::Mutex;
-use axtask::yield_now;
+use axtask::{yield_now, WaitQueue};
 
 /// IPC pipe
 pub struct Pipe {
@@ -21,6 +24,7 @@ pub struct Pipe {
 impl Pipe {
     /// create readable pipe
     pub fn read_end_with_buffer(buffer: Arc<Mutex<PipeRingBuffer>>, flags: OpenFlags) -> Self {
+        buffer.lock().open_end(true, false);
         Self {
             readable: true,
             writable: false,
@@ -30,6 +34,7 @@ impl Pipe {
     }
     /// create writable pipe
     pub fn write_end_with_buffer(buffer: Arc<Mutex<PipeRingBuffer>>, flags: OpenFlags) -> Self {
+        buffer.lock().open_end(false, true);
         Self {
             readable: false,
             writable: true,
@@ -37,6 +42,23 @@ impl Pipe {
             flags: Mutex::new(flags | OpenFlags::WRONLY),
         }
     }
+    /// 按照 `flags` 中的访问模式创建命名管道的一端，O_RDWR 打开的一端同时可读可写
+    pub fn fifo_end_with_buffer(buffer: Arc<Mutex<PipeRingBuffer>>, flags: OpenFlags) -> Self {
+        let (readable, writable) = (flags.readable(), flags.writable());
+        buffer.lock().open_end(readable, writable);
+        Self {
+            readable,
+            writable,
+            buffer,
+            flags: Mutex::new(
+                flags
+                    & (OpenFlags::WRONLY
+                        | OpenFlags::RDWR
+                        | OpenFlags::NON_BLOCK
+                        | OpenFlags::CLOEXEC),
+            ),
+        }
+    }
     /// is it set non block?
     pub fn is_non_block(&self) -> bool {
         self.flags.lock().contains(OpenFlags::NON_BLOCK)
@@ -52,12 +74,74 @@ enum RingBufferStatus {
     Normal,
 }
 
+/// 等待对端打开时每次阻塞的最长时间
+const OPEN_WAIT_PERIOD: Duration = Duration::from_millis(10);
+
+/// 管道两端的打开情况
+///
+/// 计数使用原子变量，等待对端打开的任务不需要持有缓冲区的锁就能检查条件
+pub struct PipeEnds {
+    /// 打开的读端数量
+    readers: AtomicUsize,
+    /// 打开的写端数量
+    writers: AtomicUsize,
+    /// 读端被打开的总次数，等待读端的命名管道据此发现打开后又很快关闭的读端
+    read_opens: AtomicUsize,
+    /// 写端被打开的总次数
+    write_opens: AtomicUsize,
+    /// 等待对端打开的命名管道
+    open_wq: WaitQueue,
+}
+
+impl PipeEnds {
+    const fn new() -> Self {
+        Self {
+            readers: AtomicUsize::new(0),
+            writers: AtomicUsize::new(0),
+            read_opens: AtomicUsize::new(0),
+            write_opens: AtomicUsize::new(0),
+            open_wq: WaitQueue::new(),
+        }
+    }
+
+    /// 对端是否已经打开，`opens` 为开始等待前对端被打开的总次数
+    fn peer_opened(&self, readable: bool, opens: usize) -> bool {
+        let (count, total) = if readable {
+            (&self.writers, &self.write_opens)
+        } else {
+            (&self.readers, &self.read_opens)
+        };
+        count.load(Ordering::Acquire) != 0 || total.load(Ordering::Acquire) != opens
+    }
+
+    /// 阻塞到对端被打开，对端打开后又立即关闭时也不再等待
+    ///
+    /// `readable` 表示等待的是读端，`opens` 为开始等待前对端被打开的总次数，
+    /// 等待期间收到信号时返回 `Interrupted`。
+    /// 信号可能在检查之后、阻塞之前到达，因此每次只阻塞一小段时间
+    pub fn wait_for_peer(&self, readable: bool, opens: usize) -> AxResult {
+        loop {
+            if self.peer_opened(readable, opens) {
+                return Ok(());
+            }
+            #[cfg(feature = "signal")]
+            if axprocess::current_process().have_signals().is_some() {
+                return Err(AxError::Interrupted);
+            }
+            self.open_wq
+                .wait_timeout_if(OPEN_WAIT_PERIOD, || !self.peer_opened(readable, opens));
+        }
+    }
+}
+
 pub struct PipeRingBuffer {
     arr: [u8; RING_BUFFER_SIZE],
     head: usize,
     tail: usize,
     status: RingBufferStatus,
-    write_end: Option<Weak<Pipe>>,
+    ends: Arc<PipeEnds>,
+    /// 命名管道的缓冲区在 `FIFO_BUFFERS` 中的键，匿名管道为 `None`
+    fifo_key: Option<usize>,
 }
 
 impl PipeRingBuffer {
@@ -67,12 +151,30 @@ impl PipeRingBuffer {
             head: 0,
             tail: 0,
             status: RingBufferStatus::Empty,
-            write_end: None,
+            ends: Arc::new(PipeEnds::new()),
+            fifo_key: None,
+        }
+    }
+
+    fn open_end(&mut self, readable: bool, writable: bool) {
+        if readable {
+            self.ends.readers.fetch_add(1, Ordering::AcqRel);
+            self.ends.read_opens.fetch_add(1, Ordering::AcqRel);
         }
+        if writable {
+            self.ends.writers.fetch_add(1, Ordering::AcqRel);
+            self.ends.write_opens.fetch_add(1, Ordering::AcqRel);
+        }
+        self.ends.open_wq.notify_all(false);
     }
 
-    pub fn set_write_end(&mut self, write_end: &Arc<Pipe>) {
-        self.write_end = Some(Arc::downgrade(write_end));
+    fn close_end(&mut self, readable: bool, writable: bool) {
+        if readable {
+            self.ends.readers.fetch_sub(1, Ordering::AcqRel);
+        }
+        if writable {
+            self.ends.writers.fetch_sub(1, Ordering::AcqRel);
+        }
     }
 
     pub fn write_byte(&mut self, byte: u8) {
@@ -109,7 +211,58 @@ impl PipeRingBuffer {
         }
     }
     pub fn all_write_ends_closed(&self) -> bool {
-        self.write_end.as_ref().unwrap().upgrade().is_none()
+        self.ends.writers.load(Ordering::Acquire) == 0
+    }
+    pub fn all_read_ends_closed(&self) -> bool {
+        self.ends.readers.load(Ordering::Acquire) == 0
+    }
+    pub fn read_opens(&self) -> usize {
+        self.ends.read_opens.load(Ordering::Acquire)
+    }
+    pub fn write_opens(&self) -> usize {
+        self.ends.write_opens.load(Ordering::Acquire)
+    }
+    /// 两端的打开情况，用于不持有缓冲区的锁等待对端打开
+    pub fn ends(&self) -> Arc<PipeEnds> {
+        self.ends.clone()
+    }
+}
+
+/// 命名管道使用的缓冲区，以文件系统节点的地址为键
+///
+/// 同一节点的硬链接共享缓冲区，改名或删除后仍然打开的端不受影响。
+/// 表项同时持有节点，保证缓冲区释放前节点的地址不会被新的节点复用。
+/// 所有端都关闭后表项被移除，缓冲区随之释放，其中未读的数据被丢弃
+type FifoBuffers = BTreeMap<usize, (VfsNodeRef, Weak<Mutex<PipeRingBuffer>>)>;
+
+static FIFO_BUFFERS: Mutex<FifoBuffers> = Mutex::new(BTreeMap::new());
+
+/// 取得命名管道节点 `node` 正在使用的缓冲区，没有打开的端时创建新的缓冲区
+///
+/// 不再使用返回的缓冲区时需要调用 [`put_fifo_buffer`]，打开的端会在关闭时自动调用
+pub fn fifo_buffer(node: VfsNodeRef) -> Arc<Mutex<PipeRingBuffer>> {
+    let key = Arc::as_ptr(&node) as *const () as usize;
+    let mut buffers = FIFO_BUFFERS.lock();
+    if let Some(buffer) = buffers.get(&key).and_then(|(_, buffer)| buffer.upgrade()) {
+        return buffer;
+    }
+    let mut ring_buffer = PipeRingBuffer::new();
+    ring_buffer.fifo_key = Some(key);
+    let buffer = Arc::new(Mutex::new(ring_buffer));
+    buffers.insert(key, (node, Arc::downgrade(&buffer)));
+    buffer
+}
+
+/// 放弃对命名管道缓冲区 `buffer` 的引用，这是最后一个引用时移除对应的表项
+///
+/// 取得缓冲区的引用也在 `FIFO_BUFFERS` 的锁内进行，因此引用计数在锁内是准确的
+pub fn put_fifo_buffer(buffer: &Arc<Mutex<PipeRingBuffer>>) {
+    let Some(key) = buffer.lock().fifo_key else {
+        return;
+    };
+    let mut buffers = FIFO_BUFFERS.lock();
+    if Arc::strong_count(buffer) == 1 {
+        buffers.remove(&key);
     }
 }
 
@@ -118,11 +271,17 @@ pub fn make_pipe(flags: OpenFlags) -> (Arc<Pipe>, Arc<Pipe>) {
     trace!("kernel: make_pipe");
     let buffer = Arc::new(Mutex::new(PipeRingBuffer::new()));
     let read_end = Arc::new(Pipe::read_end_with_buffer(buffer.clone(), flags));
-    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer.clone(), flags));
-    buffer.lock().set_write_end(&write_end);
+    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer, flags));
     (read_end, write_end)
 }
 
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        self.buffer.lock().close_end(self.readable, self.writable);
+        put_fifo_buffer(&self.buffer);
+    }
+}
+
 impl FileIO for Pipe {
     fn read(&self, buf: &mut [u8]) -> AxResult<usize> {
         info!("kernel: Pipe::read");
@@ -135,17 +294,18 @@ impl FileIO for Pipe {
             let loop_read = ring_buffer.available_read();
             info!("kernel: Pipe::read: loop_read = {}", loop_read);
             if loop_read == 0 {
-                // if current_task().hav
+                // 已经读到数据时直接返回，否则被信号打断
                 #[cfg(feature = "signal")]
-                if axprocess::current_process().have_signals().is_some() {
+                if already_read == 0 && axprocess::current_process().have_signals().is_some() {
                     return Err(axerrno::AxError::Interrupted);
                 }
-                if Arc::strong_count(&self.buffer) < 2
-                    || ring_buffer.all_write_ends_closed()
-                    || self.is_non_block()
-                {
+                if ring_buffer.all_write_ends_closed() {
                     return Ok(already_read);
                 }
+                // 还有写端时非阻塞读取不能返回 0，否则会被当作读到了文件末尾
+                if self.is_non_block() {
+                    return Err(AxError::WouldBlock);
+                }
                 drop(ring_buffer);
                 yield_now();
                 continue;
@@ -174,13 +334,21 @@ impl FileIO for Pipe {
         let mut already_write = 0usize;
         loop {
             let mut ring_buffer = self.buffer.lock();
+            // 读入端关闭时写入失败，由调用者发送 SIGPIPE
+            if ring_buffer.all_read_ends_closed() {
+                return Err(AxError::BrokenPipe);
+            }
             let loop_write = ring_buffer.available_write();
             if loop_write == 0 {
                 drop(ring_buffer);
 
-                if Arc::strong_count(&self.buffer) < 2 || self.is_non_block() {
-                    // 读入端关闭
-                    return Ok(already_write);
+                if self.is_non_block() {
+                    return Err(AxError::WouldBlock);
+                }
+                // 缓冲区已满时等待读入端读取，等待期间可以被信号打断
+                #[cfg(feature = "signal")]
+                if axprocess::current_process().have_signals().is_some() {
+                    return Err(AxError::Interrupted);
                 }
                 yield_now();
                 continue;
@@ -229,7 +397,7 @@ impl FileIO for Pipe {
             }
         } else {
             // 否则在写入端，只关心读入端是否被关闭
-            Arc::strong_count(&self.buffer) < 2
+            self.buffer.lock().all_read_ends_closed()
         }
     }
 
@@ -0,0 +1,200 @@
+use axerrno::{AxError, AxResult};
+use axfs::api::{FileIO, FileIOType, OpenFlags};
+use axprocess::signal::{dequeue_signal_in, has_pending_in};
+use axsignal::info::{SigInfo, SigInfoLayout};
+use axsync::Mutex;
+use axtask::yield_now;
+use bitflags::bitflags;
+
+bitflags! {
+    // https://man7.org/linux/man-pages/man2/signalfd.2.html
+    #[derive(Clone, Copy, Debug)]
+    pub struct SignalFdFlag: u32 {
+        const SFD_NONBLOCK = 0x800;
+        const SFD_CLOEXEC = 0x80000;
+    }
+}
+
+/// read 返回的 `struct signalfd_siginfo`，大小为 128 字节
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct SignalFdSigInfo {
+    ssi_signo: u32,
+    ssi_errno: i32,
+    ssi_code: i32,
+    ssi_pid: u32,
+    ssi_uid: u32,
+    ssi_fd: i32,
+    ssi_tid: u32,
+    ssi_band: u32,
+    ssi_overrun: u32,
+    ssi_trapno: u32,
+    ssi_status: i32,
+    ssi_int: i32,
+    ssi_ptr: u64,
+    ssi_utime: u64,
+    ssi_stime: u64,
+    ssi_addr: u64,
+    ssi_addr_lsb: u16,
+    _pad2: u16,
+    ssi_syscall: i32,
+    ssi_call_addr: u64,
+    ssi_arch: u32,
+    _pad: [u8; 28],
+}
+
+impl From<SigInfo> for SignalFdSigInfo {
+    fn from(info: SigInfo) -> Self {
+        let mut ssi = Self {
+            ssi_signo: info.si_signo as u32,
+            ssi_errno: info.si_errno,
+            ssi_code: info.si_code,
+            ..Default::default()
+        };
+        match info.layout() {
+            SigInfoLayout::Kill => {
+                ssi.ssi_pid = info.si_pid() as u32;
+                ssi.ssi_uid = info.si_uid();
+            }
+            SigInfoLayout::Timer => {
+                ssi.ssi_tid = info.si_timerid() as u32;
+                ssi.ssi_overrun = info.si_overrun() as u32;
+                ssi.ssi_ptr = info.si_value() as u64;
+                ssi.ssi_int = info.si_value() as i32;
+            }
+            SigInfoLayout::Rt => {
+                ssi.ssi_pid = info.si_pid() as u32;
+                ssi.ssi_uid = info.si_uid();
+                ssi.ssi_ptr = info.si_value() as u64;
+                ssi.ssi_int = info.si_value() as i32;
+            }
+            SigInfoLayout::Chld => {
+                ssi.ssi_pid = info.si_pid() as u32;
+                ssi.ssi_uid = info.si_uid();
+                ssi.ssi_status = info.si_status();
+                ssi.ssi_utime = info.si_utime() as u64;
+                ssi.ssi_stime = info.si_stime() as u64;
+            }
+            SigInfoLayout::Fault => {
+                ssi.ssi_addr = info.si_addr() as u64;
+            }
+            SigInfoLayout::Sys => {
+                ssi.ssi_call_addr = info.si_addr() as u64;
+                ssi.ssi_syscall = info.si_syscall();
+                ssi.ssi_arch = info.si_arch();
+            }
+        }
+        ssi
+    }
+}
+
+// https://man7.org/linux/man-pages/man2/signalfd.2.html
+/// 通过读取文件描述符同步接收信号
+///
+/// 读取时从当前线程与进程的未决信号中取出属于 `mask` 的信号。
+/// 这些信号通常已经被信号掩码阻塞，因此不会再交给信号处理函数
+pub struct SignalFd {
+    mask: Mutex<usize>,
+    flags: Mutex<OpenFlags>,
+}
+
+impl SignalFd {
+    pub fn new(mask: usize, flags: u32) -> SignalFd {
+        let flags = SignalFdFlag::from_bits_truncate(flags);
+        let mut status = OpenFlags::RDONLY;
+        if flags.contains(SignalFdFlag::SFD_NONBLOCK) {
+            status |= OpenFlags::NON_BLOCK;
+        }
+        if flags.contains(SignalFdFlag::SFD_CLOEXEC) {
+            status |= OpenFlags::CLOEXEC;
+        }
+        SignalFd {
+            mask: Mutex::new(mask),
+            flags: Mutex::new(status),
+        }
+    }
+
+    /// 修改需要接收的信号集合
+    pub fn set_mask(&self, mask: usize) {
+        *self.mask.lock() = mask;
+    }
+
+    fn should_block(&self) -> bool {
+        !self.flags.lock().contains(OpenFlags::NON_BLOCK)
+    }
+}
+
+impl FileIO for SignalFd {
+    fn read(&self, buf: &mut [u8]) -> AxResult<usize> {
+        let len = core::mem::size_of::<SignalFdSigInfo>();
+        if buf.len() < len {
+            return Err(AxError::InvalidInput);
+        }
+        let mask = *self.mask.lock();
+        let mut read = 0;
+        // 一次读取尽可能多的信号，但只在一个信号都没有时阻塞
+        while buf.len() - read >= len {
+            let info = if let Some(info) = dequeue_signal_in(mask) {
+                info
+            } else if read != 0 {
+                break;
+            } else if !self.should_block() {
+                return Err(AxError::WouldBlock);
+            } else if axprocess::current_process().have_signals().is_some() {
+                // 有需要交给信号处理函数的信号
+                return Err(AxError::Interrupted);
+            } else {
+                yield_now();
+                continue;
+            };
+            let ssi = SignalFdSigInfo::from(info);
+            let bytes = unsafe {
+                core::slice::from_raw_parts(&ssi as *const SignalFdSigInfo as *const u8, len)
+            };
+            buf[read..read + len].copy_from_slice(bytes);
+            read += len;
+        }
+        Ok(read)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    fn executable(&self) -> bool {
+        false
+    }
+
+    fn get_type(&self) -> FileIOType {
+        FileIOType::Other
+    }
+
+    // 当前线程或进程有属于 mask 的未决信号时可读
+    fn ready_to_read(&self) -> bool {
+        has_pending_in(*self.mask.lock())
+    }
+
+    /// F_SETFL 只能修改 O_NONBLOCK
+    fn set_status(&self, flags: OpenFlags) -> bool {
+        let mut status = self.flags.lock();
+        status.set(OpenFlags::NON_BLOCK, flags.contains(OpenFlags::NON_BLOCK));
+        true
+    }
+
+    fn get_status(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_close_on_exec(&self, is_set: bool) -> bool {
+        if is_set {
+            *self.flags.lock() |= OpenFlags::CLOEXEC;
+        } else {
+            *self.flags.lock() &= !OpenFlags::CLOEXEC;
+        }
+        true
+    }
+}
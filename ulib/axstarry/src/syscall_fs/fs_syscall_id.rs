@@ -23,8 +23,10 @@ pub enum FsSyscallId {
     DUP3 = 24,
     FCNTL64 = 25,
     IOCTL = 29,
+    MKNODAT = 33,
     MKDIRAT = 34,
     UNLINKAT = 35,
+    SYMLINKAT = 36,
     LINKAT = 37,
     RENAMEAT = 38,
     UNMOUNT = 39,
@@ -33,6 +35,8 @@ pub enum FsSyscallId {
     FTRUNCATE64 = 46,
     FACCESSAT = 48,
     CHDIR = 49,
+    FCHDIR = 50,
+    CHROOT = 51,
     FCHMODAT = 53,
     OPENAT = 56,
     CLOSE = 57,
@@ -44,6 +48,7 @@ pub enum FsSyscallId {
     READV = 65,
     WRITEV = 66,
     PPOLL = 73,
+    SIGNALFD4 = 74,
     FSTATAT = 79,
     PREAD64 = 67,
     PWRITE64 = 68,
@@ -54,6 +59,12 @@ pub enum FsSyscallId {
     SYNC = 81,
     FSYNC = 82,
     UTIMENSAT = 88,
+    MQ_OPEN = 180,
+    MQ_UNLINK = 181,
+    MQ_TIMEDSEND = 182,
+    MQ_TIMEDRECEIVE = 183,
+    MQ_NOTIFY = 184,
+    MQ_GETSETATTR = 185,
     RENAMEAT2 = 276,
     COPYFILERANGE = 285,
 }
@@ -75,17 +86,24 @@ numeric_enum_macro::numeric_enum! {
         EPOLL_CREATE = 213,
         EPOLL_CTL = 233,
         EPOLL_WAIT = 232,
+        SIGNALFD = 282,
+        SIGNALFD4 = 289,
         DUP = 32,
         DUP2 = 33,
         DUP3 = 292,
         FCNTL64 = 72,
         IOCTL = 16,
         MKDIRAT = 258,
+        MKNODAT = 259,
         RENAME = 82,
         MKDIR = 83,
+        MKNOD = 133,
         RMDIR = 84,
         UNLINKAT = 263,
         LINKAT = 265,
+        SYMLINKAT = 266,
+        LINK = 86,
+        SYMLINK = 88,
         UNMOUNT = 166,
         MOUNT = 165,
         STATFS = 137,
@@ -93,6 +111,8 @@ numeric_enum_macro::numeric_enum! {
         FACCESSAT = 269,
         ACCESS = 21,
         CHDIR = 80,
+        FCHDIR = 81,
+        CHROOT = 161,
         FCHMODAT = 268,
         OPENAT = 257,
         CLOSE = 3,
@@ -120,6 +140,12 @@ numeric_enum_macro::numeric_enum! {
         SYNC = 162,
         FSYNC = 74,
         UTIMENSAT = 280,
+        MQ_OPEN = 240,
+        MQ_UNLINK = 241,
+        MQ_TIMEDSEND = 242,
+        MQ_TIMEDRECEIVE = 243,
+        MQ_NOTIFY = 244,
+        MQ_GETSETATTR = 245,
         RENAMEAT = 264,
         RENAMEAT2 = 316,
         COPYFILERANGE = 326,
@@ -1,11 +1,14 @@
 //! 对文件系统的管理,包括目录项的创建、文件权限设置等内容
-use axfs::api::{remove_dir, remove_file, rename, OpenFlags, Permissions};
+use axerrno::AxError;
+use axfs::api::{
+    create_node, remove_dir, remove_file, rename, FileIOType, FileType, OpenFlags, Permissions,
+};
 use axlog::{debug, error, info};
 use core::ptr::copy_nonoverlapping;
 
 use crate::{
     syscall_fs::ctype::{file::new_fd, FileDesc},
-    DirEnt, DirEntType, Fcntl64Cmd, RenameFlags, SyscallError, SyscallResult, TimeSecs,
+    DirEnt, DirEntType, Fcntl64Cmd, RenameFlags, StMode, SyscallError, SyscallResult, TimeSecs,
 };
 use axhal::mem::VirtAddr;
 use axprocess::{
@@ -26,12 +29,12 @@ use alloc::string::ToString;
 /// 成功执行,则返回当前工作目录的字符串的指针。失败,则返回NULL。
 /// 暂时:成功执行,则返回当前工作目录的字符串的指针 as isize。失败,返回0。
 ///
-/// TODO: 当前写法存在问题,cwd应当是各个进程独立的,而这里修改的是整个fs的目录
+/// 工作目录是各个进程独立的，CLONE_FS 创建的进程之间共享
 pub fn syscall_getcwd(args: [usize; 6]) -> SyscallResult {
     let buf = args[0] as *mut u8;
     let len = args[1];
     debug!("Into syscall_getcwd. buf: {}, len: {}", buf as usize, len);
-    let cwd = axfs::api::current_dir().unwrap();
+    let cwd = current_process().get_cwd();
 
     // todo: 如果buf为NULL,则系统分配缓存区
     // let process = current_process();
@@ -109,6 +112,64 @@ pub fn syscall_mkdir(args: [usize; 6]) -> SyscallResult {
     syscall_mkdirat(temp_args)
 }
 
+/// st_mode 中表示文件类型的位
+const S_IFMT: u32 = 0o170000;
+
+/// 功能:创建文件系统节点；
+/// # Arguments
+/// * `dir_fd`: usize, 节点所在的目录的文件描述符。
+/// * `path`: *const u8, 节点的名称。如果path是相对路径,则它是相对于dir_fd目录而言的。如果path是相对路径,且dir_fd的值为AT_FDCWD,则它是相对于当前路径而言的。如果path是绝对路径,则dir_fd被忽略。
+/// * `mode`: u32, 节点的类型与权限。类型支持 S_IFIFO、S_IFSOCK 与 S_IFREG,为 0 时等同于 S_IFREG。权限会去掉 umask 中的位。
+/// * `dev`: usize, 设备文件的设备号。当前不支持创建设备文件。
+/// # Return
+/// 成功执行,返回0。失败,返回-1。
+pub fn syscall_mknodat(args: [usize; 6]) -> SyscallResult {
+    let dir_fd = args[0];
+    let path = args[1] as *const u8;
+    let mode = args[2] as u32;
+    let _dev = args[3];
+    let path = if let Some(path) = deal_with_path(dir_fd, Some(path), false) {
+        path
+    } else {
+        return Err(SyscallError::EINVAL);
+    };
+    debug!(
+        "Into syscall_mknodat. dirfd: {}, path: {:?}, mode: {:o}",
+        dir_fd,
+        path.path(),
+        mode
+    );
+    let kind = mode & S_IFMT;
+    let ty = if kind == 0 || kind == StMode::S_IFREG.bits() {
+        FileType::File
+    } else if kind == StMode::S_IFIFO.bits() {
+        FileType::Fifo
+    } else if kind == StMode::S_IFSOCK.bits() {
+        FileType::Socket
+    } else if kind == StMode::S_IFCHR.bits() || kind == StMode::S_IFBLK.bits() {
+        // 没有设备驱动与设备号的对应关系，无法创建设备文件
+        return Err(SyscallError::EPERM);
+    } else {
+        return Err(SyscallError::EINVAL);
+    };
+    let umask = current_process().fd_manager.get_mask() as u32;
+    let perm = Permissions::from_bits_truncate((mode & 0o777 & !umask) as u16);
+    create_node(path.path(), ty, perm)?;
+    Ok(0)
+}
+
+/// 功能:创建文件系统节点；
+/// # Arguments
+/// * `path`: *const u8, 节点的名称。
+/// * `mode`: u32, 节点的类型与权限。
+/// * `dev`: usize, 设备文件的设备号。
+/// # Return
+/// 成功执行,返回0。失败,返回-1。
+pub fn syscall_mknod(args: [usize; 6]) -> SyscallResult {
+    let temp_args = [AT_FDCWD, args[0], args[1], args[2], 0, 0];
+    syscall_mknodat(temp_args)
+}
+
 /// 功能:切换工作目录；
 /// # Arguments
 /// * `path``: *const u8, 需要切换到的目录。
@@ -129,6 +190,55 @@ pub fn syscall_chdir(args: [usize; 6]) -> SyscallResult {
     }
 }
 
+/// 功能:根据文件描述符切换工作目录；
+/// # Arguments
+/// * `fd`: usize, 需要切换到的目录的文件描述符。
+/// # Return
+/// 成功执行:返回0。失败, 返回-1。
+pub fn syscall_fchdir(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let process = current_process();
+    let file = match process.fd_manager.fd_table.lock().get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+    if file.get_type() != FileIOType::DirDesc {
+        return Err(SyscallError::ENOTDIR);
+    }
+    debug!(
+        "Into syscall_fchdir. fd: {}, path: {:?}",
+        fd,
+        file.get_path()
+    );
+    match axfs::api::set_current_dir(file.get_path().as_str()) {
+        Ok(_) => Ok(0),
+        Err(AxError::PermissionDenied) => Err(SyscallError::EACCES),
+        Err(_) => Err(SyscallError::ENOTDIR),
+    }
+}
+
+/// 功能:切换当前进程的根目录，不会改变工作目录；
+/// # Arguments
+/// * `path`: *const u8, 新的根目录。
+/// # Return
+/// 成功执行:返回0。失败, 返回-1。
+pub fn syscall_chroot(args: [usize; 6]) -> SyscallResult {
+    let path = args[0] as *const u8;
+    let path = if let Some(path) = deal_with_path(AT_FDCWD, Some(path), true) {
+        path
+    } else {
+        return Err(SyscallError::EINVAL);
+    };
+    debug!("Into syscall_chroot. path: {:?}", path.path());
+    match axfs::api::set_root_dir(path.path()) {
+        Ok(_) => Ok(0),
+        Err(AxError::NotFound) => Err(SyscallError::ENOENT),
+        Err(AxError::NotADirectory) => Err(SyscallError::ENOTDIR),
+        Err(AxError::PermissionDenied) => Err(SyscallError::EACCES),
+        Err(_) => Err(SyscallError::EINVAL),
+    }
+}
+
 /// To get the dirent structures from the directory referred to by the open file descriptor fd into the buffer
 /// # Arguments
 /// * `fd`: usize, the file descriptor of the directory to be read
@@ -381,18 +491,20 @@ pub fn syscall_fcntl64(args: [usize; 6]) -> SyscallResult {
             Ok(new_fd as isize)
         }
         Ok(Fcntl64Cmd::F_GETFD) => {
-            if file.get_status().contains(OpenFlags::CLOEXEC) {
+            if process.fd_manager.is_cloexec(fd) || file.get_status().contains(OpenFlags::CLOEXEC) {
                 Ok(1)
             } else {
                 Ok(0)
             }
         }
         Ok(Fcntl64Cmd::F_SETFD) => {
-            if file.set_close_on_exec((arg & 1) != 0) {
-                Ok(0)
-            } else {
-                Err(SyscallError::EINVAL)
+            let cloexec = (arg & 1) != 0;
+            process.fd_manager.set_cloexec(fd, cloexec);
+            if !cloexec {
+                // 清除 open 时通过 O_CLOEXEC 记录在文件状态中的标志
+                file.set_close_on_exec(false);
             }
+            Ok(0)
         }
         Ok(Fcntl64Cmd::F_GETFL) => Ok(file.get_status().bits() as isize),
         Ok(Fcntl64Cmd::F_SETFL) => {
@@ -410,13 +522,9 @@ pub fn syscall_fcntl64(args: [usize; 6]) -> SyscallResult {
                 // 文件描述符达到上限了
                 return Err(SyscallError::EMFILE);
             };
-
-            if file.set_close_on_exec((arg & 1) != 0) {
-                fd_table[new_fd] = fd_table[fd].clone();
-                Ok(new_fd as isize)
-            } else {
-                Err(SyscallError::EINVAL)
-            }
+            fd_table[new_fd] = fd_table[fd].clone();
+            process.fd_manager.set_cloexec(new_fd, true);
+            Ok(new_fd as isize)
         }
         _ => Err(SyscallError::EINVAL),
     }
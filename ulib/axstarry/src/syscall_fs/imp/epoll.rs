@@ -4,7 +4,10 @@
 extern crate alloc;
 use crate::{SyscallError, SyscallResult};
 use alloc::sync::Arc;
-use axhal::{mem::VirtAddr, time::current_ticks};
+use axhal::{
+    mem::VirtAddr,
+    time::{current_time_nanos, NANOS_PER_MILLIS},
+};
 use axprocess::current_process;
 
 use crate::syscall_fs::ctype::epoll::{EpollCtl, EpollEvent, EpollFile};
@@ -107,11 +110,13 @@ pub fn syscall_epoll_wait(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EBADF);
     };
 
-    let timeout = if timeout > 0 {
-        current_ticks() as usize + timeout as usize
+    // timeout 的单位为毫秒，为负数时一直等待
+    let timeout = if timeout >= 0 {
+        (current_time_nanos() + timeout as u64 * NANOS_PER_MILLIS) as usize
     } else {
         usize::MAX
     };
+    // 与 Linux 相同，epoll_wait 被信号打断后总是返回 EINTR，即使信号处理函数指定了 SA_RESTART
     let ret_events = epoll_file.epoll_wait(timeout);
     if ret_events.is_err() {
         return Err(SyscallError::EINTR);
@@ -1,21 +1,21 @@
 //! 负责与 IO 相关的系统调用
 extern crate alloc;
 use crate::syscall_net::Socket;
-use crate::{IoVec, SyscallError, SyscallResult};
+use crate::{broken_pipe, IoVec, SyscallError, SyscallResult, RLIMIT_FSIZE, RLIM_INFINITY};
 use alloc::string::ToString;
 use alloc::sync::Arc;
 use alloc::vec;
 use axerrno::AxError;
-use axfs::api::{FileIOType, OpenFlags, SeekFrom};
+use axfs::api::{lookup, FileIO, FileIOType, OpenFlags, SeekFrom, VfsNodeRef};
 
 use axlog::{debug, info};
 use axprocess::current_process;
-use axprocess::link::{create_link, deal_with_path, real_path, AT_FDCWD};
+use axprocess::link::{deal_with_path, AT_FDCWD};
 
 use crate::syscall_fs::ctype::{
     dir::new_dir,
     file::{new_fd, new_inode},
-    pipe::make_pipe,
+    pipe::{fifo_buffer, make_pipe, put_fifo_buffer, Pipe},
 };
 /// 功能:从一个文件描述符中读取；
 /// # Arguments
@@ -83,6 +83,8 @@ pub fn syscall_read(args: [usize; 6]) -> SyscallResult {
     match file.read(buf) {
         Ok(len) => Ok(len as isize),
         Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        // 阻塞时被信号打断，若信号处理函数指定了 SA_RESTART 则重新执行
+        Err(AxError::Interrupted) => Err(SyscallError::ERESTARTSYS),
         Err(AxError::InvalidInput) => Err(SyscallError::EINVAL),
         Err(_) => Err(SyscallError::EPERM),
     }
@@ -145,6 +147,13 @@ pub fn syscall_write(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EBADF);
     }
 
+    // 普通文件的写入受到 RLIMIT_FSIZE 的限制
+    let buf = if file.get_type() == FileIOType::FileDesc {
+        limit_file_size(&file, None, buf)?
+    } else {
+        buf
+    };
+
     // for sockets:
     // Sockets are "writable" when:
     // - connected and have space in tx buffer to write
@@ -153,15 +162,63 @@ pub fn syscall_write(args: [usize; 6]) -> SyscallResult {
 
     match file.write(buf) {
         Ok(len) => Ok(len as isize),
-        // socket with send half closed
-        // TODO: send a SIGPIPE signal to the process
-        Err(axerrno::AxError::ConnectionReset) => Err(SyscallError::EPIPE),
+        // pipe with read end closed, or socket with send half closed
+        Err(AxError::BrokenPipe) | Err(AxError::ConnectionReset) => {
+            #[cfg(feature = "net")]
+            let no_signal = file
+                .as_any()
+                .downcast_ref::<Socket>()
+                .map_or(false, |socket| socket.is_no_sigpipe());
+            #[cfg(not(feature = "net"))]
+            let no_signal = false;
+            Err(broken_pipe(no_signal))
+        }
         Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        Err(AxError::Interrupted) => Err(SyscallError::ERESTARTSYS),
         Err(AxError::InvalidInput) => Err(SyscallError::EINVAL),
         Err(_) => Err(SyscallError::EPERM),
     }
 }
 
+/// 按照 RLIMIT_FSIZE 截断写入普通文件的数据
+///
+/// `offset` 为写入的位置，为 `None` 时使用文件当前的写入位置。
+/// 写入位置已经达到上限时向当前线程发送 SIGXFSZ 并返回 EFBIG
+fn limit_file_size<'a>(
+    file: &Arc<dyn FileIO>,
+    offset: Option<u64>,
+    buf: &'a [u8],
+) -> Result<&'a [u8], SyscallError> {
+    let fsize_limit = current_process()
+        .get_rlimit(RLIMIT_FSIZE)
+        .map(|limit| limit.rlim_cur)
+        .unwrap_or(RLIM_INFINITY);
+    if fsize_limit == RLIM_INFINITY {
+        return Ok(buf);
+    }
+    let offset = offset.unwrap_or_else(|| {
+        // 追加模式下写入位置为文件末尾，而不是当前的读写指针
+        if file.get_status().is_append() {
+            file.get_stat().map_or(0, |stat| stat.st_size)
+        } else {
+            file.seek(SeekFrom::Current(0)).unwrap_or(0)
+        }
+    });
+    if offset >= fsize_limit {
+        #[cfg(feature = "signal")]
+        {
+            let tid = axtask::current().id().as_u64() as isize;
+            let _ = axprocess::signal::send_signal_to_thread(
+                tid,
+                axsignal::signal_no::SignalNo::SIGXFSZ as isize,
+            );
+        }
+        return Err(SyscallError::EFBIG);
+    }
+    let max_len = (fsize_limit - offset) as usize;
+    Ok(&buf[..buf.len().min(max_len)])
+}
+
 /// 从同一个文件描述符读取多个字符串
 /// # Arguments
 /// * `fd`: usize, 要读取文件的文件描述符。
@@ -333,6 +390,7 @@ pub fn syscall_dup3(args: [usize; 6]) -> SyscallResult {
     info!("dup3 fd {} to new fd {}", fd, new_fd);
     // 就算new_fd已经被打开了,也可以被重新替代掉
     fd_table[new_fd] = fd_table[fd].clone();
+    process.fd_manager.set_cloexec(new_fd, false);
     Ok(new_fd as isize)
 }
 
@@ -357,6 +415,17 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
     } else {
         return Err(SyscallError::EINVAL);
     };
+    // 打开命名管道可能阻塞，需要在锁住文件描述符表之前完成
+    let fifo = match lookup(path.path()) {
+        Ok(node) if node.get_attr().is_ok_and(|attr| attr.file_type().is_fifo()) => {
+            let flags = OpenFlags::from(flags);
+            if flags.creatable() && flags.new_creatable() {
+                return Err(SyscallError::EEXIST);
+            }
+            Some(open_fifo(node, flags)?)
+        }
+        _ => None,
+    };
     let process = current_process();
     let mut fd_table = process.fd_manager.fd_table.lock();
     let fd_num: usize = if let Ok(fd) = process.alloc_fd(&mut fd_table) {
@@ -365,6 +434,10 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EMFILE);
     };
     debug!("allocated fd_num: {}", fd_num);
+    if let Some(fifo) = fifo {
+        fd_table[fd_num] = Some(fifo);
+        return Ok(fd_num as isize);
+    }
     // 分配 inode
     new_inode(path.path().to_string()).unwrap();
     // 如果是DIR
@@ -380,21 +453,57 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
             Err(SyscallError::ENOENT)
         }
     }
-    // 如果是FILE,注意若创建了新文件,需要添加链接
+    // 如果是FILE
     else {
         debug!("open file");
-        if let Ok(file) = new_fd(path.path().to_string(), flags.into()) {
-            debug!("new file_desc successfully allocated");
-            fd_table[fd_num] = Some(Arc::new(file));
-            let _ = create_link(&path, &path); // 不需要检查是否成功,因为如果成功,说明是新建的文件,如果失败,说明已经存在了
-            Ok(fd_num as isize)
-        } else {
-            debug!("open file failed");
-            Err(SyscallError::ENOENT)
+        match new_fd(path.path().to_string(), flags.into()) {
+            Ok(file) => {
+                debug!("new file_desc successfully allocated");
+                fd_table[fd_num] = Some(Arc::new(file));
+                Ok(fd_num as isize)
+            }
+            // O_NOFOLLOW 时路径最后一级为符号链接
+            Err(AxError::FilesystemLoop) => Err(SyscallError::ELOOP),
+            Err(_) => {
+                debug!("open file failed");
+                Err(SyscallError::ENOENT)
+            }
         }
     }
 }
 
+/// 打开命名管道的一端
+///
+/// 只读打开时阻塞到有写端打开，只写打开时阻塞到有读端打开，读写打开不会阻塞。
+/// 设置了 O_NONBLOCK 时只读打开立即返回，没有读端时只写打开返回 ENXIO
+fn open_fifo(node: VfsNodeRef, flags: OpenFlags) -> Result<Arc<Pipe>, SyscallError> {
+    let buffer = fifo_buffer(node);
+    let (readable, writable) = (flags.readable(), flags.writable());
+    let non_block = flags.contains(OpenFlags::NON_BLOCK);
+    let ring_buffer = buffer.lock();
+    if non_block && !readable && ring_buffer.all_read_ends_closed() {
+        drop(ring_buffer);
+        put_fifo_buffer(&buffer);
+        return Err(SyscallError::ENXIO);
+    }
+    let ends = ring_buffer.ends();
+    let opens = if readable {
+        ring_buffer.write_opens()
+    } else {
+        ring_buffer.read_opens()
+    };
+    drop(ring_buffer);
+    // 缓冲区的引用交给打开的端，关闭时由它移除 FIFO_BUFFERS 中的表项
+    let fifo = Arc::new(Pipe::fifo_end_with_buffer(buffer, flags));
+    if non_block || readable == writable {
+        return Ok(fifo);
+    }
+    match ends.wait_for_peer(readable, opens) {
+        Ok(()) => Ok(fifo),
+        Err(_) => Err(SyscallError::ERESTARTSYS),
+    }
+}
+
 /// 功能:打开或创建一个文件；
 /// # Arguments
 /// * `path`: *const u8, filename是相对于当前工作目录来说的。
@@ -485,12 +594,18 @@ pub fn syscall_pwrite64(args: [usize; 6]) -> SyscallResult {
 
     let file = process.fd_manager.fd_table.lock()[fd].clone().unwrap();
 
+    let buf = unsafe { core::slice::from_raw_parts(buf, count) };
+    let buf = if file.get_type() == FileIOType::FileDesc {
+        limit_file_size(&file, Some(offset as u64), buf)?
+    } else {
+        buf
+    };
+
     let old_offset = file.seek(SeekFrom::Current(0)).unwrap();
 
-    let ret = file.seek(SeekFrom::Start(offset as u64)).and_then(|_| {
-        let res = file.write(unsafe { core::slice::from_raw_parts(buf, count) });
-        res
-    });
+    let ret = file
+        .seek(SeekFrom::Start(offset as u64))
+        .and_then(|_| file.write(buf));
 
     file.seek(SeekFrom::Start(old_offset)).unwrap();
     drop(file);
@@ -598,15 +713,15 @@ pub fn syscall_readlinkat(args: [usize; 6]) -> SyscallResult {
         return Ok(file_real_path.len() as isize);
     }
 
-    if path.path().to_string() != real_path(&(path.path().to_string())) {
-        // 说明链接存在
-        let path = path.path();
-        let len = bufsiz.min(path.len());
-        let slice = unsafe { core::slice::from_raw_parts_mut(buf, len) };
-        slice.copy_from_slice(&path.as_bytes()[..len]);
-        return Ok(path.len() as isize);
+    match axfs::api::read_link(path.path()) {
+        Ok(target) => {
+            let len = bufsiz.min(target.len());
+            let slice = unsafe { core::slice::from_raw_parts_mut(buf, len) };
+            slice.copy_from_slice(&target.as_bytes()[..len]);
+            Ok(len as isize)
+        }
+        Err(e) => Err(e.into()),
     }
-    Err(SyscallError::EINVAL)
 }
 
 /// readlinkat
@@ -5,27 +5,33 @@ extern crate alloc;
 
 use crate::{SyscallError, SyscallResult};
 use axlog::debug;
-use axprocess::link::{create_link, deal_with_path, remove_link, FilePath};
+use axprocess::current_process;
+use axprocess::link::{deal_with_path, raw_ptr_to_ref_str, FilePath, AT_FDCWD};
 
 /// Special value used to indicate openat should use the current working directory.
 pub const AT_REMOVEDIR: usize = 0x200; // Remove directory instead of unlinking file.
 
+/// 跟随 old_path 最后一级的符号链接
+pub const AT_SYMLINK_FOLLOW: usize = 0x400;
+
 /// 功能:创建文件的链接；
 /// # Arguments
 /// * `old_dir_fd`: usize, 原来的文件所在目录的文件描述符。
 /// * `old_path`: *const u8, 文件原来的名字。如果old_path是相对路径,则它是相对于old_dir_fd目录而言的。如果old_path是相对路径,且old_dir_fd的值为AT_FDCWD,则它是相对于当前路径而言的。如果old_path是绝对路径,则old_dir_fd被忽略。
 /// * `new_dir_fd`: usize, 新文件名所在的目录。
 /// * `new_path`: *const u8, 文件的新名字。new_path的使用规则同old_path。
-/// * `flags`: usize, 在2.6.18内核之前,应置为0。其它的值详见`man 2 linkat`。
+/// * `flags`: usize, 可设置为0或AT_SYMLINK_FOLLOW。其它的值详见`man 2 linkat`。
 /// # Return
 /// 成功执行,返回0。失败,返回-1。
-#[allow(dead_code)]
 pub fn sys_linkat(args: [usize; 6]) -> SyscallResult {
     let old_dir_fd = args[0];
     let old_path = args[1] as *const u8;
     let new_dir_fd = args[2];
     let new_path = args[3] as *const u8;
-    let _flags = args[4];
+    let flags = args[4];
+    if flags & !AT_SYMLINK_FOLLOW != 0 {
+        return Err(SyscallError::EINVAL);
+    }
 
     let old_path = if let Some(path) = deal_with_path(old_dir_fd, Some(old_path), false) {
         path
@@ -37,11 +43,60 @@ pub fn sys_linkat(args: [usize; 6]) -> SyscallResult {
     } else {
         return Err(SyscallError::EINVAL);
     };
-    if create_link(&old_path, &new_path) {
-        Ok(0)
-    } else {
-        Err(SyscallError::EINVAL)
+    axfs::api::hard_link(
+        old_path.path(),
+        new_path.path(),
+        flags & AT_SYMLINK_FOLLOW != 0,
+    )?;
+    Ok(0)
+}
+
+/// 功能:创建文件的链接；
+/// # Arguments
+/// * `old_path`: *const u8, 文件原来的名字。
+/// * `new_path`: *const u8, 文件的新名字。
+pub fn syscall_link(args: [usize; 6]) -> SyscallResult {
+    let temp_args = [AT_FDCWD, args[0], AT_FDCWD, args[1], 0, 0];
+    sys_linkat(temp_args)
+}
+
+/// 功能:创建符号链接；
+/// # Arguments
+/// * `target`: *const u8, 符号链接的内容,不要求其指向的文件存在。
+/// * `new_dir_fd`: usize, 符号链接所在的目录。
+/// * `link_path`: *const u8, 符号链接的名字。如果link_path是相对路径,则它是相对于new_dir_fd目录而言的。
+/// # Return
+/// 成功执行,返回0。失败,返回-1。
+pub fn syscall_symlinkat(args: [usize; 6]) -> SyscallResult {
+    let target = args[0] as *const u8;
+    let new_dir_fd = args[1];
+    let link_path = args[2] as *const u8;
+    let process = current_process();
+    if target.is_null()
+        || process
+            .manual_alloc_for_lazy((target as usize).into())
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
     }
+    // 符号链接的内容原样保存，不做规范化
+    let target = unsafe { raw_ptr_to_ref_str(target) };
+    let link_path = if let Some(path) = deal_with_path(new_dir_fd, Some(link_path), false) {
+        path
+    } else {
+        return Err(SyscallError::EINVAL);
+    };
+    axfs::api::symlink(target, link_path.path())?;
+    Ok(0)
+}
+
+/// 功能:创建符号链接；
+/// # Arguments
+/// * `target`: *const u8, 符号链接的内容。
+/// * `link_path`: *const u8, 符号链接的名字。
+pub fn syscall_symlink(args: [usize; 6]) -> SyscallResult {
+    let temp_args = [args[0], AT_FDCWD, args[1], 0, 0, 0];
+    syscall_symlinkat(temp_args)
 }
 
 /// 功能:移除指定文件的链接
@@ -51,7 +106,7 @@ pub fn sys_linkat(args: [usize; 6]) -> SyscallResult {
 /// 成功执行,返回0。失败,返回-1。
 pub fn syscall_unlink(args: [usize; 6]) -> SyscallResult {
     let path = args[0] as *const u8;
-    let temp_args = [axprocess::link::AT_FDCWD, path as usize, 0, 0, 0, 0];
+    let temp_args = [AT_FDCWD, path as usize, 0, 0, 0, 0];
     syscall_unlinkat(temp_args)
 }
 
@@ -74,9 +129,10 @@ pub fn syscall_unlinkat(args: [usize; 6]) -> SyscallResult {
 
     // unlink file
     if flags == 0 {
-        if remove_link(&path).is_none() {
-            debug!("unlink file error");
-            return Err(SyscallError::EINVAL);
+        // 若为符号链接，则删除链接本身
+        if let Err(e) = axfs::api::remove_file(path.path()) {
+            debug!("unlink file error: {:?}", e);
+            return Err(e.into());
         }
     }
     // remove dir
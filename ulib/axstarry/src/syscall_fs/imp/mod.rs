@@ -7,7 +7,10 @@ mod eventfd;
 mod io;
 mod link;
 mod mount;
+mod mqueue;
 mod poll;
+#[cfg(feature = "signal")]
+mod signalfd;
 mod stat;
 pub use ctl::*;
 pub use epoll::*;
@@ -15,5 +18,8 @@ pub use eventfd::*;
 pub use io::*;
 pub use link::*;
 pub use mount::*;
+pub use mqueue::*;
 pub use poll::*;
+#[cfg(feature = "signal")]
+pub use signalfd::*;
 pub use stat::*;
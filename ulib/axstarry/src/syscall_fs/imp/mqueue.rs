@@ -0,0 +1,382 @@
+//! POSIX 消息队列相关的系统调用
+//!
+//! 消息队列是挂载在 `/dev/mqueue` 的 mqueue 文件系统中的文件，mq_open 返回的描述符为普通的文件描述符
+use alloc::sync::Arc;
+use axerrno::AxError;
+use axfs::api::{mqueue_dir, FileIO, OpenFlags};
+use axfs::axfs_mqueuefs::{
+    MqAttr, MqNotify, DFLT_MSGMAX, DFLT_MSGSIZEMAX, HARD_MSGMAX, HARD_MSGSIZEMAX, MQ_PRIO_MAX,
+};
+use axhal::time::current_time_nanos;
+use axprocess::{current_process, link::raw_ptr_to_ref_str};
+use core::time::Duration;
+
+use crate::syscall_fs::ctype::mqueue::MessageQueueDesc;
+use crate::{
+    interrupted, SigEvent, SyscallError, SyscallResult, TimeSecs, SIGEV_NONE, SIGEV_SIGNAL,
+};
+
+/// 消息队列名称的最大长度
+const NAME_MAX: usize = 255;
+
+/// 最大的信号编号
+const MAX_SIG_NUM: i32 = 64;
+
+/// 读取并检查消息队列的名称，libc 已经去掉了名称开头的 '/'
+fn mq_name(name: *const u8) -> Result<&'static str, SyscallError> {
+    let process = current_process();
+    if name.is_null()
+        || process
+            .manual_alloc_for_lazy((name as usize).into())
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let name = unsafe { raw_ptr_to_ref_str(name) };
+    if name.is_empty() {
+        Err(SyscallError::ENOENT)
+    } else if name.len() > NAME_MAX {
+        Err(SyscallError::ENAMETOOLONG)
+    } else if name == "." || name == ".." || name.contains('/') {
+        Err(SyscallError::EACCES)
+    } else {
+        Ok(name)
+    }
+}
+
+/// 根据描述符获取消息队列，描述符不是消息队列时返回 `EBADF`
+fn mq_file(mqdes: usize) -> Result<Arc<dyn FileIO>, SyscallError> {
+    let process = current_process();
+    let fd_table = process.fd_manager.fd_table.lock();
+    match fd_table.get(mqdes) {
+        Some(Some(file)) if file.as_any().is::<MessageQueueDesc>() => Ok(file.clone()),
+        _ => Err(SyscallError::EBADF),
+    }
+}
+
+/// 读取绝对时间形式的超时时间，返回以纳秒表示的截止时间
+fn mq_deadline(abs_timeout: *const TimeSecs) -> Result<Option<usize>, SyscallError> {
+    if abs_timeout.is_null() {
+        return Ok(None);
+    }
+    if current_process()
+        .manual_alloc_type_for_lazy(abs_timeout)
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let timeout = unsafe { *abs_timeout };
+    if timeout.tv_nsec >= 1_000_000_000 || (timeout.tv_sec as isize) < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    Ok(Some(timeout.turn_to_nanos()))
+}
+
+/// 距离截止时间的剩余时间，已经超时时返回 `ETIMEDOUT`
+fn mq_timeout(deadline: Option<usize>) -> Result<Option<Duration>, SyscallError> {
+    let Some(deadline) = deadline else {
+        return Ok(None);
+    };
+    let now = current_time_nanos() as usize;
+    if now >= deadline {
+        return Err(SyscallError::ETIMEDOUT);
+    }
+    Ok(Some(Duration::from_nanos((deadline - now) as u64)))
+}
+
+/// 消息到达空队列时通知 mq_notify 注册的进程
+fn deliver_notify(notify: MqNotify) {
+    if notify.notify != SIGEV_SIGNAL {
+        return;
+    }
+    #[cfg(feature = "signal")]
+    {
+        let info = axsignal::info::SigInfo::mesgq(
+            notify.signo,
+            current_process().pid() as i32,
+            0,
+            notify.value,
+        );
+        // 注册的进程可能已经退出
+        let _ = axprocess::signal::send_signal_to_process_with_info(
+            notify.pid as isize,
+            notify.signo as isize,
+            Some(info),
+        );
+    }
+}
+
+/// 打开或创建消息队列，返回消息队列描述符
+///
+/// # Arguments
+/// * `name` - *const u8, 不含开头的 '/'
+/// * `oflag` - usize, 支持 O_RDONLY、O_WRONLY、O_RDWR、O_CREAT、O_EXCL、O_NONBLOCK 与 O_CLOEXEC
+/// * `mode` - usize, 创建时队列文件的权限位
+/// * `attr` - *const MqAttr, 创建时队列的容量，为空时使用默认值
+pub fn syscall_mq_open(args: [usize; 6]) -> SyscallResult {
+    let name = mq_name(args[0] as *const u8)?;
+    let oflag = args[1];
+    let mode = args[2];
+    let attr = args[3] as *const MqAttr;
+    let flags = OpenFlags::from_bits_truncate(oflag as u32);
+    if flags.contains(OpenFlags::WRONLY | OpenFlags::RDWR) {
+        return Err(SyscallError::EINVAL);
+    }
+    let process = current_process();
+    let dir = mqueue_dir();
+    let queue = if let Some(queue) = dir.get_queue(name) {
+        if flags.contains(OpenFlags::CREATE | OpenFlags::EXCLUSIVE) {
+            return Err(SyscallError::EEXIST);
+        }
+        queue
+    } else if flags.contains(OpenFlags::CREATE) {
+        let (max_msg, msg_size) = if attr.is_null() {
+            (DFLT_MSGMAX, DFLT_MSGSIZEMAX)
+        } else {
+            if process.manual_alloc_type_for_lazy(attr).is_err() {
+                return Err(SyscallError::EFAULT);
+            }
+            let attr = unsafe { *attr };
+            if attr.mq_maxmsg <= 0
+                || attr.mq_msgsize <= 0
+                || attr.mq_maxmsg as usize > HARD_MSGMAX
+                || attr.mq_msgsize as usize > HARD_MSGSIZEMAX
+            {
+                return Err(SyscallError::EINVAL);
+            }
+            (attr.mq_maxmsg as usize, attr.mq_msgsize as usize)
+        };
+        dir.create_queue(name, (mode & 0o777) as u16, max_msg, msg_size)?
+    } else {
+        return Err(SyscallError::ENOENT);
+    };
+    let flags =
+        flags & (OpenFlags::WRONLY | OpenFlags::RDWR | OpenFlags::NON_BLOCK | OpenFlags::CLOEXEC);
+    let mut fd_table = process.fd_manager.fd_table.lock();
+    let fd_num = if let Ok(fd) = process.alloc_fd(&mut fd_table) {
+        fd
+    } else {
+        return Err(SyscallError::EMFILE);
+    };
+    fd_table[fd_num] = Some(Arc::new(MessageQueueDesc::new(queue, flags)));
+    Ok(fd_num as isize)
+}
+
+/// 删除消息队列，已经打开该队列的进程仍然可以继续使用
+///
+/// # Arguments
+/// * `name` - *const u8, 不含开头的 '/'
+pub fn syscall_mq_unlink(args: [usize; 6]) -> SyscallResult {
+    let name = mq_name(args[0] as *const u8)?;
+    mqueue_dir().remove_queue(name)?;
+    Ok(0)
+}
+
+/// 向消息队列发送一条消息，队列已满时阻塞，直到超时或者被信号打断
+///
+/// 被信号打断时总是返回 `EINTR`，不会重新执行
+/// # Arguments
+/// * `mqdes` - usize
+/// * `msg_ptr` - *const u8
+/// * `msg_len` - usize
+/// * `msg_prio` - u32, 必须小于 MQ_PRIO_MAX
+/// * `abs_timeout` - *const TimeSecs, 为空时不会超时
+pub fn syscall_mq_timedsend(args: [usize; 6]) -> SyscallResult {
+    let mqdes = args[0];
+    let msg_ptr = args[1] as *const u8;
+    let msg_len = args[2];
+    let msg_prio = args[3] as u32;
+    let deadline = mq_deadline(args[4] as *const TimeSecs)?;
+    let file = mq_file(mqdes)?;
+    let desc = file
+        .as_any()
+        .downcast_ref::<MessageQueueDesc>()
+        .ok_or(SyscallError::EBADF)?;
+    if !desc.writable() {
+        return Err(SyscallError::EBADF);
+    }
+    let queue = desc.queue();
+    if msg_len > queue.msg_size() {
+        return Err(SyscallError::EMSGSIZE);
+    }
+    if msg_prio >= MQ_PRIO_MAX {
+        return Err(SyscallError::EINVAL);
+    }
+    if current_process()
+        .manual_alloc_range_for_lazy(
+            (msg_ptr as usize).into(),
+            (msg_ptr as usize + msg_len).into(),
+        )
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let data = unsafe { core::slice::from_raw_parts(msg_ptr, msg_len) };
+    loop {
+        match queue.try_send(data, msg_prio) {
+            Err(AxError::WouldBlock) if !desc.is_non_block() => {}
+            result => {
+                if let Some(notify) = result? {
+                    deliver_notify(notify);
+                }
+                return Ok(0);
+            }
+        }
+        let timeout = mq_timeout(deadline)?;
+        if interrupted() {
+            return Err(SyscallError::EINTR);
+        }
+        queue.wait_writable(timeout);
+    }
+}
+
+/// 从消息队列取出优先级最高的消息中最早的一条，返回消息的长度。队列为空时阻塞，直到超时或者被信号打断
+///
+/// 被信号打断时总是返回 `EINTR`，不会重新执行
+/// # Arguments
+/// * `mqdes` - usize
+/// * `msg_ptr` - *mut u8
+/// * `msg_len` - usize, 不能小于队列的 mq_msgsize
+/// * `msg_prio` - *mut u32, 不为空时写入消息的优先级
+/// * `abs_timeout` - *const TimeSecs, 为空时不会超时
+pub fn syscall_mq_timedreceive(args: [usize; 6]) -> SyscallResult {
+    let mqdes = args[0];
+    let msg_ptr = args[1] as *mut u8;
+    let msg_len = args[2];
+    let msg_prio = args[3] as *mut u32;
+    let deadline = mq_deadline(args[4] as *const TimeSecs)?;
+    let file = mq_file(mqdes)?;
+    let desc = file
+        .as_any()
+        .downcast_ref::<MessageQueueDesc>()
+        .ok_or(SyscallError::EBADF)?;
+    if !desc.readable() {
+        return Err(SyscallError::EBADF);
+    }
+    let queue = desc.queue();
+    if msg_len < queue.msg_size() {
+        return Err(SyscallError::EMSGSIZE);
+    }
+    // 取出消息之前检查地址，避免消息丢失
+    let process = current_process();
+    if process
+        .manual_alloc_range_for_lazy(
+            (msg_ptr as usize).into(),
+            (msg_ptr as usize + msg_len).into(),
+        )
+        .is_err()
+        || (!msg_prio.is_null() && process.manual_alloc_type_for_lazy(msg_prio).is_err())
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let mut waiting = false;
+    let result = loop {
+        match queue.try_receive() {
+            Err(AxError::WouldBlock) if !desc.is_non_block() => {}
+            result => break result.map_err(SyscallError::from),
+        }
+        // 有任务在等待时，新消息直接交给等待者，不发送 mq_notify 的通知
+        if !waiting {
+            queue.inc_waiting_receivers();
+            waiting = true;
+        }
+        let timeout = match mq_timeout(deadline) {
+            Ok(timeout) => timeout,
+            Err(err) => break Err(err),
+        };
+        if interrupted() {
+            break Err(SyscallError::EINTR);
+        }
+        queue.wait_readable(timeout);
+    };
+    if waiting {
+        queue.dec_waiting_receivers();
+    }
+    let (data, priority) = result?;
+    unsafe {
+        core::ptr::copy_nonoverlapping(data.as_ptr(), msg_ptr, data.len());
+        if !msg_prio.is_null() {
+            *msg_prio = priority;
+        }
+    }
+    Ok(data.len() as isize)
+}
+
+/// 注册或取消消息到达空队列时的通知，每个队列同时只能有一个进程注册，通知一次后注册即被取消
+///
+/// # Arguments
+/// * `mqdes` - usize
+/// * `sevp` - *const SigEvent, 为空时取消当前进程的注册。通知方式支持 SIGEV_SIGNAL 与 SIGEV_NONE
+pub fn syscall_mq_notify(args: [usize; 6]) -> SyscallResult {
+    let mqdes = args[0];
+    let sevp = args[1] as *const SigEvent;
+    let file = mq_file(mqdes)?;
+    let desc = file
+        .as_any()
+        .downcast_ref::<MessageQueueDesc>()
+        .ok_or(SyscallError::EBADF)?;
+    let process = current_process();
+    if sevp.is_null() {
+        desc.queue().unregister_notify(process.pid());
+        return Ok(0);
+    }
+    if process.manual_alloc_type_for_lazy(sevp).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let sev = unsafe { *sevp };
+    match sev.sigev_notify {
+        SIGEV_NONE => {}
+        SIGEV_SIGNAL if sev.sigev_signo > 0 && sev.sigev_signo <= MAX_SIG_NUM => {}
+        // SIGEV_THREAD 需要 netlink 套接字，暂不支持
+        _ => return Err(SyscallError::EINVAL),
+    }
+    desc.queue().register_notify(MqNotify {
+        pid: process.pid(),
+        notify: sev.sigev_notify,
+        signo: sev.sigev_signo,
+        value: sev.sigev_value,
+    })?;
+    Ok(0)
+}
+
+/// 获取并修改消息队列描述符的属性，只有 mq_flags 中的 O_NONBLOCK 可以修改
+///
+/// # Arguments
+/// * `mqdes` - usize
+/// * `newattr` - *const MqAttr, 为空时不修改
+/// * `oldattr` - *mut MqAttr, 不为空时写入修改前的属性
+pub fn syscall_mq_getsetattr(args: [usize; 6]) -> SyscallResult {
+    let mqdes = args[0];
+    let newattr = args[1] as *const MqAttr;
+    let oldattr = args[2] as *mut MqAttr;
+    let file = mq_file(mqdes)?;
+    let process = current_process();
+    let new_flags = if newattr.is_null() {
+        None
+    } else {
+        if process.manual_alloc_type_for_lazy(newattr).is_err() {
+            return Err(SyscallError::EFAULT);
+        }
+        let flags = unsafe { (*newattr).mq_flags };
+        if flags & !(OpenFlags::NON_BLOCK.bits() as i64) != 0 {
+            return Err(SyscallError::EINVAL);
+        }
+        Some(OpenFlags::from_bits_truncate(flags as u32))
+    };
+    if !oldattr.is_null() {
+        if process.manual_alloc_type_for_lazy(oldattr).is_err() {
+            return Err(SyscallError::EFAULT);
+        }
+        let desc = file
+            .as_any()
+            .downcast_ref::<MessageQueueDesc>()
+            .ok_or(SyscallError::EBADF)?;
+        let mut attr = desc.queue().attr();
+        attr.mq_flags = (file.get_status() & OpenFlags::NON_BLOCK).bits() as i64;
+        unsafe { *oldattr = attr };
+    }
+    if let Some(flags) = new_flags {
+        file.set_status(flags);
+    }
+    Ok(0)
+}
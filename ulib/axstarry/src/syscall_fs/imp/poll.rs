@@ -111,7 +111,9 @@ impl ShadowBitset {
 /// expire_time：时间戳,用来记录是否超时
 ///
 /// 返回值：(usize, Vec<PollFd>) 第一个参数遵守 ppoll 系统调用的返回值约定,第二个参数为返回的 `PollFd` 列表
-fn ppoll(mut fds: Vec<PollFd>, expire_time: usize) -> (isize, Vec<PollFd>) {
+///
+/// 被信号打断时返回 `ERESTARTNOHAND`
+fn ppoll(mut fds: Vec<PollFd>, expire_time: usize) -> Result<(isize, Vec<PollFd>), SyscallError> {
     loop {
         // 满足事件要求而被触发的事件描述符数量
         let mut set: isize = 0;
@@ -144,18 +146,18 @@ fn ppoll(mut fds: Vec<PollFd>, expire_time: usize) -> (isize, Vec<PollFd>) {
             }
         }
         if set > 0 {
-            return (set, fds);
+            return Ok((set, fds));
         }
         if current_ticks() as usize > expire_time {
             // 过期了,直接返回
-            return (0, fds);
+            return Ok((0, fds));
         }
         yield_now_task();
 
         #[cfg(feature = "signal")]
         if process.have_signals().is_some() {
-            // 有信号,此时停止处理,直接返回
-            return (0, fds);
+            // 有信号,此时停止处理,若信号没有处理函数则重新执行
+            return Err(SyscallError::ERESTARTNOHAND);
         }
     }
 }
@@ -199,7 +201,7 @@ pub fn syscall_ppoll(args: [usize; 6]) -> SyscallResult {
         usize::MAX
     };
 
-    let (set, ret_fds) = ppoll(fds, expire_time);
+    let (set, ret_fds) = ppoll(fds, expire_time)?;
     // 将得到的fd存储到原先的指针中
     for (i, fd) in ret_fds.iter().enumerate() {
         unsafe {
@@ -236,7 +238,7 @@ pub fn syscall_poll(args: [usize; 6]) -> SyscallResult {
     let expire_time =
         current_ticks() as usize + TimeVal::from_micro(timeout_msecs).turn_to_ticks() as usize;
 
-    let (set, ret_fds) = ppoll(fds, expire_time);
+    let (set, ret_fds) = ppoll(fds, expire_time)?;
     // 将得到的fd存储到原先的指针中
     for (i, fd) in ret_fds.iter().enumerate() {
         unsafe {
@@ -399,7 +401,7 @@ pub fn syscall_pselect6(args: [usize; 6]) -> SyscallResult {
         }
         #[cfg(feature = "signal")]
         if process.have_signals().is_some() {
-            return Err(SyscallError::EINTR);
+            return Err(SyscallError::ERESTARTNOHAND);
         }
     }
 }
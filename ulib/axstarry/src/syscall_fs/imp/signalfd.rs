@@ -0,0 +1,61 @@
+use alloc::sync::Arc;
+use axprocess::current_process;
+use axsignal::signal_no::SignalNo;
+
+use crate::syscall_fs::ctype::signalfd::{SignalFd, SignalFdFlag};
+use crate::{SyscallError, SyscallResult, SIGSET_SIZE_IN_BYTE};
+
+/// 创建 signalfd，或者修改已有 signalfd 接收的信号集合
+///
+/// SIGKILL 与 SIGSTOP 不能通过 signalfd 接收，会被静默地从信号集合中移除
+/// # Arguments
+/// * `fd` - i32, 为 -1 时创建新的 signalfd
+/// * `mask` - *const usize
+/// * `sizemask` - usize
+/// * `flags` - u32, SFD_NONBLOCK 与 SFD_CLOEXEC
+pub fn syscall_signalfd4(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0] as i32;
+    let mask = args[1] as *const usize;
+    let sizemask = args[2];
+    let flags = args[3] as u32;
+    if sizemask != SIGSET_SIZE_IN_BYTE || SignalFdFlag::from_bits(flags).is_none() {
+        return Err(SyscallError::EINVAL);
+    }
+    let process = current_process();
+    if process.manual_alloc_type_for_lazy(mask).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let mask = unsafe { *mask }
+        & !(1 << (SignalNo::SIGKILL as usize - 1))
+        & !(1 << (SignalNo::SIGSTOP as usize - 1));
+
+    let mut fd_table = process.fd_manager.fd_table.lock();
+    if fd != -1 {
+        let file = fd_table
+            .get(fd as usize)
+            .and_then(|file| file.as_ref())
+            .ok_or(SyscallError::EBADF)?;
+        let signal_fd = file
+            .as_any()
+            .downcast_ref::<SignalFd>()
+            .ok_or(SyscallError::EINVAL)?;
+        signal_fd.set_mask(mask);
+        return Ok(fd as isize);
+    }
+    let fd_num = if let Ok(fd) = process.alloc_fd(&mut fd_table) {
+        fd
+    } else {
+        return Err(SyscallError::EMFILE);
+    };
+    fd_table[fd_num] = Some(Arc::new(SignalFd::new(mask, flags)));
+    Ok(fd_num as isize)
+}
+
+/// signalfd 相当于 flags 为 0 的 signalfd4
+/// # Arguments
+/// * `fd` - i32
+/// * `mask` - *const usize
+/// * `sizemask` - usize
+pub fn syscall_signalfd(args: [usize; 6]) -> SyscallResult {
+    syscall_signalfd4([args[0], args[1], args[2], 0, 0, 0])
+}
@@ -49,15 +49,20 @@ pub fn syscall_fstat(args: [usize; 6]) -> SyscallResult {
     }
 }
 
+/// 不跟随路径最后一级的符号链接
+pub const AT_SYMLINK_NOFOLLOW: usize = 0x100;
+
 /// 获取文件状态信息，但是给出的是目录 fd 和相对路径。
 /// # Arguments
 /// * `dir_fd` - usize
 /// * `path` - *const u8
 /// * `kst` - *mut Kstat
+/// * `flags` - usize, 可包含 AT_SYMLINK_NOFOLLOW
 pub fn syscall_fstatat(args: [usize; 6]) -> SyscallResult {
     let dir_fd = args[0];
     let path = args[1] as *const u8;
     let kst = args[2] as *mut Kstat;
+    let nofollow = args[3] & AT_SYMLINK_NOFOLLOW != 0;
     let file_path = if let Some(file_path) = deal_with_path(dir_fd, Some(path), false) {
         // error!("test {:?}", file_path);
         file_path
@@ -76,10 +81,15 @@ pub fn syscall_fstatat(args: [usize; 6]) -> SyscallResult {
         panic!("Wrong path at syscall_fstatat: {}(dir_fd={})", path, dir_fd);
     };
     info!("path : {}", file_path.path());
-    if !axfs::api::path_exists(file_path.path()) {
+    let exists = if nofollow {
+        axfs::api::lookup_nofollow(file_path.path()).is_ok()
+    } else {
+        axfs::api::path_exists(file_path.path())
+    };
+    if !exists {
         return Err(SyscallError::ENOENT);
     }
-    match get_stat_in_fs(&file_path) {
+    match get_stat_in_fs(&file_path, nofollow) {
         Ok(stat) => unsafe {
             *kst = stat;
             Ok(0)
@@ -98,7 +108,7 @@ pub fn syscall_fstatat(args: [usize; 6]) -> SyscallResult {
 pub fn syscall_lstat(args: [usize; 6]) -> SyscallResult {
     let path = args[0];
     let kst = args[1];
-    let temp_args = [AT_FDCWD, path, kst, 0, 0, 0];
+    let temp_args = [AT_FDCWD, path, kst, AT_SYMLINK_NOFOLLOW, 0, 0];
     syscall_fstatat(temp_args)
 }
 
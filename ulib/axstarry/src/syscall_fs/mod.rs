@@ -23,7 +23,10 @@ pub fn fs_syscall(syscall_id: fs_syscall_id::FsSyscallId, args: [usize; 6]) -> S
         DUP => syscall_dup(args),
         DUP3 => syscall_dup3(args),
         MKDIRAT => syscall_mkdirat(args),
+        MKNODAT => syscall_mknodat(args),
         CHDIR => syscall_chdir(args),
+        FCHDIR => syscall_fchdir(args),
+        CHROOT => syscall_chroot(args),
         GETDENTS64 => syscall_getdents64(args),
         MOUNT => syscall_mount(args),
         UNMOUNT => syscall_umount(args),
@@ -51,6 +54,7 @@ pub fn fs_syscall(syscall_id: fs_syscall_id::FsSyscallId, args: [usize; 6]) -> S
         SYNC => Ok(0),
         COPYFILERANGE => syscall_copyfilerange(args),
         LINKAT => sys_linkat(args),
+        SYMLINKAT => syscall_symlinkat(args),
         UNLINKAT => syscall_unlinkat(args),
         UTIMENSAT => syscall_utimensat(args),
         EPOLL_CREATE => syscall_epoll_create1(args),
@@ -58,6 +62,16 @@ pub fn fs_syscall(syscall_id: fs_syscall_id::FsSyscallId, args: [usize; 6]) -> S
         EPOLL_WAIT => syscall_epoll_wait(args),
         PPOLL => syscall_ppoll(args),
         PSELECT6 => syscall_pselect6(args),
+        MQ_OPEN => syscall_mq_open(args),
+        MQ_UNLINK => syscall_mq_unlink(args),
+        MQ_TIMEDSEND => syscall_mq_timedsend(args),
+        MQ_TIMEDRECEIVE => syscall_mq_timedreceive(args),
+        MQ_NOTIFY => syscall_mq_notify(args),
+        MQ_GETSETATTR => syscall_mq_getsetattr(args),
+        #[cfg(feature = "signal")]
+        SIGNALFD4 => syscall_signalfd4(args),
+        #[cfg(not(feature = "signal"))]
+        SIGNALFD4 => Err(axerrno::LinuxError::ENOSYS),
 
         #[cfg(target_arch = "x86_64")]
         DUP2 => syscall_dup2(args),
@@ -69,15 +83,25 @@ pub fn fs_syscall(syscall_id: fs_syscall_id::FsSyscallId, args: [usize; 6]) -> S
         PIPE => syscall_pipe(args),
         #[cfg(target_arch = "x86_64")]
         POLL => syscall_poll(args),
+        #[cfg(all(target_arch = "x86_64", feature = "signal"))]
+        SIGNALFD => syscall_signalfd(args),
+        #[cfg(all(target_arch = "x86_64", not(feature = "signal")))]
+        SIGNALFD => Err(axerrno::LinuxError::ENOSYS),
         #[cfg(target_arch = "x86_64")]
         STAT => syscall_stat(args),
         #[cfg(target_arch = "x86_64")]
         UNLINK => syscall_unlink(args),
         #[cfg(target_arch = "x86_64")]
+        LINK => syscall_link(args),
+        #[cfg(target_arch = "x86_64")]
+        SYMLINK => syscall_symlink(args),
+        #[cfg(target_arch = "x86_64")]
         ACCESS => syscall_access(args),
         #[cfg(target_arch = "x86_64")]
         MKDIR => syscall_mkdir(args),
         #[cfg(target_arch = "x86_64")]
+        MKNOD => syscall_mknod(args),
+        #[cfg(target_arch = "x86_64")]
         RENAME => syscall_rename(args),
         #[cfg(target_arch = "x86_64")]
         RMDIR => syscall_rmdir(args),
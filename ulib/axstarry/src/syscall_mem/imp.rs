@@ -1,7 +1,14 @@
-use crate::{syscall_fs::FileDesc, MMAPFlags, SyscallError, SyscallResult, MMAPPROT};
+use crate::{
+    syscall_fs::FileDesc, MMAPFlags, SyscallError, SyscallResult, MMAPPROT, RLIMIT_AS, RLIMIT_DATA,
+    RLIM_INFINITY,
+};
 extern crate alloc;
 
-use axhal::{arch::flush_tlb, mem::VirtAddr, paging::MappingFlags};
+use axhal::{
+    arch::flush_tlb,
+    mem::{VirtAddr, PAGE_SIZE_4K},
+    paging::MappingFlags,
+};
 use axmem::MemorySet;
 
 use axprocess::current_process;
@@ -20,7 +27,16 @@ pub fn syscall_brk(args: [usize; 6]) -> SyscallResult {
     let curr_process = current_process();
     let mut return_val: isize = curr_process.get_heap_top() as isize;
     let heap_bottom = curr_process.get_heap_bottom() as usize;
-    if brk != 0 && brk >= heap_bottom && brk <= heap_bottom + MAX_HEAP_SIZE {
+    // 堆的大小同时受到 RLIMIT_DATA 的限制
+    let data_limit = curr_process
+        .get_rlimit(RLIMIT_DATA)
+        .map(|limit| limit.rlim_cur)
+        .unwrap_or(RLIM_INFINITY);
+    if brk != 0
+        && brk >= heap_bottom
+        && brk <= heap_bottom + MAX_HEAP_SIZE
+        && (data_limit == RLIM_INFINITY || (brk - heap_bottom) as u64 <= data_limit)
+    {
         curr_process.set_heap_top(brk as u64);
         return_val = brk as isize;
     }
@@ -57,6 +73,20 @@ pub fn syscall_mmap(args: [usize; 6]) -> SyscallResult {
 
     let process = current_process();
 
+    // 检查映射之后地址空间的大小是否超过了 RLIMIT_AS
+    let as_limit = process
+        .get_rlimit(RLIMIT_AS)
+        .map(|limit| limit.rlim_cur)
+        .unwrap_or(RLIM_INFINITY);
+    if as_limit != RLIM_INFINITY {
+        let total_size = process.memory_set.lock().lock().total_size();
+        let len = (len + PAGE_SIZE_4K - 1) / PAGE_SIZE_4K * PAGE_SIZE_4K;
+        if (total_size + len) as u64 > as_limit {
+            debug!("[mmap] exceed RLIMIT_AS: {:#x}", as_limit);
+            return Err(SyscallError::ENOMEM);
+        }
+    }
+
     let addr = if flags.contains(MMAPFlags::MAP_ANONYMOUS) {
         // no file
         if !(fd == -1 && offset == 0) {
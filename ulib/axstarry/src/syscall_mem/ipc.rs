@@ -0,0 +1,281 @@
+//! System V 消息队列与信号量相关的系统调用
+use crate::{interrupted, SyscallError, SyscallResult, TimeSecs};
+use alloc::vec::Vec;
+use axmem::ipc::{
+    get_msg_queue, get_or_create_msg_queue, get_or_create_sem_set, get_sem_set, remove_msg_queue,
+    remove_sem_set, MsqidDs, SemBuf, SemidDs, GETALL, GETNCNT, GETPID, GETVAL, GETZCNT, IPC_64,
+    IPC_NOWAIT, IPC_RMID, IPC_SET, IPC_STAT, MSGMAX, MSG_COPY, SEMOPM, SETALL, SETVAL,
+};
+use axprocess::current_process;
+use core::time::Duration;
+
+/// 获取或创建消息队列
+///
+/// # Arguments
+/// * `key` - i32
+/// * `msgflg` - i32
+pub fn syscall_msgget(args: [usize; 6]) -> SyscallResult {
+    let key = args[0] as i32;
+    let msgflg = args[1] as i32;
+    get_or_create_msg_queue(key, msgflg).map(|msqid| msqid as isize)
+}
+
+/// 向消息队列发送消息，`msgp` 指向 `struct msgbuf { long mtype; char mtext[msgsz]; }`
+///
+/// 被信号打断时总是返回 `EINTR`，不会重新执行
+///
+/// # Arguments
+/// * `msqid` - i32
+/// * `msgp` - *const i64
+/// * `msgsz` - usize
+/// * `msgflg` - i32
+pub fn syscall_msgsnd(args: [usize; 6]) -> SyscallResult {
+    let msqid = args[0] as i32;
+    let msgp = args[1] as *const i64;
+    let msgsz = args[2];
+    let msgflg = args[3] as i32;
+    if msqid < 0 || msgsz > MSGMAX {
+        return Err(SyscallError::EINVAL);
+    }
+    let process = current_process();
+    if process.manual_alloc_type_for_lazy(msgp).is_err()
+        || process
+            .manual_alloc_range_for_lazy((msgp as usize).into(), (msgp as usize + 8 + msgsz).into())
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let mtype = unsafe { *msgp };
+    if mtype <= 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let queue = get_msg_queue(msqid).ok_or(SyscallError::EINVAL)?;
+    let data = unsafe { core::slice::from_raw_parts(msgp.add(1) as *const u8, msgsz) };
+    queue.send(
+        mtype,
+        data,
+        process.pid() as i32,
+        msgflg & IPC_NOWAIT != 0,
+        interrupted,
+    )?;
+    Ok(0)
+}
+
+/// 从消息队列接收消息，返回复制到 `mtext` 中的字节数
+///
+/// 被信号打断时总是返回 `EINTR`，不会重新执行
+///
+/// # Arguments
+/// * `msqid` - i32
+/// * `msgp` - *mut i64
+/// * `msgsz` - usize
+/// * `msgtyp` - i64
+/// * `msgflg` - i32
+pub fn syscall_msgrcv(args: [usize; 6]) -> SyscallResult {
+    let msqid = args[0] as i32;
+    let msgp = args[1] as *mut i64;
+    let msgsz = args[2];
+    let msgtyp = args[3] as i64;
+    let msgflg = args[4] as i32;
+    if msqid < 0 || (msgsz as isize) < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if msgflg & MSG_COPY != 0 {
+        // 不支持 checkpoint/restore
+        return Err(SyscallError::ENOSYS);
+    }
+    let process = current_process();
+    let queue = get_msg_queue(msqid).ok_or(SyscallError::EINVAL)?;
+    let message = queue.receive(
+        msgtyp,
+        msgsz,
+        msgflg,
+        process.pid() as i32,
+        msgflg & IPC_NOWAIT != 0,
+        interrupted,
+    )?;
+    let len = message.data.len();
+    if process.manual_alloc_type_for_lazy(msgp).is_err()
+        || process
+            .manual_alloc_range_for_lazy((msgp as usize).into(), (msgp as usize + 8 + len).into())
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    unsafe {
+        *msgp = message.mtype;
+        core::ptr::copy_nonoverlapping(message.data.as_ptr(), msgp.add(1) as *mut u8, len);
+    }
+    Ok(len as isize)
+}
+
+/// 控制消息队列，支持 `IPC_STAT`、`IPC_SET` 与 `IPC_RMID`
+///
+/// # Arguments
+/// * `msqid` - i32
+/// * `cmd` - i32
+/// * `buf` - *mut MsqidDs
+pub fn syscall_msgctl(args: [usize; 6]) -> SyscallResult {
+    let msqid = args[0] as i32;
+    let cmd = args[1] as i32 & !IPC_64;
+    let buf = args[2] as *mut MsqidDs;
+    if msqid < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let process = current_process();
+    match cmd {
+        IPC_STAT => {
+            let queue = get_msg_queue(msqid).ok_or(SyscallError::EINVAL)?;
+            if process.manual_alloc_type_for_lazy(buf).is_err() {
+                return Err(SyscallError::EFAULT);
+            }
+            unsafe { *buf = queue.stat() };
+        }
+        IPC_SET => {
+            let queue = get_msg_queue(msqid).ok_or(SyscallError::EINVAL)?;
+            if process
+                .manual_alloc_type_for_lazy(buf as *const MsqidDs)
+                .is_err()
+            {
+                return Err(SyscallError::EFAULT);
+            }
+            queue.set(unsafe { &*buf })?;
+        }
+        IPC_RMID => remove_msg_queue(msqid)?,
+        _ => return Err(SyscallError::EINVAL),
+    }
+    Ok(0)
+}
+
+/// 获取或创建信号量集
+///
+/// # Arguments
+/// * `key` - i32
+/// * `nsems` - i32
+/// * `semflg` - i32
+pub fn syscall_semget(args: [usize; 6]) -> SyscallResult {
+    let key = args[0] as i32;
+    let nsems = args[1] as i32;
+    let semflg = args[2] as i32;
+    if nsems < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    get_or_create_sem_set(key, nsems as usize, semflg).map(|semid| semid as isize)
+}
+
+/// 原子地执行一组信号量操作，等价于不带超时时间的 semtimedop
+///
+/// # Arguments
+/// * `semid` - i32
+/// * `sops` - *const SemBuf
+/// * `nsops` - usize
+pub fn syscall_semop(args: [usize; 6]) -> SyscallResult {
+    syscall_semtimedop([args[0], args[1], args[2], 0, 0, 0])
+}
+
+/// 原子地执行一组信号量操作，无法全部执行时阻塞等待，`timeout` 为相对时间
+///
+/// 被信号打断时总是返回 `EINTR`，不会重新执行
+///
+/// # Arguments
+/// * `semid` - i32
+/// * `sops` - *const SemBuf
+/// * `nsops` - usize
+/// * `timeout` - *const TimeSecs
+pub fn syscall_semtimedop(args: [usize; 6]) -> SyscallResult {
+    let semid = args[0] as i32;
+    let sops = args[1] as *const SemBuf;
+    let nsops = args[2];
+    let timeout = args[3] as *const TimeSecs;
+    if semid < 0 || nsops == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if nsops > SEMOPM {
+        return Err(SyscallError::E2BIG);
+    }
+    let process = current_process();
+    if process
+        .manual_alloc_range_for_lazy(
+            (sops as usize).into(),
+            (sops as usize + nsops * core::mem::size_of::<SemBuf>()).into(),
+        )
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let ops: Vec<SemBuf> = unsafe { core::slice::from_raw_parts(sops, nsops) }.to_vec();
+    let timeout = if timeout.is_null() {
+        None
+    } else {
+        if process.manual_alloc_type_for_lazy(timeout).is_err() {
+            return Err(SyscallError::EFAULT);
+        }
+        let timeout = unsafe { *timeout };
+        if timeout.tv_nsec >= 1_000_000_000 {
+            return Err(SyscallError::EINVAL);
+        }
+        Some(Duration::from_nanos(timeout.turn_to_nanos() as u64))
+    };
+    let set = get_sem_set(semid).ok_or(SyscallError::EINVAL)?;
+    set.semop(&ops, process.pid(), timeout, interrupted)?;
+    Ok(0)
+}
+
+/// 控制信号量集，`arg` 为 `union semun`，根据 `cmd` 为整数值或指针
+///
+/// # Arguments
+/// * `semid` - i32
+/// * `semnum` - usize
+/// * `cmd` - i32
+/// * `arg` - usize
+pub fn syscall_semctl(args: [usize; 6]) -> SyscallResult {
+    let semid = args[0] as i32;
+    let semnum = args[1];
+    let cmd = args[2] as i32 & !IPC_64;
+    let arg = args[3];
+    if semid < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if cmd == IPC_RMID {
+        remove_sem_set(semid)?;
+        return Ok(0);
+    }
+    let process = current_process();
+    let set = get_sem_set(semid).ok_or(SyscallError::EINVAL)?;
+    match cmd {
+        IPC_STAT => {
+            let buf = arg as *mut SemidDs;
+            if process.manual_alloc_type_for_lazy(buf).is_err() {
+                return Err(SyscallError::EFAULT);
+            }
+            unsafe { *buf = set.stat() };
+        }
+        IPC_SET => {
+            let buf = arg as *const SemidDs;
+            if process.manual_alloc_type_for_lazy(buf).is_err() {
+                return Err(SyscallError::EFAULT);
+            }
+            set.set_stat(unsafe { &*buf });
+        }
+        GETVAL | GETPID | GETNCNT | GETZCNT => return Ok(set.get(semnum, cmd)? as isize),
+        SETVAL => set.set(semnum, arg as i32, process.pid())?,
+        GETALL | SETALL => {
+            let array = arg as *mut u16;
+            let nsems = set.nsems();
+            if process
+                .manual_alloc_range_for_lazy(arg.into(), (arg + nsems * 2).into())
+                .is_err()
+            {
+                return Err(SyscallError::EFAULT);
+            }
+            let array = unsafe { core::slice::from_raw_parts_mut(array, nsems) };
+            if cmd == GETALL {
+                array.copy_from_slice(&set.get_all());
+            } else {
+                set.set_all(array, process.pid())?;
+            }
+        }
+        _ => return Err(SyscallError::EINVAL),
+    }
+    Ok(0)
+}
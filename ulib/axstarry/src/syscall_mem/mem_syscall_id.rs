@@ -13,6 +13,14 @@ numeric_enum_macro::numeric_enum! {
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub enum MemSyscallId {
     // mem
+    MSGGET = 186,
+    MSGCTL = 187,
+    MSGRCV = 188,
+    MSGSND = 189,
+    SEMGET = 190,
+    SEMCTL = 191,
+    SEMTIMEDOP = 192,
+    SEMOP = 193,
     SHMGET = 194,
     SHMCTL = 195,
     SHMAT = 196,
@@ -33,6 +41,14 @@ numeric_enum_macro::numeric_enum! {
     #[derive(Eq, PartialEq, Debug, Copy, Clone)]
     pub enum MemSyscallId {
         // mem
+        MSGGET = 68,
+        MSGSND = 69,
+        MSGRCV = 70,
+        MSGCTL = 71,
+        SEMGET = 64,
+        SEMOP = 65,
+        SEMCTL = 66,
+        SEMTIMEDOP = 220,
         SHMGET = 29,
         SHMCTL = 31,
         SHMAT = 30,
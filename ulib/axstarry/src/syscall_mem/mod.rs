@@ -3,11 +3,13 @@
 use crate::SyscallResult;
 
 mod imp;
+mod ipc;
 
 mod mem_syscall_id;
 pub use mem_syscall_id::MemSyscallId::{self, *};
 
 use imp::*;
+use ipc::*;
 /// 与内存相关的系统调用
 pub fn mem_syscall(syscall_id: mem_syscall_id::MemSyscallId, args: [usize; 6]) -> SyscallResult {
     match syscall_id {
@@ -21,6 +23,14 @@ pub fn mem_syscall(syscall_id: mem_syscall_id::MemSyscallId, args: [usize; 6]) -
         SHMGET => syscall_shmget(args),
         SHMCTL => Ok(0),
         SHMAT => syscall_shmat(args),
+        MSGGET => syscall_msgget(args),
+        MSGSND => syscall_msgsnd(args),
+        MSGRCV => syscall_msgrcv(args),
+        MSGCTL => syscall_msgctl(args),
+        SEMGET => syscall_semget(args),
+        SEMOP => syscall_semop(args),
+        SEMTIMEDOP => syscall_semtimedop(args),
+        SEMCTL => syscall_semctl(args),
         #[allow(unused)]
         _ => {
             panic!("Invalid Syscall Id: {:?}!", syscall_id);
@@ -1,19 +1,64 @@
 //! 相关系统调用的具体实现
 extern crate alloc;
 use super::socket::*;
+use super::unix::{
+    unix_address_from, unix_address_to, UnixAddr, UnixControl, UnixCred, UnixSocket,
+};
+use core::mem::size_of;
+use core::ptr::{addr_of_mut, copy_nonoverlapping, null_mut, read_unaligned, write_unaligned};
 use core::slice::{from_raw_parts, from_raw_parts_mut};
 
 use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
 
-use crate::{SyscallError, SyscallResult};
+use crate::{broken_pipe, IoVec, SyscallError, SyscallResult};
 use axerrno::AxError;
+use axfs::api::FileIO;
 use axlog::{debug, error, info, warn};
 use axnet::{into_core_sockaddr, IpAddr, SocketAddr};
-use axprocess::current_process;
+use axprocess::{current_process, PID2PC};
 use num_enum::TryFromPrimitive;
 
 pub const SOCKET_TYPE_MASK: usize = 0xFF;
 
+/// AF_UNIX 套接字操作的错误码，与 [`AxError`] 默认的转换有以下不同
+///
+/// * `AlreadyExists` - 套接字已经连接，EISCONN
+/// * `Unsupported` - 套接字类型不支持该操作，EOPNOTSUPP
+/// * `Interrupted` - 阻塞时被信号打断，根据 SA_RESTART 决定是否重新执行
+fn unix_error(err: AxError) -> SyscallError {
+    match err {
+        AxError::AlreadyExists => SyscallError::EISCONN,
+        AxError::Unsupported => SyscallError::EOPNOTSUPP,
+        AxError::Interrupted => SyscallError::ERESTARTSYS,
+        err => err.into(),
+    }
+}
+
+/// AF_UNIX 支持的套接字类型
+fn is_unix_socket_type(socket_type: &SocketType) -> bool {
+    matches!(
+        socket_type,
+        SocketType::SOCK_STREAM | SocketType::SOCK_DGRAM | SocketType::SOCK_SEQPACKET
+    )
+}
+
+/// 读取用户态的 sockaddr_un，地址无法访问时返回 EFAULT，格式错误时返回 EINVAL
+fn read_unix_address(addr: *const u8, addr_len: usize) -> Result<UnixAddr, SyscallError> {
+    if addr.is_null()
+        || current_process()
+            .manual_alloc_range_for_lazy(
+                (addr as usize).into(),
+                unsafe { addr.add(addr_len) as usize }.into(),
+            )
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    unsafe { unix_address_from(addr, addr_len) }.map_err(|_| SyscallError::EINVAL)
+}
+
 /// # Arguments
 /// * `domain` - usize
 /// * `s_type` - usize
@@ -31,6 +76,9 @@ pub fn syscall_socket(args: [usize; 6]) -> SyscallResult {
         // return ErrorNo::EINVAL as isize;
         return Err(SyscallError::EINVAL);
     };
+    if matches!(domain, Domain::AF_UNIX) && !is_unix_socket_type(&socket_type) {
+        return Err(SyscallError::ESOCKTNOSUPPORT);
+    }
     let mut socket = Socket::new(domain, socket_type);
     if s_type & SOCK_NONBLOCK != 0 {
         socket.set_nonblocking(true)
@@ -58,7 +106,7 @@ pub fn syscall_socket(args: [usize; 6]) -> SyscallResult {
 pub fn syscall_bind(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let addr = args[1] as *const u8;
-    let _addr_len = args[2];
+    let addr_len = args[2];
     let curr = current_process();
 
     let file = match curr.fd_manager.fd_table.lock().get(fd) {
@@ -66,12 +114,18 @@ pub fn syscall_bind(args: [usize; 6]) -> SyscallResult {
         _ => return Err(SyscallError::EBADF),
     };
 
-    let addr = unsafe { socket_address_from(addr) };
-
     let Some(socket) = file.as_any().downcast_ref::<Socket>() else {
         return Err(SyscallError::ENOTSOCK);
     };
 
+    if let Some(unix) = socket.unix() {
+        let addr = read_unix_address(addr, addr_len)?;
+        info!("[bind()] binding unix socket {} to {:?}", fd, addr);
+        return unix.bind(addr).map(|_| 0).map_err(unix_error);
+    }
+
+    let addr = unsafe { socket_address_from(addr) };
+
     info!("[bind()] binding socket {} to {:?}", fd, addr);
 
     Ok(socket.bind(addr).map_or(-1, |_| 0))
@@ -83,7 +137,7 @@ pub fn syscall_bind(args: [usize; 6]) -> SyscallResult {
 /// * `backlog` - usize
 pub fn syscall_listen(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
-    let _backlog = args[1];
+    let backlog = args[1] as i32;
     let curr = current_process();
 
     let file = match curr.fd_manager.fd_table.lock().get(fd) {
@@ -95,6 +149,13 @@ pub fn syscall_listen(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::ENOTSOCK);
     };
 
+    if let Some(unix) = socket.unix() {
+        return unix
+            .listen(backlog.max(0) as usize)
+            .map(|_| 0)
+            .map_err(unix_error);
+    }
+
     Ok(socket.listen().map_or(-1, |_| 0))
 }
 
@@ -123,16 +184,27 @@ pub fn syscall_accept4(args: [usize; 6]) -> SyscallResult {
 
     // socket.accept() might block, we need to release all lock now.
 
-    match socket.accept() {
-        Ok((mut s, addr)) => {
+    let accepted = match socket.unix() {
+        Some(unix) => unix.accept().map(|(conn, addr)| {
+            unsafe { unix_address_to(&addr, addr_buf, addr_len) };
+            debug!("[accept()] unix socket {fd} accept {addr:?}");
+            Socket::from_unix(conn.socket_type(), conn)
+        }),
+        None => socket.accept().map(|(s, addr)| {
             let _ = unsafe { socket_address_to(addr, addr_buf, addr_len) };
+            debug!("[accept()] socket {fd} accept {addr:?}");
+            s
+        }),
+    };
 
+    match accepted {
+        Ok(mut s) => {
             let mut fd_table = curr.fd_manager.fd_table.lock();
             let Ok(new_fd) = curr.alloc_fd(&mut fd_table) else {
                 return Err(SyscallError::ENFILE); // Maybe ENFILE
             };
 
-            debug!("[accept()] socket {fd} accept new socket {new_fd} {addr:?}");
+            debug!("[accept()] socket {fd} accept new socket {new_fd}");
 
             // handle flags
             if flags & SOCK_NONBLOCK != 0 {
@@ -146,8 +218,9 @@ pub fn syscall_accept4(args: [usize; 6]) -> SyscallResult {
             Ok(new_fd as isize)
         }
         Err(AxError::Unsupported) => Err(SyscallError::EOPNOTSUPP),
-        Err(AxError::Interrupted) => Err(SyscallError::EINTR),
+        Err(AxError::Interrupted) => Err(SyscallError::ERESTARTSYS),
         Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        Err(AxError::InvalidInput) => Err(SyscallError::EINVAL),
         Err(_) => Err(SyscallError::EPERM),
     }
 }
@@ -159,7 +232,7 @@ pub fn syscall_accept4(args: [usize; 6]) -> SyscallResult {
 pub fn syscall_connect(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let addr_buf = args[1] as *const u8;
-    let _addr_len = args[2];
+    let addr_len = args[2];
     let curr = current_process();
 
     let file = match curr.fd_manager.fd_table.lock().get(fd) {
@@ -171,6 +244,12 @@ pub fn syscall_connect(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::ENOTSOCK);
     };
 
+    if let Some(unix) = socket.unix() {
+        let addr = read_unix_address(addr_buf, addr_len)?;
+        debug!("[connect()] unix socket {fd} connecting to {addr:?}");
+        return unix.connect(&addr).map(|_| 0).map_err(unix_error);
+    }
+
     let addr = unsafe { socket_address_from(addr_buf) };
 
     debug!("[connect()] socket {fd} connecting to {addr:?}");
@@ -206,6 +285,11 @@ pub fn syscall_get_sock_name(args: [usize; 6]) -> SyscallResult {
 
     debug!("[getsockname()] socket {fd}");
 
+    if let Some(unix) = socket.unix() {
+        unsafe { unix_address_to(&unix.local_addr(), addr, addr_len) };
+        return Ok(0);
+    }
+
     let Ok(name) = socket.name() else {
         return Err(SyscallError::EPERM);
     };
@@ -254,6 +338,12 @@ pub fn syscall_getpeername(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::ENOTSOCK);
     };
 
+    if let Some(unix) = socket.unix() {
+        let name = unix.peer_addr().map_err(unix_error)?;
+        unsafe { unix_address_to(&name, addr_buf, addr_len) };
+        return Ok(0);
+    }
+
     match socket.peer_name() {
         Ok(name) => Ok(unsafe { socket_address_to(name, addr_buf, addr_len) }.map_or(-1, |_| 0)),
         Err(AxError::NotConnected) => Err(SyscallError::ENOTCONN),
@@ -261,7 +351,7 @@ pub fn syscall_getpeername(args: [usize; 6]) -> SyscallResult {
     }
 }
 
-// TODO: flags
+// TODO: flags other than MSG_NOSIGNAL
 /// Calling sendto() will bind the socket if it's not bound.
 /// # Arguments
 /// * `fd` - usize
@@ -274,7 +364,7 @@ pub fn syscall_sendto(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let buf = args[1] as *const u8;
     let len = args[2];
-    let _flags = args[3];
+    let flags = args[3];
     let addr = args[4] as *const u8;
     let addr_len = args[5];
     let curr = current_process();
@@ -302,20 +392,60 @@ pub fn syscall_sendto(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EFAULT);
     };
 
-    let addr = if !addr.is_null() && addr_len != 0 {
-        match curr.manual_alloc_range_for_lazy(
-            (addr as usize).into(),
-            unsafe { addr.add(addr_len) as usize }.into(),
-        ) {
-            Ok(_) => Some(unsafe { socket_address_from(addr) }),
-            Err(_) => {
-                error!("[sendto()] addr address {addr:?} invalid");
-                return Err(SyscallError::EFAULT);
-            }
+    let addr = check_address(addr, addr_len)?;
+    socket_send(fd, socket, buf, flags, addr, addr_len, None)
+}
+
+/// 检查用户态的目标地址可以访问，没有给出地址时返回 `None`
+fn check_address(addr: *const u8, addr_len: usize) -> Result<Option<*const u8>, SyscallError> {
+    if addr.is_null() || addr_len == 0 {
+        return Ok(None);
+    }
+    match current_process().manual_alloc_range_for_lazy(
+        (addr as usize).into(),
+        unsafe { addr.add(addr_len) as usize }.into(),
+    ) {
+        Ok(_) => Ok(Some(addr)),
+        Err(_) => {
+            error!("addr address {addr:?} invalid");
+            Err(SyscallError::EFAULT)
         }
-    } else {
-        None
-    };
+    }
+}
+
+/// sendto 与 sendmsg 共用的发送过程
+///
+/// `addr` 已经检查过可以访问，`control` 为 sendmsg 传给 AF_UNIX 套接字的辅助数据
+fn socket_send(
+    fd: usize,
+    socket: &Socket,
+    buf: &[u8],
+    flags: usize,
+    addr: Option<*const u8>,
+    addr_len: usize,
+    control: Option<UnixControl>,
+) -> SyscallResult {
+    if let Some(unix) = socket.unix() {
+        let addr = match addr {
+            Some(addr) => Some(read_unix_address(addr, addr_len)?),
+            None => None,
+        };
+        let control = control.unwrap_or_else(|| UnixControl {
+            rights: Vec::new(),
+            cred: UnixCred::current(),
+        });
+        return match unix.send_msg(buf, addr.as_ref(), control.rights, control.cred) {
+            Ok(len) => Ok(len as isize),
+            Err(AxError::BrokenPipe) => Err(broken_pipe(
+                flags & MSG_NOSIGNAL != 0 || socket.is_no_sigpipe(),
+            )),
+            // 消息超出了缓冲区的容量
+            Err(AxError::InvalidInput) => Err(SyscallError::EMSGSIZE),
+            Err(err) => Err(unix_error(err)),
+        };
+    }
+
+    let addr = addr.map(|addr| unsafe { socket_address_from(addr) });
     let inner = socket.inner.lock();
     let send_result = match &*inner {
         SocketInner::Udp(s) => {
@@ -349,6 +479,7 @@ pub fn syscall_sendto(args: [usize; 6]) -> SyscallResult {
 
             s.send(buf)
         }
+        SocketInner::Unix(_) => unreachable!(),
     };
 
     match send_result {
@@ -356,7 +487,11 @@ pub fn syscall_sendto(args: [usize; 6]) -> SyscallResult {
             info!("[sendto()] socket {fd} sent {len} bytes to addr {:?}", addr);
             Ok(len as isize)
         }
-        Err(AxError::Interrupted) => Err(SyscallError::EINTR),
+        Err(AxError::Interrupted) => Err(SyscallError::ERESTARTSYS),
+        // 对端已经关闭连接或者本端已经关闭发送
+        Err(AxError::ConnectionReset) => Err(broken_pipe(
+            flags & MSG_NOSIGNAL != 0 || socket.is_no_sigpipe(),
+        )),
         Err(_) => Err(SyscallError::EPERM),
     }
 }
@@ -409,22 +544,44 @@ pub fn syscall_recvfrom(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EFAULT);
     }
     let buf = unsafe { from_raw_parts_mut(buf, len) };
+
+    // recvfrom 无法取得辅助数据，随消息传递的文件被丢弃
+    socket_recv(fd, socket, buf, addr_buf, addr_len).map(|(len, _)| len)
+}
+
+/// recvfrom 与 recvmsg 共用的接收过程，`addr_buf` 与 `addr_len` 已经检查过可以访问
+///
+/// 返回值中的辅助数据只有 AF_UNIX 套接字才有
+fn socket_recv(
+    fd: usize,
+    socket: &Socket,
+    buf: &mut [u8],
+    addr_buf: *mut u8,
+    addr_len: *mut u32,
+) -> Result<(isize, Option<UnixControl>), SyscallError> {
+    if let Some(unix) = socket.unix() {
+        let (len, addr, control) = unix.recv_msg(buf).map_err(unix_error)?;
+        info!("unix socket {fd} recv {len} bytes from {addr:?}");
+        unsafe { unix_address_to(&addr, addr_buf, addr_len) };
+        return Ok((len as isize, Some(control)));
+    }
+
     info!("recv addr: {:?}", socket.name().unwrap());
-    match socket.recv_from(buf) {
+    let len = match socket.recv_from(buf) {
         Ok((len, addr)) => {
             info!("socket {fd} recv {len} bytes from {addr:?}");
             if !addr_buf.is_null() && !addr_len.is_null() {
-                Ok(unsafe { socket_address_to(addr, addr_buf, addr_len) }
-                    .map_or(-1, |_| len as isize))
+                unsafe { socket_address_to(addr, addr_buf, addr_len) }.map_or(-1, |_| len as isize)
             } else {
-                Ok(len as isize)
+                len as isize
             }
         }
-        Err(AxError::ConnectionRefused) => Ok(0),
-        Err(AxError::Interrupted) => Err(SyscallError::EINTR),
-        Err(AxError::Timeout) | Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
-        Err(_) => Err(SyscallError::EPERM),
-    }
+        Err(AxError::ConnectionRefused) => 0,
+        Err(AxError::Interrupted) => return Err(SyscallError::ERESTARTSYS),
+        Err(AxError::Timeout) | Err(AxError::WouldBlock) => return Err(SyscallError::EAGAIN),
+        Err(_) => return Err(SyscallError::EPERM),
+    };
+    Ok((len, None))
 }
 
 /// NOTE: only support socket level options (SOL_SOCKET)
@@ -589,9 +746,10 @@ pub fn syscall_shutdown(args: [usize; 6]) -> SyscallResult {
     };
 
     match how {
-        SocketShutdown::Read => {
-            error!("[shutdown()] SHUT_RD is noop")
-        }
+        SocketShutdown::Read => match socket.unix() {
+            Some(unix) => unix.shutdown(true, false),
+            None => error!("[shutdown()] SHUT_RD is noop"),
+        },
         SocketShutdown::Write => socket.shutdown(),
         SocketShutdown::ReadWrite => {
             socket.abort();
@@ -601,6 +759,376 @@ pub fn syscall_shutdown(args: [usize; 6]) -> SyscallResult {
     Ok(0)
 }
 
-pub fn syscall_socketpair() -> SyscallResult {
-    Err(SyscallError::EAFNOSUPPORT)
+/// 只支持 AF_UNIX
+/// # Arguments
+/// * `domain` - usize
+/// * `s_type` - usize
+/// * `protocol` - usize
+/// * `sv` - *mut [i32; 2]
+pub fn syscall_socketpair(args: [usize; 6]) -> SyscallResult {
+    let domain = args[0];
+    let s_type = args[1];
+    let _protocol = args[2];
+    let sv = args[3] as *mut [i32; 2];
+    let Ok(domain) = Domain::try_from(domain) else {
+        return Err(SyscallError::EAFNOSUPPORT);
+    };
+    if !matches!(domain, Domain::AF_UNIX) {
+        return Err(SyscallError::EOPNOTSUPP);
+    }
+    let Ok(socket_type) = SocketType::try_from(s_type & SOCKET_TYPE_MASK) else {
+        return Err(SyscallError::EINVAL);
+    };
+    if !is_unix_socket_type(&socket_type) {
+        return Err(SyscallError::ESOCKTNOSUPPORT);
+    }
+    let curr = current_process();
+    if curr
+        .manual_alloc_type_for_lazy(sv as *const [i32; 2])
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+
+    let (first, second) = UnixSocket::pair(socket_type.clone());
+    let mut fds = [0; 2];
+    let mut fd_table = curr.fd_manager.fd_table.lock();
+    for (i, unix) in [first, second].into_iter().enumerate() {
+        let mut socket = Socket::from_unix(socket_type.clone(), unix);
+        if s_type & SOCK_NONBLOCK != 0 {
+            socket.set_nonblocking(true);
+        }
+        if s_type & SOCK_CLOEXEC != 0 {
+            socket.close_exec = true;
+        }
+        let Ok(fd) = curr.alloc_fd(&mut fd_table) else {
+            if i == 1 {
+                fd_table[fds[0]] = None;
+            }
+            return Err(SyscallError::EMFILE);
+        };
+        fd_table[fd] = Some(Arc::new(socket));
+        fds[i] = fd;
+    }
+    drop(fd_table);
+
+    debug!("[socketpair()] create unix sockets {fds:?}");
+    unsafe { *sv = [fds[0] as i32, fds[1] as i32] };
+    Ok(0)
+}
+
+/// 读取用户态的 iovec 数组，并检查其中的缓冲区都可以访问
+unsafe fn user_iovecs<'a>(iov: *const IoVec, iov_len: usize) -> Result<&'a [IoVec], SyscallError> {
+    if iov_len == 0 {
+        return Ok(&[]);
+    }
+    if iov_len > UIO_MAXIOV {
+        return Err(SyscallError::EMSGSIZE);
+    }
+    let curr = current_process();
+    if iov.is_null()
+        || curr
+            .manual_alloc_range_for_lazy((iov as usize).into(), (iov.add(iov_len) as usize).into())
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let iovecs = from_raw_parts(iov, iov_len);
+    for io in iovecs {
+        if io.len != 0
+            && (io.base.is_null()
+                || curr
+                    .manual_alloc_range_for_lazy(
+                        (io.base as usize).into(),
+                        (io.base.add(io.len) as usize).into(),
+                    )
+                    .is_err())
+        {
+            return Err(SyscallError::EFAULT);
+        }
+    }
+    Ok(iovecs)
+}
+
+/// 解析 sendmsg 传给 AF_UNIX 套接字的辅助数据
+///
+/// 支持 SOL_SOCKET 层的 SCM_RIGHTS 与 SCM_CREDENTIALS，其他层的控制消息被忽略。
+/// 没有指定 SCM_CREDENTIALS 时使用当前进程的身份
+fn read_control(control: *const u8, control_len: usize) -> Result<UnixControl, SyscallError> {
+    let mut rights: Vec<Arc<dyn FileIO>> = Vec::new();
+    let mut cred = UnixCred::current();
+    if control.is_null() || control_len == 0 {
+        return Ok(UnixControl { rights, cred });
+    }
+    let curr = current_process();
+    if curr
+        .manual_alloc_range_for_lazy(
+            (control as usize).into(),
+            unsafe { control.add(control_len) as usize }.into(),
+        )
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let header_len = CMsgHdr::len(0);
+    let mut offset = 0;
+    while offset + size_of::<CMsgHdr>() <= control_len {
+        let header = unsafe { read_unaligned(control.add(offset) as *const CMsgHdr) };
+        if header.len < header_len || header.len > control_len - offset {
+            return Err(SyscallError::EINVAL);
+        }
+        let data = unsafe { control.add(offset + header_len) };
+        let data_len = header.len - header_len;
+        if header.level == SocketOptionLevel::Socket as i32 {
+            match header.ty {
+                SCM_RIGHTS => {
+                    let count = data_len / size_of::<i32>();
+                    if data_len % size_of::<i32>() != 0 || rights.len() + count > SCM_MAX_FD {
+                        return Err(SyscallError::EINVAL);
+                    }
+                    let fd_table = curr.fd_manager.fd_table.lock();
+                    for i in 0..count {
+                        let fd = unsafe { read_unaligned((data as *const i32).add(i)) };
+                        let file = usize::try_from(fd)
+                            .ok()
+                            .and_then(|fd| fd_table.get(fd).cloned().flatten());
+                        let Some(file) = file else {
+                            return Err(SyscallError::EBADF);
+                        };
+                        rights.push(file);
+                    }
+                }
+                SCM_CREDENTIALS => {
+                    if data_len != size_of::<UnixCred>() {
+                        return Err(SyscallError::EINVAL);
+                    }
+                    let value = unsafe { read_unaligned(data as *const UnixCred) };
+                    // 在实现多用户权限前可以指定任意的 uid 与 gid，但 pid 必须存在
+                    if !PID2PC.lock().contains_key(&(value.pid as u64)) {
+                        return Err(SyscallError::ESRCH);
+                    }
+                    cred = value;
+                }
+                _ => return Err(SyscallError::EINVAL),
+            }
+        }
+        offset += CMsgHdr::align(header.len);
+    }
+    Ok(UnixControl { rights, cred })
+}
+
+/// 将 recvmsg 收到的辅助数据写入用户态的缓冲区，返回写入的长度
+///
+/// 设置了 SO_PASSCRED 时先写入 SCM_CREDENTIALS，再将收到的文件放入当前进程的文件描述符表中，
+/// 以 SCM_RIGHTS 返回。缓冲区放不下时在 `msg_flags` 中设置 MSG_CTRUNC，放不下的文件被关闭
+fn write_control(
+    control: UnixControl,
+    passcred: bool,
+    cloexec: bool,
+    buf: *mut u8,
+    buf_len: usize,
+    msg_flags: &mut i32,
+) -> usize {
+    let header_len = CMsgHdr::len(0);
+    let mut offset = 0;
+    if passcred {
+        let len = CMsgHdr::len(size_of::<UnixCred>());
+        if buf_len >= len {
+            unsafe {
+                write_unaligned(
+                    buf as *mut CMsgHdr,
+                    CMsgHdr {
+                        len,
+                        level: SocketOptionLevel::Socket as i32,
+                        ty: SCM_CREDENTIALS,
+                    },
+                );
+                write_unaligned(buf.add(header_len) as *mut UnixCred, control.cred);
+            }
+            offset = CMsgHdr::space(size_of::<UnixCred>()).min(buf_len);
+        } else {
+            *msg_flags |= MSG_CTRUNC;
+        }
+    }
+
+    if control.rights.is_empty() {
+        return offset;
+    }
+    let total = control.rights.len();
+    let max = (buf_len - offset).saturating_sub(header_len) / size_of::<i32>();
+    let curr = current_process();
+    let mut fd_table = curr.fd_manager.fd_table.lock();
+    let mut fds = Vec::new();
+    for file in control.rights.into_iter().take(max) {
+        let Ok(fd) = curr.alloc_fd(&mut fd_table) else {
+            break;
+        };
+        fd_table[fd] = Some(file);
+        // 只标记接收方的文件描述符，发送方持有的是同一个文件对象
+        curr.fd_manager.set_cloexec(fd, cloexec);
+        fds.push(fd as i32);
+    }
+    drop(fd_table);
+    if fds.len() < total {
+        *msg_flags |= MSG_CTRUNC;
+    }
+    if !fds.is_empty() {
+        let data_len = fds.len() * size_of::<i32>();
+        unsafe {
+            write_unaligned(
+                buf.add(offset) as *mut CMsgHdr,
+                CMsgHdr {
+                    len: CMsgHdr::len(data_len),
+                    level: SocketOptionLevel::Socket as i32,
+                    ty: SCM_RIGHTS,
+                },
+            );
+            copy_nonoverlapping(
+                fds.as_ptr() as *const u8,
+                buf.add(offset + header_len),
+                data_len,
+            );
+        }
+        offset = (offset + CMsgHdr::space(data_len)).min(buf_len);
+    }
+    offset
+}
+
+/// 辅助数据只对 AF_UNIX 套接字有效
+/// # Arguments
+/// * `fd` - usize
+/// * `msg` - *const MsgHdr
+/// * `flags` - usize
+pub fn syscall_sendmsg(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let msg = args[1] as *const MsgHdr;
+    let flags = args[2];
+    let curr = current_process();
+
+    let file = match curr.fd_manager.fd_table.lock().get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+
+    let Some(socket) = file.as_any().downcast_ref::<Socket>() else {
+        return Err(SyscallError::ENOTSOCK);
+    };
+
+    if msg.is_null() || curr.manual_alloc_type_for_lazy(msg).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let msg = unsafe { *msg };
+
+    // 将各个缓冲区的数据合并为一条消息
+    let mut buf = Vec::new();
+    for io in unsafe { user_iovecs(msg.iov, msg.iov_len) }? {
+        if io.len != 0 {
+            buf.extend_from_slice(unsafe { from_raw_parts(io.base, io.len) });
+        }
+    }
+    let addr = check_address(msg.name, msg.name_len as usize)?;
+    let control = match socket.unix() {
+        Some(_) => Some(read_control(msg.control, msg.control_len)?),
+        None => None,
+    };
+    // 没有回收引用环的机制，套接字经自身发送自己的文件描述符后将永远不会被释放。
+    // 经其他套接字间接形成的引用环同样不会被回收
+    if let Some(control) = &control {
+        if control.rights.iter().any(|right| Arc::ptr_eq(right, &file)) {
+            return Err(SyscallError::EINVAL);
+        }
+    }
+
+    socket_send(
+        fd,
+        socket,
+        &buf,
+        flags,
+        addr,
+        msg.name_len as usize,
+        control,
+    )
+}
+
+/// 辅助数据只对 AF_UNIX 套接字有效，`flags` 只支持 MSG_CMSG_CLOEXEC
+/// # Arguments
+/// * `fd` - usize
+/// * `msg` - *mut MsgHdr
+/// * `flags` - usize
+pub fn syscall_recvmsg(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let msg = args[1] as *mut MsgHdr;
+    let flags = args[2];
+    let curr = current_process();
+
+    let file = match curr.fd_manager.fd_table.lock().get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+
+    let Some(socket) = file.as_any().downcast_ref::<Socket>() else {
+        return Err(SyscallError::ENOTSOCK);
+    };
+
+    if msg.is_null()
+        || curr
+            .manual_alloc_type_for_lazy(msg as *const MsgHdr)
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let hdr = unsafe { *msg };
+    let iovecs = unsafe { user_iovecs(hdr.iov, hdr.iov_len) }?;
+    let (name, name_len) = match check_address(hdr.name, hdr.name_len as usize)? {
+        Some(name) => (name as *mut u8, unsafe { addr_of_mut!((*msg).name_len) }),
+        None => (null_mut(), null_mut()),
+    };
+    let control_len = if hdr.control.is_null() {
+        0
+    } else {
+        hdr.control_len
+    };
+    if control_len != 0
+        && curr
+            .manual_alloc_range_for_lazy(
+                (hdr.control as usize).into(),
+                unsafe { hdr.control.add(control_len) as usize }.into(),
+            )
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+
+    let mut buf = vec![0; iovecs.iter().map(|io| io.len).sum()];
+    let (len, control) = socket_recv(fd, socket, &mut buf, name, name_len)?;
+
+    // 将数据依次写入各个缓冲区
+    let mut copied = 0;
+    let total = len.max(0) as usize;
+    for io in iovecs {
+        if copied == total {
+            break;
+        }
+        let n = io.len.min(total - copied);
+        unsafe { copy_nonoverlapping(buf[copied..].as_ptr(), io.base, n) };
+        copied += n;
+    }
+
+    let mut msg_flags = 0;
+    let control_len = match control {
+        Some(control) => write_control(
+            control,
+            socket.unix().is_some_and(|unix| unix.is_passcred()),
+            flags & MSG_CMSG_CLOEXEC != 0,
+            hdr.control,
+            control_len,
+            &mut msg_flags,
+        ),
+        None => 0,
+    };
+    unsafe {
+        (*msg).control_len = control_len;
+        (*msg).flags = msg_flags;
+    }
+    Ok(len)
 }
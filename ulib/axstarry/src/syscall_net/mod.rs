@@ -5,6 +5,7 @@ mod imp;
 
 #[allow(unused)]
 mod socket;
+mod unix;
 use imp::*;
 pub use socket::Socket;
 mod net_syscall_id;
@@ -26,7 +27,9 @@ pub fn net_syscall(syscall_id: net_syscall_id::NetSyscallId, args: [usize; 6]) -
         SETSOCKOPT => syscall_set_sock_opt(args),
         // SETSOCKOPT => 0,
         GETSOCKOPT => syscall_get_sock_opt(args),
-        SOCKETPAIR => syscall_socketpair(),
+        SOCKETPAIR => syscall_socketpair(args),
+        SENDMSG => syscall_sendmsg(args),
+        RECVMSG => syscall_recvmsg(args),
         ACCEPT4 => syscall_accept4(args),
         SHUTDOWN => syscall_shutdown(args),
         #[allow(unused)]
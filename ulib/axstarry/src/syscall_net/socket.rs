@@ -7,6 +7,7 @@ use core::{
 };
 
 use alloc::string::String;
+use alloc::sync::Arc;
 use axerrno::{AxError, AxResult};
 use axfs::api::{FileIO, FileIOType, OpenFlags, Read, Write};
 
@@ -18,7 +19,8 @@ use axnet::{
 use axsync::Mutex;
 use num_enum::TryFromPrimitive;
 
-use crate::TimeVal;
+use super::unix::{UnixCred, UnixSocket};
+use crate::{IoVec, TimeVal};
 
 pub const SOCKET_TYPE_MASK: usize = 0xFF;
 
@@ -58,6 +60,71 @@ pub const SOCK_NONBLOCK: usize = 0x800;
 /// Set FD_CLOEXEC flag on the new fd
 pub const SOCK_CLOEXEC: usize = 0x80000;
 
+/// Don't raise SIGPIPE when writing to a stream socket whose peer has closed the connection
+pub const MSG_NOSIGNAL: usize = 0x4000;
+/// The maximum number of elements in the iovec array of sendmsg() and recvmsg()
+pub const UIO_MAXIOV: usize = 1024;
+/// Some control data was discarded due to lack of space in the control buffer
+pub const MSG_CTRUNC: i32 = 0x8;
+/// Set FD_CLOEXEC flag on the file descriptors received by SCM_RIGHTS
+pub const MSG_CMSG_CLOEXEC: usize = 0x40000000;
+
+/// Control message carrying file descriptors, in level SOL_SOCKET
+pub const SCM_RIGHTS: i32 = 1;
+/// Control message carrying the credentials of the sender, in level SOL_SOCKET
+pub const SCM_CREDENTIALS: i32 = 2;
+/// The maximum number of file descriptors in a SCM_RIGHTS message
+pub const SCM_MAX_FD: usize = 253;
+
+/// The message header used by sendmsg() and recvmsg(), the same as `struct msghdr`
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MsgHdr {
+    /// Optional address
+    pub name: *mut u8,
+    /// Size of the address
+    pub name_len: u32,
+    /// Scatter/gather array
+    pub iov: *mut IoVec,
+    /// Number of elements in `iov`
+    pub iov_len: usize,
+    /// Ancillary data
+    pub control: *mut u8,
+    /// Size of the ancillary data buffer
+    pub control_len: usize,
+    /// Flags on the received message
+    pub flags: i32,
+}
+
+/// The header of a control message, the same as `struct cmsghdr`
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CMsgHdr {
+    /// Length of the control message, including the header
+    pub len: usize,
+    /// Originating protocol
+    pub level: i32,
+    /// Protocol-specific type
+    pub ty: i32,
+}
+
+impl CMsgHdr {
+    /// Round up the length to the alignment of the control messages, like `CMSG_ALIGN`
+    pub const fn align(len: usize) -> usize {
+        (len + size_of::<usize>() - 1) & !(size_of::<usize>() - 1)
+    }
+
+    /// The length of a control message with `data_len` bytes of data, like `CMSG_LEN`
+    pub const fn len(data_len: usize) -> usize {
+        Self::align(size_of::<Self>()) + data_len
+    }
+
+    /// The space taken by a control message with `data_len` bytes of data, like `CMSG_SPACE`
+    pub const fn space(data_len: usize) -> usize {
+        Self::align(size_of::<Self>()) + Self::align(data_len)
+    }
+}
+
 #[derive(TryFromPrimitive, Debug)]
 #[repr(usize)]
 #[allow(non_camel_case_types)]
@@ -77,7 +144,11 @@ pub enum SocketOption {
     SO_SNDBUF = 7,
     SO_RCVBUF = 8,
     SO_KEEPALIVE = 9,
+    SO_PASSCRED = 16,
+    SO_PEERCRED = 17,
     SO_RCVTIMEO = 20,
+    /// Don't raise SIGPIPE on writes to a broken connection, value taken from BSD
+    SO_NOSIGPIPE = 0x1022,
 }
 
 #[derive(TryFromPrimitive, PartialEq)]
@@ -152,6 +223,9 @@ impl SocketOption {
                     SocketInner::Udp(_) => {
                         warn!("[setsockopt()] set SO_KEEPALIVE on udp socket, ignored")
                     }
+                    SocketInner::Unix(_) => {
+                        warn!("[setsockopt()] set SO_KEEPALIVE on unix socket, ignored")
+                    }
                     SocketInner::Tcp(s) => s.with_socket_mut(|s| match s {
                         Some(s) => s.set_keep_alive(interval),
                         None => warn!(
@@ -175,6 +249,30 @@ impl SocketOption {
                     Some(timeout)
                 });
             }
+            SocketOption::SO_NOSIGPIPE => {
+                if opt.len() < 4 {
+                    panic!("can't read a int from socket opt value");
+                }
+
+                let opt_value = i32::from_ne_bytes(<[u8; 4]>::try_from(&opt[0..4]).unwrap());
+
+                socket.set_no_sigpipe(opt_value != 0);
+            }
+            SocketOption::SO_PASSCRED => {
+                if opt.len() < 4 {
+                    panic!("can't read a int from socket opt value");
+                }
+
+                let opt_value = i32::from_ne_bytes(<[u8; 4]>::try_from(&opt[0..4]).unwrap());
+
+                match socket.unix() {
+                    Some(s) => s.set_passcred(opt_value != 0),
+                    None => warn!("[setsockopt()] set SO_PASSCRED on inet socket, ignored"),
+                }
+            }
+            SocketOption::SO_PEERCRED => {
+                warn!("[setsockopt()] SO_PEERCRED is read-only, ignored");
+            }
             SocketOption::SO_ERROR => {
                 panic!("can't set SO_ERROR");
             }
@@ -244,6 +342,7 @@ impl SocketOption {
                         warn!("[getsockopt()] get SO_KEEPALIVE on udp socket, returning false");
                         0
                     }
+                    SocketInner::Unix(_) => 0,
                     SocketInner::Tcp(s) => s.with_socket(|s| match s {
                         Some(s) => if s.keep_alive().is_some() { 1 } else { 0 },
                         None => {warn!(
@@ -279,6 +378,46 @@ impl SocketOption {
                     *opt_len = size_of::<TimeVal>() as u32;
                 }
             }
+            SocketOption::SO_NOSIGPIPE => {
+                if buf_len < 4 {
+                    panic!("can't write a int to socket opt value");
+                }
+
+                let value: i32 = if socket.is_no_sigpipe() { 1 } else { 0 };
+
+                unsafe {
+                    copy_nonoverlapping(&value.to_ne_bytes() as *const u8, opt_value, 4);
+                    *opt_len = 4;
+                }
+            }
+            SocketOption::SO_PASSCRED => {
+                if buf_len < 4 {
+                    panic!("can't write a int to socket opt value");
+                }
+
+                let value: i32 = match socket.unix() {
+                    Some(s) if s.is_passcred() => 1,
+                    _ => 0,
+                };
+
+                unsafe {
+                    copy_nonoverlapping(&value.to_ne_bytes() as *const u8, opt_value, 4);
+                    *opt_len = 4;
+                }
+            }
+            SocketOption::SO_PEERCRED => {
+                // only connected unix sockets know the credentials of the peer
+                let cred = socket
+                    .unix()
+                    .and_then(|s| s.peer_cred())
+                    .unwrap_or(UnixCred::NONE);
+                let len = buf_len.min(size_of::<UnixCred>());
+
+                unsafe {
+                    copy_nonoverlapping(&cred as *const UnixCred as *const u8, opt_value, len);
+                    *opt_len = len as u32;
+                }
+            }
             SocketOption::SO_ERROR => {
                 // 当前没有存储错误列表，因此不做处理
             }
@@ -374,6 +513,8 @@ pub struct Socket {
     /// Whether the socket is set to close on exec
     pub close_exec: bool,
     recv_timeout: Mutex<Option<TimeVal>>,
+    /// Whether SIGPIPE is suppressed on writes to a broken connection
+    no_sigpipe: AtomicBool,
 
     // fake options
     reuse_addr: AtomicBool,
@@ -389,6 +530,8 @@ pub enum SocketInner {
     Tcp(TcpSocket),
     /// UDP socket
     Udp(UdpSocket),
+    /// AF_UNIX socket, shared so that blocking operations can be done without holding the lock
+    Unix(Arc<UnixSocket>),
 }
 
 impl Socket {
@@ -436,21 +579,44 @@ impl Socket {
         *self.congestion.lock() = congestion;
     }
 
+    fn set_no_sigpipe(&self, flag: bool) {
+        self.no_sigpipe
+            .store(flag, core::sync::atomic::Ordering::Release)
+    }
+
+    /// Whether SIGPIPE is suppressed by SO_NOSIGPIPE
+    pub fn is_no_sigpipe(&self) -> bool {
+        self.no_sigpipe.load(core::sync::atomic::Ordering::Acquire)
+    }
+
     /// Create a new socket with the given domain and socket type.
     pub fn new(domain: Domain, socket_type: SocketType) -> Self {
-        let inner = match socket_type {
-            SocketType::SOCK_STREAM | SocketType::SOCK_SEQPACKET => {
-                SocketInner::Tcp(TcpSocket::new())
-            }
-            SocketType::SOCK_DGRAM => SocketInner::Udp(UdpSocket::new()),
-            _ => unimplemented!(),
+        let inner = match domain {
+            Domain::AF_UNIX => SocketInner::Unix(Arc::new(UnixSocket::new(socket_type.clone()))),
+            Domain::AF_INET => match socket_type {
+                SocketType::SOCK_STREAM | SocketType::SOCK_SEQPACKET => {
+                    SocketInner::Tcp(TcpSocket::new())
+                }
+                SocketType::SOCK_DGRAM => SocketInner::Udp(UdpSocket::new()),
+                _ => unimplemented!(),
+            },
         };
+        Self::with_inner(domain, socket_type, inner)
+    }
+
+    /// Wrap an AF_UNIX socket, which is created by accept() or socketpair()
+    pub fn from_unix(socket_type: SocketType, unix: Arc<UnixSocket>) -> Self {
+        Self::with_inner(Domain::AF_UNIX, socket_type, SocketInner::Unix(unix))
+    }
+
+    fn with_inner(domain: Domain, socket_type: SocketType, inner: SocketInner) -> Self {
         Self {
             domain,
             socket_type,
             inner: Mutex::new(inner),
             close_exec: false,
             recv_timeout: Mutex::new(None),
+            no_sigpipe: AtomicBool::new(false),
             reuse_addr: AtomicBool::new(false),
             dont_route: false,
             send_buf_size: AtomicU64::new(64 * 1024),
@@ -459,6 +625,14 @@ impl Socket {
         }
     }
 
+    /// Return the inner AF_UNIX socket
+    pub fn unix(&self) -> Option<Arc<UnixSocket>> {
+        match &*self.inner.lock() {
+            SocketInner::Unix(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+
     /// set the socket to non-blocking mode
     pub fn set_nonblocking(&self, nonblocking: bool) {
         let inner = self.inner.lock();
@@ -466,6 +640,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.set_nonblocking(nonblocking),
             SocketInner::Udp(s) => s.set_nonblocking(nonblocking),
+            SocketInner::Unix(s) => s.set_nonblocking(nonblocking),
         }
     }
 
@@ -475,6 +650,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.is_nonblocking(),
             SocketInner::Udp(s) => s.is_nonblocking(),
+            SocketInner::Unix(s) => s.is_nonblocking(),
         }
     }
 
@@ -484,6 +660,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.is_connected(),
             SocketInner::Udp(s) => s.with_socket(|s| s.is_open()),
+            SocketInner::Unix(s) => s.is_connected(),
         }
     }
 
@@ -493,6 +670,8 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.local_addr(),
             SocketInner::Udp(s) => s.local_addr(),
+            // AF_UNIX addresses are handled by UnixSocket
+            SocketInner::Unix(_) => Err(AxError::Unsupported),
         }
         .map(from_core_sockaddr)
     }
@@ -503,6 +682,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.peer_addr(),
             SocketInner::Udp(s) => s.peer_addr(),
+            SocketInner::Unix(_) => Err(AxError::Unsupported),
         }
         .map(from_core_sockaddr)
     }
@@ -513,6 +693,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.bind(into_core_sockaddr(addr)),
             SocketInner::Udp(s) => s.bind(into_core_sockaddr(addr)),
+            SocketInner::Unix(_) => Err(AxError::Unsupported),
         }
     }
 
@@ -530,7 +711,7 @@ impl Socket {
         let inner = self.inner.lock();
         match &*inner {
             SocketInner::Tcp(s) => s.listen(),
-            SocketInner::Udp(_) => Err(AxError::Unsupported),
+            SocketInner::Udp(_) | SocketInner::Unix(_) => Err(AxError::Unsupported),
         }
     }
 
@@ -544,23 +725,16 @@ impl Socket {
         let inner = self.inner.lock();
         let new_socket = match &*inner {
             SocketInner::Tcp(s) => s.accept()?,
-            SocketInner::Udp(_) => Err(AxError::Unsupported)?,
+            SocketInner::Udp(_) | SocketInner::Unix(_) => Err(AxError::Unsupported)?,
         };
         let addr = new_socket.peer_addr()?;
 
         Ok((
-            Self {
-                domain: self.domain.clone(),
-                socket_type: self.socket_type.clone(),
-                inner: Mutex::new(SocketInner::Tcp(new_socket)),
-                close_exec: false,
-                recv_timeout: Mutex::new(None),
-                reuse_addr: AtomicBool::new(false),
-                dont_route: false,
-                send_buf_size: AtomicU64::new(64 * 1024),
-                recv_buf_size: AtomicU64::new(64 * 1024),
-                congestion: Mutex::new(String::from("reno")),
-            },
+            Self::with_inner(
+                self.domain.clone(),
+                self.socket_type.clone(),
+                SocketInner::Tcp(new_socket),
+            ),
             from_core_sockaddr(addr),
         ))
     }
@@ -571,6 +745,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.connect(into_core_sockaddr(addr)),
             SocketInner::Udp(s) => s.connect(into_core_sockaddr(addr)),
+            SocketInner::Unix(_) => Err(AxError::Unsupported),
         }
     }
 
@@ -581,6 +756,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.local_addr().is_ok(),
             SocketInner::Udp(s) => s.local_addr().is_ok(),
+            SocketInner::Unix(s) => s.is_bound(),
         }
     }
     #[allow(unused)]
@@ -590,6 +766,7 @@ impl Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.send(buf),
             SocketInner::Udp(s) => s.send_to(buf, into_core_sockaddr(addr)),
+            SocketInner::Unix(_) => Err(AxError::Unsupported),
         }
     }
 
@@ -614,6 +791,7 @@ impl Socket {
                     .recv_from(buf)
                     .map(|(val, addr)| (val, from_core_sockaddr(addr))),
             },
+            SocketInner::Unix(_) => Err(AxError::Unsupported),
         }
     }
 
@@ -625,6 +803,7 @@ impl Socket {
                 s.shutdown();
             }
             SocketInner::Tcp(s) => s.close(),
+            SocketInner::Unix(s) => s.shutdown(false, true),
         };
     }
 
@@ -640,6 +819,7 @@ impl Socket {
                     s.abort();
                 }
             }),
+            SocketInner::Unix(s) => s.shutdown(true, true),
         }
     }
 }
@@ -650,6 +830,12 @@ impl FileIO for Socket {
         match &mut *inner {
             SocketInner::Tcp(s) => s.read(buf),
             SocketInner::Udp(s) => s.read(buf),
+            // reading a unix socket may block, so the lock is released first
+            SocketInner::Unix(s) => {
+                let s = s.clone();
+                drop(inner);
+                s.recv(buf).map(|(len, _)| len)
+            }
         }
     }
 
@@ -658,6 +844,11 @@ impl FileIO for Socket {
         match &mut *inner {
             SocketInner::Tcp(s) => s.write(buf),
             SocketInner::Udp(s) => s.write(buf),
+            SocketInner::Unix(s) => {
+                let s = s.clone();
+                drop(inner);
+                s.send(buf, None)
+            }
         }
     }
 
@@ -671,6 +862,7 @@ impl FileIO for Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.poll().map_or(false, |p| p.readable),
             SocketInner::Udp(s) => s.poll().map_or(false, |p| p.readable),
+            SocketInner::Unix(s) => s.poll_readable(),
         }
     }
 
@@ -680,6 +872,7 @@ impl FileIO for Socket {
         match &*inner {
             SocketInner::Tcp(s) => s.poll().map_or(false, |p| p.writable),
             SocketInner::Udp(s) => s.poll().map_or(false, |p| p.writable),
+            SocketInner::Unix(s) => s.poll_writable(),
         }
     }
 
@@ -711,6 +904,13 @@ impl FileIO for Socket {
         true
     }
 
+    fn is_hang_up(&self) -> bool {
+        match &*self.inner.lock() {
+            SocketInner::Unix(s) => s.is_hang_up(),
+            _ => false,
+        }
+    }
+
     fn ready_to_read(&self) -> bool {
         self.readable()
     }
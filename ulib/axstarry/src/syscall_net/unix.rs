@@ -0,0 +1,766 @@
+//! AF_UNIX 本地套接字
+//!
+//! 支持 SOCK_STREAM、SOCK_DGRAM 与 SOCK_SEQPACKET 三种类型。每个套接字拥有自己的接收缓冲区，
+//! 向对端发送数据即写入对端的接收缓冲区。
+//!
+//! 绑定到路径的套接字会在文件系统中创建一个套接字文件，抽象命名空间中的地址则不出现在文件系统中。
+//! 两种地址都记录在 [`UNIX_NAMESPACE`] 中，路径地址以规范化后的绝对路径为键。
+//!
+//! 每条消息可以附带 SCM_RIGHTS 传递的文件与发送者的身份，接收者通过 recvmsg 取得。
+extern crate alloc;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::mem::take;
+use core::ptr::copy_nonoverlapping;
+use core::slice::from_raw_parts;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use axerrno::{AxError, AxResult};
+use axfs::api::{canonicalize, create_node, lookup, FileIO, FileType, Permissions};
+use axprocess::current_process;
+use axsync::Mutex;
+use axtask::yield_now;
+
+use super::socket::{Domain, SocketType};
+
+/// 每个接收缓冲区的容量（字节）
+const UNIX_BUF_SIZE: usize = 64 * 1024;
+
+/// sockaddr_un 中 sun_path 的长度
+const UNIX_PATH_MAX: usize = 108;
+
+/// listen 允许的最大 backlog
+const SOMAXCONN: usize = 4096;
+
+/// 本地套接字的地址
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum UnixAddr {
+    /// 未绑定的套接字
+    Unnamed,
+    /// 文件系统中的路径
+    Path(String),
+    /// 抽象命名空间中的名字，即 sun_path 中第一个字节为 0 之后的内容
+    Abstract(Vec<u8>),
+}
+
+/// 所有已绑定的本地套接字
+static UNIX_NAMESPACE: Mutex<BTreeMap<UnixAddr, Weak<UnixSocket>>> = Mutex::new(BTreeMap::new());
+
+/// 自动绑定时分配的下一个抽象地址
+static AUTOBIND_NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// 进程的身份，与 struct ucred 的布局相同
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UnixCred {
+    /// 进程号
+    pub pid: u32,
+    /// 用户 id
+    pub uid: u32,
+    /// 用户组 id
+    pub gid: u32,
+}
+
+impl UnixCred {
+    /// 没有对端时返回的身份，uid 与 gid 为 Linux 的 overflowuid 与 overflowgid
+    pub const NONE: Self = Self {
+        pid: 0,
+        uid: 65534,
+        gid: 65534,
+    };
+
+    /// 当前进程的身份。在实现多用户权限前用户与用户组均为 0
+    pub fn current() -> Self {
+        Self {
+            pid: current_process().pid() as u32,
+            uid: 0,
+            gid: 0,
+        }
+    }
+}
+
+/// 接收消息时一同取得的辅助数据
+pub struct UnixControl {
+    /// 发送者通过 SCM_RIGHTS 传递的文件
+    pub rights: Vec<Arc<dyn FileIO>>,
+    /// 发送者的身份
+    pub cred: UnixCred,
+}
+
+/// 缓冲区中的一条消息
+///
+/// 数据报与有序分组套接字每次读取一条消息，流式套接字则可以跨消息读取
+struct UnixMessage {
+    data: Vec<u8>,
+    /// 发送者的地址
+    from: UnixAddr,
+    /// 随消息传递的文件，读取消息的第一部分数据时交给接收者
+    rights: Vec<Arc<dyn FileIO>>,
+    /// 发送者的身份
+    cred: UnixCred,
+}
+
+/// 套接字的接收缓冲区
+struct UnixBuffer {
+    messages: VecDeque<UnixMessage>,
+    /// 缓冲区中数据的总长度
+    len: usize,
+    /// 对端已关闭或者 shutdown 了写端，读完缓冲区后读取返回 0
+    write_closed: bool,
+    /// 本端已关闭或者 shutdown 了读端，对端写入失败
+    read_closed: bool,
+}
+
+impl UnixBuffer {
+    const fn new() -> Self {
+        Self {
+            messages: VecDeque::new(),
+            len: 0,
+            write_closed: false,
+            read_closed: false,
+        }
+    }
+
+    fn space(&self) -> usize {
+        UNIX_BUF_SIZE.saturating_sub(self.len)
+    }
+
+    fn push(
+        &mut self,
+        data: Vec<u8>,
+        from: UnixAddr,
+        rights: Vec<Arc<dyn FileIO>>,
+        cred: UnixCred,
+    ) {
+        self.len += data.len();
+        self.messages.push_back(UnixMessage {
+            data,
+            from,
+            rights,
+            cred,
+        });
+    }
+
+    /// 流式读取，尽可能填满 `buf`
+    ///
+    /// 带有文件的消息不与其他消息在同一次读取中合并；`check_cred` 为真时，
+    /// 来自不同发送者的消息也不合并
+    fn read_stream(&mut self, buf: &mut [u8], check_cred: bool) -> (usize, UnixAddr, UnixControl) {
+        let (from, cred) = self
+            .messages
+            .front()
+            .map_or((UnixAddr::Unnamed, UnixCred::NONE), |msg| {
+                (msg.from.clone(), msg.cred)
+            });
+        let mut rights = Vec::new();
+        let mut read = 0;
+        while read < buf.len() {
+            let Some(msg) = self.messages.front_mut() else {
+                break;
+            };
+            if read > 0 && (!msg.rights.is_empty() || (check_cred && msg.cred != cred)) {
+                break;
+            }
+            rights = take(&mut msg.rights);
+            let len = msg.data.len().min(buf.len() - read);
+            buf[read..read + len].copy_from_slice(&msg.data[..len]);
+            msg.data.drain(..len);
+            read += len;
+            if msg.data.is_empty() {
+                self.messages.pop_front();
+            }
+            if !rights.is_empty() {
+                break;
+            }
+        }
+        self.len -= read;
+        (read, from, UnixControl { rights, cred })
+    }
+
+    /// 读取一条完整的消息，超出 `buf` 的部分被丢弃
+    fn read_packet(&mut self, buf: &mut [u8]) -> (usize, UnixAddr, UnixControl) {
+        let msg = self.messages.pop_front().unwrap();
+        self.len -= msg.data.len();
+        let len = msg.data.len().min(buf.len());
+        buf[..len].copy_from_slice(&msg.data[..len]);
+        let control = UnixControl {
+            rights: msg.rights,
+            cred: msg.cred,
+        };
+        (len, msg.from, control)
+    }
+}
+
+enum UnixState {
+    /// 未连接
+    Unconnected,
+    /// 正在监听，`pending` 中为已经建立但尚未被 accept 的连接
+    Listening {
+        backlog: usize,
+        pending: VecDeque<Arc<UnixSocket>>,
+        /// 调用 listen 的进程的身份，作为连接者的 SO_PEERCRED
+        cred: UnixCred,
+    },
+    /// 已连接。对于数据报套接字，表示设置了默认的发送对象
+    Connected {
+        /// 对端的接收缓冲区
+        tx: Arc<Mutex<UnixBuffer>>,
+        peer_addr: UnixAddr,
+        /// 建立连接时对端进程的身份，数据报套接字没有
+        peer_cred: Option<UnixCred>,
+    },
+}
+
+struct UnixInner {
+    addr: UnixAddr,
+    /// 在 [`UNIX_NAMESPACE`] 中注册的键
+    key: Option<UnixAddr>,
+    state: UnixState,
+}
+
+/// AF_UNIX 套接字，作为 [`super::Socket`] 的内部实现
+pub struct UnixSocket {
+    socket_type: SocketType,
+    nonblocking: AtomicBool,
+    /// SO_PASSCRED，接收消息时是否取得发送者的身份
+    passcred: AtomicBool,
+    rx: Arc<Mutex<UnixBuffer>>,
+    inner: Mutex<UnixInner>,
+}
+
+impl UnixSocket {
+    /// 创建一个未绑定、未连接的套接字
+    pub fn new(socket_type: SocketType) -> Self {
+        Self {
+            socket_type,
+            nonblocking: AtomicBool::new(false),
+            passcred: AtomicBool::new(false),
+            rx: Arc::new(Mutex::new(UnixBuffer::new())),
+            inner: Mutex::new(UnixInner {
+                addr: UnixAddr::Unnamed,
+                key: None,
+                state: UnixState::Unconnected,
+            }),
+        }
+    }
+
+    /// 创建一对互相连接的匿名套接字，用于 socketpair
+    pub fn pair(socket_type: SocketType) -> (Arc<Self>, Arc<Self>) {
+        let first = Arc::new(Self::new(socket_type.clone()));
+        let second = Arc::new(Self::new(socket_type));
+        let cred = UnixCred::current();
+        first.inner.lock().state = UnixState::Connected {
+            tx: second.rx.clone(),
+            peer_addr: UnixAddr::Unnamed,
+            peer_cred: Some(cred),
+        };
+        second.inner.lock().state = UnixState::Connected {
+            tx: first.rx.clone(),
+            peer_addr: UnixAddr::Unnamed,
+            peer_cred: Some(cred),
+        };
+        (first, second)
+    }
+
+    /// 套接字的类型
+    pub fn socket_type(&self) -> SocketType {
+        self.socket_type.clone()
+    }
+
+    /// 设置是否为非阻塞模式
+    pub fn set_nonblocking(&self, nonblocking: bool) {
+        self.nonblocking.store(nonblocking, Ordering::Release)
+    }
+
+    /// 是否为非阻塞模式
+    pub fn is_nonblocking(&self) -> bool {
+        self.nonblocking.load(Ordering::Acquire)
+    }
+
+    /// 设置 SO_PASSCRED
+    pub fn set_passcred(&self, passcred: bool) {
+        self.passcred.store(passcred, Ordering::Release)
+    }
+
+    /// 是否设置了 SO_PASSCRED
+    pub fn is_passcred(&self) -> bool {
+        self.passcred.load(Ordering::Acquire)
+    }
+
+    /// 是否已经连接
+    pub fn is_connected(&self) -> bool {
+        matches!(self.inner.lock().state, UnixState::Connected { .. })
+    }
+
+    /// 是否已经绑定地址
+    pub fn is_bound(&self) -> bool {
+        self.inner.lock().addr != UnixAddr::Unnamed
+    }
+
+    /// 本端绑定的地址
+    pub fn local_addr(&self) -> UnixAddr {
+        self.inner.lock().addr.clone()
+    }
+
+    /// 对端的地址
+    pub fn peer_addr(&self) -> AxResult<UnixAddr> {
+        match &self.inner.lock().state {
+            UnixState::Connected { peer_addr, .. } => Ok(peer_addr.clone()),
+            _ => Err(AxError::NotConnected),
+        }
+    }
+
+    /// 对端进程的身份，即 SO_PEERCRED
+    ///
+    /// 只有通过 connect、accept 或 socketpair 建立的连接才有
+    pub fn peer_cred(&self) -> Option<UnixCred> {
+        match &self.inner.lock().state {
+            UnixState::Connected { peer_cred, .. } => *peer_cred,
+            _ => None,
+        }
+    }
+
+    /// 阻塞等待之前调用，非阻塞模式下返回 WouldBlock，有待处理的信号时返回 Interrupted
+    fn before_block(&self) -> AxResult {
+        if self.is_nonblocking() {
+            return Err(AxError::WouldBlock);
+        }
+        #[cfg(feature = "signal")]
+        if axprocess::current_process().have_signals().is_some() {
+            return Err(AxError::Interrupted);
+        }
+        Ok(())
+    }
+
+    /// 绑定到地址 `addr`，`Unnamed` 表示自动绑定到一个抽象地址
+    ///
+    /// 路径地址会在文件系统中创建套接字文件，路径已存在时返回 AddrInUse
+    pub fn bind(self: &Arc<Self>, addr: UnixAddr) -> AxResult {
+        let mut inner = self.inner.lock();
+        if inner.addr != UnixAddr::Unnamed {
+            return Err(AxError::InvalidInput);
+        }
+        let mut namespace = UNIX_NAMESPACE.lock();
+        let in_use = |namespace: &BTreeMap<UnixAddr, Weak<UnixSocket>>, key: &UnixAddr| {
+            namespace
+                .get(key)
+                .is_some_and(|socket| socket.strong_count() != 0)
+        };
+        let (addr, key) = match addr {
+            UnixAddr::Unnamed => loop {
+                let name = format!(
+                    "{:05x}",
+                    AUTOBIND_NEXT.fetch_add(1, Ordering::Relaxed) & 0xfffff
+                );
+                let addr = UnixAddr::Abstract(name.into_bytes());
+                if !in_use(&namespace, &addr) {
+                    break (addr.clone(), addr);
+                }
+            },
+            UnixAddr::Path(path) => {
+                let key = UnixAddr::Path(canonicalize(&path)?);
+                // 原来的套接字文件被删除后，旧的套接字不能再通过该路径访问
+                let umask = current_process().fd_manager.get_mask() as u16;
+                let perm = Permissions::from_bits_truncate(0o777 & !umask);
+                create_node(&path, FileType::Socket, perm).map_err(|err| match err {
+                    AxError::AlreadyExists => AxError::AddrInUse,
+                    err => err,
+                })?;
+                (UnixAddr::Path(path), key)
+            }
+            addr => {
+                if in_use(&namespace, &addr) {
+                    return Err(AxError::AddrInUse);
+                }
+                (addr.clone(), addr)
+            }
+        };
+        namespace.insert(key.clone(), Arc::downgrade(self));
+        inner.addr = addr;
+        inner.key = Some(key);
+        Ok(())
+    }
+
+    /// 开始监听连接，只支持流式与有序分组套接字
+    pub fn listen(&self, backlog: usize) -> AxResult {
+        if self.socket_type == SocketType::SOCK_DGRAM {
+            return Err(AxError::Unsupported);
+        }
+        let backlog = backlog.min(SOMAXCONN);
+        let mut inner = self.inner.lock();
+        if inner.addr == UnixAddr::Unnamed {
+            return Err(AxError::InvalidInput);
+        }
+        match &mut inner.state {
+            UnixState::Unconnected => {
+                inner.state = UnixState::Listening {
+                    backlog,
+                    pending: VecDeque::new(),
+                    cred: UnixCred::current(),
+                }
+            }
+            UnixState::Listening { backlog: old, .. } => *old = backlog,
+            UnixState::Connected { .. } => return Err(AxError::InvalidInput),
+        }
+        Ok(())
+    }
+
+    /// 接受一个连接，返回新的套接字和对端的地址
+    pub fn accept(&self) -> AxResult<(Arc<Self>, UnixAddr)> {
+        if self.socket_type == SocketType::SOCK_DGRAM {
+            return Err(AxError::Unsupported);
+        }
+        loop {
+            let mut inner = self.inner.lock();
+            let UnixState::Listening { pending, .. } = &mut inner.state else {
+                return Err(AxError::InvalidInput);
+            };
+            if let Some(conn) = pending.pop_front() {
+                let addr = conn.peer_addr().unwrap_or(UnixAddr::Unnamed);
+                return Ok((conn, addr));
+            }
+            drop(inner);
+            self.before_block()?;
+            yield_now();
+        }
+    }
+
+    /// 连接到地址 `addr`
+    ///
+    /// 流式与有序分组套接字会创建一个与自身相连的套接字，放入监听者的队列中等待 accept，
+    /// 数据报套接字只记录默认的发送对象
+    pub fn connect(self: &Arc<Self>, addr: &UnixAddr) -> AxResult {
+        let target = lookup_socket(addr)?;
+        if target.socket_type != self.socket_type {
+            return Err(AxError::ConnectionRefused);
+        }
+        // 数据报套接字可以连接到自身，因此先取出对端地址再加锁
+        let peer_addr = target.local_addr();
+        if self.socket_type == SocketType::SOCK_DGRAM {
+            self.inner.lock().state = UnixState::Connected {
+                tx: target.rx.clone(),
+                peer_addr,
+                peer_cred: None,
+            };
+            return Ok(());
+        }
+
+        match self.inner.lock().state {
+            UnixState::Unconnected => {}
+            UnixState::Listening { .. } => return Err(AxError::InvalidInput),
+            UnixState::Connected { .. } => return Err(AxError::AlreadyExists),
+        }
+        let server = Arc::new(Self::new(self.socket_type.clone()));
+        let server_rx = server.rx.clone();
+        {
+            let mut server = server.inner.lock();
+            server.addr = peer_addr.clone();
+            server.state = UnixState::Connected {
+                tx: self.rx.clone(),
+                peer_addr: self.local_addr(),
+                peer_cred: Some(UnixCred::current()),
+            };
+        }
+        let listener_cred = loop {
+            let mut listener = target.inner.lock();
+            let UnixState::Listening {
+                backlog,
+                pending,
+                cred,
+            } = &mut listener.state
+            else {
+                return Err(AxError::ConnectionRefused);
+            };
+            if pending.len() <= *backlog {
+                pending.push_back(server);
+                break *cred;
+            }
+            drop(listener);
+            self.before_block()?;
+            yield_now();
+        };
+        self.inner.lock().state = UnixState::Connected {
+            tx: server_rx,
+            peer_addr,
+            peer_cred: Some(listener_cred),
+        };
+        Ok(())
+    }
+
+    /// 发送数据，`to` 为数据报套接字的目标地址，为 `None` 时发送给已连接的对端
+    pub fn send(&self, buf: &[u8], to: Option<&UnixAddr>) -> AxResult<usize> {
+        self.send_msg(buf, to, Vec::new(), UnixCred::current())
+    }
+
+    /// 发送一条带有辅助数据的消息，`rights` 为传递给接收者的文件，`cred` 为发送者的身份
+    ///
+    /// 流式套接字在阻塞模式下会写完所有数据，文件随第一部分数据发送；
+    /// 其他类型的套接字每次发送一条消息，消息过长时返回 InvalidInput
+    pub fn send_msg(
+        &self,
+        buf: &[u8],
+        to: Option<&UnixAddr>,
+        rights: Vec<Arc<dyn FileIO>>,
+        cred: UnixCred,
+    ) -> AxResult<usize> {
+        let tx = match (to, &self.inner.lock().state) {
+            (Some(addr), _) if self.socket_type == SocketType::SOCK_DGRAM => {
+                let target = lookup_socket(addr)?;
+                if target.socket_type != SocketType::SOCK_DGRAM {
+                    return Err(AxError::ConnectionRefused);
+                }
+                target.rx.clone()
+            }
+            (Some(_), UnixState::Connected { .. }) => return Err(AxError::AlreadyExists),
+            (Some(_), _) => return Err(AxError::Unsupported),
+            (None, UnixState::Connected { tx, .. }) => tx.clone(),
+            (None, _) => return Err(AxError::NotConnected),
+        };
+        let from = self.local_addr();
+        if self.socket_type == SocketType::SOCK_STREAM {
+            self.send_stream(&tx, buf, from, rights, cred)
+        } else {
+            self.send_packet(&tx, buf, from, rights, cred)
+        }
+    }
+
+    fn send_stream(
+        &self,
+        tx: &Mutex<UnixBuffer>,
+        buf: &[u8],
+        from: UnixAddr,
+        mut rights: Vec<Arc<dyn FileIO>>,
+        cred: UnixCred,
+    ) -> AxResult<usize> {
+        let mut sent = 0;
+        loop {
+            let mut tx = tx.lock();
+            if tx.read_closed || tx.write_closed {
+                // 已经写入部分数据时返回写入的长度
+                return if sent > 0 {
+                    Ok(sent)
+                } else {
+                    Err(AxError::BrokenPipe)
+                };
+            }
+            let len = tx.space().min(buf.len() - sent);
+            if len > 0 {
+                tx.push(
+                    buf[sent..sent + len].to_vec(),
+                    from.clone(),
+                    take(&mut rights),
+                    cred,
+                );
+                sent += len;
+            }
+            if sent == buf.len() {
+                return Ok(sent);
+            }
+            drop(tx);
+            if let Err(err) = self.before_block() {
+                return if sent > 0 { Ok(sent) } else { Err(err) };
+            }
+            yield_now();
+        }
+    }
+
+    fn send_packet(
+        &self,
+        tx: &Mutex<UnixBuffer>,
+        buf: &[u8],
+        from: UnixAddr,
+        rights: Vec<Arc<dyn FileIO>>,
+        cred: UnixCred,
+    ) -> AxResult<usize> {
+        if buf.len() > UNIX_BUF_SIZE {
+            return Err(AxError::InvalidInput);
+        }
+        loop {
+            let mut tx = tx.lock();
+            if tx.read_closed || tx.write_closed {
+                // 数据报的接收者已经关闭
+                return Err(if self.socket_type == SocketType::SOCK_DGRAM {
+                    AxError::ConnectionRefused
+                } else {
+                    AxError::BrokenPipe
+                });
+            }
+            if tx.space() >= buf.len() {
+                tx.push(buf.to_vec(), from, rights, cred);
+                return Ok(buf.len());
+            }
+            drop(tx);
+            self.before_block()?;
+            yield_now();
+        }
+    }
+
+    /// 接收数据，返回读取的长度与发送者的地址，随消息传递的文件被丢弃
+    pub fn recv(&self, buf: &mut [u8]) -> AxResult<(usize, UnixAddr)> {
+        self.recv_msg(buf).map(|(len, from, _)| (len, from))
+    }
+
+    /// 接收数据，返回读取的长度、发送者的地址与辅助数据
+    ///
+    /// 对端关闭写端且缓冲区为空时返回 0
+    pub fn recv_msg(&self, buf: &mut [u8]) -> AxResult<(usize, UnixAddr, UnixControl)> {
+        match self.inner.lock().state {
+            UnixState::Listening { .. } => return Err(AxError::InvalidInput),
+            UnixState::Unconnected if self.socket_type != SocketType::SOCK_DGRAM => {
+                return Err(AxError::NotConnected)
+            }
+            _ => {}
+        }
+        loop {
+            let mut rx = self.rx.lock();
+            if !rx.messages.is_empty() {
+                return Ok(if self.socket_type == SocketType::SOCK_STREAM {
+                    rx.read_stream(buf, self.is_passcred())
+                } else {
+                    rx.read_packet(buf)
+                });
+            }
+            if rx.read_closed || rx.write_closed {
+                let control = UnixControl {
+                    rights: Vec::new(),
+                    cred: UnixCred::NONE,
+                };
+                return Ok((0, UnixAddr::Unnamed, control));
+            }
+            drop(rx);
+            self.before_block()?;
+            yield_now();
+        }
+    }
+
+    /// 关闭读端或写端
+    pub fn shutdown(&self, read: bool, write: bool) {
+        if read {
+            self.rx.lock().read_closed = true;
+        }
+        // 数据报套接字的发送缓冲区被多个发送者共享，不能关闭
+        if write && self.socket_type != SocketType::SOCK_DGRAM {
+            if let UnixState::Connected { tx, .. } = &self.inner.lock().state {
+                tx.lock().write_closed = true;
+            }
+        }
+    }
+
+    /// 有数据可读、有连接可以 accept 或者读取会立即返回 0 时可读
+    pub fn poll_readable(&self) -> bool {
+        if let UnixState::Listening { pending, .. } = &self.inner.lock().state {
+            return !pending.is_empty();
+        }
+        let rx = self.rx.lock();
+        !rx.messages.is_empty() || rx.read_closed || rx.write_closed
+    }
+
+    /// 对端的缓冲区有空间或者写入会立即失败时可写
+    pub fn poll_writable(&self) -> bool {
+        match &self.inner.lock().state {
+            UnixState::Connected { tx, .. } => {
+                let tx = tx.lock();
+                tx.read_closed || tx.write_closed || tx.space() > 0
+            }
+            UnixState::Listening { .. } => false,
+            UnixState::Unconnected => self.socket_type == SocketType::SOCK_DGRAM,
+        }
+    }
+
+    /// 面向连接的套接字的对端已经关闭
+    pub fn is_hang_up(&self) -> bool {
+        if self.socket_type == SocketType::SOCK_DGRAM {
+            return false;
+        }
+        // 不同时持有两个缓冲区的锁，避免与对端互相等待
+        let peer_closed = match &self.inner.lock().state {
+            UnixState::Connected { tx, .. } => tx.lock().read_closed,
+            _ => false,
+        };
+        peer_closed && self.rx.lock().write_closed
+    }
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        self.shutdown(true, true);
+        if let Some(key) = self.inner.lock().key.take() {
+            let mut namespace = UNIX_NAMESPACE.lock();
+            // 地址可能已经被其他套接字重新绑定
+            if namespace
+                .get(&key)
+                .is_some_and(|socket| socket.strong_count() == 0)
+            {
+                namespace.remove(&key);
+            }
+        }
+    }
+}
+
+/// 查找绑定到 `addr` 的套接字
+fn lookup_socket(addr: &UnixAddr) -> AxResult<Arc<UnixSocket>> {
+    let key = match addr {
+        UnixAddr::Unnamed => return Err(AxError::InvalidInput),
+        UnixAddr::Path(path) => {
+            if !lookup(path)?.get_attr()?.file_type().is_socket() {
+                return Err(AxError::ConnectionRefused);
+            }
+            UnixAddr::Path(canonicalize(path)?)
+        }
+        addr => addr.clone(),
+    };
+    UNIX_NAMESPACE
+        .lock()
+        .get(&key)
+        .and_then(Weak::upgrade)
+        .ok_or(AxError::ConnectionRefused)
+}
+
+/// 从用户态的 sockaddr_un 中读取地址，`addr_len` 为地址的长度
+///
+/// 只有 sun_family 的地址为 `Unnamed`
+pub unsafe fn unix_address_from(addr: *const u8, addr_len: usize) -> AxResult<UnixAddr> {
+    if !(2..=2 + UNIX_PATH_MAX).contains(&addr_len)
+        || *(addr as *const u16) != Domain::AF_UNIX as u16
+    {
+        return Err(AxError::InvalidInput);
+    }
+    let path = from_raw_parts(addr.add(2), addr_len - 2);
+    match path.first() {
+        None => Ok(UnixAddr::Unnamed),
+        Some(0) => Ok(UnixAddr::Abstract(path[1..].to_vec())),
+        Some(_) => {
+            let len = path.iter().position(|&c| c == 0).unwrap_or(path.len());
+            String::from_utf8(path[..len].to_vec())
+                .map(UnixAddr::Path)
+                .map_err(|_| AxError::InvalidInput)
+        }
+    }
+}
+
+/// 将地址写入用户态的 sockaddr_un
+///
+/// 地址超出 `*buf_len` 的部分被截断，`*buf_len` 被设为地址的实际长度
+pub unsafe fn unix_address_to(addr: &UnixAddr, buf: *mut u8, buf_len: *mut u32) {
+    if buf.is_null() || buf_len.is_null() {
+        return;
+    }
+    let mut bytes = (Domain::AF_UNIX as u16).to_ne_bytes().to_vec();
+    match addr {
+        UnixAddr::Unnamed => {}
+        UnixAddr::Path(path) => {
+            bytes.extend_from_slice(path.as_bytes());
+            bytes.push(0);
+        }
+        UnixAddr::Abstract(name) => {
+            bytes.push(0);
+            bytes.extend_from_slice(name);
+        }
+    }
+    let len = (*buf_len as usize).min(bytes.len());
+    copy_nonoverlapping(bytes.as_ptr(), buf, len);
+    *buf_len = bytes.len() as u32;
+}
@@ -76,6 +76,11 @@ pub fn futex(
                 // debug!("ready wait!");
                 if timeout == 0 {
                     yield_now_task();
+                    #[cfg(feature = "signal")]
+                    if process.have_signals().is_some() {
+                        // 被信号打断，没有超时时间的等待可以重新执行
+                        return Err(SyscallError::ERESTARTSYS);
+                    }
                 }
                 #[cfg(feature = "signal")]
                 {
@@ -84,7 +89,7 @@ pub fn futex(
                         && !WAIT_FOR_FUTEX.wait_timeout(Duration::from_nanos(timeout as u64))
                         && process.have_signals().is_some()
                     {
-                        // 被信号打断
+                        // 被信号打断，重新执行会使用原来的相对超时时间，因此直接返回 EINTR
                         return Err(SyscallError::EINTR);
                     }
                 }
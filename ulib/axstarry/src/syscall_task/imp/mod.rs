@@ -6,8 +6,12 @@ mod futex;
 
 mod schedule;
 
+mod seccomp;
+
 mod task;
 
+mod timer;
+
 mod utils;
 
 #[cfg(feature = "signal")]
@@ -18,6 +22,10 @@ pub use futex::*;
 
 pub use schedule::*;
 
+pub use seccomp::*;
+
 pub use task::*;
 
+pub use timer::*;
+
 pub use utils::*;
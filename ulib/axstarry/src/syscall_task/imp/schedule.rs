@@ -1,14 +1,74 @@
 //! 支持与任务调度相关的 syscall
 extern crate alloc;
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec, vec::Vec};
 use axconfig::SMP;
-use axhal::mem::VirtAddr;
-use axprocess::{current_process, current_task, PID2PC, TID2TASK};
+use axhal::{
+    cpu::this_cpu_id,
+    mem::{VirtAddr, PAGE_SIZE_4K},
+};
+use axprocess::{current_process, current_task, yield_now_task, PID2PC, TID2TASK};
 
 // #[cfg(feature = "signal")]
-use axtask::{SchedPolicy, SchedStatus};
+use axtask::{AxTaskRef, SchedPolicy, SchedStatus};
 
-use crate::{SchedParam, SyscallError, SyscallResult};
+use crate::{
+    SchedAttr, SchedParam, SyscallError, SyscallResult, TimeSecs, PRIO_PGRP, PRIO_PROCESS,
+    PRIO_USER, SCHED_ATTR_SIZE_VER0,
+};
+
+/// 根据 pid 查找任务
+///
+/// pid 可以是线程 ID，也可以是进程 ID（对应进程的主线程），为 0 时表示当前任务
+fn find_task(pid: u64) -> Result<AxTaskRef, SyscallError> {
+    if let Some(task) = TID2TASK.lock().get(&pid) {
+        return Ok(Arc::clone(task));
+    }
+    if let Some(process) = PID2PC.lock().get(&pid) {
+        return process
+            .tasks
+            .lock()
+            .iter()
+            .find(|task| task.is_leader())
+            .map(Arc::clone)
+            .ok_or(SyscallError::ESRCH);
+    }
+    if pid == 0 {
+        return Ok(Arc::clone(current_task().as_task_ref()));
+    }
+    Err(SyscallError::ESRCH)
+}
+
+/// 修改任务的调度策略与实时优先级
+///
+/// 调度器不支持实时策略时返回 EPERM，不支持的非实时策略只记录在任务中
+fn apply_sched_status(task: &AxTaskRef, status: SchedStatus) -> Result<(), SyscallError> {
+    if axtask::set_sched_status(task, status) {
+        return Ok(());
+    }
+    match status.policy {
+        SchedPolicy::SCHED_FIFO | SchedPolicy::SCHED_RR => Err(SyscallError::EPERM),
+        SchedPolicy::SCHED_UNKNOWN => Err(SyscallError::EINVAL),
+        _ => Ok(()),
+    }
+}
+
+/// 检查调度策略对应的实时优先级是否合法
+///
+/// SCHED_FIFO 与 SCHED_RR 的优先级为 1 到 99，其他策略的优先级必须为 0
+fn check_sched_priority(policy: SchedPolicy, priority: usize) -> Result<(), SyscallError> {
+    let valid = match policy {
+        SchedPolicy::SCHED_FIFO | SchedPolicy::SCHED_RR => (1..=99).contains(&priority),
+        SchedPolicy::SCHED_OTHER | SchedPolicy::SCHED_BATCH | SchedPolicy::SCHED_IDLE => {
+            priority == 0
+        }
+        SchedPolicy::SCHED_UNKNOWN => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(SyscallError::EINVAL)
+    }
+}
 /// 获取对应任务的CPU适配集
 ///
 /// 若pid是进程ID，则获取对应的进程的主线程的信息
@@ -26,31 +86,7 @@ pub fn syscall_sched_getaffinity(args: [usize; 6]) -> SyscallResult {
     let pid = args[0];
     let cpu_set_size = args[1];
     let mask = args[2] as *mut usize;
-    // let task: LazyInit<AxTaskRef> = LazyInit::new();
-    let tid2task = TID2TASK.lock();
-    let pid2task = PID2PC.lock();
-    let pid = pid as u64;
-    let task = if tid2task.contains_key(&pid) {
-        Arc::clone(tid2task.get(&pid).unwrap())
-    } else if pid2task.contains_key(&pid) {
-        let process = pid2task.get(&pid).unwrap();
-
-        process
-            .tasks
-            .lock()
-            .iter()
-            .find(|task| task.is_leader())
-            .map(Arc::clone)
-            .unwrap()
-    } else if pid == 0 {
-        Arc::clone(current_task().as_task_ref())
-    } else {
-        // 找不到对应任务
-        return Err(SyscallError::ESRCH);
-    };
-
-    drop(pid2task);
-    drop(tid2task);
+    let task = find_task(pid as u64)?;
 
     let process = current_process();
     if process
@@ -59,51 +95,30 @@ pub fn syscall_sched_getaffinity(args: [usize; 6]) -> SyscallResult {
     {
         return Err(SyscallError::EFAULT);
     }
-    let cpu_set = task.get_cpu_set();
-    let mut prev_mask = unsafe { *mask };
-    let len = SMP.min(cpu_set_size * 4);
-    prev_mask &= !((1 << len) - 1);
-    prev_mask &= cpu_set & ((1 << len) - 1);
+    // 缓冲区需要能容纳所有 CPU 对应的位
+    if cpu_set_size.saturating_mul(8) < SMP {
+        return Err(SyscallError::EINVAL);
+    }
     unsafe {
-        *mask = prev_mask;
+        *mask = task.get_cpu_set();
     }
     // 返回成功填充的缓冲区的长度
-    Ok(SMP as isize)
+    Ok(core::mem::size_of::<usize>() as isize)
 }
 
+/// 设置对应任务的CPU适配集
+///
+/// pid 的含义与 `syscall_sched_getaffinity` 相同，mask 中不包含任何可用的 CPU 时返回 EINVAL。
+/// 若当前任务不再允许在所在的 CPU 上运行，会立即让出 CPU 以迁移到其他 CPU
 /// # Arguments
 /// * `pid` - usize
 /// * `cpu_set_size` - usize
 /// * `mask` - *const usize
-#[allow(unused)]
 pub fn syscall_sched_setaffinity(args: [usize; 6]) -> SyscallResult {
     let pid = args[0];
     let cpu_set_size = args[1];
     let mask = args[2] as *const usize;
-    let tid2task = TID2TASK.lock();
-    let pid2task = PID2PC.lock();
-    let pid = pid as u64;
-    let task = if tid2task.contains_key(&pid) {
-        Arc::clone(tid2task.get(&pid).unwrap())
-    } else if pid2task.contains_key(&pid) {
-        let process = pid2task.get(&pid).unwrap();
-
-        process
-            .tasks
-            .lock()
-            .iter()
-            .find(|task| task.is_leader())
-            .map(Arc::clone)
-            .unwrap()
-    } else if pid == 0 {
-        Arc::clone(current_task().as_task_ref())
-    } else {
-        // 找不到对应任务
-        return Err(SyscallError::ESRCH);
-    };
-
-    drop(pid2task);
-    drop(tid2task);
+    let task = find_task(pid as u64)?;
 
     let process = current_process();
     if process
@@ -115,7 +130,15 @@ pub fn syscall_sched_setaffinity(args: [usize; 6]) -> SyscallResult {
 
     let mask = unsafe { *mask };
 
+    let old_mask = task.get_cpu_set();
     task.set_cpu_set(mask, cpu_set_size);
+    if task.get_cpu_set() == 0 {
+        task.set_cpu_set(old_mask, core::mem::size_of::<usize>());
+        return Err(SyscallError::EINVAL);
+    }
+    if Arc::ptr_eq(&task, current_task().as_task_ref()) && !task.allowed_on(this_cpu_id()) {
+        yield_now_task();
+    }
 
     Ok(0)
 }
@@ -132,30 +155,7 @@ pub fn syscall_sched_setscheduler(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EINVAL);
     }
 
-    let tid2task = TID2TASK.lock();
-    let pid2task = PID2PC.lock();
-    let pid = pid as u64;
-    let task = if tid2task.contains_key(&pid) {
-        Arc::clone(tid2task.get(&pid).unwrap())
-    } else if pid2task.contains_key(&pid) {
-        let process = pid2task.get(&pid).unwrap();
-
-        process
-            .tasks
-            .lock()
-            .iter()
-            .find(|task| task.is_leader())
-            .map(Arc::clone)
-            .unwrap()
-    } else if pid == 0 {
-        Arc::clone(current_task().as_task_ref())
-    } else {
-        // 找不到对应任务
-        return Err(SyscallError::ESRCH);
-    };
-
-    drop(pid2task);
-    drop(tid2task);
+    let task = find_task(pid as u64)?;
 
     let process = current_process();
     if process
@@ -167,61 +167,258 @@ pub fn syscall_sched_setscheduler(args: [usize; 6]) -> SyscallResult {
 
     let param = unsafe { *param };
     let policy = SchedPolicy::from(policy);
-    if policy == SchedPolicy::SCHED_UNKNOWN {
+    check_sched_priority(policy, param.sched_priority)?;
+
+    apply_sched_status(
+        &task,
+        SchedStatus {
+            policy,
+            priority: param.sched_priority,
+        },
+    )?;
+
+    Ok(0)
+}
+
+/// # Arguments
+/// * `pid` - usize
+pub fn syscall_sched_getscheduler(args: [usize; 6]) -> SyscallResult {
+    let pid = args[0];
+    if (pid as isize) < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let task = find_task(pid as u64)?;
+
+    let policy: isize = task.get_sched_status().policy.into();
+    Ok(policy)
+}
+
+/// 获取调度策略对应的最高优先级
+///
+/// SCHED_FIFO 与 SCHED_RR 为 99，其他策略为 0
+/// # Arguments
+/// * `policy` - usize
+pub fn syscall_sched_get_priority_max(args: [usize; 6]) -> SyscallResult {
+    match SchedPolicy::from(args[0]) {
+        SchedPolicy::SCHED_FIFO | SchedPolicy::SCHED_RR => Ok(99),
+        SchedPolicy::SCHED_UNKNOWN => Err(SyscallError::EINVAL),
+        _ => Ok(0),
+    }
+}
+
+/// 获取调度策略对应的最低优先级
+///
+/// SCHED_FIFO 与 SCHED_RR 为 1，其他策略为 0
+/// # Arguments
+/// * `policy` - usize
+pub fn syscall_sched_get_priority_min(args: [usize; 6]) -> SyscallResult {
+    match SchedPolicy::from(args[0]) {
+        SchedPolicy::SCHED_FIFO | SchedPolicy::SCHED_RR => Ok(1),
+        SchedPolicy::SCHED_UNKNOWN => Err(SyscallError::EINVAL),
+        _ => Ok(0),
+    }
+}
+
+/// 获取对应任务在 SCHED_RR 策略下的时间片长度
+///
+/// 其他策略的任务不会按时间片轮转，返回 0
+/// # Arguments
+/// * `pid` - usize
+/// * `interval` - *mut TimeSecs
+pub fn syscall_sched_rr_get_interval(args: [usize; 6]) -> SyscallResult {
+    let pid = args[0];
+    let interval = args[1] as *mut TimeSecs;
+    if (pid as isize) < 0 {
         return Err(SyscallError::EINVAL);
     }
-    if policy == SchedPolicy::SCHED_OTHER
-        || policy == SchedPolicy::SCHED_BATCH
-        || policy == SchedPolicy::SCHED_IDLE
+
+    let task = find_task(pid as u64)?;
+
+    let process = current_process();
+    if process
+        .manual_alloc_type_for_lazy(interval as *const TimeSecs)
+        .is_err()
     {
-        if param.sched_priority != 0 {
-            return Err(SyscallError::EINVAL);
+        return Err(SyscallError::EFAULT);
+    }
+    let interval_ns = if task.get_sched_status().policy == SchedPolicy::SCHED_RR {
+        axtask::rr_interval().as_nanos() as usize
+    } else {
+        0
+    };
+    unsafe {
+        *interval = TimeSecs::from_nanos(interval_ns);
+    }
+    Ok(0)
+}
+
+/// 获取 getpriority / setpriority 作用的所有任务
+///
+/// PRIO_PROCESS 的 who 为线程 ID 或进程 ID，为 0 时表示当前任务。
+/// 在实现进程组与多用户之前，PRIO_PGRP 与 PRIO_USER 的 who 只能为 0，
+/// 此时作用于调用者进程及其后代进程的所有线程
+fn priority_targets(which: usize, who: usize) -> Result<Vec<AxTaskRef>, SyscallError> {
+    match which {
+        PRIO_PROCESS => Ok(vec![find_task(who as u64)?]),
+        PRIO_PGRP | PRIO_USER => {
+            if who != 0 {
+                return Err(SyscallError::ESRCH);
+            }
+            let process = current_process();
+            let mut tasks = process.tasks.lock().clone();
+            for descendant in process.descendants() {
+                tasks.extend(descendant.tasks.lock().iter().cloned());
+            }
+            Ok(tasks)
         }
-    } else if param.sched_priority < 1 || param.sched_priority > 99 {
-        return Err(SyscallError::EINVAL);
+        _ => Err(SyscallError::EINVAL),
     }
+}
 
-    task.set_sched_status(SchedStatus {
-        policy,
-        priority: param.sched_priority,
-    });
+/// 获取任务的 nice 值
+///
+/// 作用于多个任务时取其中最高的优先级，即最小的 nice 值。
+/// 为了避免返回负数，返回值为 20 - nice，范围为 1 到 40，由 libc 转换为 nice 值
+/// # Arguments
+/// * `which` - usize
+/// * `who` - usize
+pub fn syscall_getpriority(args: [usize; 6]) -> SyscallResult {
+    let which = args[0];
+    let who = args[1];
+    let nice = priority_targets(which, who)?
+        .iter()
+        .map(|task| task.nice())
+        .min()
+        .unwrap_or(0);
+    Ok(20 - nice)
+}
 
+/// 设置任务的 nice 值，超出 -20 到 19 的值会被截断
+///
+/// nice 值决定了任务在公平调度类中的权重，会被 clone 出的子任务继承
+/// # Arguments
+/// * `which` - usize
+/// * `who` - usize
+/// * `prio` - i32
+pub fn syscall_setpriority(args: [usize; 6]) -> SyscallResult {
+    let which = args[0];
+    let who = args[1];
+    let nice = (args[2] as i32 as isize).clamp(-20, 19);
+    for task in priority_targets(which, who)? {
+        axtask::set_task_priority(&task, nice);
+    }
     Ok(0)
 }
 
+/// 设置任务的调度策略与参数
+///
+/// 对于 SCHED_OTHER 与 SCHED_BATCH 策略，同时设置任务的 nice 值。
+/// 不支持 SCHED_DEADLINE 策略与任何调度标志
 /// # Arguments
 /// * `pid` - usize
-pub fn syscall_sched_getscheduler(args: [usize; 6]) -> SyscallResult {
+/// * `attr` - *const SchedAttr
+/// * `flags` - usize
+pub fn syscall_sched_setattr(args: [usize; 6]) -> SyscallResult {
     let pid = args[0];
-    if (pid as isize) < 0 {
+    let attr = args[1] as *mut SchedAttr;
+    let flags = args[2];
+    if (pid as isize) < 0 || attr.is_null() || flags != 0 {
         return Err(SyscallError::EINVAL);
     }
 
-    let tid2task = TID2TASK.lock();
-    let pid2task = PID2PC.lock();
-    let pid = pid as u64;
-    let task = if tid2task.contains_key(&pid) {
-        Arc::clone(tid2task.get(&pid).unwrap())
-    } else if pid2task.contains_key(&pid) {
-        let process = pid2task.get(&pid).unwrap();
+    let process = current_process();
+    if process
+        .manual_alloc_type_for_lazy(attr as *const SchedAttr)
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let sched_attr = unsafe { *attr };
+    // size 为 0 时表示第一个版本的结构体
+    let size = match sched_attr.size {
+        0 => SCHED_ATTR_SIZE_VER0,
+        size => size,
+    } as usize;
+    let ver0_size = SCHED_ATTR_SIZE_VER0 as usize;
+    let mut too_big = size < ver0_size || size > PAGE_SIZE_4K;
+    if !too_big && size > ver0_size {
+        // 更高版本的结构体中，不支持的字段必须为 0
+        let start = attr as usize + ver0_size;
+        if process
+            .manual_alloc_range_for_lazy(start.into(), (attr as usize + size).into())
+            .is_err()
+        {
+            return Err(SyscallError::EFAULT);
+        }
+        let extra = unsafe { core::slice::from_raw_parts(start as *const u8, size - ver0_size) };
+        too_big = extra.iter().any(|&byte| byte != 0);
+    }
+    if too_big {
+        // 告知用户态支持的结构体大小
+        unsafe {
+            (*attr).size = SCHED_ATTR_SIZE_VER0;
+        }
+        return Err(SyscallError::E2BIG);
+    }
+    if sched_attr.sched_flags != 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let policy = SchedPolicy::from(sched_attr.sched_policy as usize);
+    let priority = sched_attr.sched_priority as usize;
+    check_sched_priority(policy, priority)?;
 
-        process
-            .tasks
-            .lock()
-            .iter()
-            .find(|task| task.is_leader())
-            .map(Arc::clone)
-            .unwrap()
-    } else if pid == 0 {
-        Arc::clone(current_task().as_task_ref())
-    } else {
-        // 找不到对应任务
-        return Err(SyscallError::ESRCH);
-    };
+    let task = find_task(pid as u64)?;
+    apply_sched_status(&task, SchedStatus { policy, priority })?;
+    if matches!(policy, SchedPolicy::SCHED_OTHER | SchedPolicy::SCHED_BATCH) {
+        axtask::set_task_priority(&task, (sched_attr.sched_nice as isize).clamp(-20, 19));
+    }
+    Ok(0)
+}
 
-    drop(pid2task);
-    drop(tid2task);
+/// 获取任务的调度策略与参数
+///
+/// # Arguments
+/// * `pid` - usize
+/// * `attr` - *mut SchedAttr
+/// * `size` - usize, 用户态结构体的大小
+/// * `flags` - usize
+pub fn syscall_sched_getattr(args: [usize; 6]) -> SyscallResult {
+    let pid = args[0];
+    let attr = args[1] as *mut SchedAttr;
+    let size = args[2];
+    let flags = args[3];
+    if (pid as isize) < 0
+        || attr.is_null()
+        || size < SCHED_ATTR_SIZE_VER0 as usize
+        || size > PAGE_SIZE_4K
+        || flags != 0
+    {
+        return Err(SyscallError::EINVAL);
+    }
+    let task = find_task(pid as u64)?;
 
-    let policy: isize = task.get_sched_status().policy.into();
-    Ok(policy)
+    let process = current_process();
+    if process
+        .manual_alloc_type_for_lazy(attr as *const SchedAttr)
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let status = task.get_sched_status();
+    let mut sched_attr = SchedAttr {
+        size: SCHED_ATTR_SIZE_VER0,
+        sched_policy: isize::from(status.policy) as u32,
+        ..Default::default()
+    };
+    match status.policy {
+        SchedPolicy::SCHED_FIFO | SchedPolicy::SCHED_RR => {
+            sched_attr.sched_priority = status.priority as u32
+        }
+        _ => sched_attr.sched_nice = task.nice() as i32,
+    }
+    unsafe {
+        *attr = sched_attr;
+    }
+    Ok(0)
 }
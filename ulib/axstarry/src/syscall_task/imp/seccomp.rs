@@ -0,0 +1,252 @@
+//! 与 seccomp 相关的系统调用
+extern crate alloc;
+use alloc::vec::Vec;
+use core::sync::atomic::Ordering;
+
+use axlog::{info, warn};
+use axprocess::{
+    current_process, current_task,
+    seccomp::{
+        SeccompData, SeccompFilter, SockFilter, SockFprog, AUDIT_ARCH_CURRENT, BPF_MAXINSNS,
+        SECCOMP_MODE_DISABLED, SECCOMP_MODE_FILTER, SECCOMP_MODE_STRICT, SECCOMP_RET_ACTION_FULL,
+        SECCOMP_RET_ALLOW, SECCOMP_RET_DATA, SECCOMP_RET_ERRNO, SECCOMP_RET_KILL_PROCESS,
+        SECCOMP_RET_KILL_THREAD, SECCOMP_RET_LOG, SECCOMP_RET_TRACE, SECCOMP_RET_TRAP,
+        SECCOMP_RET_USER_NOTIF,
+    },
+    Process,
+};
+#[cfg(feature = "signal")]
+use axsignal::{info::SigInfo, signal_no::SignalNo};
+
+use crate::{syscall_fs::FsSyscallId, syscall_task::TaskSyscallId, SyscallError, SyscallResult};
+
+/// 进入 STRICT 模式
+const SECCOMP_SET_MODE_STRICT: usize = 0;
+/// 安装 BPF 过滤器
+const SECCOMP_SET_MODE_FILTER: usize = 1;
+/// 查询内核是否支持某个动作
+const SECCOMP_GET_ACTION_AVAIL: usize = 2;
+/// 将过滤器同步到进程内所有线程，当前过滤器本身就由进程内线程共享
+const SECCOMP_FILTER_FLAG_TSYNC: usize = 1;
+
+/// 在分发系统调用之前进行 seccomp 检查
+///
+/// 若系统调用不应当被执行，则返回需要交给用户的返回值
+pub fn seccomp_check(syscall_id: usize, args: [usize; 6]) -> Option<isize> {
+    // 模式只能从关闭切换到开启，任务上的缓存为 0 时进程一定没有开启 seccomp
+    if current_task().seccomp_mode() == SECCOMP_MODE_DISABLED {
+        return None;
+    }
+    let process = current_process();
+    let seccomp = process.seccomp.lock();
+    let action = match seccomp.mode {
+        SECCOMP_MODE_STRICT => {
+            if syscall_id == FsSyscallId::READ as usize
+                || syscall_id == FsSyscallId::WRITE as usize
+                || syscall_id == TaskSyscallId::EXIT as usize
+                || syscall_id == TaskSyscallId::SIGRETURN as usize
+            {
+                SECCOMP_RET_ALLOW
+            } else {
+                SECCOMP_RET_KILL_THREAD
+            }
+        }
+        SECCOMP_MODE_FILTER => {
+            let pc = unsafe { (*current_task().get_first_trap_frame()).get_pc() };
+            let data = SeccompData {
+                nr: syscall_id as i32,
+                arch: AUDIT_ARCH_CURRENT,
+                instruction_pointer: pc as u64,
+                args: args.map(|arg| arg as u64),
+            };
+            seccomp.run_filters(&data)
+        }
+        _ => return None,
+    };
+    drop(seccomp);
+    let data = action & SECCOMP_RET_DATA;
+    match action & SECCOMP_RET_ACTION_FULL {
+        SECCOMP_RET_ALLOW => None,
+        SECCOMP_RET_LOG => {
+            info!("[seccomp] log syscall {}", syscall_id);
+            None
+        }
+        SECCOMP_RET_ERRNO => Some(-(data as isize)),
+        SECCOMP_RET_TRAP => {
+            // 不执行系统调用，并向当前线程发送 SIGSYS
+            #[cfg(feature = "signal")]
+            {
+                let pc = unsafe { (*current_task().get_first_trap_frame()).get_pc() };
+                let info = SigInfo::seccomp(pc, syscall_id as i32, AUDIT_ARCH_CURRENT, data as i32);
+                let _ = axprocess::signal::send_signal_to_thread_with_info(
+                    current_task().id().as_u64() as isize,
+                    SignalNo::SIGSYS as isize,
+                    Some(info),
+                );
+            }
+            Some(-(SyscallError::ENOSYS.code() as isize))
+        }
+        // 没有跟踪者与监听者时，与 Linux 相同地返回 ENOSYS
+        SECCOMP_RET_TRACE | SECCOMP_RET_USER_NOTIF => Some(-(SyscallError::ENOSYS.code() as isize)),
+        // 与 Linux 相同，进程只剩一个线程时 KILL_THREAD 等同于 KILL_PROCESS
+        SECCOMP_RET_KILL_THREAD if process.tasks.lock().len() > 1 => {
+            let task = current_task();
+            warn!(
+                "[seccomp] kill thread {} at syscall {}",
+                task.id().as_u64(),
+                syscall_id
+            );
+            // 主线程被终止时，进程在其余线程退出后以 SIGSYS 终止
+            #[cfg(feature = "signal")]
+            let exit_code = {
+                if task.is_leader() {
+                    process.set_term_signal(SignalNo::SIGSYS as i32);
+                }
+                SignalNo::SIGSYS as i32
+            };
+            #[cfg(not(feature = "signal"))]
+            let exit_code = -1;
+            drop(process);
+            axprocess::exit_current_task(exit_code)
+        }
+        _ => {
+            warn!(
+                "[seccomp] kill process {} at syscall {}",
+                process.pid(),
+                syscall_id
+            );
+            #[cfg(feature = "signal")]
+            {
+                let _ = axprocess::signal::send_signal_to_process(
+                    process.pid() as isize,
+                    SignalNo::SIGKILL as isize,
+                );
+                Some(-(SyscallError::ENOSYS.code() as isize))
+            }
+            #[cfg(not(feature = "signal"))]
+            axprocess::exit_current_task(-1)
+        }
+    }
+}
+
+/// 从用户空间读入 BPF 程序，并安装到当前进程
+fn install_filter(prog: *const SockFprog) -> SyscallResult {
+    let process = current_process();
+    if process.manual_alloc_type_for_lazy(prog).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let prog = unsafe { *prog };
+    let len = prog.len as usize;
+    if len == 0 || len > BPF_MAXINSNS {
+        return Err(SyscallError::EINVAL);
+    }
+    let start = prog.filter as usize;
+    let end = start + len * core::mem::size_of::<SockFilter>();
+    if process
+        .manual_alloc_range_for_lazy(start.into(), (end - 1).into())
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let insns: Vec<SockFilter> = unsafe { core::slice::from_raw_parts(prog.filter, len) }.to_vec();
+    let filter = SeccompFilter::new(insns).map_err(|_| SyscallError::EINVAL)?;
+    // 当前所有进程都以 root 身份运行，相当于拥有 CAP_SYS_ADMIN，
+    // 因此不强制要求先设置 PR_SET_NO_NEW_PRIVS
+    let mut seccomp = process.seccomp.lock();
+    seccomp
+        .add_filter(filter)
+        .map_err(|_| SyscallError::EINVAL)?;
+    sync_task_mode(&process, seccomp.mode);
+    Ok(0)
+}
+
+/// 将进程的 seccomp 模式同步到其下所有任务的缓存中
+///
+/// 调用者需要持有进程的 seccomp 锁，保证与 clone 时读取模式的顺序一致
+fn sync_task_mode(process: &Process, mode: u8) {
+    for task in process.tasks.lock().iter() {
+        task.set_seccomp_mode(mode);
+    }
+}
+
+/// 设置 seccomp 模式，供 seccomp 与 prctl(PR_SET_SECCOMP) 使用
+pub fn set_seccomp_mode(mode: u8, prog: *const SockFprog) -> SyscallResult {
+    match mode {
+        SECCOMP_MODE_STRICT => {
+            let process = current_process();
+            let mut seccomp = process.seccomp.lock();
+            seccomp.set_strict().map_err(|_| SyscallError::EINVAL)?;
+            sync_task_mode(&process, seccomp.mode);
+            Ok(0)
+        }
+        SECCOMP_MODE_FILTER => install_filter(prog),
+        _ => Err(SyscallError::EINVAL),
+    }
+}
+
+/// 获取当前进程的 seccomp 模式
+pub fn get_seccomp_mode() -> u8 {
+    current_process().seccomp.lock().mode
+}
+
+/// 设置 no_new_privs 标志，设置后不能取消，并会被子进程继承
+pub fn set_no_new_privs() {
+    current_process()
+        .no_new_privs
+        .store(true, Ordering::Release);
+}
+
+/// 查询 no_new_privs 标志
+pub fn get_no_new_privs() -> bool {
+    current_process().no_new_privs.load(Ordering::Acquire)
+}
+
+/// # Arguments
+/// * `operation` - usize
+/// * `flags` - usize
+/// * `args` - *const u8
+pub fn syscall_seccomp(args: [usize; 6]) -> SyscallResult {
+    let operation = args[0];
+    let flags = args[1];
+    let uargs = args[2];
+    info!(
+        "[seccomp] operation: {}, flags: {:#x}, args: {:#x}",
+        operation, flags, uargs
+    );
+    match operation {
+        SECCOMP_SET_MODE_STRICT => {
+            if flags != 0 || uargs != 0 {
+                return Err(SyscallError::EINVAL);
+            }
+            set_seccomp_mode(SECCOMP_MODE_STRICT, core::ptr::null())
+        }
+        SECCOMP_SET_MODE_FILTER => {
+            if flags & !SECCOMP_FILTER_FLAG_TSYNC != 0 {
+                return Err(SyscallError::EINVAL);
+            }
+            set_seccomp_mode(SECCOMP_MODE_FILTER, uargs as *const SockFprog)
+        }
+        SECCOMP_GET_ACTION_AVAIL => {
+            if flags != 0 {
+                return Err(SyscallError::EINVAL);
+            }
+            let action = uargs as *const u32;
+            if current_process()
+                .manual_alloc_type_for_lazy(action)
+                .is_err()
+            {
+                return Err(SyscallError::EFAULT);
+            }
+            match unsafe { *action } {
+                SECCOMP_RET_KILL_PROCESS
+                | SECCOMP_RET_KILL_THREAD
+                | SECCOMP_RET_TRAP
+                | SECCOMP_RET_ERRNO
+                | SECCOMP_RET_LOG
+                | SECCOMP_RET_ALLOW => Ok(0),
+                _ => Err(SyscallError::EOPNOTSUPP),
+            }
+        }
+        _ => Err(SyscallError::EINVAL),
+    }
+}
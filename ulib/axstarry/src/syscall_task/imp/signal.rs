@@ -1,13 +1,19 @@
 //! 支持信号相关的 syscall
 //! 与信号处理相关的系统调用
 
+use axerrno::AxError;
 use axhal::cpu::this_cpu_id;
+use axhal::time::current_time_nanos;
 use axlog::{debug, info};
-use axprocess::{current_process, current_task, yield_now_task};
+use axprocess::signal::{dequeue_signal_in, pending_signals, take_restart_block, RestartBlock};
+use axprocess::{current_process, current_task, yield_now_task, TID2TASK};
 use axsignal::action::SigAction;
-use axsignal::signal_no::SignalNo;
+use axsignal::info::{SigInfo, SI_TKILL, SI_USER};
+use axsignal::signal_no::{SignalNo, MAX_SIG_NUM};
+use axsignal::ucontext::{SignalStack, MINSIGSTKSZ, SS_AUTODISARM, SS_DISABLE, SS_ONSTACK};
 
-use crate::{SigMaskFlag, SyscallError, SyscallResult, SIGSET_SIZE_IN_BYTE};
+use super::utils::nanosleep_until;
+use crate::{SigMaskFlag, SyscallError, SyscallResult, TimeSecs, SIGSET_SIZE_IN_BYTE};
 
 /// # Arguments
 /// * `signum` - usize
@@ -67,7 +73,8 @@ pub fn syscall_sigaction(args: [usize; 6]) -> SyscallResult {
 }
 
 /// 实现sigsuspend系统调用
-/// TODO: 这里实现的似乎和文档有出入，应该有 BUG
+///
+/// 临时将信号掩码替换为 `mask` 并等待一个没有被阻塞的信号，原来的掩码在处理完信号后恢复
 /// # Arguments
 /// * `mask` - *const usize
 pub fn syscall_sigsuspend(args: [usize; 6]) -> SyscallResult {
@@ -89,27 +96,77 @@ pub fn syscall_sigsuspend(args: [usize; 6]) -> SyscallResult {
         // 信号嵌套的情况下触发这个调用
         return Err(SyscallError::EINTR);
     }
-    signal_module.signal_set.mask = unsafe { *mask };
+    signal_module.saved_mask = Some(signal_module.mask);
+    signal_module.set_mask(unsafe { *mask });
     drop(signal_modules);
-    loop {
-        let mut signal_modules = process.signal_modules.lock();
-        let signal_module = signal_modules
-            .get_mut(&current_task().id().as_u64())
-            .unwrap();
-
-        if signal_module.signal_set.find_signal().is_none() {
-            // 记得释放锁
-            drop(signal_modules);
-            yield_now_task();
-            if process.have_signals().is_some() {
-                return Err(SyscallError::EINTR);
+    while process.have_signals().is_none() {
+        yield_now_task();
+    }
+    // 若信号没有处理函数，则恢复掩码后重新等待
+    Err(SyscallError::ERESTARTNOHAND)
+}
+
+/// 继续执行被信号打断的系统调用
+///
+/// 被打断的系统调用返回 `ERESTART_RESTARTBLOCK` 且信号没有处理函数时，
+/// 返回用户态前会将其改为这一系统调用，并按照保存的状态继续执行
+pub fn syscall_restart_syscall() -> SyscallResult {
+    match take_restart_block() {
+        Some(RestartBlock::Nanosleep { deadline, rem }) => {
+            nanosleep_until(deadline, Some(rem as *mut TimeSecs))
+        }
+        None => Err(SyscallError::EINTR),
+    }
+}
+
+/// 设置或查询当前线程的备用信号栈
+///
+/// 正在备用信号栈上处理信号时不能修改备用信号栈
+/// # Arguments
+/// * `ss` - *const SignalStack
+/// * `old_ss` - *mut SignalStack
+pub fn syscall_sigaltstack(args: [usize; 6]) -> SyscallResult {
+    let ss = args[0] as *const SignalStack;
+    let old_ss = args[1] as *mut SignalStack;
+    let process = current_process();
+    if !ss.is_null() && process.manual_alloc_type_for_lazy(ss).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    if !old_ss.is_null() && process.manual_alloc_type_for_lazy(old_ss).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let current_task = current_task();
+    let sp = unsafe { (*current_task.get_first_trap_frame()).get_sp() };
+    let mut signal_modules = process.signal_modules.lock();
+    let signal_module = signal_modules.get_mut(&current_task.id().as_u64()).unwrap();
+    let old = signal_module.alternate_stack.output(sp);
+    if !ss.is_null() {
+        let new = unsafe { *ss };
+        if signal_module.alternate_stack.on_stack(sp) {
+            return Err(SyscallError::EPERM);
+        }
+        signal_module.alternate_stack = match new.flags & !SS_AUTODISARM {
+            SS_DISABLE => SignalStack::default(),
+            // 为了兼容旧的程序，SS_ONSTACK 与 0 含义相同
+            0 | SS_ONSTACK => {
+                if new.size < MINSIGSTKSZ {
+                    return Err(SyscallError::ENOMEM);
+                }
+                SignalStack {
+                    sp: new.sp,
+                    flags: new.flags & SS_AUTODISARM,
+                    size: new.size,
+                }
             }
-        } else {
-            // 说明来了一个信号
-            break;
+            _ => return Err(SyscallError::EINVAL),
+        };
+    }
+    if !old_ss.is_null() {
+        unsafe {
+            *old_ss = old;
         }
     }
-    Err(SyscallError::EINTR)
+    Ok(0)
 }
 
 /// Note: It can only be called by the signal processing function during signal processing.
@@ -154,30 +211,107 @@ pub fn syscall_sigprocmask(args: [usize; 6]) -> SyscallResult {
         .unwrap();
     if old_mask as usize != 0 {
         unsafe {
-            *old_mask = signal_module.signal_set.mask;
+            *old_mask = signal_module.mask;
         }
     }
 
     if new_mask as usize != 0 {
         let now_mask = unsafe { *new_mask };
-        match flag {
-            SigMaskFlag::Block => {
-                signal_module.signal_set.mask |= now_mask;
-            }
-            SigMaskFlag::Unblock => {
-                signal_module.signal_set.mask &= !now_mask;
-            }
-            SigMaskFlag::Setmask => {
-                signal_module.signal_set.mask = now_mask;
+        // SIGKILL 与 SIGSTOP 不能被阻塞，由 set_mask 过滤
+        let mask = match flag {
+            SigMaskFlag::Block => signal_module.mask | now_mask,
+            SigMaskFlag::Unblock => signal_module.mask & !now_mask,
+            SigMaskFlag::Setmask => now_mask,
+        };
+        signal_module.set_mask(mask);
+    }
+    Ok(0)
+}
+
+/// 查询当前线程被阻塞的未决信号，包括发送给进程的信号
+/// # Arguments
+/// * `set` - *mut usize
+/// * `sigsetsize` - usize
+pub fn syscall_sigpending(args: [usize; 6]) -> SyscallResult {
+    let set = args[0] as *mut usize;
+    let sigsetsize = args[1];
+    if sigsetsize != SIGSET_SIZE_IN_BYTE {
+        return Err(SyscallError::EINVAL);
+    }
+    let process = current_process();
+    if process.manual_alloc_type_for_lazy(set).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let mask = process
+        .signal_modules
+        .lock()
+        .get(&current_task().id().as_u64())
+        .unwrap()
+        .mask;
+    unsafe {
+        *set = pending_signals() & mask;
+    }
+    Ok(0)
+}
+
+/// 同步等待 `set` 中的信号，取出后返回其编号，该信号不会再交给信号处理函数
+///
+/// `timeout` 为空指针时一直等待，超时返回 EAGAIN；等待期间被其他信号打断时返回 EINTR
+/// # Arguments
+/// * `set` - *const usize
+/// * `info` - *mut SigInfo
+/// * `timeout` - *const TimeSecs
+/// * `sigsetsize` - usize
+pub fn syscall_sigtimedwait(args: [usize; 6]) -> SyscallResult {
+    let set = args[0] as *const usize;
+    let info = args[1] as *mut SigInfo;
+    let timeout = args[2] as *const TimeSecs;
+    let sigsetsize = args[3];
+    if sigsetsize != SIGSET_SIZE_IN_BYTE {
+        return Err(SyscallError::EINVAL);
+    }
+    let process = current_process();
+    if process.manual_alloc_type_for_lazy(set).is_err()
+        || (!info.is_null() && process.manual_alloc_type_for_lazy(info).is_err())
+        || (!timeout.is_null() && process.manual_alloc_type_for_lazy(timeout).is_err())
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    // SIGKILL 与 SIGSTOP 不能被同步等待
+    let set = unsafe { *set }
+        & !(1 << (SignalNo::SIGKILL as usize - 1))
+        & !(1 << (SignalNo::SIGSTOP as usize - 1));
+    let deadline = if timeout.is_null() {
+        None
+    } else {
+        let timeout = unsafe { *timeout };
+        if timeout.tv_nsec >= 1_000_000_000 {
+            return Err(SyscallError::EINVAL);
+        }
+        Some(current_time_nanos() as usize + timeout.turn_to_nanos())
+    };
+    loop {
+        if let Some(signal) = dequeue_signal_in(set) {
+            if !info.is_null() {
+                unsafe {
+                    *info = signal;
+                }
             }
+            return Ok(signal.si_signo as isize);
         }
+        if deadline.map_or(false, |deadline| current_time_nanos() as usize >= deadline) {
+            return Err(SyscallError::EAGAIN);
+        }
+        if process.have_signals().is_some() {
+            return Err(SyscallError::EINTR);
+        }
+        yield_now_task();
     }
-    Ok(0)
 }
 
 /// 向pid指定的进程发送信号
 ///
-/// 由于处理信号的单位在线程上，所以若进程中有多个线程，则会发送给主线程
+/// 信号由进程中的主线程或任意一个没有阻塞该信号的线程处理
 /// # Arguments
 /// * `pid` - isize
 /// * `signum` - isize
@@ -185,8 +319,9 @@ pub fn syscall_kill(args: [usize; 6]) -> SyscallResult {
     let pid = args[0] as isize;
     let signum = args[1] as isize;
     if pid > 0 && signum > 0 {
+        let info = SigInfo::user(signum as i32, SI_USER, current_process().pid() as i32, 0);
         // 不关心是否成功
-        let _ = axprocess::signal::send_signal_to_process(pid, signum);
+        let _ = axprocess::signal::send_signal_to_process_with_info(pid, signum, Some(info));
         Ok(0)
     } else if pid == 0 {
         Err(SyscallError::ESRCH)
@@ -209,9 +344,99 @@ pub fn syscall_tkill(args: [usize; 6]) -> SyscallResult {
         tid
     );
     if tid > 0 && signum > 0 {
-        let _ = axprocess::signal::send_signal_to_thread(tid, signum);
+        let info = SigInfo::user(signum as i32, SI_TKILL, current_process().pid() as i32, 0);
+        let _ = axprocess::signal::send_signal_to_thread_with_info(tid, signum, Some(info));
         Ok(0)
     } else {
         Err(SyscallError::EINVAL)
     }
 }
+
+/// 从用户空间读取 rt_sigqueueinfo 与 rt_tgsigqueueinfo 附带的信息
+///
+/// 进程只能以 `kill` 或 `tkill` 的名义向自己发送信号，`tgid` 为目标进程号
+fn read_user_siginfo(
+    tgid: usize,
+    signum: usize,
+    info: *const SigInfo,
+) -> Result<SigInfo, SyscallError> {
+    if signum > MAX_SIG_NUM {
+        return Err(SyscallError::EINVAL);
+    }
+    let process = current_process();
+    if process.manual_alloc_type_for_lazy(info).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let mut info = unsafe { *info };
+    if (info.si_code >= 0 || info.si_code == SI_TKILL) && tgid as u64 != process.pid() {
+        return Err(SyscallError::EPERM);
+    }
+    info.si_signo = signum as i32;
+    Ok(info)
+}
+
+/// 将发送信号的错误转换为系统调用的错误
+fn queue_result(result: axerrno::AxResult<()>) -> SyscallResult {
+    match result {
+        Ok(()) => Ok(0),
+        Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        Err(_) => Err(SyscallError::ESRCH),
+    }
+}
+
+/// 向进程发送带有附加信息的信号，用于实现 sigqueue
+///
+/// 实时信号的每一个实例都会排队，排队的信号数目达到 RLIMIT_SIGPENDING 时返回 EAGAIN
+/// # Arguments
+/// * `tgid` - usize
+/// * `signum` - usize
+/// * `info` - *const SigInfo
+pub fn syscall_sigqueueinfo(args: [usize; 6]) -> SyscallResult {
+    let tgid = args[0];
+    let signum = args[1];
+    let info = read_user_siginfo(tgid, signum, args[2] as *const SigInfo)?;
+    if signum == 0 {
+        // 只检查进程是否存在
+        return if axprocess::PID2PC.lock().contains_key(&(tgid as u64)) {
+            Ok(0)
+        } else {
+            Err(SyscallError::ESRCH)
+        };
+    }
+    queue_result(axprocess::signal::send_signal_to_process_with_info(
+        tgid as isize,
+        signum as isize,
+        Some(info),
+    ))
+}
+
+/// 向指定进程中的指定线程发送带有附加信息的信号
+/// # Arguments
+/// * `tgid` - usize
+/// * `tid` - usize
+/// * `signum` - usize
+/// * `info` - *const SigInfo
+pub fn syscall_tgsigqueueinfo(args: [usize; 6]) -> SyscallResult {
+    let tgid = args[0];
+    let tid = args[1];
+    let signum = args[2];
+    if tgid as isize <= 0 || tid as isize <= 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let info = read_user_siginfo(tgid, signum, args[3] as *const SigInfo)?;
+    let in_group = TID2TASK
+        .lock()
+        .get(&(tid as u64))
+        .map_or(false, |task| task.get_process_id() == tgid as u64);
+    if !in_group {
+        return Err(SyscallError::ESRCH);
+    }
+    if signum == 0 {
+        return Ok(0);
+    }
+    queue_result(axprocess::signal::send_signal_to_thread_with_info(
+        tid as isize,
+        signum as isize,
+        Some(info),
+    ))
+}
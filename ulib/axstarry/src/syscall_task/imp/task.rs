@@ -3,23 +3,26 @@ use axtask::current;
 use core::time::Duration;
 use core::{mem::size_of, ptr::slice_from_raw_parts_mut};
 
-use axconfig::TASK_STACK_SIZE;
+use axerrno::AxError;
 use axhal::time::current_time;
 use axprocess::{
     current_process, current_task, exit_current_task,
     flags::{CloneFlags, WaitStatus},
     futex::clear_wait,
     link::{deal_with_path, raw_ptr_to_ref_str, AT_FDCWD},
-    set_child_tid, sleep_now_task, wait_pid, yield_now_task, Process, PID2PC,
+    seccomp::SockFprog,
+    set_child_tid, wait_pid_with_usage, yield_now_task, Process, PID2PC,
 };
 use axsync::Mutex;
 // use axtask::{
 //     monolithic_task::task::{SchedPolicy, SchedStatus},
 //     AxTaskRef,
 // };
+use super::utils::nanosleep_until;
+use super::{get_no_new_privs, get_seccomp_mode, set_no_new_privs, set_seccomp_mode};
 use crate::{
-    CloneArgs, PrctlOption, RLimit, SyscallError, SyscallResult, TimeSecs, WaitFlags, PR_NAME_SIZE,
-    RLIMIT_AS, RLIMIT_NOFILE, RLIMIT_STACK,
+    CloneArgs, PrctlOption, RLimit, Rusage, SyscallError, SyscallResult, TimeSecs, WaitFlags,
+    PR_NAME_SIZE,
 };
 use axlog::{info, warn};
 use axtask::TaskId;
@@ -193,7 +196,7 @@ pub fn syscall_clone(args: [usize; 6]) -> SyscallResult {
     #[cfg(feature = "signal")]
     let sig_child = SignalNo::from(flags & 0x3f) == SignalNo::SIGCHLD;
 
-    if let Ok(new_task_id) = curr_process.clone_task(
+    match curr_process.clone_task(
         clone_flags,
         stack,
         ptid,
@@ -202,9 +205,10 @@ pub fn syscall_clone(args: [usize; 6]) -> SyscallResult {
         #[cfg(feature = "signal")]
         sig_child,
     ) {
-        Ok(new_task_id as isize)
-    } else {
-        Err(SyscallError::ENOMEM)
+        Ok(new_task_id) => Ok(new_task_id as isize),
+        // 超出了 RLIMIT_NPROC
+        Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        Err(_) => Err(SyscallError::ENOMEM),
     }
 }
 
@@ -235,7 +239,7 @@ pub fn syscall_clone3(args: [usize; 6]) -> SyscallResult {
     let sig_child = SignalNo::from(args.exit_signal as usize & 0x3f) == SignalNo::SIGCHLD;
 
     warn!("stack size  {}", args.stack_size);
-    if let Ok(new_task_id) = curr_process.clone_task(
+    match curr_process.clone_task(
         clone_flags,
         stack,
         args.parent_tid as usize,
@@ -244,9 +248,10 @@ pub fn syscall_clone3(args: [usize; 6]) -> SyscallResult {
         #[cfg(feature = "signal")]
         sig_child,
     ) {
-        Ok(new_task_id as isize)
-    } else {
-        Err(SyscallError::ENOMEM)
+        Ok(new_task_id) => Ok(new_task_id as isize),
+        // 超出了 RLIMIT_NPROC
+        Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        Err(_) => Err(SyscallError::ENOMEM),
     }
 }
 
@@ -262,20 +267,35 @@ pub fn syscall_vfork() -> SyscallResult {
 /// * `pid` - isize
 /// * `exit_code_ptr` - *mut i32
 /// * `option` - WaitFlags
+/// * `rusage` - *mut Rusage
 pub fn syscall_wait4(args: [usize; 6]) -> SyscallResult {
     let pid = args[0] as isize;
     let exit_code_ptr = args[1] as *mut i32;
     let option = WaitFlags::from_bits(args[2] as u32).unwrap();
+    let rusage = args[3] as *mut Rusage;
+    if !rusage.is_null()
+        && current_process()
+            .manual_alloc_type_for_lazy(rusage)
+            .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
     loop {
-        let answer = unsafe { wait_pid(pid, exit_code_ptr) };
+        let answer = unsafe { wait_pid_with_usage(pid, exit_code_ptr) };
         match answer {
-            Ok(pid) => {
+            Ok((pid, usage)) => {
+                if !rusage.is_null() {
+                    unsafe {
+                        *rusage = Rusage::from(usage);
+                    }
+                }
                 return Ok(pid as isize);
             }
             Err(status) => {
                 match status {
                     WaitStatus::NotExist => {
-                        return Err(SyscallError::EPERM);
+                        // 没有可以等待的子进程，包括已经被自动回收的情况
+                        return Err(SyscallError::ECHILD);
                     }
                     WaitStatus::Running => {
                         if option.contains(WaitFlags::WNOHANG) {
@@ -285,7 +305,7 @@ pub fn syscall_wait4(args: [usize; 6]) -> SyscallResult {
                             // wait回来之后，如果还需要wait，先检查是否有信号未处理
                             #[cfg(feature = "signal")]
                             if current_process().have_signals().is_some() {
-                                return Err(SyscallError::EINTR);
+                                return Err(SyscallError::ERESTARTSYS);
                             }
                             // 执行yield操作，切换任务
                             yield_now_task();
@@ -307,43 +327,20 @@ pub fn syscall_yield() -> SyscallResult {
 }
 
 /// 当前任务进入睡眠，req指定了睡眠的时间
-/// rem存储当睡眠完成时，真实睡眠时间和预期睡眠时间之间的差值
+/// 被信号打断时，rem存储真实睡眠时间和预期睡眠时间之间的差值
 /// # Arguments
 /// * `req` - *const TimeSecs
 /// * `rem` - *mut TimeSecs
 pub fn syscall_sleep(args: [usize; 6]) -> SyscallResult {
     let req = args[0] as *const TimeSecs;
     let rem = args[1] as *mut TimeSecs;
+    if current_process().manual_alloc_type_for_lazy(req).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
     let req_time = unsafe { *req };
-    let start_to_sleep = current_time();
     // info!("sleep: req_time = {:?}", req_time);
     let dur = Duration::new(req_time.tv_sec as u64, req_time.tv_nsec as u32);
-    sleep_now_task(dur);
-    // 若被唤醒时时间小于请求时间，则将剩余时间写入rem
-    let sleep_time = current_time() - start_to_sleep;
-    if rem as usize != 0 {
-        if sleep_time < dur {
-            let delta = (dur - sleep_time).as_nanos() as usize;
-            unsafe {
-                *rem = TimeSecs {
-                    tv_sec: delta / 1_000_000_000,
-                    tv_nsec: delta % 1_000_000_000,
-                }
-            };
-        } else {
-            unsafe {
-                *rem = TimeSecs {
-                    tv_sec: 0,
-                    tv_nsec: 0,
-                }
-            };
-        }
-    }
-    #[cfg(feature = "signal")]
-    if current_process().have_signals().is_some() {
-        return Err(SyscallError::EINTR);
-    }
-    Ok(0)
+    nanosleep_until(current_time() + dur, Some(rem))
 }
 
 /// 设置tid对应的指针
@@ -370,53 +367,53 @@ pub fn syscall_prlimit64(args: [usize; 6]) -> SyscallResult {
     let resource = args[1] as i32;
     let new_limit = args[2] as *const RLimit;
     let old_limit = args[3] as *mut RLimit;
-    // 当pid不为0，其实没有权利去修改其他的进程的资源限制
     let curr_process = current_process();
-    if pid == 0 || pid == curr_process.pid() as usize {
-        match resource {
-            RLIMIT_STACK => {
-                if old_limit as usize != 0 {
-                    unsafe {
-                        *old_limit = RLimit {
-                            rlim_cur: TASK_STACK_SIZE as u64,
-                            rlim_max: TASK_STACK_SIZE as u64,
-                        };
-                    }
-                }
-            }
-            RLIMIT_NOFILE => {
-                // 仅支持修改最大文件数
-                if old_limit as usize != 0 {
-                    let limit = curr_process.fd_manager.get_limit();
-                    unsafe {
-                        *old_limit = RLimit {
-                            rlim_cur: limit as u64,
-                            rlim_max: limit as u64,
-                        };
-                    }
-                }
-                if new_limit as usize != 0 {
-                    let new_limit = unsafe { (*new_limit).rlim_cur };
-                    curr_process.fd_manager.set_limit(new_limit);
-                }
-            }
-            RLIMIT_AS => {
-                const USER_MEMORY_LIMIT: usize = 0xffff_ffff;
-                if old_limit as usize != 0 {
-                    unsafe {
-                        *old_limit = RLimit {
-                            rlim_cur: USER_MEMORY_LIMIT as u64,
-                            rlim_max: USER_MEMORY_LIMIT as u64,
-                        };
-                    }
-                }
-            }
-            _ => {}
+    let process = if pid == 0 || pid == curr_process.pid() as usize {
+        Arc::clone(&curr_process)
+    } else if let Some(process) = PID2PC.lock().get(&(pid as u64)) {
+        Arc::clone(process)
+    } else {
+        return Err(SyscallError::ESRCH);
+    };
+    let limit = process
+        .get_rlimit(resource)
+        .map_err(|_| SyscallError::EINVAL)?;
+    if !new_limit.is_null() {
+        if curr_process.manual_alloc_type_for_lazy(new_limit).is_err() {
+            return Err(SyscallError::EFAULT);
+        }
+        let new_limit = unsafe { *new_limit };
+        process
+            .set_rlimit(resource, new_limit)
+            .map_err(|_| SyscallError::EINVAL)?;
+    }
+    if !old_limit.is_null() {
+        if curr_process.manual_alloc_type_for_lazy(old_limit).is_err() {
+            return Err(SyscallError::EFAULT);
+        }
+        unsafe {
+            *old_limit = limit;
         }
     }
     Ok(0)
 }
 
+/// 获取当前进程的资源限制
+/// # Arguments
+/// * `resource` - i32
+/// * `old_limit` - *mut RLimit
+pub fn syscall_getrlimit(args: [usize; 6]) -> SyscallResult {
+    syscall_prlimit64([0, args[0], 0, args[1], 0, 0])
+}
+
+/// 设置当前进程的资源限制
+/// # Arguments
+/// * `resource` - i32
+/// * `new_limit` - *const RLimit
+pub fn syscall_setrlimit(args: [usize; 6]) -> SyscallResult {
+    syscall_prlimit64([0, args[0], args[1], 0, 0, 0])
+}
+
 /// not support
 pub fn syscall_getpgid() -> SyscallResult {
     Ok(0)
@@ -501,7 +498,7 @@ pub fn syscall_setsid() -> SyscallResult {
     new_process
         .signal_modules
         .lock()
-        .insert(task_id, SignalModule::init_signal(None));
+        .insert(task_id, SignalModule::init_signal(None, 0));
 
     new_process.tasks.lock().push(task.as_task_ref().clone());
     task.set_leader(true);
@@ -588,6 +585,36 @@ pub fn syscall_prctl(args: [usize; 6]) -> SyscallResult {
             }
         }
         Ok(PrctlOption::PR_SET_NAME) => Ok(0),
+        Ok(PrctlOption::PR_SET_CHILD_SUBREAPER) => {
+            current_process().set_child_subreaper(args[1] != 0);
+            Ok(0)
+        }
+        Ok(PrctlOption::PR_GET_CHILD_SUBREAPER) => {
+            let status = args[1] as *mut i32;
+            if current_process()
+                .manual_alloc_type_for_lazy(status as *const i32)
+                .is_err()
+            {
+                return Err(SyscallError::EFAULT);
+            }
+            unsafe {
+                *status = current_process().get_child_subreaper() as i32;
+            }
+            Ok(0)
+        }
+        Ok(PrctlOption::PR_GET_SECCOMP) => Ok(get_seccomp_mode() as isize),
+        Ok(PrctlOption::PR_SET_SECCOMP) => {
+            set_seccomp_mode(args[1] as u8, args[2] as *const SockFprog)
+        }
+        Ok(PrctlOption::PR_SET_NO_NEW_PRIVS) => {
+            // 该标志一旦设置便不能取消
+            if args[1] != 1 || args[2] != 0 || args[3] != 0 || args[4] != 0 {
+                return Err(SyscallError::EINVAL);
+            }
+            set_no_new_privs();
+            Ok(0)
+        }
+        Ok(PrctlOption::PR_GET_NO_NEW_PRIVS) => Ok(get_no_new_privs() as isize),
         _ => Ok(0),
     }
 }
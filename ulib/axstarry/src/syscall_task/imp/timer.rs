@@ -0,0 +1,284 @@
+//! 支持间隔计时器与 POSIX 计时器相关的 syscall
+//!
+//! 计时器的到期处理见 `axprocess::timer`
+use axprocess::{
+    current_process,
+    timer::{
+        clock_now_ns, TimerClock, TimerId, TimerNotify, ITIMER_PROF, ITIMER_REAL, ITIMER_VIRTUAL,
+    },
+    TID2TASK,
+};
+
+use crate::{
+    ClockId, ITimerSpec, ITimerVal, SigEvent, SyscallError, SyscallResult, SIGEV_NONE,
+    SIGEV_SIGNAL, SIGEV_THREAD_ID, TIMER_ABSTIME,
+};
+
+/// 信号编号的最大值
+const MAX_SIG_NUM: i32 = 64;
+
+/// 计时器未指定 sigevent 时发送的信号，即 SIGALRM
+const DEFAULT_TIMER_SIGNAL: usize = 14;
+
+/// 时钟 ID 对应的计时器时钟
+fn timer_clock(clock_id: usize) -> Option<TimerClock> {
+    match ClockId::try_from(clock_id) {
+        Ok(ClockId::CLOCK_REALTIME) | Ok(ClockId::CLOCK_MONOTONIC) => Some(TimerClock::Real),
+        Ok(ClockId::CLOCK_PROCESS_CPUTIME_ID) => Some(TimerClock::Prof),
+        _ => None,
+    }
+}
+
+/// setitimer 的 which 对应的计时器时钟
+fn itimer_clock(which: usize) -> Option<TimerClock> {
+    match which {
+        ITIMER_REAL => Some(TimerClock::Real),
+        ITIMER_VIRTUAL => Some(TimerClock::Virtual),
+        ITIMER_PROF => Some(TimerClock::Prof),
+        _ => None,
+    }
+}
+
+/// 设置 ITIMER_REAL / ITIMER_VIRTUAL / ITIMER_PROF 计时器
+///
+/// 三种计时器分别依据墙上时钟、进程的用户态时间与进程的总 CPU 时间，到期时发送 SIGALRM / SIGVTALRM / SIGPROF
+/// # Arguments
+/// * `which` - usize
+/// * `new_value` - *const ITimerVal
+/// * `old_value` - *mut ITimerVal
+pub fn syscall_settimer(args: [usize; 6]) -> SyscallResult {
+    let which = args[0];
+    let new_value = args[1] as *const ITimerVal;
+    let old_value = args[2] as *mut ITimerVal;
+    let process = current_process();
+
+    if new_value.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+
+    let new_value = match process.manual_alloc_type_for_lazy(new_value) {
+        Ok(_) => unsafe { *new_value },
+        Err(_) => return Err(SyscallError::EFAULT),
+    };
+    if new_value.it_value.usec >= 1_000_000 || new_value.it_interval.usec >= 1_000_000 {
+        return Err(SyscallError::EINVAL);
+    }
+    if !old_value.is_null() && process.manual_alloc_type_for_lazy(old_value).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let clock = itimer_clock(which).ok_or(SyscallError::EINVAL)?;
+    let now_ns = clock_now_ns(&process, clock);
+    let (old_value_ns, old_interval_ns) = process.timers.lock().set(
+        process.pid(),
+        TimerId::ITimer(which),
+        new_value.it_value.turn_to_nanos() as u64,
+        new_value.it_interval.turn_to_nanos() as u64,
+        false,
+        now_ns,
+    )?;
+    if !old_value.is_null() {
+        unsafe {
+            *old_value = ITimerVal::from_nanos(old_value_ns, old_interval_ns);
+        }
+    }
+    Ok(0)
+}
+
+/// 获取 ITIMER_REAL / ITIMER_VIRTUAL / ITIMER_PROF 计时器的剩余时间与周期
+/// # Arguments
+/// * `which` - usize
+/// * `value` - *mut ITimerVal
+pub fn syscall_gettimer(args: [usize; 6]) -> SyscallResult {
+    let which = args[0];
+    let value = args[1] as *mut ITimerVal;
+    let process = current_process();
+    let clock = itimer_clock(which).ok_or(SyscallError::EINVAL)?;
+    if process
+        .manual_alloc_type_for_lazy(value as *const ITimerVal)
+        .is_err()
+    {
+        return Err(SyscallError::EFAULT);
+    }
+    let now_ns = clock_now_ns(&process, clock);
+    let (value_ns, interval_ns) = process
+        .timers
+        .lock()
+        .get(TimerId::ITimer(which))
+        .map_or((0, 0), |timer| timer.output(now_ns));
+    unsafe {
+        *value = ITimerVal::from_nanos(value_ns, interval_ns);
+    }
+    Ok(0)
+}
+
+/// 创建一个 POSIX 计时器
+///
+/// 支持 CLOCK_REALTIME、CLOCK_MONOTONIC 与 CLOCK_PROCESS_CPUTIME_ID，
+/// 通知方式支持 SIGEV_SIGNAL、SIGEV_THREAD_ID 与 SIGEV_NONE。
+/// SIGEV_THREAD 由 libc 基于 SIGEV_THREAD_ID 实现，内核不直接支持
+/// # Arguments
+/// * `clock_id` - usize
+/// * `sevp` - *const SigEvent, 为空时表示到期发送 SIGALRM，附带的值为计时器编号
+/// * `timer_id` - *mut i32
+pub fn syscall_timer_create(args: [usize; 6]) -> SyscallResult {
+    let clock_id = args[0];
+    let sevp = args[1] as *const SigEvent;
+    let timer_id = args[2] as *mut i32;
+    let process = current_process();
+    let clock = timer_clock(clock_id).ok_or(SyscallError::EINVAL)?;
+    let (notify, value) = if sevp.is_null() {
+        (TimerNotify::Signal(DEFAULT_TIMER_SIGNAL), None)
+    } else {
+        if process.manual_alloc_type_for_lazy(sevp).is_err() {
+            return Err(SyscallError::EFAULT);
+        }
+        let sev = unsafe { *sevp };
+        let signo = sev.sigev_signo;
+        let notify = match sev.sigev_notify {
+            SIGEV_NONE => TimerNotify::None,
+            SIGEV_SIGNAL if signo > 0 && signo <= MAX_SIG_NUM => {
+                TimerNotify::Signal(signo as usize)
+            }
+            SIGEV_THREAD_ID if signo > 0 && signo <= MAX_SIG_NUM => {
+                // 接收信号的线程必须属于当前进程
+                let tid = sev.sigev_notify_thread_id as u64;
+                match TID2TASK.lock().get(&tid) {
+                    Some(task) if task.get_process_id() == process.pid() => {}
+                    _ => return Err(SyscallError::EINVAL),
+                }
+                TimerNotify::Thread(tid, signo as usize)
+            }
+            _ => return Err(SyscallError::EINVAL),
+        };
+        (notify, Some(sev.sigev_value))
+    };
+    if process.manual_alloc_type_for_lazy(timer_id).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let id = process.timers.lock().create(clock, notify, value)?;
+    unsafe {
+        *timer_id = id as i32;
+    }
+    Ok(0)
+}
+
+/// 启动或停止一个 POSIX 计时器
+///
+/// flags 为 TIMER_ABSTIME 时，it_value 为计时器时钟的绝对时间
+/// # Arguments
+/// * `timer_id` - usize
+/// * `flags` - usize
+/// * `new_value` - *const ITimerSpec
+/// * `old_value` - *mut ITimerSpec
+pub fn syscall_timer_settime(args: [usize; 6]) -> SyscallResult {
+    let timer_id = args[0];
+    let flags = args[1];
+    let new_value = args[2] as *const ITimerSpec;
+    let old_value = args[3] as *mut ITimerSpec;
+    let process = current_process();
+    if new_value.is_null() || process.manual_alloc_type_for_lazy(new_value).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let new_value = unsafe { *new_value };
+    if new_value.it_value.tv_nsec >= 1_000_000_000 || new_value.it_interval.tv_nsec >= 1_000_000_000
+    {
+        return Err(SyscallError::EINVAL);
+    }
+    if !old_value.is_null() && process.manual_alloc_type_for_lazy(old_value).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let clock = process
+        .timers
+        .lock()
+        .get(TimerId::Posix(timer_id))
+        .map(|timer| timer.clock)
+        .ok_or(SyscallError::EINVAL)?;
+    let now_ns = clock_now_ns(&process, clock);
+    let (old_value_ns, old_interval_ns) = process.timers.lock().set(
+        process.pid(),
+        TimerId::Posix(timer_id),
+        new_value.it_value.turn_to_nanos() as u64,
+        new_value.it_interval.turn_to_nanos() as u64,
+        flags & TIMER_ABSTIME != 0,
+        now_ns,
+    )?;
+    if !old_value.is_null() {
+        unsafe {
+            *old_value = ITimerSpec::from_nanos(old_value_ns, old_interval_ns);
+        }
+    }
+    Ok(0)
+}
+
+/// 获取一个 POSIX 计时器的剩余时间与周期
+/// # Arguments
+/// * `timer_id` - usize
+/// * `curr_value` - *mut ITimerSpec
+pub fn syscall_timer_gettime(args: [usize; 6]) -> SyscallResult {
+    let timer_id = args[0];
+    let curr_value = args[1] as *mut ITimerSpec;
+    let process = current_process();
+    let clock = process
+        .timers
+        .lock()
+        .get(TimerId::Posix(timer_id))
+        .map(|timer| timer.clock)
+        .ok_or(SyscallError::EINVAL)?;
+    if process.manual_alloc_type_for_lazy(curr_value).is_err() {
+        return Err(SyscallError::EFAULT);
+    }
+    let now_ns = clock_now_ns(&process, clock);
+    let (value_ns, interval_ns) = process
+        .timers
+        .lock()
+        .get(TimerId::Posix(timer_id))
+        .map(|timer| timer.output(now_ns))
+        .ok_or(SyscallError::EINVAL)?;
+    unsafe {
+        *curr_value = ITimerSpec::from_nanos(value_ns, interval_ns);
+    }
+    Ok(0)
+}
+
+/// 获取 POSIX 计时器最近一次发出信号之后额外到期的次数
+/// # Arguments
+/// * `timer_id` - usize
+pub fn syscall_timer_getoverrun(args: [usize; 6]) -> SyscallResult {
+    let timer_id = args[0];
+    let overrun = current_process().timers.lock().overrun(timer_id)?;
+    // 超过 DELAYTIMER_MAX 时返回 DELAYTIMER_MAX
+    Ok(overrun.min(i32::MAX as usize) as isize)
+}
+
+/// 删除一个 POSIX 计时器，尚未处理的信号不受影响
+/// # Arguments
+/// * `timer_id` - usize
+pub fn syscall_timer_delete(args: [usize; 6]) -> SyscallResult {
+    let timer_id = args[0];
+    let process = current_process();
+    process.timers.lock().delete(process.pid(), timer_id)?;
+    Ok(0)
+}
+
+/// 在指定秒数后发送 SIGALRM，返回原先的 ITIMER_REAL 计时器剩余的秒数
+/// # Arguments
+/// * `seconds` - usize
+#[cfg(target_arch = "x86_64")]
+pub fn syscall_alarm(args: [usize; 6]) -> SyscallResult {
+    let seconds = args[0] as u64;
+    let process = current_process();
+    let (old_value_ns, _) = process.timers.lock().set(
+        process.pid(),
+        TimerId::ITimer(ITIMER_REAL),
+        seconds * 1_000_000_000,
+        0,
+        false,
+        clock_now_ns(&process, TimerClock::Real),
+    )?;
+    // 四舍五入到秒，但仍在运行的计时器至少返回 1
+    let old_seconds = (old_value_ns + 500_000_000) / 1_000_000_000;
+    if old_value_ns != 0 && old_seconds == 0 {
+        return Ok(1);
+    }
+    Ok(old_seconds as isize)
+}
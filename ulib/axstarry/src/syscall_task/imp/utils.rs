@@ -1,30 +1,45 @@
 use core::{slice::from_raw_parts_mut, time::Duration};
 
-use axhal::time::{current_time, current_time_nanos, nanos_to_ticks, NANOS_PER_SEC};
+use axhal::time::{current_time, current_time_nanos, MICROS_PER_SEC, NANOS_PER_SEC};
 
-use axprocess::{current_process, current_task, time_stat_output};
+use axprocess::{
+    current_process, current_task,
+    rusage::ResourceUsage,
+    timer::{clock_now_ns, TimerClock},
+};
 use rand::{rngs::SmallRng, Fill, SeedableRng};
 
 use crate::{
-    ClockId, ITimerVal, RusageFlags, SysInfo, SyscallError, SyscallResult, TimeSecs, TimeVal, Tms,
+    ClockId, Rusage, RusageFlags, SysInfo, SyscallError, SyscallResult, TimeSecs, TimeVal, Tms,
     UtsName,
 };
 
 /// 返回值为当前经过的时钟中断数
+///
+/// 进程时间为所有线程时间之和，子进程时间为所有已回收子进程及其后代的时间之和，
+/// 与返回值相同，均以时钟滴答(1 / TICKS_PER_SEC 秒)为单位
 /// # Arguments
 /// * `tms` - *mut Tms
 pub fn syscall_time(args: [usize; 6]) -> SyscallResult {
     let tms = args[0] as *mut Tms;
-    let (_, utime_us, _, stime_us) = time_stat_output();
-    unsafe {
-        *tms = Tms {
-            tms_utime: utime_us,
-            tms_stime: stime_us,
-            tms_cutime: utime_us,
-            tms_cstime: stime_us,
+    if !tms.is_null() {
+        let process = current_process();
+        if process.manual_alloc_type_for_lazy(tms).is_err() {
+            return Err(SyscallError::EFAULT);
+        }
+        let usage = process.self_usage();
+        let children = process.children_usage();
+        let to_ticks = |us: usize| us * axconfig::TICKS_PER_SEC / MICROS_PER_SEC as usize;
+        unsafe {
+            *tms = Tms {
+                tms_utime: to_ticks(usage.utime_us),
+                tms_stime: to_ticks(usage.stime_us),
+                tms_cutime: to_ticks(children.utime_us),
+                tms_cstime: to_ticks(children.stime_us),
+            }
         }
     }
-    Ok(nanos_to_ticks(current_time_nanos()) as isize)
+    Ok((current_time_nanos() / (NANOS_PER_SEC / axconfig::TICKS_PER_SEC as u64)) as isize)
 }
 
 /// 获取当前系统时间并且存储在给定结构体中
@@ -43,14 +58,22 @@ pub fn syscall_get_time_of_day(args: [usize; 6]) -> SyscallResult {
 }
 
 /// 用于获取当前系统时间并且存储在对应的结构体中
+///
+/// CLOCK_PROCESS_CPUTIME_ID 返回进程所有线程占用的 CPU 时间，其余时钟均返回系统时间
 /// # Arguments
 /// * `clock_id` - usize
 /// * `ts` - *mut TimeSecs
 pub fn syscall_clock_get_time(args: [usize; 6]) -> SyscallResult {
-    let _clock_id = args[0];
+    let clock_id = args[0];
     let ts = args[1] as *mut TimeSecs;
+    let time = match ClockId::try_from(clock_id) {
+        Ok(ClockId::CLOCK_PROCESS_CPUTIME_ID) => {
+            TimeSecs::from_nanos(clock_now_ns(&current_process(), TimerClock::Prof) as usize)
+        }
+        _ => TimeSecs::now(),
+    };
     unsafe {
-        (*ts) = TimeSecs::now();
+        (*ts) = time;
     }
     Ok(0)
 }
@@ -86,92 +109,31 @@ pub fn syscall_sysinfo(args: [usize; 6]) -> SyscallResult {
     Ok(0)
 }
 
-/// # Arguments
-/// * `which` - usize
-/// * `new_value` - *const ITimerVal
-/// * `old_value` - *mut ITimerVal
-pub fn syscall_settimer(args: [usize; 6]) -> SyscallResult {
-    let which = args[0];
-    let new_value = args[1] as *const ITimerVal;
-    let old_value = args[2] as *mut ITimerVal;
-    let process = current_process();
-
-    if new_value.is_null() {
-        return Err(SyscallError::EFAULT);
-    }
-
-    let new_value = match process.manual_alloc_type_for_lazy(new_value) {
-        Ok(_) => unsafe { &*new_value },
-        Err(_) => return Err(SyscallError::EFAULT),
-    };
-
-    if !old_value.is_null() {
-        if process.manual_alloc_type_for_lazy(old_value).is_err() {
-            return Err(SyscallError::EFAULT);
-        }
-
-        let (time_interval_us, time_remained_us) = current_task().timer_output();
-        unsafe {
-            (*old_value).it_interval = TimeVal::from_micro(time_interval_us);
-            (*old_value).it_value = TimeVal::from_micro(time_remained_us);
-        }
-    }
-    let (time_interval_ns, time_remained_ns) = (
-        new_value.it_interval.turn_to_nanos(),
-        new_value.it_value.turn_to_nanos(),
-    );
-    if current_task().set_timer(time_interval_ns, time_remained_ns, which) {
-        Ok(0)
-    } else {
-        // 说明which参数错误
-        Err(SyscallError::EFAULT)
-    }
-}
-
-/// # Arguments
-/// * `which` - usize
-/// * `value` - *mut ITimerVal
-pub fn syscall_gettimer(args: [usize; 6]) -> SyscallResult {
-    let _which = args[0];
-    let value = args[1] as *mut ITimerVal;
-    let process = current_process();
-    if process
-        .manual_alloc_type_for_lazy(value as *const ITimerVal)
-        .is_err()
-    {
-        return Err(SyscallError::EFAULT);
-    }
-    let (time_interval_us, time_remained_us) = current_task().timer_output();
-    unsafe {
-        (*value).it_interval = TimeVal::from_micro(time_interval_us);
-        (*value).it_value = TimeVal::from_micro(time_remained_us);
-    }
-    Ok(0)
-}
-
 /// # Arguments
 /// * `who` - i32
-/// * `utime` - *mut TimeVal
+/// * `usage` - *mut Rusage
 pub fn syscall_getrusage(args: [usize; 6]) -> SyscallResult {
     let who = args[0] as i32;
-    let utime = args[1] as *mut TimeVal;
-    let stime: *mut TimeVal = unsafe { utime.add(1) };
+    let usage = args[1] as *mut Rusage;
     let process = current_process();
-    if process.manual_alloc_type_for_lazy(utime).is_err()
-        || process.manual_alloc_type_for_lazy(stime).is_err()
-    {
+    let resource_usage = match RusageFlags::from(who) {
+        Some(RusageFlags::RUSAGE_SELF) => process.self_usage(),
+        Some(RusageFlags::RUSAGE_CHILDREN) => process.children_usage(),
+        Some(RusageFlags::RUSAGE_THREAD) => {
+            let mut thread_usage = ResourceUsage::from_task(&current_task());
+            // 常驻内存是进程级别的信息
+            thread_usage.maxrss_kb = process.self_usage().maxrss_kb;
+            thread_usage
+        }
+        None => return Err(SyscallError::EINVAL),
+    };
+    if process.manual_alloc_type_for_lazy(usage).is_err() {
         return Err(SyscallError::EFAULT);
     }
-    if RusageFlags::from(who).is_some() {
-        let (_, utime_us, _, stime_us) = time_stat_output();
-        unsafe {
-            *utime = TimeVal::from_micro(utime_us);
-            *stime = TimeVal::from_micro(stime_us);
-        }
-        Ok(0)
-    } else {
-        Err(SyscallError::EINVAL)
+    unsafe {
+        *usage = Rusage::from(resource_usage);
     }
+    Ok(0)
 }
 
 /// # Arguments
@@ -275,31 +237,50 @@ pub fn syscall_clock_nanosleep(args: [usize; 6]) -> SyscallResult {
     }
     let request_time = unsafe { *request };
     let request_time = Duration::new(request_time.tv_sec as u64, request_time.tv_nsec as u32);
-    let deadline = if flags != TIMER_ABSTIME {
-        current_time() + request_time
+    if flags != TIMER_ABSTIME {
+        nanosleep_until(current_time() + request_time, Some(remain))
     } else {
-        if request_time < current_time() {
+        // 绝对时间的睡眠被打断后可以使用原来的参数重新执行
+        nanosleep_until(request_time, None)
+    }
+}
+
+/// 睡眠到 `deadline`，用于 nanosleep 与 clock_nanosleep
+///
+/// `rem` 为 `None` 时表示绝对时间的睡眠，被信号打断后返回 `ERESTARTNOHAND`。
+/// 否则为相对时间的睡眠，被打断后将剩余时间写入 `rem` 指向的地址（可以为空），
+/// 并返回 `ERESTART_RESTARTBLOCK`，此时若信号没有处理函数，则通过 restart_syscall 继续睡眠到原来的截止时间
+pub(crate) fn nanosleep_until(deadline: Duration, rem: Option<*mut TimeSecs>) -> SyscallResult {
+    #[cfg(not(feature = "signal"))]
+    let _ = rem;
+    loop {
+        axtask::sleep_until(deadline);
+        let now = current_time();
+        if now >= deadline {
             return Ok(0);
         }
-        request_time
-    };
-
-    axtask::sleep_until(deadline);
-
-    let current_time = current_time();
-    if current_time < deadline && !remain.is_null() {
-        if process.manual_alloc_type_for_lazy(remain).is_err() {
-            return Err(SyscallError::EFAULT);
-        } else {
-            let delta = (deadline - current_time).as_nanos() as usize;
-            unsafe {
-                *remain = TimeSecs {
-                    tv_sec: delta / 1_000_000_000,
-                    tv_nsec: delta % 1_000_000_000,
-                }
+        #[cfg(feature = "signal")]
+        if current_process().have_signals().is_some() {
+            let Some(rem) = rem else {
+                return Err(SyscallError::ERESTARTNOHAND);
             };
-            return Err(SyscallError::EINTR);
+            if !rem.is_null() {
+                if current_process().manual_alloc_type_for_lazy(rem).is_err() {
+                    return Err(SyscallError::EFAULT);
+                }
+                let delta = (deadline - now).as_nanos() as usize;
+                unsafe {
+                    *rem = TimeSecs {
+                        tv_sec: delta / 1_000_000_000,
+                        tv_nsec: delta % 1_000_000_000,
+                    }
+                };
+            }
+            axprocess::signal::set_restart_block(axprocess::signal::RestartBlock::Nanosleep {
+                deadline,
+                rem: rem as usize,
+            });
+            return Err(SyscallError::ERESTART_RESTARTBLOCK);
         }
     }
-    Ok(0)
 }
@@ -17,6 +17,11 @@ pub fn task_syscall(syscall_id: task_syscall_id::TaskSyscallId, args: [usize; 6]
         CLONE => syscall_clone(args),
         CLONE3 => syscall_clone3(args),
         NANO_SLEEP => syscall_sleep(args),
+        #[cfg(feature = "signal")]
+        RESTART_SYSCALL => syscall_restart_syscall(),
+        // 没有被信号打断的系统调用，不需要继续执行
+        #[cfg(not(feature = "signal"))]
+        RESTART_SYSCALL => Err(crate::SyscallError::EINTR),
         SCHED_YIELD => syscall_yield(),
         TIMES => syscall_time(args),
         UNAME => syscall_uname(args),
@@ -40,10 +45,24 @@ pub fn task_syscall(syscall_id: task_syscall_id::TaskSyscallId, args: [usize; 6]
         #[cfg(feature = "signal")]
         SIGPROCMASK => syscall_sigprocmask(args),
         #[cfg(feature = "signal")]
+        SIGPENDING => syscall_sigpending(args),
+        #[cfg(feature = "signal")]
+        SIGTIMEDWAIT => syscall_sigtimedwait(args),
+        #[cfg(feature = "signal")]
         SIGRETURN => syscall_sigreturn(),
+        #[cfg(feature = "signal")]
+        SIGALTSTACK => syscall_sigaltstack(args),
+        #[cfg(feature = "signal")]
+        SIGQUEUEINFO => syscall_sigqueueinfo(args),
+        #[cfg(feature = "signal")]
+        TGSIGQUEUEINFO => syscall_tgsigqueueinfo(args),
         EXIT_GROUP => syscall_exit(args),
         SET_TID_ADDRESS => syscall_set_tid_address(args),
         PRLIMIT64 => syscall_prlimit64(args),
+        GETRLIMIT => syscall_getrlimit(args),
+        SETRLIMIT => syscall_setrlimit(args),
+        SECCOMP => syscall_seccomp(args),
+        PRCTL => syscall_prctl(args),
         CLOCK_GET_TIME => syscall_clock_get_time(args),
         GETUID => syscall_getuid(),
         GETEUID => syscall_geteuid(),
@@ -59,31 +78,42 @@ pub fn task_syscall(syscall_id: task_syscall_id::TaskSyscallId, args: [usize; 6]
         SYSINFO => syscall_sysinfo(args),
         SETITIMER => syscall_settimer(args),
         GETTIMER => syscall_gettimer(args),
+        TIMER_CREATE => syscall_timer_create(args),
+        TIMER_SETTIME => syscall_timer_settime(args),
+        TIMER_GETTIME => syscall_timer_gettime(args),
+        TIMER_GETOVERRUN => syscall_timer_getoverrun(args),
+        TIMER_DELETE => syscall_timer_delete(args),
         SETSID => syscall_setsid(),
         GETRUSAGE => syscall_getrusage(args),
         UMASK => syscall_umask(args),
         // 不做处理即可
+        #[cfg(not(feature = "signal"))]
         SIGTIMEDWAIT => Ok(0),
         SYSLOG => Ok(0),
         MADVICE => Ok(0),
-        SCHED_SETAFFINITY => Ok(0),
+        SCHED_SETAFFINITY => syscall_sched_setaffinity(args),
         SCHED_GETAFFINITY => syscall_sched_getaffinity(args),
+        SCHED_GET_PRIORITY_MAX => syscall_sched_get_priority_max(args),
+        SCHED_GET_PRIORITY_MIN => syscall_sched_get_priority_min(args),
+        SCHED_RR_GET_INTERVAL => syscall_sched_rr_get_interval(args),
         SCHED_SETSCHEDULER => syscall_sched_setscheduler(args),
         SCHED_GETSCHEDULER => syscall_sched_getscheduler(args),
+        SCHED_SETATTR => syscall_sched_setattr(args),
+        SCHED_GETATTR => syscall_sched_getattr(args),
+        SETPRIORITY => syscall_setpriority(args),
+        GETPRIORITY => syscall_getpriority(args),
         GET_MEMPOLICY => Ok(0),
         CLOCK_GETRES => syscall_clock_getres(args),
         CLOCK_NANOSLEEP => syscall_clock_nanosleep(args),
         // syscall below just for x86_64
         #[cfg(target_arch = "x86_64")]
-        PRCTL => syscall_prctl(args),
-        #[cfg(target_arch = "x86_64")]
         VFORK => syscall_vfork(),
         #[cfg(target_arch = "x86_64")]
         ARCH_PRCTL => syscall_arch_prctl(args),
         #[cfg(target_arch = "x86_64")]
         FORK => syscall_fork(),
         #[cfg(target_arch = "x86_64")]
-        ALARM => Ok(0),
+        ALARM => syscall_alarm(args),
         #[cfg(target_arch = "x86_64")]
         RSEQ => Ok(0),
         #[cfg(target_arch = "x86_64")]
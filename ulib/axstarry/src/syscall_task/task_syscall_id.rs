@@ -21,6 +21,11 @@ pub enum TaskSyscallId {
     NANO_SLEEP = 101,
     GETTIMER = 102,
     SETITIMER = 103,
+    TIMER_CREATE = 107,
+    TIMER_GETTIME = 108,
+    TIMER_GETOVERRUN = 109,
+    TIMER_SETTIME = 110,
+    TIMER_DELETE = 111,
     CLOCK_GETRES = 114,
     CLOCK_NANOSLEEP = 115,
     SYSLOG = 116,
@@ -28,6 +33,14 @@ pub enum TaskSyscallId {
     SCHED_GETSCHEDULER = 120,
     SCHED_SETAFFINITY = 122,
     SCHED_GETAFFINITY = 123,
+    SCHED_GET_PRIORITY_MAX = 125,
+    SCHED_GET_PRIORITY_MIN = 126,
+    SCHED_RR_GET_INTERVAL = 127,
+    RESTART_SYSCALL = 128,
+    SETPRIORITY = 140,
+    GETPRIORITY = 141,
+    SCHED_SETATTR = 274,
+    SCHED_GETATTR = 275,
     GET_MEMPOLICY = 236,
     SETPGID = 154,
     GETPGID = 155,
@@ -55,15 +68,22 @@ pub enum TaskSyscallId {
     TIMES = 153,
     UNAME = 160,
     GETTIMEOFDAY = 169,
+    GETRLIMIT = 163,
+    SETRLIMIT = 164,
     PRLIMIT64 = 261,
+    SECCOMP = 277,
     // 信号模块
     KILL = 129,
     TKILL = 130,
     TGKILL = 131,
+    SIGALTSTACK = 132,
     SIGSUSPEND = 133,
     SIGACTION = 134,
     SIGPROCMASK = 135,
+    SIGPENDING = 136,
+    SIGQUEUEINFO = 138,
     SIGRETURN = 139,
+    TGSIGQUEUEINFO = 240,
 }
 }
 
@@ -79,11 +99,17 @@ numeric_enum_macro::numeric_enum! {
         EXIT_GROUP = 231,
         SET_TID_ADDRESS = 218,
         FUTEX = 202,
+        RESTART_SYSCALL = 219,
         SET_ROBUST_LIST = 273,
         GET_ROBUST_LIST = 274,
         NANO_SLEEP = 35,
         GETTIMER = 36,
         SETITIMER = 38,
+        TIMER_CREATE = 222,
+        TIMER_SETTIME = 223,
+        TIMER_GETTIME = 224,
+        TIMER_GETOVERRUN = 225,
+        TIMER_DELETE = 226,
         CLOCK_GETRES = 229,
         CLOCK_NANOSLEEP = 230,
         TIME = 201,
@@ -92,6 +118,13 @@ numeric_enum_macro::numeric_enum! {
         SCHED_GETSCHEDULER = 145,
         SCHED_SETAFFINITY = 203,
         SCHED_GETAFFINITY = 204,
+        SCHED_GET_PRIORITY_MAX = 146,
+        SCHED_GET_PRIORITY_MIN = 147,
+        SCHED_RR_GET_INTERVAL = 148,
+        GETPRIORITY = 140,
+        SETPRIORITY = 141,
+        SCHED_SETATTR = 314,
+        SCHED_GETATTR = 315,
         GET_MEMPOLICY = 239,
         SETSID = 112,
         GETRUSAGE = 98,
@@ -119,16 +152,23 @@ numeric_enum_macro::numeric_enum! {
         TIMES = 100,
         UNAME = 63,
         GETTIMEOFDAY = 96,
+        GETRLIMIT = 97,
+        SETRLIMIT = 160,
         PRLIMIT64 = 302,
+        SECCOMP = 317,
         RSEQ = 334,
         // 信号模块
         KILL = 62,
         TKILL = 200,
         TGKILL = 234,
         SIGSUSPEND = 130,
+        SIGALTSTACK = 131,
         SIGACTION = 13,
         SIGPROCMASK = 14,
+        SIGPENDING = 127,
         SIGRETURN = 15,
+        SIGQUEUEINFO = 129,
+        TGSIGQUEUEINFO = 297,
         FORK = 57,
         VFORK = 58,
         ALARM = 37,
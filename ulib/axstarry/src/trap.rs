@@ -16,6 +16,15 @@ impl axhal::trap::TrapHandler for TrapHandlerImpl {
                 axprocess::time_stat_from_user_to_kernel();
             }
             axhal::irq::dispatch_irq(_irq_num);
+            if _irq_num == axhal::time::TIMER_IRQ_NUM {
+                // 记录用户态时间片，CPU 时间计时器与 CPU 时间限制在返回用户态前检查
+                if from_user {
+                    axprocess::account_user_tick();
+                }
+                // 到期的墙上时钟计时器交给内核任务处理
+                axprocess::timer::expire_real_timers();
+                axprocess::timer::rearm_timer_irq();
+            }
             if from_user {
                 axprocess::time_stat_from_kernel_to_user();
             }
@@ -41,6 +50,7 @@ impl axhal::trap::TrapHandler for TrapHandlerImpl {
 
     #[cfg(feature = "signal")]
     fn handle_signal() {
+        axprocess::handle_user_tick();
         axprocess::signal::handle_signals();
     }
 }